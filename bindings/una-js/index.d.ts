@@ -3,22 +3,218 @@
 
 /* auto-generated by NAPI-RS */
 
+export function decodeInvoice(bolt11: string): DecodedInvoice
+export type JsInvoiceSubscription = InvoiceSubscription
+export class InvoiceSubscription {
+  close(): void
+}
+export type JsPaymentSubscription = PaymentSubscription
+export class PaymentSubscription {
+  close(): void
+}
 export type JsNode = Node
 export class Node {
   constructor(backend: Backend, config: NodeConfig)
+  cancelHoldInvoice(paymentHash: string): Promise<void>
+  cancelInvoice(paymentHash: string): Promise<void>
+  closeChannel(params: CloseChannelParams): Promise<CloseChannelResult>
+  connectPeer(params: ConnectPeerParams): Promise<void>
+  createHoldInvoice(params: CreateHoldInvoiceParams): Promise<CreateInvoiceResult>
   createInvoice(invoice: CreateInvoiceParams): Promise<CreateInvoiceResult>
+  decodeInvoice(bolt11: string): Promise<DecodedInvoice>
+  deleteExpiredInvoices(before?: number | null): Promise<void>
+  disconnectPeer(pubkey: string): Promise<void>
+  getBalance(): Promise<Balance>
+  getChannelPolicy(channelId: string): Promise<ChannelPolicy>
   getInfo(): Promise<NodeInfo>
+  getInvoice(paymentHash: string): Promise<Invoice>
+  getPayment(paymentHash: string): Promise<Payment>
+  keysend(params: KeysendParams): Promise<PayInvoiceResult>
+  listChannels(): Promise<Channel[]>
+  listForwards(params: ListForwardsParams): Promise<ListForwardsResult>
+  listInvoices(params: ListInvoicesParams): Promise<ListInvoicesResult>
+  listOnchainTransactions(): Promise<OnchainTransaction[]>
+  listPayments(params: ListPaymentsParams): Promise<ListPaymentsResult>
+  listPeers(): Promise<Peer[]>
+  newAddress(addressType: AddressType): Promise<string>
+  openChannel(params: OpenChannelParams): Promise<OpenChannelResult>
   payInvoice(invoice: PayInvoiceParams): Promise<PayInvoiceResult>
+  sendOnchain(params: SendOnchainParams): Promise<string>
+  sendPaymentAsync(invoice: PayInvoiceParams): Promise<string>
+  settleHoldInvoice(preimage: string): Promise<void>
+  signMessage(message: string): Promise<string>
+  subscribeInvoices(params: SubscribeInvoicesParams, callback: (err: Error | null, event: InvoiceEvent) => void): InvoiceSubscription
+  trackPayment(paymentHash: string, callback: (err: Error | null, payment: Payment) => void): PaymentSubscription
+  updateChannelPolicy(params: UpdatePolicyParams): Promise<void>
+  verifyMessage(params: VerifyMessageParams): Promise<VerifyMessageResult>
 }
 
+export type AddressType = "P2wpkh" | "P2tr" | "NestedP2wpkh";
+
 export type Backend = "LndRest" | "LndGrpc" | "ClnGrpc" | "EclairRest" | "InvalidBackend";
 
+/**
+ * Funds of the node, all amounts in msat.
+ */
+export interface Balance {
+  /**
+   * Local funds of the open channels which can be sent, without the
+   * channel reserves.
+   */
+  lightning_local_msat: number;
+  /**
+   * Remote funds of the open channels which can be received, without the
+   * channel reserves.
+   */
+  lightning_remote_msat: number;
+  onchain_confirmed_msat: number;
+  /**
+   * On-chain funds reserved or leased, which can't be spent for now.
+   */
+  onchain_locked_msat: number;
+  onchain_unconfirmed_msat: number;
+  /**
+   * Local funds of the channels being closed, not back in the wallet yet.
+   */
+  pending_close_msat: number;
+  /**
+   * Local funds of the channels being opened.
+   */
+  pending_open_msat: number;
+}
+
+export interface Channel {
+  /**
+   * Whether the channel can be used for payments, open with the peer online.
+   */
+  active: boolean;
+  capacity_msat: number;
+  /**
+   * Channel id of BOLT 2, in hex.
+   */
+  channel_id: string;
+  /**
+   * Funding outpoint of the channel, as `txid:output_index`.
+   */
+  channel_point?: string | null;
+  /**
+   * Whether the node opened the channel.
+   */
+  initiator: boolean;
+  local_balance_msat: number;
+  /**
+   * Amount the node must keep on its side of the channel.
+   */
+  local_reserve_msat: number;
+  peer_pubkey: string;
+  /**
+   * Whether the channel isn't announced to the network.
+   */
+  private: boolean;
+  remote_balance_msat: number;
+  /**
+   * Amount the peer must keep on its side of the channel.
+   */
+  remote_reserve_msat: number;
+  /**
+   * Short channel id as `block x transaction x output`, once the funding transaction is confirmed.
+   */
+  short_channel_id?: string | null;
+  status: ChannelStatus;
+}
+
+/**
+ * Fees and limits the node applies to the payments it forwards through a channel.
+ */
+export interface ChannelPolicy {
+  base_fee_msat: number;
+  /**
+   * Channel id of BOLT 2, in hex.
+   */
+  channel_id: string;
+  /**
+   * Proportional fee, in millionths of the forwarded amount.
+   */
+  fee_rate_ppm: number;
+  max_htlc_msat?: number | null;
+  min_htlc_msat: number;
+  /**
+   * CLTV delta required to forward a payment, unknown to Core Lightning until the channel is announced.
+   */
+  time_lock_delta?: number | null;
+}
+
 export interface ChannelStats {
   active: number;
   inactive: number;
   pending: number;
 }
 
+export type ChannelStatus = "Opening" | "Open" | "Closing" | "Closed";
+
+/**
+ * Channel to close, given by either its channel point or its channel id.
+ */
+export interface CloseChannelParams {
+  /**
+   * Channel id of BOLT 2, in hex.
+   */
+  channel_id?: string | null;
+  /**
+   * Funding outpoint of the channel, as `txid:output_index`.
+   */
+  channel_point?: string | null;
+  /**
+   * Address the local funds are sent to, a wallet address otherwise.
+   */
+  delivery_address?: string | null;
+  /**
+   * Fee rate of the closing transaction, in sat/vbyte.
+   */
+  fee_rate?: number | null;
+  /**
+   * Whether to publish the commitment transaction instead of negotiating the close with the peer, without a fee rate or delivery address. Core Lightning still negotiates for a second first, so the close can end up a mutual one with a peer online.
+   */
+  force?: boolean | null;
+}
+
+export interface CloseChannelResult {
+  /**
+   * Transactions closing the channel, empty while the backend doesn't know them yet.
+   */
+  closing_txids: string[];
+}
+
+export interface ConnectPeerParams {
+  /**
+   * Address of the peer as `host:port`. When missing, LND takes the first address of the peer in the network graph, while Core Lightning and Eclair look it up on their own.
+   */
+  host?: string | null;
+  /**
+   * Public key of the peer, in hex.
+   */
+  pubkey: string;
+}
+
+/**
+ * Invoice whose payments are held until settled with the preimage, or
+ * cancelled.
+ */
+export interface CreateHoldInvoiceParams {
+  amount?: number | null;
+  amount_msat?: number | null;
+  cltv_expiry?: number | null;
+  description?: string | null;
+  description_hash?: string | null;
+  expire_in?: number | null;
+  fallback_address?: string | null;
+  /**
+   * Payment hash of the invoice, in hex, the preimage being kept by the
+   * caller.
+   */
+  payment_hash: string;
+}
+
 export interface CreateInvoiceParams {
   amount?: number | null;
   amount_msat?: number | null;
@@ -37,6 +233,201 @@ export interface CreateInvoiceResult {
   payment_request: string;
 }
 
+/**
+ * TLV record of a payment, such as the podcast metadata of a boostagram.
+ */
+export interface CustomRecord {
+  record_type: number;
+  /**
+   * Value of the record, in hex.
+   */
+  value: string;
+}
+
+export interface DecodedInvoice {
+  amount?: number | null;
+  amount_msat?: number | null;
+  bolt11: string;
+  creation_date: number;
+  description?: string | null;
+  description_hash?: string | null;
+  expiry: number;
+  features: Feature[];
+  min_final_cltv_expiry: number;
+  network: Network;
+  payee: string;
+  payment_hash: string;
+  payment_secret?: string | null;
+  route_hints: RouteHint[];
+}
+
+export interface Feature {
+  bit: number;
+  is_known: boolean;
+  is_required: boolean;
+  name?: string | null;
+}
+
+/**
+ * Payment forwarded by the node from one of its channels to another.
+ */
+export interface Forward {
+  amount_in_msat: number;
+  /**
+   * Amount sent on the outgoing channel, `None` when the payment failed before an outgoing channel was picked.
+   */
+  amount_out_msat?: number | null;
+  /**
+   * Fee earned by the node, zero unless the forward is settled.
+   */
+  fee_msat: number;
+  /**
+   * Channel id of BOLT 2 of the incoming channel, in hex, `None` when the backend only tells its short channel id and the channel is closed.
+   */
+  incoming_channel_id?: string | null;
+  /**
+   * Short channel id of the incoming channel, as `block x transaction x output`.
+   */
+  incoming_short_channel_id?: string | null;
+  /**
+   * Channel id of BOLT 2 of the outgoing channel, in hex, `None` when the backend only tells its short channel id and the channel is closed.
+   */
+  outgoing_channel_id?: string | null;
+  /**
+   * Short channel id of the outgoing channel, as `block x transaction x output`.
+   */
+  outgoing_short_channel_id?: string | null;
+  /**
+   * UNIX time the incoming HTLC was received, `None` when the backend doesn't tell.
+   */
+  received_at?: number | null;
+  /**
+   * UNIX time the forward was settled or failed, `None` while it is pending or when the backend doesn't tell.
+   */
+  resolved_at?: number | null;
+  status: ForwardStatus;
+}
+
+export type ForwardStatus = "Offered" | "Settled" | "Failed";
+
+export interface Invoice {
+  amount: number;
+  amount_msat: number;
+  bolt11: string;
+  creation_date: number;
+  expiry: number;
+  memo: string;
+  payment_hash: string;
+  pre_image?: string | null;
+  settle_date?: number | null;
+  settled: boolean;
+  status: InvoiceStatus;
+}
+
+/**
+ * Position of an invoice event, to resume a subscription without missing events.
+ *
+ * Eclair has no event index, so its events have an empty cursor and its
+ * subscriptions only send the events from now on.
+ *
+ * LND subscriptions resumed without a settle index send every settlement again.
+ */
+export interface InvoiceCursor {
+  add_index?: number | null;
+  settle_index?: number | null;
+}
+
+export interface InvoiceEvent {
+  cursor: InvoiceCursor;
+  event_type: InvoiceEventType;
+  invoice: Invoice;
+}
+
+export type InvoiceEventType = "Created" | "Settled" | "Cancelled" | "Accepted";
+
+export type InvoiceStatus = "Pending" | "Settled" | "Cancelled" | "Accepted";
+
+export interface KeysendParams {
+  amount?: number | null;
+  amount_msat?: number | null;
+  /**
+   * Records sent to the destination along with the payment. Not supported
+   * by Core Lightning before v22.11.
+   */
+  custom_records?: CustomRecord[] | null;
+  /**
+   * Public key of the node to pay, in hex.
+   */
+  destination: string;
+  max_fee_msat?: number | null;
+  max_fee_percent?: number | null;
+  max_fee_sat?: number | null;
+}
+
+export interface ListForwardsParams {
+  /**
+   * Opaque pagination cursor, as returned in `ListForwardsResult::next_cursor`.
+   */
+  cursor?: number | null;
+  date_end?: number | null;
+  date_start?: number | null;
+  max_forwards?: number | null;
+}
+
+export interface ListForwardsResult {
+  forwards: Forward[];
+  /**
+   * Cursor of the next page, or `None` once all forwards have been listed.
+   */
+  next_cursor?: number | null;
+}
+
+export interface ListInvoicesParams {
+  creation_date_end?: number | null;
+  creation_date_start?: number | null;
+  /**
+   * Opaque pagination cursor, as returned in `ListInvoicesResult::next_cursor`.
+   */
+  cursor?: number | null;
+  /**
+   * Size of the pages, 100 if not set or 0. Eclair looks up the settled
+   * invoices of the page one by one, so at most this many calls are made.
+   */
+  max_invoices?: number | null;
+  pending_only?: boolean | null;
+  status?: InvoiceStatus | null;
+}
+
+export interface ListInvoicesResult {
+  invoices: Invoice[];
+  /**
+   * Cursor of the next page, or `None` once all invoices have been listed.
+   */
+  next_cursor?: number | null;
+}
+
+export interface ListPaymentsParams {
+  creation_date_end?: number | null;
+  creation_date_start?: number | null;
+  /**
+   * Opaque pagination cursor, as returned in `ListPaymentsResult::next_cursor`.
+   */
+  cursor?: number | null;
+  max_payments?: number | null;
+  /**
+   * Eclair only lists the payments that succeeded, and doesn't support the other statuses.
+   */
+  status?: PaymentStatus | null;
+}
+
+export interface ListPaymentsResult {
+  /**
+   * Cursor of the next page, or `None` once all payments have been listed.
+   */
+  next_cursor?: number | null;
+  payments: Payment[];
+}
+
 export type Network =
   | ("mainnet" | "testnet" | "regtest")
   | {
@@ -44,8 +435,16 @@ export type Network =
     };
 
 export interface NodeConfig {
+  /**
+   * Seconds to wait for a connection to the node.
+   */
+  connect_timeout?: number | null;
   macaroon?: string | null;
   password?: string | null;
+  /**
+   * Seconds to wait for the node to answer a request. Payments, channel closes and subscriptions aren't bound by it.
+   */
+  request_timeout?: number | null;
   tls_certificate?: string | null;
   tls_client_certificate?: string | null;
   tls_client_key?: string | null;
@@ -61,6 +460,76 @@ export interface NodeInfo {
   version: string;
 }
 
+export interface OnchainTransaction {
+  /**
+   * Funds the transaction brought to the wallet, in msat.
+   */
+  amount_received_msat: number;
+  /**
+   * Funds the transaction took out of the wallet, fee included, in msat.
+   */
+  amount_sent_msat: number;
+  /**
+   * Height of the block including the transaction, `None` while unconfirmed.
+   */
+  block_height?: number | null;
+  confirmations: number;
+  /**
+   * Fee paid by the wallet, in msat, `None` when the backend doesn't tell.
+   */
+  fee_msat?: number | null;
+  label?: string | null;
+  /**
+   * Short channel id of the channel the transaction opens or closes, as `block x transaction x output`.
+   */
+  short_channel_id?: string | null;
+  /**
+   * UNIX time the transaction was first seen, `None` when the backend doesn't tell.
+   */
+  timestamp?: number | null;
+  txid: string;
+}
+
+export interface OpenChannelParams {
+  /**
+   * Funding amount of the channel, in sat.
+   */
+  amount: number;
+  /**
+   * Fee rate of the funding transaction, in sat/vbyte.
+   */
+  fee_rate?: number | null;
+  /**
+   * Minimum confirmations of the outputs funding the channel.
+   */
+  min_confs?: number | null;
+  /**
+   * Address of the peer as `host:port`, to connect to it first when given.
+   */
+  peer_host?: string | null;
+  /**
+   * Public key of the peer to open the channel with, in hex.
+   */
+  peer_pubkey: string;
+  /**
+   * Whether to keep the channel unannounced to the network.
+   */
+  private?: boolean | null;
+  /**
+   * Amount given to the peer on opening, in sat.
+   */
+  push_amount?: number | null;
+}
+
+export interface OpenChannelResult {
+  /**
+   * Channel id of BOLT 2, in hex.
+   */
+  channel_id: string;
+  funding_txid: string;
+  output_index: number;
+}
+
 export interface PayInvoiceParams {
   amount?: number | null;
   amount_msat?: number | null;
@@ -74,4 +543,148 @@ export interface PayInvoiceResult {
   fees_msat?: number | null;
   payment_hash: string;
   payment_preimage: string;
+}
+
+export interface Payment {
+  /**
+   * Amount delivered to the destination, in msat.
+   */
+  amount_msat: number;
+  /**
+   * Invoice paid, `None` for keysend payments.
+   */
+  bolt11?: string | null;
+  /**
+   * UNIX time the payment was started.
+   */
+  created_at: number;
+  /**
+   * Public key of the node paid, in hex.
+   */
+  destination?: string | null;
+  failure_reason?: string | null;
+  /**
+   * Fee paid to the routing nodes, in msat, `None` when the backend doesn't tell.
+   */
+  fee_msat?: number | null;
+  /**
+   * Parts the payment was split into, empty when the backend doesn't tell them.
+   */
+  parts: PaymentPart[];
+  payment_hash: string;
+  /**
+   * Preimage of the payment, `None` until it succeeds.
+   */
+  payment_preimage?: string | null;
+  status: PaymentStatus;
+}
+
+export interface PaymentPart {
+  /**
+   * Amount delivered by the part, in msat.
+   */
+  amount_msat: number;
+  fee_msat: number;
+  status: PaymentStatus;
+}
+
+export type PaymentStatus = "Pending" | "Succeeded" | "Failed";
+
+export interface Peer {
+  /**
+   * Network addresses of the peer, as `host:port`.
+   */
+  addresses: string[];
+  connected: boolean;
+  /**
+   * Features the peer advertised, empty when the backend doesn't tell them.
+   */
+  features: Feature[];
+  /**
+   * Whether the peer opened the connection, `None` when the backend doesn't tell.
+   */
+  inbound?: boolean | null;
+  pubkey: string;
+}
+
+export interface RouteHint {
+  hops: RouteHintHop[];
+}
+
+export interface RouteHintHop {
+  cltv_expiry_delta: number;
+  fee_base_msat: number;
+  fee_proportional_millionths: number;
+  node_id: string;
+  short_channel_id: string;
+}
+
+export interface SendOnchainParams {
+  address: string;
+  /**
+   * Amount to send in sat, required unless `send_all` is set.
+   */
+  amount?: number | null;
+  /**
+   * Fee rate of the transaction, in sat/vbyte.
+   */
+  fee_rate?: number | null;
+  /**
+   * Sends all the funds of the wallet, the fee being taken out of them.
+   */
+  send_all?: boolean | null;
+  /**
+   * Number of blocks the transaction should confirm within, used to estimate the fee rate when `fee_rate` is missing.
+   */
+  target_conf?: number | null;
+  /**
+   * Outputs to spend, as `txid:output_index`, picked by the wallet when missing.
+   */
+  utxos?: string[] | null;
+}
+
+export interface SubscribeInvoicesParams {
+  /**
+   * Cursor of the last processed event. Only new events are sent when missing.
+   */
+  cursor?: InvoiceCursor | null;
+}
+
+/**
+ * Changes of the forwarding policy, the fields left to `None` are kept.
+ */
+export interface UpdatePolicyParams {
+  base_fee_msat?: number | null;
+  /**
+   * Channel id of BOLT 2 of the channel to update, every channel if `None`.
+   * Eclair sets the fees by peer, and fails when the peer has other channels.
+   */
+  channel_id?: string | null;
+  /**
+   * Proportional fee, in millionths of the forwarded amount.
+   */
+  fee_rate_ppm?: number | null;
+  max_htlc_msat?: number | null;
+  min_htlc_msat?: number | null;
+  time_lock_delta?: number | null;
+}
+
+export interface VerifyMessageParams {
+  message: string;
+  /**
+   * Public key the signature must be made by, in hex. Without it, LND and Core Lightning only accept the signatures of nodes in their graph.
+   */
+  pubkey?: string | null;
+  /**
+   * Signature of the message, in zbase32 as returned by `sign_message`.
+   */
+  signature: string;
+}
+
+export interface VerifyMessageResult {
+  /**
+   * Public key recovered from the signature, in hex.
+   */
+  pubkey: string;
+  valid: boolean;
 }
\ No newline at end of file
@@ -236,6 +236,8 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { Node } = nativeBinding
+const { decodeInvoice, InvoiceSubscription, Node } = nativeBinding
 
+module.exports.decodeInvoice = decodeInvoice
+module.exports.InvoiceSubscription = InvoiceSubscription
 module.exports.Node = Node
@@ -13,6 +13,7 @@ impl From<NapiError> for Error {
 
         let (status, reason) = match una_err {
             UnaError::InvalidBackend => (Status::InvalidArg, reason),
+            UnaError::InvalidInvoice(_) => (Status::InvalidArg, reason),
             UnaError::ConfigError(config_err) => {
                 let reason = config_err.to_string();
 
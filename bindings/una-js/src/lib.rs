@@ -6,26 +6,63 @@ extern crate napi_derive;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-use napi::{Env, JsObject, Result};
+use futures::StreamExt;
+use napi::{
+    bindgen_prelude::spawn,
+    threadsafe_function::{
+        ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+    },
+    Env, JsFunction, JsObject, JsString, JsUnknown, Result,
+};
+use tokio::task::JoinHandle;
 
 use una_core::{
-    backends::{
-        cln::grpc::{config::ClnGrpcConfig, node::ClnGrpc},
-        eclair::rest::{config::EclairRestConfig, node::EclairRest},
-        lnd::rest::{config::LndRestConfig, node::LndRest},
-    },
-    error::Error as UnaError,
+    bolt11,
     node::{Node, NodeMethods},
-    types::{Backend, CreateInvoiceParams, NodeConfig, NodeInfo, PayInvoiceParams},
+    types::{
+        AddressType, Backend, CloseChannelParams, ConnectPeerParams, CreateHoldInvoiceParams,
+        CreateInvoiceParams, DecodedInvoice, Invoice, InvoiceEvent, KeysendParams,
+        ListForwardsParams, ListInvoicesParams, ListPaymentsParams, NodeConfig, NodeInfo,
+        OpenChannelParams, PayInvoiceParams, Payment, SendOnchainParams, SubscribeInvoicesParams,
+        UpdatePolicyParams, VerifyMessageParams,
+    },
 };
 
 pub mod error;
 
 use error::OrNapiError;
 
+#[napi(ts_args_type = "bolt11: string", ts_return_type = "DecodedInvoice")]
+pub fn decode_invoice(env: Env, bolt11: String) -> Result<JsUnknown> {
+    let invoice: DecodedInvoice = bolt11::decode_invoice(&bolt11).or_napi_error()?;
+    env.to_js_value(&invoice)
+}
+
 #[napi(js_name = "Node")]
 struct JsNode(Arc<Mutex<Node>>);
 
+#[napi(js_name = "InvoiceSubscription")]
+struct JsInvoiceSubscription(JoinHandle<()>);
+
+#[napi]
+impl JsInvoiceSubscription {
+    #[napi]
+    pub fn close(&self) {
+        self.0.abort();
+    }
+}
+
+#[napi(js_name = "PaymentSubscription")]
+struct JsPaymentSubscription(JoinHandle<()>);
+
+#[napi]
+impl JsPaymentSubscription {
+    #[napi]
+    pub fn close(&self) {
+        self.0.abort();
+    }
+}
+
 #[napi]
 impl JsNode {
     #[napi(constructor, ts_args_type = "backend: Backend, config: NodeConfig")]
@@ -33,36 +70,111 @@ impl JsNode {
         let backend: Backend = backend.as_str().into();
         let config: NodeConfig = env.from_js_value(config)?;
 
-        let node = match backend {
-            Backend::LndRest => {
-                let config = TryInto::<LndRestConfig>::try_into(config).or_napi_error()?;
-                let node = LndRest::new(config).or_napi_error()?;
-                Ok(Node {
-                    backend,
-                    node: Box::new(node),
-                })
-            }
-            Backend::ClnGrpc => {
-                let config = TryInto::<ClnGrpcConfig>::try_into(config).or_napi_error()?;
-                let node = ClnGrpc::new(config).or_napi_error()?;
-                Ok(Node {
-                    backend,
-                    node: Box::new(node),
-                })
-            }
-            Backend::EclairRest => {
-                let config = TryInto::<EclairRestConfig>::try_into(config).or_napi_error()?;
-                let node = EclairRest::new(config).or_napi_error()?;
-                Ok(Node {
-                    backend,
-                    node: Box::new(node),
-                })
-            }
-            Backend::LndGrpc => todo!(),
-            Backend::InvalidBackend => Err(UnaError::InvalidBackend),
-        };
+        let node = Node::new(backend, config).or_napi_error()?;
+
+        Ok(Self(Arc::new(Mutex::new(node))))
+    }
+
+    #[napi(ts_args_type = "paymentHash: string", ts_return_type = "Promise<void>")]
+    pub fn cancel_hold_invoice(&self, env: Env, payment_hash: String) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        env.execute_tokio_future(
+            async move {
+                node.lock()
+                    .await
+                    .cancel_hold_invoice(payment_hash)
+                    .await
+                    .or_napi_error()?;
+                Ok(())
+            },
+            |&mut env, _| env.get_undefined(),
+        )
+    }
+
+    #[napi(ts_args_type = "paymentHash: string", ts_return_type = "Promise<void>")]
+    pub fn cancel_invoice(&self, env: Env, payment_hash: String) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        env.execute_tokio_future(
+            async move {
+                node.lock()
+                    .await
+                    .cancel_invoice(payment_hash)
+                    .await
+                    .or_napi_error()?;
+                Ok(())
+            },
+            |&mut env, _| env.get_undefined(),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "params: CloseChannelParams",
+        ts_return_type = "Promise<CloseChannelResult>"
+    )]
+    pub fn close_channel(&self, env: Env, params: JsObject) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        let params: CloseChannelParams = env.from_js_value(params)?;
+
+        env.execute_tokio_future(
+            async move {
+                let result = node
+                    .lock()
+                    .await
+                    .close_channel(params)
+                    .await
+                    .or_napi_error()?;
+                Ok(result)
+            },
+            |&mut env, result| Ok(env.to_js_value(&result)),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "params: ConnectPeerParams",
+        ts_return_type = "Promise<void>"
+    )]
+    pub fn connect_peer(&self, env: Env, params: JsObject) -> Result<JsObject> {
+        let node = self.0.clone();
 
-        Ok(Self(Arc::new(Mutex::new(node.or_napi_error()?))))
+        let params: ConnectPeerParams = env.from_js_value(params)?;
+
+        env.execute_tokio_future(
+            async move {
+                node.lock()
+                    .await
+                    .connect_peer(params)
+                    .await
+                    .or_napi_error()?;
+                Ok(())
+            },
+            |&mut env, _| env.get_undefined(),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "params: CreateHoldInvoiceParams",
+        ts_return_type = "Promise<CreateInvoiceResult>"
+    )]
+    pub fn create_hold_invoice(&self, env: Env, params: JsObject) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        let params: CreateHoldInvoiceParams = env.from_js_value(params)?;
+
+        env.execute_tokio_future(
+            async move {
+                let result = node
+                    .lock()
+                    .await
+                    .create_hold_invoice(params)
+                    .await
+                    .or_napi_error()?;
+                Ok(result)
+            },
+            |&mut env, result| Ok(env.to_js_value(&result)),
+        )
     }
 
     #[napi(
@@ -88,6 +200,98 @@ impl JsNode {
         )
     }
 
+    #[napi(
+        ts_args_type = "bolt11: string",
+        ts_return_type = "Promise<DecodedInvoice>"
+    )]
+    pub fn decode_invoice(&self, env: Env, bolt11: String) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        env.execute_tokio_future(
+            async move {
+                let invoice = node
+                    .lock()
+                    .await
+                    .decode_invoice(bolt11)
+                    .await
+                    .or_napi_error()?;
+                Ok(invoice)
+            },
+            |&mut env, invoice| Ok(env.to_js_value(&invoice)),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "before?: number | null",
+        ts_return_type = "Promise<void>"
+    )]
+    pub fn delete_expired_invoices(&self, env: Env, before: Option<u32>) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        env.execute_tokio_future(
+            async move {
+                node.lock()
+                    .await
+                    .delete_expired_invoices(before.map(u64::from))
+                    .await
+                    .or_napi_error()?;
+                Ok(())
+            },
+            |&mut env, _| env.get_undefined(),
+        )
+    }
+
+    #[napi(ts_args_type = "pubkey: string", ts_return_type = "Promise<void>")]
+    pub fn disconnect_peer(&self, env: Env, pubkey: String) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        env.execute_tokio_future(
+            async move {
+                node.lock()
+                    .await
+                    .disconnect_peer(pubkey)
+                    .await
+                    .or_napi_error()?;
+                Ok(())
+            },
+            |&mut env, _| env.get_undefined(),
+        )
+    }
+
+    #[napi(ts_return_type = "Promise<Balance>")]
+    pub fn get_balance(&self, env: Env) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        env.execute_tokio_future(
+            async move {
+                let balance = node.lock().await.get_balance().await.or_napi_error()?;
+                Ok(balance)
+            },
+            |&mut env, balance| Ok(env.to_js_value(&balance)),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "channelId: string",
+        ts_return_type = "Promise<ChannelPolicy>"
+    )]
+    pub fn get_channel_policy(&self, env: Env, channel_id: String) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        env.execute_tokio_future(
+            async move {
+                let policy = node
+                    .lock()
+                    .await
+                    .get_channel_policy(channel_id)
+                    .await
+                    .or_napi_error()?;
+                Ok(policy)
+            },
+            |&mut env, policy| Ok(env.to_js_value(&policy)),
+        )
+    }
+
     #[napi(ts_return_type = "Promise<NodeInfo>")]
     pub fn get_info(&self, env: Env) -> Result<JsObject> {
         let node = self.0.clone();
@@ -101,6 +305,225 @@ impl JsNode {
         )
     }
 
+    #[napi(
+        ts_args_type = "paymentHash: string",
+        ts_return_type = "Promise<Invoice>"
+    )]
+    pub fn get_invoice(&self, env: Env, payment_hash: String) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        env.execute_tokio_future(
+            async move {
+                let invoice: Invoice = node
+                    .lock()
+                    .await
+                    .get_invoice(payment_hash)
+                    .await
+                    .or_napi_error()?;
+                Ok(invoice)
+            },
+            |&mut env, invoice| Ok(env.to_js_value(&invoice)),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "paymentHash: string",
+        ts_return_type = "Promise<Payment>"
+    )]
+    pub fn get_payment(&self, env: Env, payment_hash: String) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        env.execute_tokio_future(
+            async move {
+                let payment = node
+                    .lock()
+                    .await
+                    .get_payment(payment_hash)
+                    .await
+                    .or_napi_error()?;
+                Ok(payment)
+            },
+            |&mut env, payment| Ok(env.to_js_value(&payment)),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "params: KeysendParams",
+        ts_return_type = "Promise<PayInvoiceResult>"
+    )]
+    pub fn keysend(&self, env: Env, params: JsObject) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        let params: KeysendParams = env.from_js_value(params)?;
+
+        env.execute_tokio_future(
+            async move {
+                let result = node.lock().await.keysend(params).await.or_napi_error()?;
+                Ok(result)
+            },
+            |&mut env, result| Ok(env.to_js_value(&result)),
+        )
+    }
+
+    #[napi(ts_return_type = "Promise<Channel[]>")]
+    pub fn list_channels(&self, env: Env) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        env.execute_tokio_future(
+            async move {
+                let channels = node.lock().await.list_channels().await.or_napi_error()?;
+                Ok(channels)
+            },
+            |&mut env, channels| Ok(env.to_js_value(&channels)),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "params: ListForwardsParams",
+        ts_return_type = "Promise<ListForwardsResult>"
+    )]
+    pub fn list_forwards(&self, env: Env, params: JsObject) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        let params: ListForwardsParams = env.from_js_value(params)?;
+
+        env.execute_tokio_future(
+            async move {
+                let forwards = node
+                    .lock()
+                    .await
+                    .list_forwards(params)
+                    .await
+                    .or_napi_error()?;
+                Ok(forwards)
+            },
+            |&mut env, forwards| Ok(env.to_js_value(&forwards)),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "params: ListInvoicesParams",
+        ts_return_type = "Promise<ListInvoicesResult>"
+    )]
+    pub fn list_invoices(&self, env: Env, params: JsObject) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        let params: ListInvoicesParams = env.from_js_value(params)?;
+
+        env.execute_tokio_future(
+            async move {
+                let invoices = node
+                    .lock()
+                    .await
+                    .list_invoices(params)
+                    .await
+                    .or_napi_error()?;
+                Ok(invoices)
+            },
+            |&mut env, invoices| Ok(env.to_js_value(&invoices)),
+        )
+    }
+
+    #[napi(ts_return_type = "Promise<OnchainTransaction[]>")]
+    pub fn list_onchain_transactions(&self, env: Env) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        env.execute_tokio_future(
+            async move {
+                let transactions = node
+                    .lock()
+                    .await
+                    .list_onchain_transactions()
+                    .await
+                    .or_napi_error()?;
+                Ok(transactions)
+            },
+            |&mut env, transactions| Ok(env.to_js_value(&transactions)),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "params: ListPaymentsParams",
+        ts_return_type = "Promise<ListPaymentsResult>"
+    )]
+    pub fn list_payments(&self, env: Env, params: JsObject) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        let params: ListPaymentsParams = env.from_js_value(params)?;
+
+        env.execute_tokio_future(
+            async move {
+                let payments = node
+                    .lock()
+                    .await
+                    .list_payments(params)
+                    .await
+                    .or_napi_error()?;
+                Ok(payments)
+            },
+            |&mut env, payments| Ok(env.to_js_value(&payments)),
+        )
+    }
+
+    #[napi(ts_return_type = "Promise<Peer[]>")]
+    pub fn list_peers(&self, env: Env) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        env.execute_tokio_future(
+            async move {
+                let peers = node.lock().await.list_peers().await.or_napi_error()?;
+                Ok(peers)
+            },
+            |&mut env, peers| Ok(env.to_js_value(&peers)),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "addressType: AddressType",
+        ts_return_type = "Promise<string>"
+    )]
+    pub fn new_address(&self, env: Env, address_type: JsString) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        let address_type: AddressType = env.from_js_value(address_type)?;
+
+        env.execute_tokio_future(
+            async move {
+                let address = node
+                    .lock()
+                    .await
+                    .new_address(address_type)
+                    .await
+                    .or_napi_error()?;
+                Ok(address)
+            },
+            |&mut env, address| Ok(env.to_js_value(&address)),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "params: OpenChannelParams",
+        ts_return_type = "Promise<OpenChannelResult>"
+    )]
+    pub fn open_channel(&self, env: Env, params: JsObject) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        let params: OpenChannelParams = env.from_js_value(params)?;
+
+        env.execute_tokio_future(
+            async move {
+                let result = node
+                    .lock()
+                    .await
+                    .open_channel(params)
+                    .await
+                    .or_napi_error()?;
+                Ok(result)
+            },
+            |&mut env, result| Ok(env.to_js_value(&result)),
+        )
+    }
+
     #[napi(
         ts_args_type = "invoice: PayInvoiceParams",
         ts_return_type = "Promise<PayInvoiceResult>"
@@ -123,4 +546,202 @@ impl JsNode {
             |&mut env, payreq| Ok(env.to_js_value(&payreq)),
         )
     }
+
+    #[napi(
+        ts_args_type = "params: SendOnchainParams",
+        ts_return_type = "Promise<string>"
+    )]
+    pub fn send_onchain(&self, env: Env, params: JsObject) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        let params: SendOnchainParams = env.from_js_value(params)?;
+
+        env.execute_tokio_future(
+            async move {
+                let txid = node
+                    .lock()
+                    .await
+                    .send_onchain(params)
+                    .await
+                    .or_napi_error()?;
+                Ok(txid)
+            },
+            |&mut env, txid| Ok(env.to_js_value(&txid)),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "invoice: PayInvoiceParams",
+        ts_return_type = "Promise<string>"
+    )]
+    pub fn send_payment_async(&self, env: Env, params: JsObject) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        let params: PayInvoiceParams = env.from_js_value(params)?;
+
+        env.execute_tokio_future(
+            async move {
+                let payment_hash = node
+                    .lock()
+                    .await
+                    .send_payment_async(params)
+                    .await
+                    .or_napi_error()?;
+                Ok(payment_hash)
+            },
+            |&mut env, payment_hash| Ok(env.to_js_value(&payment_hash)),
+        )
+    }
+
+    #[napi(ts_args_type = "preimage: string", ts_return_type = "Promise<void>")]
+    pub fn settle_hold_invoice(&self, env: Env, preimage: String) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        env.execute_tokio_future(
+            async move {
+                node.lock()
+                    .await
+                    .settle_hold_invoice(preimage)
+                    .await
+                    .or_napi_error()?;
+                Ok(())
+            },
+            |&mut env, _| env.get_undefined(),
+        )
+    }
+
+    #[napi(ts_args_type = "message: string", ts_return_type = "Promise<string>")]
+    pub fn sign_message(&self, env: Env, message: String) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        env.execute_tokio_future(
+            async move {
+                let signature = node
+                    .lock()
+                    .await
+                    .sign_message(message)
+                    .await
+                    .or_napi_error()?;
+                Ok(signature)
+            },
+            |&mut env, signature| Ok(env.to_js_value(&signature)),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "params: SubscribeInvoicesParams, callback: (err: Error | null, event: InvoiceEvent) => void"
+    )]
+    pub fn subscribe_invoices(
+        &self,
+        env: Env,
+        params: JsObject,
+        callback: JsFunction,
+    ) -> Result<JsInvoiceSubscription> {
+        let node = self.0.clone();
+
+        let params: SubscribeInvoicesParams = env.from_js_value(params)?;
+        let callback: ThreadsafeFunction<InvoiceEvent, ErrorStrategy::CalleeHandled> = callback
+            .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<InvoiceEvent>| {
+                Ok(vec![ctx.env.to_js_value(&ctx.value)?])
+            })?;
+
+        let handle = spawn(async move {
+            let stream = node.lock().await.subscribe_invoices(params).await;
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    let err = Err(err).or_napi_error().map_err(Into::into);
+                    callback.call(err, ThreadsafeFunctionCallMode::NonBlocking);
+                    return;
+                }
+            };
+
+            while let Some(event) = stream.next().await {
+                let event = event.or_napi_error().map_err(Into::into);
+                callback.call(event, ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        });
+
+        Ok(JsInvoiceSubscription(handle))
+    }
+
+    #[napi(
+        ts_args_type = "paymentHash: string, callback: (err: Error | null, payment: Payment) => void"
+    )]
+    pub fn track_payment(
+        &self,
+        payment_hash: String,
+        callback: JsFunction,
+    ) -> Result<JsPaymentSubscription> {
+        let node = self.0.clone();
+
+        let callback: ThreadsafeFunction<Payment, ErrorStrategy::CalleeHandled> = callback
+            .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<Payment>| {
+                Ok(vec![ctx.env.to_js_value(&ctx.value)?])
+            })?;
+
+        let handle = spawn(async move {
+            let stream = node.lock().await.track_payment(payment_hash).await;
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    let err = Err(err).or_napi_error().map_err(Into::into);
+                    callback.call(err, ThreadsafeFunctionCallMode::NonBlocking);
+                    return;
+                }
+            };
+
+            while let Some(payment) = stream.next().await {
+                let payment = payment.or_napi_error().map_err(Into::into);
+                callback.call(payment, ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        });
+
+        Ok(JsPaymentSubscription(handle))
+    }
+
+    #[napi(
+        ts_args_type = "params: UpdatePolicyParams",
+        ts_return_type = "Promise<void>"
+    )]
+    pub fn update_channel_policy(&self, env: Env, params: JsObject) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        let params: UpdatePolicyParams = env.from_js_value(params)?;
+
+        env.execute_tokio_future(
+            async move {
+                node.lock()
+                    .await
+                    .update_channel_policy(params)
+                    .await
+                    .or_napi_error()?;
+                Ok(())
+            },
+            |&mut env, _| env.get_undefined(),
+        )
+    }
+
+    #[napi(
+        ts_args_type = "params: VerifyMessageParams",
+        ts_return_type = "Promise<VerifyMessageResult>"
+    )]
+    pub fn verify_message(&self, env: Env, params: JsObject) -> Result<JsObject> {
+        let node = self.0.clone();
+
+        let params: VerifyMessageParams = env.from_js_value(params)?;
+
+        env.execute_tokio_future(
+            async move {
+                let result = node
+                    .lock()
+                    .await
+                    .verify_message(params)
+                    .await
+                    .or_napi_error()?;
+                Ok(result)
+            },
+            |&mut env, result| Ok(env.to_js_value(&result)),
+        )
+    }
 }
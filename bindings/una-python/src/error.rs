@@ -21,6 +21,7 @@ impl From<PyError> for PyErr {
 
         match una_err {
             UnaError::InvalidBackend => PyValueError::new_err(message),
+            UnaError::InvalidInvoice(_) => PyValueError::new_err(message),
             UnaError::ConfigError(config_err) => {
                 let message = config_err.to_string();
 
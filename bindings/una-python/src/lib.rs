@@ -1,18 +1,21 @@
-use pyo3::{exceptions::PyValueError, prelude::*};
+use futures::StreamExt;
+use pyo3::{exceptions::PyStopAsyncIteration, prelude::*};
 use pythonize::{depythonize, pythonize};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use una_core::{
-    backends::{
-        cln::grpc::{config::ClnGrpcConfig, node::ClnGrpc},
-        eclair::rest::{config::EclairRestConfig, node::EclairRest},
-        lnd::rest::{config::LndRestConfig, node::LndRest},
-    },
-    node::{Node, NodeMethods},
+    bolt11,
+    node::{InvoiceStream, Node, NodeMethods, PaymentStream},
     types::{
-        Backend, CreateInvoiceParams, CreateInvoiceResult, NodeConfig, NodeInfo, PayInvoiceParams,
-        PayInvoiceResult,
+        AddressType, Backend, Balance, Channel, ChannelPolicy, CloseChannelParams,
+        CloseChannelResult, ConnectPeerParams, CreateHoldInvoiceParams, CreateInvoiceParams,
+        CreateInvoiceResult, DecodedInvoice, Invoice, InvoiceEvent, KeysendParams,
+        ListForwardsParams, ListForwardsResult, ListInvoicesParams, ListInvoicesResult,
+        ListPaymentsParams, ListPaymentsResult, NodeConfig, NodeInfo, OnchainTransaction,
+        OpenChannelParams, OpenChannelResult, PayInvoiceParams, PayInvoiceResult, Payment, Peer,
+        SendOnchainParams, SubscribeInvoicesParams, UpdatePolicyParams, VerifyMessageParams,
+        VerifyMessageResult,
     },
 };
 
@@ -23,6 +26,62 @@ use error::{OrPyError, PyApiError, PyConfigError};
 #[pyclass(name = "Node")]
 struct PyNode(Arc<Mutex<Node>>);
 
+#[pyclass(name = "InvoiceSubscription")]
+struct PyInvoiceSubscription(Arc<Mutex<InvoiceStream>>);
+
+#[pymethods]
+impl PyInvoiceSubscription {
+    fn __aiter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __anext__(&self, py: Python) -> PyResult<Option<PyObject>> {
+        let stream = self.0.clone();
+
+        let event = pyo3_asyncio::tokio::future_into_py(py, async move {
+            match stream.lock().await.next().await {
+                Some(event) => {
+                    let event = event.or_py_error()?;
+                    let event =
+                        Python::with_gil(|py| pythonize::<InvoiceEvent>(py, &event).or_py_error())?;
+                    Ok(event)
+                }
+                None => Err(PyStopAsyncIteration::new_err("subscription closed")),
+            }
+        })?;
+
+        Ok(Some(event.into()))
+    }
+}
+
+#[pyclass(name = "PaymentSubscription")]
+struct PyPaymentSubscription(Arc<Mutex<PaymentStream>>);
+
+#[pymethods]
+impl PyPaymentSubscription {
+    fn __aiter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __anext__(&self, py: Python) -> PyResult<Option<PyObject>> {
+        let stream = self.0.clone();
+
+        let payment = pyo3_asyncio::tokio::future_into_py(py, async move {
+            match stream.lock().await.next().await {
+                Some(payment) => {
+                    let payment = payment.or_py_error()?;
+                    let payment =
+                        Python::with_gil(|py| pythonize::<Payment>(py, &payment).or_py_error())?;
+                    Ok(payment)
+                }
+                None => Err(PyStopAsyncIteration::new_err("payment tracking ended")),
+            }
+        })?;
+
+        Ok(Some(payment.into()))
+    }
+}
+
 #[pymethods]
 impl PyNode {
     #[new]
@@ -31,34 +90,92 @@ impl PyNode {
         let config =
             Python::with_gil(|py| depythonize::<NodeConfig>(config.as_ref(py)).or_py_error())?;
 
-        match backend {
-            Backend::LndRest => {
-                let config = TryInto::<LndRestConfig>::try_into(config).or_py_error()?;
-                let node = LndRest::new(config).or_py_error()?;
-                Ok(Self(Arc::new(Mutex::new(Node {
-                    backend: Backend::LndRest,
-                    node: Box::new(node),
-                }))))
-            }
-            Backend::ClnGrpc => {
-                let config = TryInto::<ClnGrpcConfig>::try_into(config).or_py_error()?;
-                let node = ClnGrpc::new(config).or_py_error()?;
-                Ok(Self(Arc::new(Mutex::new(Node {
-                    backend: Backend::ClnGrpc,
-                    node: Box::new(node),
-                }))))
-            }
-            Backend::EclairRest => {
-                let config = TryInto::<EclairRestConfig>::try_into(config).or_py_error()?;
-                let node = EclairRest::new(config).or_py_error()?;
-                Ok(Self(Arc::new(Mutex::new(Node {
-                    backend: Backend::EclairRest,
-                    node: Box::new(node),
-                }))))
-            }
-            Backend::LndGrpc => todo!(),
-            Backend::InvalidBackend => Err(PyValueError::new_err("Invalid backend")),
-        }
+        let node = Node::new(backend, config).or_py_error()?;
+
+        Ok(Self(Arc::new(Mutex::new(node))))
+    }
+
+    pub fn cancel_hold_invoice<'p>(
+        &self,
+        py: Python<'p>,
+        payment_hash: String,
+    ) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            node.lock()
+                .await
+                .cancel_hold_invoice(payment_hash)
+                .await
+                .or_py_error()?;
+            Ok(Python::with_gil(|py| py.None()))
+        })
+    }
+
+    pub fn cancel_invoice<'p>(&self, py: Python<'p>, payment_hash: String) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            node.lock()
+                .await
+                .cancel_invoice(payment_hash)
+                .await
+                .or_py_error()?;
+            Ok(Python::with_gil(|py| py.None()))
+        })
+    }
+
+    pub fn close_channel<'p>(&self, py: Python<'p>, params: PyObject) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        let params = Python::with_gil(|py| {
+            depythonize::<CloseChannelParams>(params.as_ref(py)).or_py_error()
+        })?;
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node
+                .lock()
+                .await
+                .close_channel(params)
+                .await
+                .or_py_error()?;
+            let result =
+                Python::with_gil(|py| pythonize::<CloseChannelResult>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn connect_peer<'p>(&self, py: Python<'p>, params: PyObject) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        let params = Python::with_gil(|py| {
+            depythonize::<ConnectPeerParams>(params.as_ref(py)).or_py_error()
+        })?;
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            node.lock().await.connect_peer(params).await.or_py_error()?;
+            Ok(Python::with_gil(|py| py.None()))
+        })
+    }
+
+    pub fn create_hold_invoice<'p>(&self, py: Python<'p>, params: PyObject) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        let params = Python::with_gil(|py| {
+            depythonize::<CreateHoldInvoiceParams>(params.as_ref(py)).or_py_error()
+        })?;
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node
+                .lock()
+                .await
+                .create_hold_invoice(params)
+                .await
+                .or_py_error()?;
+            let result =
+                Python::with_gil(|py| pythonize::<CreateInvoiceResult>(py, &result).or_py_error())?;
+            Ok(result)
+        })
     }
 
     pub fn create_invoice<'p>(&self, py: Python<'p>, invoice: PyObject) -> PyResult<&'p PyAny> {
@@ -81,6 +198,82 @@ impl PyNode {
         })
     }
 
+    pub fn decode_invoice<'p>(&self, py: Python<'p>, bolt11: String) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node
+                .lock()
+                .await
+                .decode_invoice(bolt11)
+                .await
+                .or_py_error()?;
+            let result =
+                Python::with_gil(|py| pythonize::<DecodedInvoice>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn delete_expired_invoices<'p>(
+        &self,
+        py: Python<'p>,
+        before: Option<u64>,
+    ) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            node.lock()
+                .await
+                .delete_expired_invoices(before)
+                .await
+                .or_py_error()?;
+            Ok(Python::with_gil(|py| py.None()))
+        })
+    }
+
+    pub fn disconnect_peer<'p>(&self, py: Python<'p>, pubkey: String) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            node.lock()
+                .await
+                .disconnect_peer(pubkey)
+                .await
+                .or_py_error()?;
+            Ok(Python::with_gil(|py| py.None()))
+        })
+    }
+
+    pub fn get_balance<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node.lock().await.get_balance().await.or_py_error()?;
+            let result = Python::with_gil(|py| pythonize::<Balance>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn get_channel_policy<'p>(
+        &self,
+        py: Python<'p>,
+        channel_id: String,
+    ) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node
+                .lock()
+                .await
+                .get_channel_policy(channel_id)
+                .await
+                .or_py_error()?;
+            let result =
+                Python::with_gil(|py| pythonize::<ChannelPolicy>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
     pub fn get_info<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
         let node = self.0.clone();
 
@@ -91,6 +284,182 @@ impl PyNode {
         })
     }
 
+    pub fn get_invoice<'p>(&self, py: Python<'p>, payment_hash: String) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node
+                .lock()
+                .await
+                .get_invoice(payment_hash)
+                .await
+                .or_py_error()?;
+            let result = Python::with_gil(|py| pythonize::<Invoice>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn get_payment<'p>(&self, py: Python<'p>, payment_hash: String) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node
+                .lock()
+                .await
+                .get_payment(payment_hash)
+                .await
+                .or_py_error()?;
+            let result = Python::with_gil(|py| pythonize::<Payment>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn keysend<'p>(&self, py: Python<'p>, params: PyObject) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        let params =
+            Python::with_gil(|py| depythonize::<KeysendParams>(params.as_ref(py)).or_py_error())?;
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node.lock().await.keysend(params).await.or_py_error()?;
+            let result =
+                Python::with_gil(|py| pythonize::<PayInvoiceResult>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn list_channels<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node.lock().await.list_channels().await.or_py_error()?;
+            let result =
+                Python::with_gil(|py| pythonize::<Vec<Channel>>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn list_forwards<'p>(&self, py: Python<'p>, params: PyObject) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        let params = Python::with_gil(|py| {
+            depythonize::<ListForwardsParams>(params.as_ref(py)).or_py_error()
+        })?;
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node
+                .lock()
+                .await
+                .list_forwards(params)
+                .await
+                .or_py_error()?;
+            let result =
+                Python::with_gil(|py| pythonize::<ListForwardsResult>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn list_invoices<'p>(&self, py: Python<'p>, params: PyObject) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        let params = Python::with_gil(|py| {
+            depythonize::<ListInvoicesParams>(params.as_ref(py)).or_py_error()
+        })?;
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node
+                .lock()
+                .await
+                .list_invoices(params)
+                .await
+                .or_py_error()?;
+            let result =
+                Python::with_gil(|py| pythonize::<ListInvoicesResult>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn list_onchain_transactions<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node
+                .lock()
+                .await
+                .list_onchain_transactions()
+                .await
+                .or_py_error()?;
+            let result = Python::with_gil(|py| {
+                pythonize::<Vec<OnchainTransaction>>(py, &result).or_py_error()
+            })?;
+            Ok(result)
+        })
+    }
+
+    pub fn list_payments<'p>(&self, py: Python<'p>, params: PyObject) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        let params = Python::with_gil(|py| {
+            depythonize::<ListPaymentsParams>(params.as_ref(py)).or_py_error()
+        })?;
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node
+                .lock()
+                .await
+                .list_payments(params)
+                .await
+                .or_py_error()?;
+            let result =
+                Python::with_gil(|py| pythonize::<ListPaymentsResult>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn list_peers<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node.lock().await.list_peers().await.or_py_error()?;
+            let result = Python::with_gil(|py| pythonize::<Vec<Peer>>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn new_address<'p>(&self, py: Python<'p>, address_type: PyObject) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        let address_type = Python::with_gil(|py| {
+            depythonize::<AddressType>(address_type.as_ref(py)).or_py_error()
+        })?;
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node
+                .lock()
+                .await
+                .new_address(address_type)
+                .await
+                .or_py_error()?;
+            let result = Python::with_gil(|py| pythonize::<String>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn open_channel<'p>(&self, py: Python<'p>, params: PyObject) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        let params = Python::with_gil(|py| {
+            depythonize::<OpenChannelParams>(params.as_ref(py)).or_py_error()
+        })?;
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node.lock().await.open_channel(params).await.or_py_error()?;
+            let result =
+                Python::with_gil(|py| pythonize::<OpenChannelResult>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
     pub fn pay_invoice<'p>(&self, py: Python<'p>, invoice: PyObject) -> PyResult<&'p PyAny> {
         let node = self.0.clone();
 
@@ -105,12 +474,156 @@ impl PyNode {
             Ok(result)
         })
     }
+
+    pub fn send_onchain<'p>(&self, py: Python<'p>, params: PyObject) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        let params = Python::with_gil(|py| {
+            depythonize::<SendOnchainParams>(params.as_ref(py)).or_py_error()
+        })?;
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node.lock().await.send_onchain(params).await.or_py_error()?;
+            let result = Python::with_gil(|py| pythonize::<String>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn send_payment_async<'p>(&self, py: Python<'p>, params: PyObject) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        let params = Python::with_gil(|py| {
+            depythonize::<PayInvoiceParams>(params.as_ref(py)).or_py_error()
+        })?;
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node
+                .lock()
+                .await
+                .send_payment_async(params)
+                .await
+                .or_py_error()?;
+            let result = Python::with_gil(|py| pythonize::<String>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn settle_hold_invoice<'p>(&self, py: Python<'p>, preimage: String) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            node.lock()
+                .await
+                .settle_hold_invoice(preimage)
+                .await
+                .or_py_error()?;
+            Ok(Python::with_gil(|py| py.None()))
+        })
+    }
+
+    pub fn sign_message<'p>(&self, py: Python<'p>, message: String) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node
+                .lock()
+                .await
+                .sign_message(message)
+                .await
+                .or_py_error()?;
+            let result = Python::with_gil(|py| pythonize::<String>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+
+    pub fn subscribe_invoices<'p>(&self, py: Python<'p>, params: PyObject) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        let params = Python::with_gil(|py| {
+            depythonize::<SubscribeInvoicesParams>(params.as_ref(py)).or_py_error()
+        })?;
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let stream = node
+                .lock()
+                .await
+                .subscribe_invoices(params)
+                .await
+                .or_py_error()?;
+            Ok(PyInvoiceSubscription(Arc::new(Mutex::new(stream))))
+        })
+    }
+
+    pub fn track_payment<'p>(&self, py: Python<'p>, payment_hash: String) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let stream = node
+                .lock()
+                .await
+                .track_payment(payment_hash)
+                .await
+                .or_py_error()?;
+            Ok(PyPaymentSubscription(Arc::new(Mutex::new(stream))))
+        })
+    }
+
+    pub fn update_channel_policy<'p>(
+        &self,
+        py: Python<'p>,
+        params: PyObject,
+    ) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        let params = Python::with_gil(|py| {
+            depythonize::<UpdatePolicyParams>(params.as_ref(py)).or_py_error()
+        })?;
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            node.lock()
+                .await
+                .update_channel_policy(params)
+                .await
+                .or_py_error()?;
+            Ok(Python::with_gil(|py| py.None()))
+        })
+    }
+
+    pub fn verify_message<'p>(&self, py: Python<'p>, params: PyObject) -> PyResult<&'p PyAny> {
+        let node = self.0.clone();
+
+        let params = Python::with_gil(|py| {
+            depythonize::<VerifyMessageParams>(params.as_ref(py)).or_py_error()
+        })?;
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let result = node
+                .lock()
+                .await
+                .verify_message(params)
+                .await
+                .or_py_error()?;
+            let result =
+                Python::with_gil(|py| pythonize::<VerifyMessageResult>(py, &result).or_py_error())?;
+            Ok(result)
+        })
+    }
+}
+
+#[pyfunction]
+fn decode_invoice(py: Python, bolt11: &str) -> PyResult<PyObject> {
+    let invoice = bolt11::decode_invoice(bolt11).or_py_error()?;
+    let invoice = pythonize::<DecodedInvoice>(py, &invoice).or_py_error()?;
+    Ok(invoice)
 }
 
 /// A Python module implemented in Rust.
 #[pymodule]
 fn una(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<PyNode>()?;
+    m.add_class::<PyInvoiceSubscription>()?;
+    m.add_class::<PyPaymentSubscription>()?;
+    m.add_function(wrap_pyfunction!(decode_invoice, m)?)?;
     m.add("ApiError", py.get_type::<PyApiError>())?;
     m.add("ConfigError", py.get_type::<PyConfigError>())?;
     Ok(())
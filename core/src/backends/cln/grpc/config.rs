@@ -1,8 +1,10 @@
 use http::uri::Uri;
 use std::convert::TryFrom;
+use std::time::Duration;
 
 use crate::error::{ConfigError, Error};
 use crate::types::NodeConfig;
+use crate::utils;
 
 #[derive(Clone, Debug)]
 pub struct ClnGrpcConfig {
@@ -10,6 +12,8 @@ pub struct ClnGrpcConfig {
     pub tls_certificate: Vec<u8>,
     pub tls_client_key: Vec<u8>,
     pub tls_client_certificate: Vec<u8>,
+    pub connect_timeout: Duration,
+    pub request_timeout: Option<Duration>,
 }
 
 impl TryFrom<NodeConfig> for ClnGrpcConfig {
@@ -41,6 +45,11 @@ impl TryFrom<NodeConfig> for ClnGrpcConfig {
                 .map_err(|_| ConfigError::ParsingHexError("tls_client_key".to_string()))?,
             tls_client_certificate: hex::decode(&tls_client_certificate)
                 .map_err(|_| ConfigError::ParsingHexError("tls_client_certificate".to_string()))?,
+            connect_timeout: config
+                .connect_timeout
+                .map(Duration::from_secs)
+                .unwrap_or(utils::DEFAULT_CONNECT_TIMEOUT),
+            request_timeout: config.request_timeout.map(Duration::from_secs),
         };
 
         Ok(config)
@@ -1,16 +1,43 @@
-use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity};
+use std::sync::Arc;
+use std::time::Duration;
 
+use tokio::sync::OnceCell;
+use tonic::transport::{self, Certificate, ClientTlsConfig, Endpoint, Identity};
+
+use crate::bolt11;
 use crate::error::Error;
-use crate::node::NodeMethods;
+use crate::node::{InvoiceStream, NodeMethods, PaymentStream};
 use crate::types::{
-    CreateInvoiceParams, CreateInvoiceResult, NodeInfo, PayInvoiceParams, PayInvoiceResult,
+    AddressType, Balance, Channel, ChannelPolicy, CloseChannelParams, CloseChannelResult,
+    ConnectPeerParams, CreateHoldInvoiceParams, CreateInvoiceParams, CreateInvoiceResult,
+    DecodedInvoice, Forward, Invoice, InvoiceCursor, InvoiceEvent, InvoiceEventType, KeysendParams,
+    ListForwardsParams, ListForwardsResult, ListInvoicesParams, ListInvoicesResult,
+    ListPaymentsParams, ListPaymentsResult, NodeInfo, OnchainTransaction, OpenChannelParams,
+    OpenChannelResult, PayInvoiceParams, PayInvoiceResult, Payment, PaymentStatus, Peer,
+    SendOnchainParams, SubscribeInvoicesParams, UpdatePolicyParams, VerifyMessageParams,
+    VerifyMessageResult,
 };
+use crate::utils;
 
 use super::config::ClnGrpcConfig;
-use super::pb::{node_client::NodeClient, GetinfoRequest, InvoiceRequest, PayRequest};
+use super::pb::{
+    delinvoice_request::DelinvoiceStatus, listinvoices_invoices::ListinvoicesInvoicesStatus,
+    node_client::NodeClient, onchain_transactions, payments, CheckmessageRequest, CloseRequest,
+    ConnectRequest, DecodepayRequest, DelexpiredinvoiceRequest, DelinvoiceRequest,
+    DisconnectRequest, FundchannelRequest, GetinfoRequest, InvoiceRequest, KeysendRequest,
+    ListchannelsRequest, ListforwardsRequest, ListfundsRequest, ListinvoicesRequest,
+    ListpeersRequest, ListsendpaysRequest, ListtransactionsRequest, NewaddrRequest, PayRequest,
+    SetchannelRequest, SignmessageRequest, WaitanyinvoiceRequest, WithdrawRequest,
+};
+
+/// Interval at which a payment is looked up again while waiting for it.
+const PAYMENT_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
+#[derive(Clone)]
 pub struct ClnGrpc {
     endpoint: Endpoint,
+    channel: Arc<OnceCell<transport::Channel>>,
+    request_timeout: Option<Duration>,
 }
 
 impl ClnGrpc {
@@ -25,42 +52,454 @@ impl ClnGrpc {
             .ca_certificate(tls_certificate)
             .identity(client_identity);
 
-        let endpoint = Channel::from_shared(config.url)
+        let endpoint = transport::Channel::from_shared(config.url)
             .expect("Already verified URL failed parsing")
-            .tls_config(tls)?;
+            .tls_config(tls)?
+            .connect_timeout(config.connect_timeout)
+            .tcp_keepalive(Some(Duration::from_secs(60)))
+            .http2_keep_alive_interval(Duration::from_secs(30))
+            .keep_alive_timeout(Duration::from_secs(10))
+            .keep_alive_while_idle(true);
+
+        Ok(ClnGrpc {
+            endpoint,
+            channel: Arc::new(OnceCell::new()),
+            request_timeout: config.request_timeout,
+        })
+    }
 
-        Ok(ClnGrpc { endpoint })
+    // The channel is shared by all requests and reconnects on its own when the
+    // connection drops. It is created on first use, as it needs a runtime.
+    async fn get_client(&self) -> NodeClient<transport::Channel> {
+        let channel = self
+            .channel
+            .get_or_init(|| async { self.endpoint.connect_lazy() })
+            .await;
+
+        NodeClient::new(channel.clone())
     }
 
-    // This is a temporary workaround, as we should spawn a unique channel for all
-    // requests, but I'm not yet experienced enough with Rust to find a proper way
-    // to do it.
-    async fn get_client(&self) -> Result<NodeClient<Channel>, Error> {
-        let channel = self.endpoint.connect().await?;
-        Ok(NodeClient::new(channel))
+    fn request<T>(&self, message: T) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        if let Some(request_timeout) = self.request_timeout {
+            request.set_timeout(request_timeout);
+        }
+
+        request
     }
 }
 
 #[async_trait::async_trait]
 impl NodeMethods for ClnGrpc {
+    async fn cancel_hold_invoice(&self, _payment_hash: String) -> Result<(), Error> {
+        Err(Error::NotImplemented)
+    }
+
+    async fn cancel_invoice(&self, payment_hash: String) -> Result<(), Error> {
+        let mut client = self.get_client().await;
+
+        let request = ListinvoicesRequest {
+            label: None,
+            invstring: None,
+            payment_hash: Some(hex::decode(payment_hash)?),
+            offer_id: None,
+        };
+        let response = client
+            .list_invoices(self.request(request))
+            .await?
+            .into_inner();
+        let invoice = response
+            .invoices
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::ApiError(String::from("invoice not found")))?;
+
+        // Core Lightning can't cancel invoices, they're deleted instead. The
+        // status is checked again on deletion, so an invoice paid meanwhile is
+        // kept.
+        let status = match invoice.status() {
+            ListinvoicesInvoicesStatus::Unpaid => DelinvoiceStatus::Unpaid,
+            ListinvoicesInvoicesStatus::Expired => DelinvoiceStatus::Expired,
+            ListinvoicesInvoicesStatus::Paid => {
+                return Err(Error::ApiError(String::from("invoice is already paid")))
+            }
+        };
+        let request = DelinvoiceRequest {
+            label: invoice.label,
+            status: status.into(),
+            desconly: None,
+        };
+        client.del_invoice(self.request(request)).await?;
+
+        Ok(())
+    }
+
+    async fn close_channel(&self, params: CloseChannelParams) -> Result<CloseChannelResult, Error> {
+        let mut client = self.get_client().await;
+
+        // Closing waits for the peer, payments for their outcome, so neither
+        // is bound by the request timeout.
+        let request: CloseRequest = params.try_into()?;
+        let response = client.close(request).await?.into_inner();
+
+        Ok(response.into())
+    }
+
+    async fn connect_peer(&self, params: ConnectPeerParams) -> Result<(), Error> {
+        let mut client = self.get_client().await;
+
+        let request: ConnectRequest = params.try_into()?;
+        client.connect_peer(self.request(request)).await?;
+
+        Ok(())
+    }
+
+    async fn decode_invoice(&self, bolt11: String) -> Result<DecodedInvoice, Error> {
+        let mut client = self.get_client().await;
+
+        let request = DecodepayRequest {
+            bolt11: bolt11.clone(),
+            description: None,
+        };
+        let response = client.decode_pay(self.request(request)).await?.into_inner();
+
+        (bolt11, response).try_into()
+    }
+
+    async fn delete_expired_invoices(&self, before: Option<u64>) -> Result<(), Error> {
+        let mut client = self.get_client().await;
+
+        let request = DelexpiredinvoiceRequest {
+            maxexpirytime: before,
+        };
+        client.del_expired_invoice(self.request(request)).await?;
+
+        Ok(())
+    }
+
+    async fn disconnect_peer(&self, pubkey: String) -> Result<(), Error> {
+        let mut client = self.get_client().await;
+
+        let request = DisconnectRequest {
+            id: hex::decode(pubkey)?,
+            force: None,
+        };
+        client.disconnect(self.request(request)).await?;
+
+        Ok(())
+    }
+
+    async fn get_balance(&self) -> Result<Balance, Error> {
+        let mut client = self.get_client().await;
+
+        let request = ListfundsRequest { spent: None };
+        let funds = client.list_funds(self.request(request)).await?.into_inner();
+
+        let request = ListpeersRequest {
+            id: None,
+            level: None,
+        };
+        let peers = client.list_peers(self.request(request)).await?.into_inner();
+
+        Ok((funds, peers).into())
+    }
+
+    async fn get_channel_policy(&self, channel_id: String) -> Result<ChannelPolicy, Error> {
+        let mut client = self.get_client().await;
+
+        let id = hex::decode(channel_id)?;
+        let request = ListpeersRequest {
+            id: None,
+            level: None,
+        };
+        let response = client.list_peers(self.request(request)).await?.into_inner();
+        let (peer_id, channel) = response
+            .peers
+            .into_iter()
+            .flat_map(|peer| {
+                let peer_id = peer.id;
+                peer.channels
+                    .into_iter()
+                    .map(move |channel| (peer_id.clone(), channel))
+            })
+            .find(|(_, channel)| channel.channel_id.as_ref() == Some(&id))
+            .ok_or_else(|| Error::ApiError(String::from("channel not found")))?;
+
+        // The direction of the channel set by the node is the one to the peer.
+        let time_lock_delta = match &channel.short_channel_id {
+            Some(short_channel_id) => {
+                let request = ListchannelsRequest {
+                    short_channel_id: Some(short_channel_id.clone()),
+                    source: None,
+                    destination: Some(peer_id),
+                };
+                let response = client
+                    .list_channels(self.request(request))
+                    .await?
+                    .into_inner();
+                response.channels.first().map(|channel| channel.delay)
+            }
+            None => None,
+        };
+
+        Ok((channel, time_lock_delta).into())
+    }
+
     async fn get_info(&self) -> Result<NodeInfo, Error> {
-        let mut client = self.get_client().await?;
+        let mut client = self.get_client().await;
 
         let request = GetinfoRequest {};
-        let response = client.getinfo(request).await?.into_inner();
+        let response = client.getinfo(self.request(request)).await?.into_inner();
 
         Ok(response.into())
     }
 
+    async fn get_invoice(&self, payment_hash: String) -> Result<Invoice, Error> {
+        let mut client = self.get_client().await;
+
+        let request = ListinvoicesRequest {
+            label: None,
+            invstring: None,
+            payment_hash: Some(hex::decode(payment_hash)?),
+            offer_id: None,
+        };
+        let response = client
+            .list_invoices(self.request(request))
+            .await?
+            .into_inner();
+
+        let invoice = response
+            .invoices
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::ApiError(String::from("invoice not found")))?;
+
+        invoice.try_into()
+    }
+
+    async fn get_payment(&self, payment_hash: String) -> Result<Payment, Error> {
+        let mut client = self.get_client().await;
+
+        let request = ListsendpaysRequest {
+            bolt11: None,
+            payment_hash: Some(hex::decode(payment_hash)?),
+            status: None,
+        };
+        let response = client
+            .list_send_pays(self.request(request))
+            .await?
+            .into_inner();
+
+        payments(response.payments)
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::ApiError(String::from("payment not found")))
+    }
+
+    async fn keysend(&self, params: KeysendParams) -> Result<PayInvoiceResult, Error> {
+        let mut client = self.get_client().await;
+
+        // The gRPC plugin of the v0.x releases drops the records instead of
+        // sending them, so the payment isn't made without them.
+        let custom_records = params.custom_records.as_ref();
+        if custom_records.is_some_and(|records| !records.is_empty()) {
+            let request = GetinfoRequest {};
+            let response = client.getinfo(self.request(request)).await?.into_inner();
+            if response.version.starts_with("v0.") {
+                return Err(Error::NotImplemented);
+            }
+        }
+
+        let request: KeysendRequest = params.try_into()?;
+        let response = client.key_send(request).await?.into_inner();
+
+        Ok(response.into())
+    }
+
+    async fn list_channels(&self) -> Result<Vec<Channel>, Error> {
+        let mut client = self.get_client().await;
+
+        let request = ListpeersRequest {
+            id: None,
+            level: None,
+        };
+        let response = client.list_peers(self.request(request)).await?.into_inner();
+
+        let channels = response
+            .peers
+            .iter()
+            .flat_map(|peer| {
+                peer.channels
+                    .iter()
+                    .map(move |channel| (peer, channel.clone()).into())
+            })
+            .collect();
+
+        Ok(channels)
+    }
+
+    async fn list_forwards(&self, params: ListForwardsParams) -> Result<ListForwardsResult, Error> {
+        let mut client = self.get_client().await;
+
+        let request = ListforwardsRequest {
+            status: None,
+            in_channel: None,
+            out_channel: None,
+        };
+        let response = client
+            .list_forwards(self.request(request))
+            .await?
+            .into_inner();
+
+        // Core Lightning has no pagination, so forwards are filtered and paged here.
+        let mut forwards: Vec<Forward> = response
+            .forwards
+            .into_iter()
+            .map(|forward| forward.into())
+            .filter(|forward| utils::forward_matches(forward, &params))
+            .collect();
+
+        let channels = self.list_channels().await?;
+        utils::fill_forward_channels(&mut forwards, &channels);
+
+        let (forwards, next_cursor) = utils::paginate(
+            forwards,
+            params.cursor,
+            params.max_forwards,
+            utils::DEFAULT_MAX_FORWARDS,
+        );
+
+        Ok(ListForwardsResult {
+            forwards,
+            next_cursor,
+        })
+    }
+
+    async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, Error> {
+        let mut client = self.get_client().await;
+
+        // The confirmations are counted from the height of the chain tip.
+        let request = GetinfoRequest {};
+        let info = client.getinfo(self.request(request)).await?.into_inner();
+
+        let request = ListtransactionsRequest {};
+        let response = client
+            .list_transactions(self.request(request))
+            .await?
+            .into_inner();
+
+        let request = ListfundsRequest { spent: Some(true) };
+        let funds = client.list_funds(self.request(request)).await?.into_inner();
+
+        Ok(onchain_transactions(
+            response.transactions,
+            funds.outputs,
+            info.blockheight,
+        ))
+    }
+
+    async fn list_peers(&self) -> Result<Vec<Peer>, Error> {
+        let mut client = self.get_client().await;
+
+        let request = ListpeersRequest {
+            id: None,
+            level: None,
+        };
+        let response = client.list_peers(self.request(request)).await?.into_inner();
+
+        Ok(response.peers.into_iter().map(Into::into).collect())
+    }
+
+    async fn list_payments(&self, params: ListPaymentsParams) -> Result<ListPaymentsResult, Error> {
+        let mut client = self.get_client().await;
+
+        let request = ListsendpaysRequest {
+            bolt11: None,
+            payment_hash: None,
+            status: None,
+        };
+        let response = client
+            .list_send_pays(self.request(request))
+            .await?
+            .into_inner();
+
+        // Core Lightning has no pagination, so payments are filtered and paged here.
+        let payments: Vec<Payment> = payments(response.payments)
+            .into_iter()
+            .filter(|payment| utils::payment_matches(payment, &params))
+            .collect();
+
+        let (payments, next_cursor) = utils::paginate(
+            payments,
+            params.cursor,
+            params.max_payments,
+            utils::DEFAULT_MAX_PAYMENTS,
+        );
+
+        Ok(ListPaymentsResult {
+            payments,
+            next_cursor,
+        })
+    }
+
+    async fn list_invoices(&self, params: ListInvoicesParams) -> Result<ListInvoicesResult, Error> {
+        let mut client = self.get_client().await;
+
+        let request = ListinvoicesRequest {
+            label: None,
+            invstring: None,
+            payment_hash: None,
+            offer_id: None,
+        };
+        let response = client
+            .list_invoices(self.request(request))
+            .await?
+            .into_inner();
+
+        // Core Lightning has no pagination, so invoices are filtered and paged here.
+        let mut invoices = Vec::new();
+        for invoice in response.invoices {
+            if invoice.bolt11.is_none() {
+                continue;
+            }
+
+            let invoice: Invoice = invoice.try_into()?;
+            if utils::invoice_matches(&invoice, &params) {
+                invoices.push(invoice);
+            }
+        }
+
+        let (invoices, next_cursor) = utils::paginate(
+            invoices,
+            params.cursor,
+            params.max_invoices,
+            utils::DEFAULT_MAX_INVOICES,
+        );
+
+        Ok(ListInvoicesResult {
+            invoices,
+            next_cursor,
+        })
+    }
+
+    // Hold invoices need the holdinvoice plugin, which isn't part of the
+    // gRPC interface of Core Lightning.
+    async fn create_hold_invoice(
+        &self,
+        _invoice: CreateHoldInvoiceParams,
+    ) -> Result<CreateInvoiceResult, Error> {
+        Err(Error::NotImplemented)
+    }
+
     async fn create_invoice(
         &self,
         invoice: CreateInvoiceParams,
     ) -> Result<CreateInvoiceResult, Error> {
-        let mut client = self.get_client().await?;
+        let mut client = self.get_client().await;
 
         let request: InvoiceRequest = invoice.into();
         let label = request.label.clone();
-        let response = client.invoice(request).await?.into_inner();
+        let response = client.invoice(self.request(request)).await?.into_inner();
 
         let mut result: CreateInvoiceResult = response.into();
         result.label = Some(label);
@@ -68,12 +507,208 @@ impl NodeMethods for ClnGrpc {
         Ok(result)
     }
 
+    async fn new_address(&self, address_type: AddressType) -> Result<String, Error> {
+        let mut client = self.get_client().await;
+
+        let request: NewaddrRequest = (&address_type).try_into()?;
+        let response = client.new_addr(self.request(request)).await?.into_inner();
+
+        let address = match address_type {
+            AddressType::NestedP2wpkh => response.p2sh_segwit,
+            _ => response.bech32,
+        };
+
+        address.ok_or_else(|| Error::ApiError(String::from("no address in the response")))
+    }
+
+    async fn open_channel(&self, params: OpenChannelParams) -> Result<OpenChannelResult, Error> {
+        // The params are checked before connecting to the peer, which isn't
+        // undone when they are invalid.
+        let peer = params.peer_host.clone().map(|host| ConnectPeerParams {
+            pubkey: params.peer_pubkey.clone(),
+            host: Some(host),
+        });
+        let request: FundchannelRequest = params.try_into()?;
+        if let Some(peer) = peer {
+            self.connect_peer(peer).await?;
+        }
+
+        let mut client = self.get_client().await;
+        let response = client
+            .fund_channel(self.request(request))
+            .await?
+            .into_inner();
+
+        Ok(response.into())
+    }
+
     async fn pay_invoice(&self, invoice: PayInvoiceParams) -> Result<PayInvoiceResult, Error> {
-        let mut client = self.get_client().await?;
+        let mut client = self.get_client().await;
 
         let request: PayRequest = invoice.into();
         let response = client.pay(request).await?.into_inner();
 
         Ok(response.into())
     }
+
+    async fn send_onchain(&self, params: SendOnchainParams) -> Result<String, Error> {
+        let mut client = self.get_client().await;
+
+        let request: WithdrawRequest = params.try_into()?;
+        let response = client.withdraw(self.request(request)).await?.into_inner();
+
+        Ok(hex::encode(response.txid))
+    }
+
+    async fn send_payment_async(&self, invoice: PayInvoiceParams) -> Result<String, Error> {
+        let mut client = self.get_client().await;
+
+        let payment_hash = bolt11::decode_invoice(&invoice.payment_request)?.payment_hash;
+        let request: PayRequest = invoice.into();
+
+        // `pay` blocks until the outcome, so it's left running in the
+        // background once its first part is sent. Errors raised before that,
+        // like a missing route, are returned here.
+        let mut pay = tokio::spawn(async move { client.pay(request).await });
+        loop {
+            tokio::select! {
+                result = &mut pay => {
+                    return match result {
+                        Ok(Ok(_)) => Ok(payment_hash),
+                        Ok(Err(status)) => Err(status.into()),
+                        Err(err) => Err(Error::ApiError(err.to_string())),
+                    };
+                }
+                _ = tokio::time::sleep(PAYMENT_POLL_INTERVAL) => {
+                    if self.get_payment(payment_hash.clone()).await.is_ok() {
+                        return Ok(payment_hash);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn settle_hold_invoice(&self, _preimage: String) -> Result<(), Error> {
+        Err(Error::NotImplemented)
+    }
+
+    async fn sign_message(&self, message: String) -> Result<String, Error> {
+        let mut client = self.get_client().await;
+
+        let request = SignmessageRequest { message };
+        let response = client
+            .sign_message(self.request(request))
+            .await?
+            .into_inner();
+
+        Ok(response.zbase)
+    }
+
+    async fn subscribe_invoices(
+        &self,
+        params: SubscribeInvoicesParams,
+    ) -> Result<InvoiceStream, Error> {
+        let mut client = self.get_client().await;
+
+        // Core Lightning only notifies paid invoices, indexed by `pay_index`.
+        // Without a cursor, the subscription starts after the last paid invoice.
+        let cursor = params.cursor.and_then(|cursor| cursor.settle_index);
+        let mut lastpay_index = match cursor {
+            Some(settle_index) => settle_index,
+            None => {
+                let request = ListinvoicesRequest {
+                    label: None,
+                    invstring: None,
+                    payment_hash: None,
+                    offer_id: None,
+                };
+                let response = client
+                    .list_invoices(self.request(request))
+                    .await?
+                    .into_inner();
+
+                response
+                    .invoices
+                    .iter()
+                    .filter_map(|invoice| invoice.pay_index)
+                    .max()
+                    .unwrap_or(0)
+            }
+        };
+
+        let stream = async_stream::try_stream! {
+            loop {
+                let request = WaitanyinvoiceRequest {
+                    lastpay_index: Some(lastpay_index),
+                    timeout: None,
+                };
+                // Blocks until the next payment, so the request timeout doesn't apply.
+                let response = client.wait_any_invoice(request).await?.into_inner();
+                lastpay_index = response.pay_index.unwrap_or(lastpay_index);
+
+                let event_type: InvoiceEventType = (&response).into();
+                let invoice: Invoice = response.try_into()?;
+
+                yield InvoiceEvent {
+                    event_type,
+                    invoice,
+                    cursor: InvoiceCursor {
+                        add_index: None,
+                        settle_index: Some(lastpay_index),
+                    },
+                };
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn track_payment(&self, payment_hash: String) -> Result<PaymentStream, Error> {
+        let node = self.clone();
+
+        // `waitsendpay` only waits for a single part of the payment, which is
+        // polled instead until all its parts are resolved.
+        let stream = async_stream::try_stream! {
+            let mut status = None;
+            loop {
+                let payment = node.get_payment(payment_hash.clone()).await?;
+                let pending = payment.status == PaymentStatus::Pending;
+                if status.as_ref() != Some(&payment.status) {
+                    status = Some(payment.status.clone());
+                    yield payment;
+                }
+                if !pending {
+                    break;
+                }
+
+                tokio::time::sleep(PAYMENT_POLL_INTERVAL).await;
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn update_channel_policy(&self, params: UpdatePolicyParams) -> Result<(), Error> {
+        let mut client = self.get_client().await;
+
+        let request: SetchannelRequest = (&params).try_into()?;
+        client.set_channel(self.request(request)).await?;
+
+        Ok(())
+    }
+
+    async fn verify_message(
+        &self,
+        params: VerifyMessageParams,
+    ) -> Result<VerifyMessageResult, Error> {
+        let mut client = self.get_client().await;
+
+        let request: CheckmessageRequest = (&params).try_into()?;
+        let response = client
+            .check_message(self.request(request))
+            .await?
+            .into_inner();
+
+        Ok((&params, response).into())
+    }
 }
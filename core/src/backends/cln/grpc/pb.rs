@@ -1,11 +1,27 @@
 #![allow(clippy::from_over_into)]
 
-use crate::{types::*, utils};
+use crate::error::Error;
+use crate::{bolt11, types::*, utils};
 use cuid;
 
 include!(concat!(env!("PROTOBUFS_DIR"), "/cln.rs"));
 
+// A vbyte is four weight units, so a fee rate in sat/vbyte is 250 times that
+// in sat/kw.
+fn feerate_perkw(sat_per_vbyte: u64) -> Result<Feerate, Error> {
+    let perkw = sat_per_vbyte
+        .checked_mul(250)
+        .ok_or_else(|| Error::ConversionError(format!("fee rate {} is too high", sat_per_vbyte)))?;
+    let feerate = Feerate {
+        style: Some(feerate::Style::Perkw(u32::try_from(perkw)?)),
+    };
+
+    Ok(feerate)
+}
+
 impl From<CreateInvoiceParams> for InvoiceRequest {
+    // `cuid::cuid` is deprecated, but still gives the labels of the invoices.
+    #[allow(deprecated)]
     fn from(params: CreateInvoiceParams) -> Self {
         let value = match (params.amount, params.amount_msat) {
             (Some(amount), _) => amount_or_any::Value::Amount(Amount {
@@ -17,7 +33,7 @@ impl From<CreateInvoiceParams> for InvoiceRequest {
 
         InvoiceRequest {
             msatoshi: Some(AmountOrAny { value: Some(value) }),
-            description: params.description.unwrap_or_else(|| "".to_string()),
+            description: params.description.unwrap_or_default(),
             label: params.label.unwrap_or_else(|| cuid::cuid().unwrap()),
             expiry: params.expire_in.map(|expire_in| expire_in as u64),
             fallbacks: params
@@ -28,7 +44,7 @@ impl From<CreateInvoiceParams> for InvoiceRequest {
                 .payment_preimage
                 .map(|preimage| preimage.into_bytes()),
             exposeprivatechannels: None,
-            cltv: params.cltv_expiry.map(|cltv_expiry| cltv_expiry as u32),
+            cltv: params.cltv_expiry,
             deschashonly: Some(false),
         }
     }
@@ -50,7 +66,7 @@ impl Into<NodeInfo> for GetinfoResponse {
             "bitcoin" => Network::Mainnet,
             "testnet" => Network::Testnet,
             "regtest" => Network::Regtest,
-            _ => Network::Mainnet,
+            _ => Network::Unknown(self.network.clone()),
         };
 
         NodeInfo {
@@ -67,6 +83,168 @@ impl Into<NodeInfo> for GetinfoResponse {
     }
 }
 
+impl Into<Balance> for (ListfundsResponse, ListpeersResponse) {
+    fn into(self) -> Balance {
+        use listpeers_peers_channels::ListpeersPeersChannelsState;
+
+        let (funds, peers) = self;
+        let mut balance = Balance::default();
+
+        for output in funds.outputs {
+            let status = output.status();
+            let amount_msat = output.amount_msat.map(|amount| amount.msat).unwrap_or(0);
+            match (status, output.reserved) {
+                (listfunds_outputs::ListfundsOutputsStatus::Spent, _) => {}
+                (_, true) => balance.onchain_locked_msat += amount_msat,
+                (listfunds_outputs::ListfundsOutputsStatus::Confirmed, false) => {
+                    balance.onchain_confirmed_msat += amount_msat
+                }
+                (listfunds_outputs::ListfundsOutputsStatus::Unconfirmed, false) => {
+                    balance.onchain_unconfirmed_msat += amount_msat
+                }
+            }
+        }
+
+        for channel in funds.channels {
+            let state = channel.state();
+            let our_amount_msat = channel
+                .our_amount_msat
+                .map(|amount| amount.msat)
+                .unwrap_or(0);
+            match state {
+                // Open channels are counted with the peers below.
+                ChannelState::ChanneldNormal => {}
+                ChannelState::Openingd
+                | ChannelState::ChanneldAwaitingLockin
+                | ChannelState::DualopendOpenInit
+                | ChannelState::DualopendAwaitingLockin => {
+                    balance.pending_open_msat += our_amount_msat
+                }
+                ChannelState::ChanneldShuttingDown
+                | ChannelState::ClosingdSigexchange
+                | ChannelState::ClosingdComplete
+                | ChannelState::AwaitingUnilateral
+                | ChannelState::FundingSpendSeen => balance.pending_close_msat += our_amount_msat,
+                // Closed channels are listed until their outputs are deeply
+                // buried, while the funds may already be back in the wallet.
+                ChannelState::Onchain => {}
+            }
+        }
+
+        // The amounts of the funds include the channel reserves, which can't
+        // be sent, unlike the spendable amounts of the peers.
+        let msat = |amount: Option<Amount>| amount.map(|amount| amount.msat).unwrap_or(0);
+        for channel in peers.peers.into_iter().flat_map(|peer| peer.channels) {
+            if channel.state() == ListpeersPeersChannelsState::ChanneldNormal {
+                balance.lightning_local_msat += msat(channel.spendable_msat);
+                balance.lightning_remote_msat += msat(channel.receivable_msat);
+            }
+        }
+
+        balance
+    }
+}
+
+impl Into<Channel> for (&ListpeersPeers, ListpeersPeersChannels) {
+    fn into(self) -> Channel {
+        use listpeers_peers_channels::ListpeersPeersChannelsState as State;
+
+        let (peer, channel) = self;
+
+        let status = match channel.state() {
+            State::Openingd
+            | State::ChanneldAwaitingLockin
+            | State::DualopendOpenInit
+            | State::DualopendAwaitingLockin => ChannelStatus::Opening,
+            State::ChanneldNormal => ChannelStatus::Open,
+            State::ChanneldShuttingDown
+            | State::ClosingdSigexchange
+            | State::ClosingdComplete
+            | State::AwaitingUnilateral
+            | State::FundingSpendSeen => ChannelStatus::Closing,
+            State::Onchain => ChannelStatus::Closed,
+        };
+        let msat = |amount: &Option<Amount>| amount.as_ref().map(|amount| amount.msat).unwrap_or(0);
+        let capacity_msat = msat(&channel.total_msat);
+        let local_balance_msat = msat(&channel.to_us_msat);
+        let channel_point = match (&channel.funding_txid, channel.funding_outnum) {
+            (Some(txid), Some(output_index)) => {
+                Some(format!("{}:{}", hex::encode(txid), output_index))
+            }
+            _ => None,
+        };
+
+        Channel {
+            channel_id: channel
+                .channel_id
+                .as_ref()
+                .map(hex::encode)
+                .unwrap_or_default(),
+            channel_point,
+            short_channel_id: channel.short_channel_id.clone(),
+            peer_pubkey: hex::encode(&peer.id),
+            capacity_msat,
+            local_balance_msat,
+            remote_balance_msat: capacity_msat.saturating_sub(local_balance_msat),
+            local_reserve_msat: msat(&channel.our_reserve_msat),
+            remote_reserve_msat: msat(&channel.their_reserve_msat),
+            active: peer.connected && status == ChannelStatus::Open,
+            private: channel.private.unwrap_or(false),
+            // `IN` is the local side, renamed `LOCAL` in later releases.
+            initiator: channel.opener() == ChannelSide::In,
+            status,
+        }
+    }
+}
+
+// The CLTV delta is only known from the announcement of the channel.
+impl Into<ChannelPolicy> for (ListpeersPeersChannels, Option<u32>) {
+    fn into(self) -> ChannelPolicy {
+        let (channel, time_lock_delta) = self;
+
+        let msat = |amount: &Option<Amount>| amount.as_ref().map(|amount| amount.msat);
+
+        ChannelPolicy {
+            channel_id: channel
+                .channel_id
+                .as_ref()
+                .map(hex::encode)
+                .unwrap_or_default(),
+            base_fee_msat: msat(&channel.fee_base_msat).unwrap_or(0),
+            fee_rate_ppm: channel.fee_proportional_millionths.unwrap_or(0).into(),
+            time_lock_delta,
+            min_htlc_msat: msat(&channel.minimum_htlc_out_msat).unwrap_or(0),
+            max_htlc_msat: msat(&channel.maximum_htlc_out_msat),
+        }
+    }
+}
+
+// The CLTV delta is set for the whole node in its config.
+impl TryFrom<&UpdatePolicyParams> for SetchannelRequest {
+    type Error = Error;
+
+    fn try_from(params: &UpdatePolicyParams) -> Result<Self, Self::Error> {
+        if params.time_lock_delta.is_some() {
+            return Err(Error::NotImplemented);
+        }
+
+        let amount = |msat: Option<u64>| msat.map(|msat| Amount { msat });
+        let request = SetchannelRequest {
+            id: params
+                .channel_id
+                .clone()
+                .unwrap_or_else(|| String::from("all")),
+            feebase: amount(params.base_fee_msat),
+            feeppm: params.fee_rate_ppm.map(u32::try_from).transpose()?,
+            htlcmin: amount(params.min_htlc_msat),
+            htlcmax: amount(params.max_htlc_msat),
+            enforcedelay: None,
+        };
+
+        Ok(request)
+    }
+}
+
 impl From<PayInvoiceParams> for PayRequest {
     fn from(params: PayInvoiceParams) -> Self {
         let amount_msat =
@@ -83,7 +261,7 @@ impl From<PayInvoiceParams> for PayRequest {
             exemptfee: None,
             localofferid: None,
             exclude: vec![],
-            maxfee: params.max_fee_msat.map(|v| Amount { msat: v as u64 }),
+            maxfee: params.max_fee_msat.map(|v| Amount { msat: v }),
             description: None,
         }
     }
@@ -105,3 +283,563 @@ impl Into<PayInvoiceResult> for PayResponse {
         }
     }
 }
+
+impl TryFrom<KeysendParams> for KeysendRequest {
+    type Error = Error;
+
+    fn try_from(params: KeysendParams) -> Result<Self, Self::Error> {
+        let amount_msat = utils::get_amount_msat(params.amount, params.amount_msat)
+            .ok_or_else(|| Error::ApiError(String::from("amount is required")))?;
+
+        // Keysend only takes a percentage, and doesn't check fees below
+        // `exemptfee`, which enforces an absolute limit with a zero percentage.
+        let max_fee_msat = utils::get_amount_msat(params.max_fee_sat, params.max_fee_msat);
+        let (maxfeepercent, exemptfee) = match (max_fee_msat, params.max_fee_percent) {
+            (Some(max_fee_msat), _) => (Some(0.0), Some(Amount { msat: max_fee_msat })),
+            (None, Some(max_fee_percent)) => (Some(max_fee_percent), Some(Amount { msat: 0 })),
+            (None, None) => (None, None),
+        };
+
+        let mut entries = Vec::new();
+        for record in params.custom_records.unwrap_or_default() {
+            entries.push(TlvEntry {
+                r#type: record.record_type,
+                value: hex::decode(record.value)?,
+            });
+        }
+
+        let request = KeysendRequest {
+            destination: hex::decode(params.destination)?,
+            msatoshi: Some(Amount { msat: amount_msat }),
+            label: None,
+            maxfeepercent,
+            retry_for: None,
+            maxdelay: None,
+            exemptfee,
+            routehints: None,
+            extratlvs: match entries.is_empty() {
+                true => None,
+                false => Some(TlvStream { entries }),
+            },
+        };
+
+        Ok(request)
+    }
+}
+
+impl Into<PayInvoiceResult> for KeysendResponse {
+    fn into(self) -> PayInvoiceResult {
+        let fees_msat = match (self.amount_msat, self.amount_sent_msat) {
+            (Some(amount_msat), Some(amount_sent_msat)) => {
+                Some(amount_sent_msat.msat - amount_msat.msat)
+            }
+            _ => None,
+        };
+
+        PayInvoiceResult {
+            payment_preimage: hex::encode(self.payment_preimage),
+            payment_hash: hex::encode(self.payment_hash),
+            fees_msat,
+        }
+    }
+}
+
+impl TryInto<Invoice> for ListinvoicesInvoices {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Invoice, Self::Error> {
+        let status = match self.status() {
+            listinvoices_invoices::ListinvoicesInvoicesStatus::Unpaid => InvoiceStatus::Pending,
+            listinvoices_invoices::ListinvoicesInvoicesStatus::Paid => InvoiceStatus::Settled,
+            listinvoices_invoices::ListinvoicesInvoicesStatus::Expired => InvoiceStatus::Cancelled,
+        };
+
+        let bolt11 = self.bolt11.unwrap_or_default();
+        let creation_date = bolt11::decode_invoice(&bolt11)?.creation_date;
+        let amount_msat = self.amount_msat.map(|amount| amount.msat).unwrap_or(0);
+
+        let invoice = Invoice {
+            bolt11,
+            memo: self.description.unwrap_or_default(),
+            amount: utils::msat_to_sat(amount_msat),
+            amount_msat,
+            pre_image: self.payment_preimage.map(hex::encode),
+            payment_hash: hex::encode(self.payment_hash),
+            settled: matches!(status, InvoiceStatus::Settled),
+            settle_date: self.paid_at.map(|paid_at| paid_at as i64),
+            creation_date,
+            expiry: self.expires_at.saturating_sub(creation_date as u64) as i32,
+            status,
+        };
+
+        Ok(invoice)
+    }
+}
+
+impl From<&WaitanyinvoiceResponse> for InvoiceEventType {
+    fn from(response: &WaitanyinvoiceResponse) -> Self {
+        match response.status() {
+            waitanyinvoice_response::WaitanyinvoiceStatus::Paid => InvoiceEventType::Settled,
+            waitanyinvoice_response::WaitanyinvoiceStatus::Expired => InvoiceEventType::Cancelled,
+        }
+    }
+}
+
+impl TryInto<Invoice> for WaitanyinvoiceResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Invoice, Self::Error> {
+        let status = match self.status() {
+            waitanyinvoice_response::WaitanyinvoiceStatus::Paid => InvoiceStatus::Settled,
+            waitanyinvoice_response::WaitanyinvoiceStatus::Expired => InvoiceStatus::Cancelled,
+        };
+
+        let bolt11 = self.bolt11.unwrap_or_default();
+        let creation_date = bolt11::decode_invoice(&bolt11)?.creation_date;
+        let amount_msat = self.amount_msat.map(|amount| amount.msat).unwrap_or(0);
+
+        let invoice = Invoice {
+            bolt11,
+            memo: self.description,
+            amount: utils::msat_to_sat(amount_msat),
+            amount_msat,
+            pre_image: self.payment_preimage.map(hex::encode),
+            payment_hash: hex::encode(self.payment_hash),
+            settled: matches!(status, InvoiceStatus::Settled),
+            settle_date: self.paid_at.map(|paid_at| paid_at as i64),
+            creation_date,
+            expiry: self.expires_at.saturating_sub(creation_date as u64) as i32,
+            status,
+        };
+
+        Ok(invoice)
+    }
+}
+
+impl TryInto<DecodedInvoice> for (String, DecodepayResponse) {
+    type Error = Error;
+
+    fn try_into(self) -> Result<DecodedInvoice, Self::Error> {
+        let (bolt11, response) = self;
+
+        let amount_msat = response.amount_msat.map(|amount| amount.msat);
+
+        // Route hints aren't part of the gRPC `DecodePay` response.
+        let invoice = DecodedInvoice {
+            network: bolt11::get_network(&bolt11)?,
+            bolt11,
+            payee: hex::encode(response.payee),
+            amount: amount_msat.map(utils::msat_to_sat),
+            amount_msat,
+            payment_hash: hex::encode(response.payment_hash),
+            payment_secret: response.payment_secret.map(hex::encode),
+            description: response.description,
+            description_hash: response.description_hash.map(hex::encode),
+            creation_date: response.created_at as i64,
+            expiry: response.expiry as i32,
+            min_final_cltv_expiry: response.min_final_cltv_expiry as u64,
+            route_hints: Vec::new(),
+            features: response
+                .features
+                .map(|features| bolt11::features_from_bytes(&features))
+                .unwrap_or_default(),
+        };
+
+        Ok(invoice)
+    }
+}
+
+impl TryFrom<OpenChannelParams> for FundchannelRequest {
+    type Error = Error;
+
+    fn try_from(params: OpenChannelParams) -> Result<Self, Self::Error> {
+        let feerate = params.fee_rate.map(feerate_perkw).transpose()?;
+
+        let request = FundchannelRequest {
+            id: hex::decode(params.peer_pubkey)?,
+            amount: Some(AmountOrAll {
+                value: Some(amount_or_all::Value::Amount(Amount {
+                    msat: utils::sat_to_msat(params.amount),
+                })),
+            }),
+            feerate,
+            announce: params.private.map(|private| !private),
+            minconf: params.min_confs,
+            push_msat: params.push_amount.map(|push_amount| Amount {
+                msat: utils::sat_to_msat(push_amount),
+            }),
+            ..Default::default()
+        };
+
+        Ok(request)
+    }
+}
+
+impl Into<OpenChannelResult> for FundchannelResponse {
+    fn into(self) -> OpenChannelResult {
+        OpenChannelResult {
+            channel_id: hex::encode(self.channel_id),
+            funding_txid: hex::encode(self.txid),
+            output_index: self.outnum,
+        }
+    }
+}
+
+impl TryFrom<CloseChannelParams> for CloseRequest {
+    type Error = Error;
+
+    fn try_from(params: CloseChannelParams) -> Result<Self, Self::Error> {
+        // The fee range is narrowed to the requested fee rate.
+        let feerange = match params.fee_rate {
+            Some(fee_rate) => vec![feerate_perkw(fee_rate)?, feerate_perkw(fee_rate)?],
+            None => Vec::new(),
+        };
+        // Core Lightning closes unilaterally once the peer fails to negotiate
+        // within the timeout, the shortest one leaving a peer online a second
+        // to agree on a mutual close.
+        let unilateraltimeout = match params.force.unwrap_or(false) {
+            true => Some(1),
+            false => None,
+        };
+
+        let request = CloseRequest {
+            id: utils::get_channel_id(&params)?,
+            unilateraltimeout,
+            destination: params.delivery_address,
+            feerange,
+            ..Default::default()
+        };
+
+        Ok(request)
+    }
+}
+
+impl Into<CloseChannelResult> for CloseResponse {
+    fn into(self) -> CloseChannelResult {
+        CloseChannelResult {
+            closing_txids: self.txid.map(hex::encode).into_iter().collect(),
+        }
+    }
+}
+
+impl TryFrom<ConnectPeerParams> for ConnectRequest {
+    type Error = Error;
+
+    fn try_from(params: ConnectPeerParams) -> Result<Self, Self::Error> {
+        let (host, port) = match params.host {
+            Some(host) => match host.rsplit_once(':') {
+                Some((host, port)) => (Some(host.to_string()), Some(port.parse()?)),
+                None => (Some(host), None),
+            },
+            None => (None, None),
+        };
+
+        let request = ConnectRequest {
+            id: params.pubkey,
+            host,
+            port,
+        };
+
+        Ok(request)
+    }
+}
+
+impl Into<Peer> for ListpeersPeers {
+    fn into(self) -> Peer {
+        Peer {
+            pubkey: hex::encode(self.id),
+            addresses: self.netaddr,
+            connected: self.connected,
+            features: self
+                .features
+                .map(|features| bolt11::features_from_bytes(&features))
+                .unwrap_or_default(),
+            inbound: None,
+        }
+    }
+}
+
+// Core Lightning v0.11 has no taproot addresses yet.
+impl TryFrom<&AddressType> for NewaddrRequest {
+    type Error = Error;
+
+    fn try_from(address_type: &AddressType) -> Result<Self, Self::Error> {
+        let addresstype = match address_type {
+            AddressType::P2wpkh => newaddr_request::NewaddrAddresstype::Bech32,
+            AddressType::NestedP2wpkh => newaddr_request::NewaddrAddresstype::P2shSegwit,
+            AddressType::P2tr => return Err(Error::NotImplemented),
+        };
+
+        Ok(NewaddrRequest {
+            addresstype: Some(addresstype as i32),
+        })
+    }
+}
+
+// Core Lightning only takes named targets, aiming at the next block, the next
+// 4 blocks or so and the next 100 blocks or so.
+fn feerate_target(target_conf: u32) -> Feerate {
+    let style = match target_conf {
+        0..=2 => feerate::Style::Urgent(true),
+        3..=20 => feerate::Style::Normal(true),
+        _ => feerate::Style::Slow(true),
+    };
+
+    Feerate { style: Some(style) }
+}
+
+fn outpoint(utxo: &str) -> Result<Outpoint, Error> {
+    let (txid, outnum) = utxo
+        .split_once(':')
+        .ok_or_else(|| Error::ConversionError(format!("invalid outpoint {}", utxo)))?;
+
+    Ok(Outpoint {
+        txid: hex::decode(txid)?,
+        outnum: outnum.parse()?,
+    })
+}
+
+impl TryFrom<SendOnchainParams> for WithdrawRequest {
+    type Error = Error;
+
+    fn try_from(params: SendOnchainParams) -> Result<Self, Self::Error> {
+        let value = match utils::get_onchain_amount(&params)? {
+            Some(amount) => amount_or_all::Value::Amount(Amount {
+                msat: utils::sat_to_msat(amount),
+            }),
+            None => amount_or_all::Value::All(true),
+        };
+        let feerate = match (params.fee_rate, params.target_conf) {
+            (Some(fee_rate), _) => Some(feerate_perkw(fee_rate)?),
+            (None, Some(target_conf)) => Some(feerate_target(target_conf)),
+            (None, None) => None,
+        };
+
+        let request = WithdrawRequest {
+            destination: params.address,
+            satoshi: Some(AmountOrAll { value: Some(value) }),
+            feerate,
+            minconf: None,
+            utxos: params
+                .utxos
+                .unwrap_or_default()
+                .iter()
+                .map(|utxo| outpoint(utxo))
+                .collect::<Result<_, _>>()?,
+        };
+
+        Ok(request)
+    }
+}
+
+// Core Lightning only tells which outputs belong to the wallet when built with
+// experimental features, so they are looked up in the outputs of the wallet,
+// spent ones included. The inputs reference the outputs they spend.
+pub fn onchain_transactions(
+    transactions: Vec<ListtransactionsTransactions>,
+    outputs: Vec<ListfundsOutputs>,
+    blockheight: u32,
+) -> Vec<OnchainTransaction> {
+    let amount = |output: &ListtransactionsTransactionsOutputs| {
+        output.msat.as_ref().map_or(0, |amount| amount.msat)
+    };
+
+    let wallet_outputs: std::collections::HashMap<(Vec<u8>, u32), u64> = outputs
+        .into_iter()
+        .map(|output| {
+            let amount_msat = output.amount_msat.map_or(0, |amount| amount.msat);
+            ((output.txid, output.output), amount_msat)
+        })
+        .collect();
+
+    transactions
+        .into_iter()
+        .map(|transaction| {
+            let spent: Vec<Option<u64>> = transaction
+                .inputs
+                .iter()
+                .map(|input| {
+                    wallet_outputs
+                        .get(&(input.txid.clone(), input.index))
+                        .copied()
+                })
+                .collect();
+            let sent: u64 = spent.iter().flatten().sum();
+            let received: u64 = transaction
+                .outputs
+                .iter()
+                .filter_map(|output| wallet_outputs.get(&(transaction.hash.clone(), output.index)))
+                .sum();
+            // The fee is only known when all the inputs come from the wallet.
+            let fee_msat = match !spent.is_empty() && spent.iter().all(Option::is_some) {
+                true => Some(sent.saturating_sub(transaction.outputs.iter().map(amount).sum())),
+                false => None,
+            };
+            let block_height = match transaction.blockheight {
+                0 => None,
+                block_height => Some(block_height),
+            };
+
+            OnchainTransaction {
+                txid: hex::encode(transaction.hash),
+                amount_received_msat: received.saturating_sub(sent),
+                amount_sent_msat: sent.saturating_sub(received),
+                fee_msat,
+                confirmations: block_height.map_or(0, |block_height| {
+                    blockheight.saturating_sub(block_height) + 1
+                }),
+                block_height,
+                timestamp: None,
+                label: None,
+                short_channel_id: transaction.channel,
+            }
+        })
+        .collect()
+}
+
+impl From<listsendpays_payments::ListsendpaysPaymentsStatus> for PaymentStatus {
+    fn from(status: listsendpays_payments::ListsendpaysPaymentsStatus) -> Self {
+        match status {
+            listsendpays_payments::ListsendpaysPaymentsStatus::Pending => PaymentStatus::Pending,
+            listsendpays_payments::ListsendpaysPaymentsStatus::Failed => PaymentStatus::Failed,
+            listsendpays_payments::ListsendpaysPaymentsStatus::Complete => PaymentStatus::Succeeded,
+        }
+    }
+}
+
+// `listsendpays` lists the parts of the payments, each attempt at a payment
+// getting its own group of parts. The payments are rebuilt from their last
+// attempt, in the order they were first sent.
+pub fn payments(parts: Vec<ListsendpaysPayments>) -> Vec<Payment> {
+    let mut attempts: Vec<(Vec<u8>, u64, Vec<ListsendpaysPayments>)> = Vec::new();
+    for part in parts {
+        let position = attempts
+            .iter()
+            .position(|(payment_hash, _, _)| *payment_hash == part.payment_hash);
+        match position {
+            Some(position) => {
+                let (_, _, attempt) = &mut attempts[position];
+                let groupid = attempt[0].groupid;
+                if part.groupid > groupid {
+                    attempt.clear();
+                }
+                if part.groupid >= groupid {
+                    attempt.push(part);
+                }
+            }
+            None => attempts.push((part.payment_hash.clone(), part.created_at, vec![part])),
+        }
+    }
+
+    attempts
+        .into_iter()
+        .map(|(payment_hash, created_at, attempt)| {
+            let amount = |amount: &Option<Amount>| amount.as_ref().map_or(0, |amount| amount.msat);
+            let parts: Vec<PaymentPart> = attempt
+                .iter()
+                .map(|part| PaymentPart {
+                    amount_msat: amount(&part.amount_msat),
+                    fee_msat: amount(&part.amount_sent_msat)
+                        .saturating_sub(amount(&part.amount_msat)),
+                    status: part.status().into(),
+                })
+                .collect();
+
+            let has_status = |status| parts.iter().any(|part| part.status == status);
+            let status = if has_status(PaymentStatus::Succeeded) {
+                PaymentStatus::Succeeded
+            } else if has_status(PaymentStatus::Pending) {
+                PaymentStatus::Pending
+            } else {
+                PaymentStatus::Failed
+            };
+            let failure_reason = match status {
+                PaymentStatus::Failed => Some(String::from("payment failed")),
+                _ => None,
+            };
+            let first = &attempt[0];
+
+            Payment {
+                payment_hash: hex::encode(payment_hash),
+                payment_preimage: attempt
+                    .iter()
+                    .find_map(|part| part.payment_preimage.as_ref())
+                    .map(hex::encode),
+                amount_msat: parts.iter().map(|part| part.amount_msat).sum(),
+                fee_msat: Some(parts.iter().map(|part| part.fee_msat).sum()),
+                status,
+                created_at: created_at as i64,
+                destination: first.destination.as_ref().map(hex::encode),
+                bolt11: first.bolt11.clone(),
+                failure_reason,
+                parts,
+            }
+        })
+        .collect()
+}
+
+// Core Lightning identifies the channels of a forward by their short channel
+// ids, and only reports when the incoming HTLC was received.
+impl Into<Forward> for ListforwardsForwards {
+    fn into(self) -> Forward {
+        let status = match self.status() {
+            listforwards_forwards::ListforwardsForwardsStatus::Offered => ForwardStatus::Offered,
+            listforwards_forwards::ListforwardsForwardsStatus::Settled => ForwardStatus::Settled,
+            listforwards_forwards::ListforwardsForwardsStatus::LocalFailed
+            | listforwards_forwards::ListforwardsForwardsStatus::Failed => ForwardStatus::Failed,
+        };
+
+        let fee_msat = match status {
+            ForwardStatus::Settled => self.fee_msat.map(|amount| amount.msat).unwrap_or(0),
+            _ => 0,
+        };
+
+        Forward {
+            incoming_channel_id: None,
+            incoming_short_channel_id: Some(self.in_channel),
+            outgoing_channel_id: None,
+            outgoing_short_channel_id: self.out_channel,
+            amount_in_msat: self.in_msat.map(|amount| amount.msat).unwrap_or(0),
+            amount_out_msat: self.out_msat.map(|amount| amount.msat),
+            fee_msat,
+            status,
+            received_at: Some(self.received_time as i64),
+            resolved_at: None,
+        }
+    }
+}
+
+impl TryFrom<&VerifyMessageParams> for CheckmessageRequest {
+    type Error = Error;
+
+    fn try_from(params: &VerifyMessageParams) -> Result<Self, Self::Error> {
+        let pubkey = match &params.pubkey {
+            Some(pubkey) => Some(hex::decode(pubkey)?),
+            None => None,
+        };
+
+        let request = CheckmessageRequest {
+            message: params.message.clone(),
+            zbase: params.signature.clone(),
+            pubkey,
+        };
+
+        Ok(request)
+    }
+}
+
+impl Into<VerifyMessageResult> for (&VerifyMessageParams, CheckmessageResponse) {
+    fn into(self) -> VerifyMessageResult {
+        let (params, response) = self;
+
+        let pubkey = match response.pubkey {
+            Some(pubkey) => hex::encode(pubkey),
+            None => params.pubkey.clone().unwrap_or_default(),
+        };
+
+        VerifyMessageResult {
+            valid: response.verified,
+            pubkey,
+        }
+    }
+}
@@ -1,11 +1,16 @@
+use std::time::Duration;
+
 use crate::error::{ConfigError, Error};
 use crate::types::NodeConfig;
+use crate::utils;
 
 #[derive(Clone, Debug)]
 pub struct EclairRestConfig {
     pub url: String,
     pub username: String,
     pub password: String,
+    pub connect_timeout: Duration,
+    pub request_timeout: Option<Duration>,
 }
 
 impl TryFrom<NodeConfig> for EclairRestConfig {
@@ -26,6 +31,11 @@ impl TryFrom<NodeConfig> for EclairRestConfig {
             url,
             username,
             password,
+            connect_timeout: config
+                .connect_timeout
+                .map(Duration::from_secs)
+                .unwrap_or(utils::DEFAULT_CONNECT_TIMEOUT),
+            request_timeout: config.request_timeout.map(Duration::from_secs),
         };
 
         Ok(config)
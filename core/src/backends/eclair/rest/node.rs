@@ -1,18 +1,50 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use futures::StreamExt;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message};
+
+use crate::bolt11;
 use crate::error::Error;
-use crate::node::NodeMethods;
+use crate::node::{InvoiceStream, NodeMethods, PaymentStream};
 use crate::types::{
-    CreateInvoiceParams, CreateInvoiceResult, NodeInfo, PayInvoiceParams, PayInvoiceResult,
+    AddressType, Balance, Channel, ChannelPolicy, ChannelStatus, CloseChannelParams,
+    CloseChannelResult, ConnectPeerParams, CreateHoldInvoiceParams, CreateInvoiceParams,
+    CreateInvoiceResult, DecodedInvoice, Invoice, InvoiceCursor, InvoiceEvent, InvoiceEventType,
+    InvoiceStatus, KeysendParams, ListForwardsParams, ListForwardsResult, ListInvoicesParams,
+    ListInvoicesResult, ListPaymentsParams, ListPaymentsResult, NodeInfo, OnchainTransaction,
+    OpenChannelParams, OpenChannelResult, PayInvoiceParams, PayInvoiceResult, Payment,
+    PaymentStatus, Peer, SendOnchainParams, SubscribeInvoicesParams, UpdatePolicyParams,
+    VerifyMessageParams, VerifyMessageResult,
 };
+use crate::utils;
 
 use super::config::EclairRestConfig;
 use super::types::{
-    ApiError, ChannelState, CreateInvoiceRequest, CreateInvoiceResponse, GetChannelsResponse,
-    GetInfoResponse, PayInvoiceRequest, PayInvoiceResponse,
+    onchain_transactions, payment, ApiError, AuditRequest, AuditResponse, ChannelState,
+    CloseRequest, CloseResponse, ConnectRequest, CreateInvoiceRequest, CreateInvoiceResponse,
+    DisconnectRequest, ForceCloseRequest, GetChannelRequest, GetChannelsResponse, GetInfoResponse,
+    GetInvoiceRequest, GetReceivedInfoRequest, GetReceivedInfoResponse, GetSentInfoRequest,
+    InvoiceResponse, ListInvoicesRequest, OnchainBalanceResponse, OnchainTransactionsRequest,
+    OpenRequest, OpenResponse, OutgoingPayment, ParseInvoiceRequest, ParseInvoiceResponse,
+    PayInvoiceRequest, PayInvoiceResponse, PeerResponse, RelayedResponse, SendOnchainRequest,
+    SendToNodeRequest, SignMessageRequest, SignMessageResponse, UpdateRelayFeeRequest,
+    UpdateRelayFeeResponse, UsableBalance, VerifyMessageRequest, VerifyMessageResponse,
+    WalletTransaction, WebSocketEvent,
 };
 
+/// Number of transactions fetched at once when listing the on-chain ones.
+const ONCHAIN_TRANSACTIONS_PAGE: u64 = 1000;
+const PAYMENT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Time a payment is polled for before giving up on waiting for its outcome.
+const PAYMENT_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
 pub struct EclairRest {
     config: EclairRestConfig,
     client: reqwest::Client,
+    authorization: String,
 }
 
 impl EclairRest {
@@ -24,14 +56,94 @@ impl EclairRest {
         let authorization_value = reqwest::header::HeaderValue::from_str(&authorization)?;
         headers.insert("Authorization", authorization_value);
 
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .connect_timeout(config.connect_timeout)
+            .build()?;
+
         Ok(EclairRest {
             config,
-            client: reqwest::Client::builder()
-                .default_headers(headers)
-                .build()?,
+            client,
+            authorization,
         })
     }
 
+    // Blocking payments and closes last until they're done, so they're sent
+    // with the client directly, without the request timeout.
+    fn post(&self, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.post(url);
+
+        match self.config.request_timeout {
+            Some(request_timeout) => request.timeout(request_timeout),
+            None => request,
+        }
+    }
+
+    // Eclair only knows about the payment status of an invoice once it has
+    // been stored as an incoming payment, so a missing entry means pending.
+    async fn get_received_info(
+        &self,
+        payment_hash: String,
+    ) -> Result<Option<GetReceivedInfoResponse>, Error> {
+        let url = format!("{}/getreceivedinfo", self.config.url);
+
+        let request = GetReceivedInfoRequest { payment_hash };
+        let response = self.post(&url).form(&request).send().await?;
+
+        match response.status() {
+            reqwest::StatusCode::NOT_FOUND => Ok(None),
+            _ => Ok(Some(Self::on_response(response).await?.json().await?)),
+        }
+    }
+
+    async fn get_channel(&self, channel_id: String) -> Result<GetChannelsResponse, Error> {
+        let url = format!("{}/channel", self.config.url);
+        let request = GetChannelRequest { channel_id };
+
+        let mut response = self.post(&url).form(&request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        Ok(response.json().await?)
+    }
+
+    async fn get_sent_info(
+        &self,
+        request: &GetSentInfoRequest,
+    ) -> Result<Vec<OutgoingPayment>, Error> {
+        let url = format!("{}/getsentinfo", self.config.url);
+
+        let mut response = self.post(&url).form(request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        Ok(response.json().await?)
+    }
+
+    // `sendtonode` returns once the payment is started, so its parts are polled
+    // until it either succeeds or fails. A payment in flight can still succeed,
+    // so only one that never shows up is given up on.
+    async fn wait_for_payment(&self, id: String) -> Result<PayInvoiceResult, Error> {
+        let deadline = Instant::now() + PAYMENT_TIMEOUT;
+
+        let request = GetSentInfoRequest {
+            id: Some(id),
+            payment_hash: None,
+        };
+        loop {
+            let parts = self.get_sent_info(&request).await?;
+
+            if parts.is_empty() && Instant::now() >= deadline {
+                return Err(Error::ApiError(String::from("payment not found")));
+            }
+            if let Some(result) = payment_result(parts) {
+                return result;
+            }
+
+            tokio::time::sleep(PAYMENT_POLL_INTERVAL).await;
+        }
+    }
+
     pub async fn on_response(response: reqwest::Response) -> Result<reqwest::Response, Error> {
         let status = response.status();
 
@@ -50,17 +162,128 @@ impl EclairRest {
     }
 }
 
-fn is_channel_in_state(n: &GetChannelsResponse, state: ChannelState) -> bool {
-    match state {
-        ChannelState::Normal => matches!((*n).state, ChannelState::Normal),
-        ChannelState::Pending => matches!((*n).state, ChannelState::Pending),
-        ChannelState::Offline => matches!((*n).state, ChannelState::Offline),
-        _ => false,
+/// Outcome of a payment from the status of its parts, `None` while in flight.
+fn payment_result(parts: Vec<OutgoingPayment>) -> Option<Result<PayInvoiceResult, Error>> {
+    if parts.is_empty()
+        || parts
+            .iter()
+            .any(|part| part.status.type_field.as_str() == "pending")
+    {
+        return None;
+    }
+
+    let sent: Vec<&OutgoingPayment> = parts
+        .iter()
+        .filter(|part| part.status.type_field.as_str() == "sent")
+        .collect();
+
+    if sent.is_empty() {
+        let message = parts
+            .iter()
+            .rev()
+            .filter_map(|part| part.status.failures.as_ref()?.last())
+            .map(|failure| failure.failure_message.clone())
+            .next()
+            .unwrap_or_else(|| String::from("payment failed"));
+
+        return Some(Err(Error::ApiError(message)));
+    }
+
+    let result = PayInvoiceResult {
+        payment_hash: sent[0].payment_hash.clone(),
+        payment_preimage: sent[0].status.payment_preimage.clone().unwrap_or_default(),
+        fees_msat: Some(
+            sent.iter()
+                .map(|part| part.status.fees_paid.unwrap_or(0))
+                .sum(),
+        ),
+    };
+
+    Some(Ok(result))
+}
+
+// Eclair has no event index, so its events have no cursor.
+fn settled_invoice_event(invoice: Invoice) -> InvoiceEvent {
+    InvoiceEvent {
+        event_type: InvoiceEventType::Settled,
+        invoice,
+        cursor: InvoiceCursor::default(),
     }
 }
 
 #[async_trait::async_trait]
 impl NodeMethods for EclairRest {
+    async fn cancel_hold_invoice(&self, _payment_hash: String) -> Result<(), Error> {
+        Err(Error::NotImplemented)
+    }
+
+    async fn cancel_invoice(&self, _payment_hash: String) -> Result<(), Error> {
+        // Eclair can neither cancel nor delete invoices.
+        Err(Error::NotImplemented)
+    }
+
+    async fn close_channel(&self, params: CloseChannelParams) -> Result<CloseChannelResult, Error> {
+        let channel_id = utils::get_channel_id(&params)?;
+
+        let mut response = match params.force.unwrap_or(false) {
+            // The commitment transaction was signed beforehand, and pays the
+            // local funds to the wallet.
+            true if params.fee_rate.is_some() || params.delivery_address.is_some() => {
+                return Err(Error::ApiError(String::from(
+                    "fee rate and delivery address can't be set for a force close",
+                )));
+            }
+            true => {
+                let url = format!("{}/forceclose", self.config.url);
+                let request = ForceCloseRequest {
+                    channel_id: channel_id.clone(),
+                };
+                self.client.post(&url).form(&request).send().await?
+            }
+            false => {
+                let url = format!("{}/close", self.config.url);
+                let request: CloseRequest = (&params).try_into()?;
+                self.client.post(&url).form(&request).send().await?
+            }
+        };
+
+        response = Self::on_response(response).await?;
+
+        let data: CloseResponse = response.json().await?;
+        match data.0.get(&channel_id).map(String::as_str) {
+            Some("ok") => {}
+            Some(error) => return Err(Error::ApiError(error.to_string())),
+            None => return Err(Error::ApiError(String::from("channel not found"))),
+        }
+
+        // The closing transactions are only known from the channel, a mutual
+        // close has none until the fees are agreed on with the peer.
+        let channel = self.get_channel(channel_id).await?;
+
+        Ok(CloseChannelResult {
+            closing_txids: channel.closing_txids(),
+        })
+    }
+
+    async fn connect_peer(&self, params: ConnectPeerParams) -> Result<(), Error> {
+        let url = format!("{}/connect", self.config.url);
+
+        let request: ConnectRequest = params.into();
+        let response = self.post(&url).form(&request).send().await?;
+
+        Self::on_response(response).await?;
+
+        Ok(())
+    }
+
+    // Eclair has no hold invoices.
+    async fn create_hold_invoice(
+        &self,
+        _invoice: CreateHoldInvoiceParams,
+    ) -> Result<CreateInvoiceResult, Error> {
+        Err(Error::NotImplemented)
+    }
+
     async fn create_invoice(
         &self,
         invoice: CreateInvoiceParams,
@@ -68,7 +291,7 @@ impl NodeMethods for EclairRest {
         let url = format!("{}/createinvoice", self.config.url);
 
         let request: CreateInvoiceRequest = invoice.into();
-        let mut response = self.client.post(&url).form(&request).send().await?;
+        let mut response = self.post(&url).form(&request).send().await?;
 
         response = Self::on_response(response).await?;
 
@@ -77,36 +300,383 @@ impl NodeMethods for EclairRest {
         Ok(data.into())
     }
 
+    async fn decode_invoice(&self, bolt11: String) -> Result<DecodedInvoice, Error> {
+        let url = format!("{}/parseinvoice", self.config.url);
+
+        let request = ParseInvoiceRequest { invoice: bolt11 };
+        let mut response = self.post(&url).form(&request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: ParseInvoiceResponse = response.json().await?;
+
+        data.try_into()
+    }
+
+    async fn delete_expired_invoices(&self, _before: Option<u64>) -> Result<(), Error> {
+        Err(Error::NotImplemented)
+    }
+
+    async fn disconnect_peer(&self, pubkey: String) -> Result<(), Error> {
+        let url = format!("{}/disconnect", self.config.url);
+
+        let request = DisconnectRequest { node_id: pubkey };
+        let response = self.post(&url).form(&request).send().await?;
+
+        Self::on_response(response).await?;
+
+        Ok(())
+    }
+
+    async fn get_balance(&self) -> Result<Balance, Error> {
+        let url = format!("{}/onchainbalance", self.config.url);
+        let mut response = self.post(&url).send().await?;
+        response = Self::on_response(response).await?;
+        let onchain: OnchainBalanceResponse = response.json().await?;
+
+        let url = format!("{}/usablebalances", self.config.url);
+        let mut response = self.post(&url).send().await?;
+        response = Self::on_response(response).await?;
+        let usable: Vec<UsableBalance> = response.json().await?;
+
+        // Only the channels in the normal state have a usable balance.
+        let url = format!("{}/channels", self.config.url);
+        let mut response = self.post(&url).send().await?;
+        response = Self::on_response(response).await?;
+        let channels: Vec<GetChannelsResponse> = response.json().await?;
+
+        let mut balance: Balance = onchain.into();
+        balance.lightning_local_msat = usable.iter().map(|channel| channel.can_send).sum();
+        balance.lightning_remote_msat = usable.iter().map(|channel| channel.can_receive).sum();
+        for channel in channels {
+            match channel.state {
+                ChannelState::WaitForFundingConfirmed
+                | ChannelState::WaitForFundingLocked
+                | ChannelState::WaitForChannelReady => {
+                    balance.pending_open_msat += channel.to_local_msat()
+                }
+                ChannelState::Shutdown | ChannelState::Negotiating | ChannelState::Closing => {
+                    balance.pending_close_msat += channel.to_local_msat()
+                }
+                _ => {}
+            }
+        }
+
+        Ok(balance)
+    }
+
+    async fn get_channel_policy(&self, channel_id: String) -> Result<ChannelPolicy, Error> {
+        self.get_channel(channel_id).await?.try_into()
+    }
+
     async fn get_info(&self) -> Result<NodeInfo, Error> {
         let url = format!("{}/getinfo", self.config.url);
-        let mut response = self.client.post(&url).send().await?;
+        let mut response = self.post(&url).send().await?;
         response = Self::on_response(response).await?;
         let data: GetInfoResponse = response.json().await?;
 
         let url_channels = format!("{}/channels", self.config.url);
-        let mut response_channels = self.client.post(&url_channels).send().await?;
+        let mut response_channels = self.post(&url_channels).send().await?;
         response_channels = Self::on_response(response_channels).await?;
 
         let data_channels: Vec<GetChannelsResponse> = response_channels.json().await?;
+        let channels: Vec<Channel> = data_channels.into_iter().map(Into::into).collect();
 
         let mut node_info: NodeInfo = data.into();
 
-        node_info.channels.active = data_channels
-            .iter()
-            .filter(|n| is_channel_in_state(n, ChannelState::Normal))
-            .count() as i64;
-        node_info.channels.inactive = data_channels
+        node_info.channels.active = channels.iter().filter(|c| c.active).count() as i64;
+        node_info.channels.inactive = channels
             .iter()
-            .filter(|n| is_channel_in_state(n, ChannelState::Offline))
+            .filter(|c| c.status == ChannelStatus::Open && !c.active)
             .count() as i64;
-        node_info.channels.pending = data_channels
+        node_info.channels.pending = channels
             .iter()
-            .filter(|n| is_channel_in_state(n, ChannelState::Pending))
+            .filter(|c| c.status == ChannelStatus::Opening)
             .count() as i64;
 
         Ok(node_info)
     }
 
+    async fn get_invoice(&self, payment_hash: String) -> Result<Invoice, Error> {
+        let url = format!("{}/getinvoice", self.config.url);
+
+        let request = GetInvoiceRequest {
+            payment_hash: payment_hash.clone(),
+        };
+        let mut response = self.post(&url).form(&request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: InvoiceResponse = response.json().await?;
+        let data_received_info = self.get_received_info(payment_hash).await?;
+
+        Ok((data, data_received_info).into())
+    }
+
+    async fn get_payment(&self, payment_hash: String) -> Result<Payment, Error> {
+        let request = GetSentInfoRequest {
+            id: None,
+            payment_hash: Some(payment_hash),
+        };
+        let parts = self.get_sent_info(&request).await?;
+
+        payment(parts).ok_or_else(|| Error::ApiError(String::from("payment not found")))
+    }
+
+    async fn keysend(&self, params: KeysendParams) -> Result<PayInvoiceResult, Error> {
+        let url = format!("{}/sendtonode", self.config.url);
+
+        let request: SendToNodeRequest = params.try_into()?;
+        let mut response = self.post(&url).form(&request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let id: String = response.json().await?;
+
+        self.wait_for_payment(id).await
+    }
+
+    async fn list_channels(&self) -> Result<Vec<Channel>, Error> {
+        let url = format!("{}/channels", self.config.url);
+        let mut response = self.post(&url).send().await?;
+        response = Self::on_response(response).await?;
+        let channels: Vec<GetChannelsResponse> = response.json().await?;
+
+        Ok(channels.into_iter().map(Into::into).collect())
+    }
+
+    async fn list_forwards(&self, params: ListForwardsParams) -> Result<ListForwardsResult, Error> {
+        let url = format!("{}/audit", self.config.url);
+
+        let request: AuditRequest = (&params).into();
+        let mut response = self.post(&url).form(&request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: AuditResponse = response.json().await?;
+
+        let mut forwards = Vec::new();
+        for relayed in data.relayed {
+            if let RelayedResponse::PaymentRelayed(relayed) = relayed {
+                forwards.push(relayed.into());
+            }
+        }
+
+        let channels = self.list_channels().await?;
+        utils::fill_forward_channels(&mut forwards, &channels);
+
+        let (forwards, next_cursor) = utils::paginate(
+            forwards,
+            params.cursor,
+            params.max_forwards,
+            utils::DEFAULT_MAX_FORWARDS,
+        );
+
+        Ok(ListForwardsResult {
+            forwards,
+            next_cursor,
+        })
+    }
+
+    async fn list_invoices(&self, params: ListInvoicesParams) -> Result<ListInvoicesResult, Error> {
+        let request: ListInvoicesRequest = (&params).into();
+
+        let url = format!("{}/listinvoices", self.config.url);
+        let mut response = self.post(&url).form(&request).send().await?;
+        response = Self::on_response(response).await?;
+        let data: Vec<InvoiceResponse> = response.json().await?;
+
+        let url_pending = format!("{}/listpendinginvoices", self.config.url);
+        let mut response_pending = self.post(&url_pending).form(&request).send().await?;
+        response_pending = Self::on_response(response_pending).await?;
+        let data_pending: Vec<InvoiceResponse> = response_pending.json().await?;
+
+        let pending_hashes: HashSet<String> = data_pending
+            .into_iter()
+            .map(|invoice| invoice.payment_hash)
+            .collect();
+
+        // Eclair doesn't list invoice statuses. The settled invoices are found
+        // in one go among the received payments of the audit, the invoices
+        // neither pending nor settled having expired.
+        let url_audit = format!("{}/audit", self.config.url);
+        let request = AuditRequest {
+            from: params.creation_date_start,
+            to: None,
+        };
+        let mut response_audit = self.post(&url_audit).form(&request).send().await?;
+        response_audit = Self::on_response(response_audit).await?;
+        let audit: AuditResponse = response_audit.json().await?;
+
+        let received_hashes: HashSet<String> = audit
+            .received
+            .into_iter()
+            .map(|payment| payment.payment_hash)
+            .collect();
+
+        let invoices: Vec<(InvoiceResponse, Invoice)> = data
+            .into_iter()
+            .map(|data| {
+                let mut invoice: Invoice = (data.clone(), None).into();
+                invoice.status = match (
+                    pending_hashes.contains(&invoice.payment_hash),
+                    received_hashes.contains(&invoice.payment_hash),
+                ) {
+                    (true, _) => InvoiceStatus::Pending,
+                    (false, true) => InvoiceStatus::Settled,
+                    (false, false) => InvoiceStatus::Cancelled,
+                };
+                (data, invoice)
+            })
+            .filter(|(_, invoice)| utils::invoice_matches(invoice, &params))
+            .collect();
+
+        let (page, next_cursor) = utils::paginate(
+            invoices,
+            params.cursor,
+            params.max_invoices,
+            utils::DEFAULT_MAX_INVOICES,
+        );
+
+        // The preimages and settle dates are only known once looked up, which
+        // is done one by one for the settled invoices of the page only.
+        let mut invoices = Vec::new();
+        for (data, invoice) in page {
+            let invoice = match invoice.status {
+                InvoiceStatus::Settled => {
+                    let received_info = self.get_received_info(invoice.payment_hash).await?;
+                    (data, received_info).into()
+                }
+                _ => invoice,
+            };
+            invoices.push(invoice);
+        }
+
+        Ok(ListInvoicesResult {
+            invoices,
+            next_cursor,
+        })
+    }
+
+    async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, Error> {
+        // The block heights are recovered from the confirmations.
+        let url = format!("{}/getinfo", self.config.url);
+        let mut response = self.post(&url).send().await?;
+        response = Self::on_response(response).await?;
+        let info: GetInfoResponse = response.json().await?;
+
+        let url = format!("{}/onchaintransactions", self.config.url);
+        let mut wallet_transactions = Vec::new();
+        loop {
+            let request = OnchainTransactionsRequest {
+                count: ONCHAIN_TRANSACTIONS_PAGE,
+                skip: wallet_transactions.len() as u64,
+            };
+            let mut response = self.post(&url).form(&request).send().await?;
+            response = Self::on_response(response).await?;
+            let page: Vec<WalletTransaction> = response.json().await?;
+
+            let is_last = (page.len() as u64) < ONCHAIN_TRANSACTIONS_PAGE;
+            wallet_transactions.extend(page);
+            if is_last {
+                break;
+            }
+        }
+
+        onchain_transactions(wallet_transactions, info.block_height)
+    }
+
+    async fn list_payments(&self, params: ListPaymentsParams) -> Result<ListPaymentsResult, Error> {
+        // Eclair only lists the payments that succeeded, pending and failed
+        // payments have to be looked up with `get_payment`.
+        if matches!(
+            params.status,
+            Some(PaymentStatus::Pending) | Some(PaymentStatus::Failed)
+        ) {
+            return Err(Error::NotImplemented);
+        }
+
+        let url = format!("{}/audit", self.config.url);
+
+        let request: AuditRequest = (&params).into();
+        let mut response = self.post(&url).form(&request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: AuditResponse = response.json().await?;
+
+        let payments: Vec<Payment> = data
+            .sent
+            .into_iter()
+            .map(Into::into)
+            .filter(|payment| utils::payment_matches(payment, &params))
+            .collect();
+
+        let (payments, next_cursor) = utils::paginate(
+            payments,
+            params.cursor,
+            params.max_payments,
+            utils::DEFAULT_MAX_PAYMENTS,
+        );
+
+        Ok(ListPaymentsResult {
+            payments,
+            next_cursor,
+        })
+    }
+
+    async fn list_peers(&self) -> Result<Vec<Peer>, Error> {
+        let url = format!("{}/peers", self.config.url);
+
+        let mut response = self.post(&url).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: Vec<PeerResponse> = response.json().await?;
+
+        Ok(data.into_iter().map(Into::into).collect())
+    }
+
+    async fn new_address(&self, address_type: AddressType) -> Result<String, Error> {
+        // Eclair takes the addresses from the Bitcoin Core wallet, which it
+        // requires to be a bech32 one.
+        if address_type != AddressType::P2wpkh {
+            return Err(Error::NotImplemented);
+        }
+
+        let url = format!("{}/getnewaddress", self.config.url);
+
+        let mut response = self.post(&url).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let address: String = response.json().await?;
+
+        Ok(address)
+    }
+
+    async fn open_channel(&self, params: OpenChannelParams) -> Result<OpenChannelResult, Error> {
+        // The params are checked before connecting to the peer, which isn't
+        // undone when they are invalid.
+        let peer = params.peer_host.clone().map(|host| ConnectPeerParams {
+            pubkey: params.peer_pubkey.clone(),
+            host: Some(host),
+        });
+        let request: OpenRequest = params.try_into()?;
+        if let Some(peer) = peer {
+            self.connect_peer(peer).await?;
+        }
+
+        let url = format!("{}/open", self.config.url);
+        let mut response = self.post(&url).form(&request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: OpenResponse = response.json().await?;
+
+        data.try_into()
+    }
+
     async fn pay_invoice(&self, invoice: PayInvoiceParams) -> Result<PayInvoiceResult, Error> {
         let url = format!("{}/payinvoice", self.config.url);
 
@@ -119,4 +689,219 @@ impl NodeMethods for EclairRest {
 
         Ok(data.try_into()?)
     }
+
+    async fn send_onchain(&self, params: SendOnchainParams) -> Result<String, Error> {
+        let url = format!("{}/sendonchain", self.config.url);
+
+        let request: SendOnchainRequest = params.try_into()?;
+        let mut response = self.post(&url).form(&request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let txid: String = response.json().await?;
+
+        Ok(txid)
+    }
+
+    async fn send_payment_async(&self, invoice: PayInvoiceParams) -> Result<String, Error> {
+        let url = format!("{}/payinvoice", self.config.url);
+
+        let payment_hash = bolt11::decode_invoice(&invoice.payment_request)?.payment_hash;
+        let request = PayInvoiceRequest {
+            blocking: false,
+            ..invoice.into()
+        };
+        let response = self.post(&url).form(&request).send().await?;
+
+        // Without blocking, Eclair only returns the id of the payment.
+        Self::on_response(response).await?;
+
+        Ok(payment_hash)
+    }
+
+    async fn settle_hold_invoice(&self, _preimage: String) -> Result<(), Error> {
+        Err(Error::NotImplemented)
+    }
+
+    async fn sign_message(&self, message: String) -> Result<String, Error> {
+        let url = format!("{}/signmessage", self.config.url);
+
+        let request: SignMessageRequest = message.as_str().into();
+        let mut response = self.post(&url).form(&request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: SignMessageResponse = response.json().await?;
+
+        data.try_into()
+    }
+
+    async fn subscribe_invoices(
+        &self,
+        _params: SubscribeInvoicesParams,
+    ) -> Result<InvoiceStream, Error> {
+        let url = format!("{}/ws", self.config.url.replacen("http", "ws", 1));
+
+        let mut request = url.into_client_request()?;
+        request
+            .headers_mut()
+            .insert("Authorization", self.authorization.parse()?);
+        let connect = tokio_tungstenite::connect_async(request);
+        let (mut socket, _) = tokio::time::timeout(self.config.connect_timeout, connect)
+            .await
+            .map_err(|_| Error::ConnectionError(String::from("connection timeout")))??;
+
+        // Eclair has no event index, so subscriptions can't be resumed from a
+        // cursor and only the events from now on are sent.
+        let node = self.clone();
+        let stream = async_stream::stream! {
+            while let Some(message) = socket.next().await {
+                let text = match message {
+                    Ok(Message::Text(text)) => text,
+                    Ok(Message::Close(_)) => break,
+                    Ok(_) => continue,
+                    Err(err) => {
+                        yield Err(err.into());
+                        break;
+                    }
+                };
+
+                // Events the node can't be asked about are reported without
+                // ending the subscription.
+                let event: WebSocketEvent = match serde_json::from_str(&text) {
+                    Ok(event) => event,
+                    Err(err) => {
+                        yield Err(err.into());
+                        continue;
+                    }
+                };
+                if let ("payment-received", Some(payment_hash)) =
+                    (event.type_field.as_str(), event.payment_hash)
+                {
+                    yield node.get_invoice(payment_hash).await.map(settled_invoice_event);
+                }
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn track_payment(&self, payment_hash: String) -> Result<PaymentStream, Error> {
+        let node = self.clone();
+
+        // The websocket only notifies the outcome of payments, so they are
+        // polled to report their current state first. Payments sent without
+        // blocking only show up once started, so they are waited for a while.
+        let stream = async_stream::try_stream! {
+            let deadline = Instant::now() + PAYMENT_TIMEOUT;
+            let request = GetSentInfoRequest {
+                id: None,
+                payment_hash: Some(payment_hash),
+            };
+            let mut status = None;
+            loop {
+                match payment(node.get_sent_info(&request).await?) {
+                    Some(payment) => {
+                        let pending = payment.status == PaymentStatus::Pending;
+                        if status.as_ref() != Some(&payment.status) {
+                            status = Some(payment.status.clone());
+                            yield payment;
+                        }
+                        if !pending {
+                            break;
+                        }
+                    }
+                    None if Instant::now() < deadline => {}
+                    None => Err(Error::ApiError(String::from("payment not found")))?,
+                }
+
+                tokio::time::sleep(PAYMENT_POLL_INTERVAL).await;
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn update_channel_policy(&self, params: UpdatePolicyParams) -> Result<(), Error> {
+        // Only the relay fees can be changed, and they apply to every channel
+        // with the peer.
+        if params.time_lock_delta.is_some()
+            || params.min_htlc_msat.is_some()
+            || params.max_htlc_msat.is_some()
+        {
+            return Err(Error::NotImplemented);
+        }
+
+        let (node_ids, params) = match &params.channel_id {
+            Some(channel_id) => {
+                let channel = self.get_channel(channel_id.clone()).await?;
+                let node_id = channel.node_id.clone();
+                let peer_channels = self
+                    .list_channels()
+                    .await?
+                    .into_iter()
+                    .filter(|channel| channel.peer_pubkey == node_id)
+                    .count();
+                if peer_channels > 1 {
+                    return Err(Error::ApiError(String::from(
+                        "the fees can't be updated for a single channel of a peer with several channels",
+                    )));
+                }
+                let policy: ChannelPolicy = channel.try_into()?;
+                (vec![node_id], utils::fill_channel_policy(&params, &policy))
+            }
+            None => {
+                let mut node_ids: Vec<String> = self
+                    .list_channels()
+                    .await?
+                    .into_iter()
+                    .map(|channel| channel.peer_pubkey)
+                    .collect();
+                node_ids.sort();
+                node_ids.dedup();
+                (node_ids, params)
+            }
+        };
+        if node_ids.is_empty() {
+            return Ok(());
+        }
+
+        let request = match (params.base_fee_msat, params.fee_rate_ppm) {
+            (Some(fee_base_msat), Some(fee_proportional_millionths)) => UpdateRelayFeeRequest {
+                node_ids: node_ids.join(","),
+                fee_base_msat,
+                fee_proportional_millionths,
+            },
+            _ => {
+                return Err(Error::ApiError(String::from(
+                    "base_fee_msat and fee_rate_ppm are required to update every channel",
+                )))
+            }
+        };
+        let url = format!("{}/updaterelayfee", self.config.url);
+        let mut response = self.post(&url).form(&request).send().await?;
+        response = Self::on_response(response).await?;
+        let data: UpdateRelayFeeResponse = response.json().await?;
+
+        match data.0.into_values().find(|outcome| outcome != "ok") {
+            Some(error) => Err(Error::ApiError(error)),
+            None => Ok(()),
+        }
+    }
+
+    async fn verify_message(
+        &self,
+        params: VerifyMessageParams,
+    ) -> Result<VerifyMessageResult, Error> {
+        let url = format!("{}/verifymessage", self.config.url);
+
+        let request: VerifyMessageRequest = (&params).try_into()?;
+        let mut response = self.post(&url).form(&request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: VerifyMessageResponse = response.json().await?;
+
+        Ok((&params, data).into())
+    }
 }
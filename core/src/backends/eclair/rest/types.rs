@@ -2,9 +2,10 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 
 use crate::error::Error;
-use crate::{types::*, utils};
+use crate::{bolt11, types::*, utils};
 
 #[derive(Debug, Deserialize)]
 pub struct ApiError {
@@ -60,6 +61,255 @@ impl Into<CreateInvoiceResult> for CreateInvoiceResponse {
     }
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetInvoiceRequest {
+    pub payment_hash: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceResponse {
+    pub prefix: String,
+    pub timestamp: i64,
+    pub node_id: String,
+    pub serialized: String,
+    pub description: Option<String>,
+    pub description_hash: Option<String>,
+    pub payment_hash: String,
+    pub expiry: Option<i32>,
+    pub amount: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ParseInvoiceRequest {
+    pub invoice: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseInvoiceResponse {
+    pub timestamp: i64,
+    pub node_id: String,
+    pub serialized: String,
+    pub description: Option<String>,
+    pub description_hash: Option<String>,
+    pub payment_hash: String,
+    pub expiry: Option<i32>,
+    pub min_final_cltv_expiry: Option<u64>,
+    pub amount: Option<u64>,
+    pub features: InvoiceFeatures,
+    pub routing_info: Vec<Vec<ExtraHop>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InvoiceFeatures {
+    pub activated: HashMap<String, String>,
+    pub unknown: Vec<UnknownFeature>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnknownFeature {
+    pub bit_index: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtraHop {
+    pub node_id: String,
+    pub short_channel_id: String,
+    pub fee_base: u32,
+    pub fee_proportional_millionths: u32,
+    pub cltv_expiry_delta: u16,
+}
+
+// Bits of the features Eclair names, besides the ones bolt11 knows, as
+// defined in BOLT 9 or by Eclair for its own features.
+const ECLAIR_FEATURES: [(&str, u32); 15] = [
+    ("option_data_loss_protect", 0),
+    ("initial_routing_sync", 2),
+    ("option_upfront_shutdown_script", 4),
+    ("gossip_queries", 6),
+    ("gossip_queries_ex", 10),
+    ("option_static_remotekey", 12),
+    ("option_support_large_channel", 18),
+    ("option_anchor_outputs", 20),
+    ("option_anchors_zero_fee_htlc_tx", 22),
+    ("option_dual_fund", 28),
+    ("option_onion_messages", 38),
+    ("option_channel_type", 44),
+    ("option_zeroconf", 50),
+    ("keysend", 54),
+    ("trampoline_payment_prototype", 148),
+];
+
+// Features named by Eclair are known, whether bolt11 knows them or not. The
+// ones without a known bit are dropped.
+fn eclair_feature(name: &str, is_required: bool) -> Option<Feature> {
+    let bit = bolt11::feature_bit(name, is_required).or_else(|| {
+        ECLAIR_FEATURES
+            .iter()
+            .find(|(eclair_name, _)| *eclair_name == name)
+            .map(|(_, bit)| if is_required { *bit } else { bit + 1 })
+    })?;
+
+    Some(Feature {
+        bit,
+        name: Some(name.to_string()),
+        is_required,
+        is_known: true,
+    })
+}
+
+impl TryInto<DecodedInvoice> for ParseInvoiceResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<DecodedInvoice, Self::Error> {
+        // Eclair names the features it knows, only the unknown ones have a bit.
+        let mut features: Vec<Feature> = self
+            .features
+            .activated
+            .iter()
+            .filter_map(|(name, support)| eclair_feature(name, support == "mandatory"))
+            .collect();
+        features.extend(self.features.unknown.iter().map(|unknown| {
+            let mut feature = bolt11::feature(unknown.bit_index);
+            feature.is_known = false;
+            feature
+        }));
+        features.sort_by_key(|feature| feature.bit);
+
+        let route_hints = self
+            .routing_info
+            .into_iter()
+            .map(|hops| RouteHint {
+                hops: hops
+                    .into_iter()
+                    .map(|hop| RouteHintHop {
+                        node_id: hop.node_id,
+                        short_channel_id: hop.short_channel_id,
+                        fee_base_msat: hop.fee_base,
+                        fee_proportional_millionths: hop.fee_proportional_millionths,
+                        cltv_expiry_delta: hop.cltv_expiry_delta,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        // Eclair doesn't return the payment secret.
+        let invoice = DecodedInvoice {
+            network: bolt11::get_network(&self.serialized)?,
+            bolt11: self.serialized,
+            payee: self.node_id,
+            amount: self.amount.map(utils::msat_to_sat),
+            amount_msat: self.amount,
+            payment_hash: self.payment_hash,
+            payment_secret: None,
+            description: self.description,
+            description_hash: self.description_hash,
+            creation_date: self.timestamp,
+            expiry: self.expiry.unwrap_or(3600),
+            min_final_cltv_expiry: self.min_final_cltv_expiry.unwrap_or(18),
+            route_hints,
+            features,
+        };
+
+        Ok(invoice)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListInvoicesRequest {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl From<&ListInvoicesParams> for ListInvoicesRequest {
+    fn from(params: &ListInvoicesParams) -> Self {
+        ListInvoicesRequest {
+            from: params.creation_date_start,
+            to: params.creation_date_end,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetReceivedInfoRequest {
+    pub payment_hash: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetReceivedInfoResponse {
+    pub payment_preimage: String,
+    pub payment_type: String,
+    pub status: ReceivedStatus,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceivedStatus {
+    #[serde(rename = "type")]
+    pub type_field: ReceivedStatusType,
+    pub amount: Option<u64>,
+    pub received_at: Option<Timestamp>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReceivedStatusType {
+    Pending,
+    Received,
+    Expired,
+}
+
+impl From<(InvoiceResponse, Option<GetReceivedInfoResponse>)> for Invoice {
+    fn from((invoice, received_info): (InvoiceResponse, Option<GetReceivedInfoResponse>)) -> Self {
+        let status = match received_info.as_ref().map(|info| &info.status.type_field) {
+            Some(ReceivedStatusType::Received) => InvoiceStatus::Settled,
+            Some(ReceivedStatusType::Expired) => InvoiceStatus::Cancelled,
+            Some(ReceivedStatusType::Pending) | None => InvoiceStatus::Pending,
+        };
+        let settled = matches!(status, InvoiceStatus::Settled);
+
+        let (pre_image, settle_date) = match (settled, received_info) {
+            (true, Some(info)) => (
+                Some(info.payment_preimage),
+                info.status
+                    .received_at
+                    .map(|received_at| received_at.unix as i64),
+            ),
+            _ => (None, None),
+        };
+        let amount_msat = invoice.amount.unwrap_or(0);
+
+        Invoice {
+            bolt11: invoice.serialized,
+            memo: invoice.description.unwrap_or_default(),
+            amount: utils::msat_to_sat(amount_msat),
+            amount_msat,
+            pre_image,
+            payment_hash: invoice.payment_hash,
+            settled,
+            settle_date,
+            creation_date: invoice.timestamp,
+            expiry: invoice.expiry.unwrap_or(3600),
+            status,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketEvent {
+    #[serde(rename = "type")]
+    pub type_field: String,
+    pub payment_hash: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetInfoResponse {
@@ -68,20 +318,225 @@ pub struct GetInfoResponse {
     pub alias: String,
     pub color: String,
     pub network: String,
+    pub block_height: u32,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct GetChannelsResponse {
+    pub node_id: String,
+    pub channel_id: String,
     pub state: ChannelState,
+    pub data: Option<ChannelData>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
-#[serde(rename_all = "UPPERCASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ChannelState {
     Normal,
     Offline,
     Closed,
-    Pending,
+    WaitForInitInternal,
+    WaitForOpenChannel,
+    WaitForAcceptChannel,
+    WaitForFundingInternal,
+    WaitForFundingCreated,
+    WaitForFundingSigned,
+    WaitForFundingConfirmed,
+    WaitForFundingLocked,
+    /// `WaitForFundingLocked` since Eclair v0.8.
+    WaitForChannelReady,
+    Syncing,
+    Shutdown,
+    Negotiating,
+    Closing,
+    WaitForRemotePublishFutureCommitment,
+    ErrInformationLeak,
+    #[serde(other)]
+    Unknown,
+}
+
+impl Into<ChannelStatus> for &ChannelState {
+    fn into(self) -> ChannelStatus {
+        match self {
+            ChannelState::WaitForInitInternal
+            | ChannelState::WaitForOpenChannel
+            | ChannelState::WaitForAcceptChannel
+            | ChannelState::WaitForFundingInternal
+            | ChannelState::WaitForFundingCreated
+            | ChannelState::WaitForFundingSigned
+            | ChannelState::WaitForFundingConfirmed
+            | ChannelState::WaitForFundingLocked
+            | ChannelState::WaitForChannelReady => ChannelStatus::Opening,
+            ChannelState::Normal | ChannelState::Offline | ChannelState::Syncing => {
+                ChannelStatus::Open
+            }
+            ChannelState::Shutdown
+            | ChannelState::Negotiating
+            | ChannelState::Closing
+            | ChannelState::WaitForRemotePublishFutureCommitment
+            | ChannelState::ErrInformationLeak => ChannelStatus::Closing,
+            ChannelState::Closed => ChannelStatus::Closed,
+            // The states added by later releases are the ones of the dual
+            // funded channels being opened.
+            ChannelState::Unknown => ChannelStatus::Opening,
+        }
+    }
+}
+
+// Channels waiting for their funding transaction have no commitments yet.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelData {
+    pub commitments: Option<Commitments>,
+    pub short_channel_id: Option<String>,
+    pub mutual_close_published: Option<Vec<PublishedTransaction>>,
+    pub local_commit_published: Option<LocalCommitPublished>,
+    pub channel_update: Option<ChannelUpdate>,
+}
+
+// Last update of the channel sent by the node, holding its relay policy.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelUpdate {
+    pub cltv_expiry_delta: u32,
+    pub htlc_minimum_msat: u64,
+    pub htlc_maximum_msat: Option<u64>,
+    pub fee_base_msat: u64,
+    pub fee_proportional_millionths: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PublishedTransaction {
+    pub txid: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalCommitPublished {
+    pub commit_tx: PublishedTransaction,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Commitments {
+    pub local_params: ChannelParams,
+    pub remote_params: ChannelParams,
+    pub channel_flags: OpenChannelFlags,
+    pub local_commit: LocalCommit,
+    pub commit_input: CommitInput,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelParams {
+    pub channel_reserve: Option<u64>,
+    #[serde(alias = "isInitiator")]
+    pub is_funder: Option<bool>,
+}
+
+// Eclair v0.7.0 describes the flags, older versions send the raw byte.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum OpenChannelFlags {
+    Flags {
+        #[serde(rename = "announceChannel")]
+        announce_channel: bool,
+    },
+    Byte(u8),
+}
+
+impl OpenChannelFlags {
+    pub fn announce_channel(&self) -> bool {
+        match self {
+            OpenChannelFlags::Flags { announce_channel } => *announce_channel,
+            OpenChannelFlags::Byte(flags) => flags & 1 == 1,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LocalCommit {
+    pub spec: CommitmentSpec,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitmentSpec {
+    pub to_local: u64,
+    pub to_remote: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitInput {
+    pub out_point: String,
+    pub amount_satoshis: u64,
+}
+
+impl GetChannelsResponse {
+    pub fn to_local_msat(&self) -> u64 {
+        self.commitments()
+            .map(|commitments| commitments.local_commit.spec.to_local)
+            .unwrap_or(0)
+    }
+
+    /// Lists the closing transactions published by the node.
+    pub fn closing_txids(&self) -> Vec<String> {
+        self.data
+            .iter()
+            .flat_map(|data| {
+                let mutual_close = data.mutual_close_published.iter().flatten();
+                let local_commit = data.local_commit_published.iter().map(|c| &c.commit_tx);
+                mutual_close.chain(local_commit)
+            })
+            .map(|transaction| transaction.txid.clone())
+            .collect()
+    }
+
+    fn commitments(&self) -> Option<&Commitments> {
+        self.data.as_ref()?.commitments.as_ref()
+    }
+}
+
+impl Into<Channel> for GetChannelsResponse {
+    fn into(self) -> Channel {
+        let status: ChannelStatus = (&self.state).into();
+        let commitments = self.commitments();
+
+        Channel {
+            channel_point: commitments.map(|c| c.commit_input.out_point.clone()),
+            short_channel_id: self
+                .data
+                .as_ref()
+                .and_then(|data| data.short_channel_id.clone()),
+            capacity_msat: commitments
+                .map(|c| utils::sat_to_msat(c.commit_input.amount_satoshis))
+                .unwrap_or(0),
+            local_balance_msat: self.to_local_msat(),
+            remote_balance_msat: commitments
+                .map(|c| c.local_commit.spec.to_remote)
+                .unwrap_or(0),
+            local_reserve_msat: commitments
+                .and_then(|c| c.local_params.channel_reserve)
+                .map(utils::sat_to_msat)
+                .unwrap_or(0),
+            remote_reserve_msat: commitments
+                .and_then(|c| c.remote_params.channel_reserve)
+                .map(utils::sat_to_msat)
+                .unwrap_or(0),
+            active: matches!(self.state, ChannelState::Normal),
+            private: commitments
+                .map(|c| !c.channel_flags.announce_channel())
+                .unwrap_or(false),
+            initiator: commitments
+                .and_then(|c| c.local_params.is_funder)
+                .unwrap_or(false),
+            channel_id: self.channel_id,
+            peer_pubkey: self.node_id,
+            status,
+        }
+    }
 }
 
 impl Into<NodeInfo> for GetInfoResponse {
@@ -196,14 +651,12 @@ pub struct TlvStream {
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-
 pub struct ChannelFlags {
     pub is_enabled: bool,
     pub is_node1: bool,
 }
 
 #[derive(Debug, Deserialize)]
-
 pub struct Timestamp {
     pub iso: String,
     pub unix: u64,
@@ -268,3 +721,677 @@ impl TryInto<PayInvoiceResult> for PayInvoiceResponse {
         Ok(result)
     }
 }
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendToNodeRequest {
+    pub node_id: String,
+    pub amount_msat: u64,
+    pub max_attempts: Option<u64>,
+    pub max_fee_flat_sat: Option<u64>,
+    pub max_fee_pct: Option<f64>,
+    pub external_id: Option<String>,
+    pub path_finding_experiment_name: Option<String>,
+}
+
+impl TryFrom<KeysendParams> for SendToNodeRequest {
+    type Error = Error;
+
+    fn try_from(params: KeysendParams) -> Result<Self, Self::Error> {
+        // Eclair's API has no way to send custom records.
+        if params
+            .custom_records
+            .as_ref()
+            .is_some_and(|custom_records| !custom_records.is_empty())
+        {
+            return Err(Error::NotImplemented);
+        }
+
+        let amount_msat = utils::get_amount_msat(params.amount, params.amount_msat)
+            .ok_or_else(|| Error::ApiError(String::from("amount is required")))?;
+
+        // Eclair allows the highest of both limits, so the one not given is
+        // zeroed instead of falling back to the node defaults.
+        let max_fee_sat = utils::get_amount_sat(params.max_fee_sat, params.max_fee_msat);
+        let (max_fee_flat_sat, max_fee_pct) = match (max_fee_sat, params.max_fee_percent) {
+            (Some(max_fee_sat), _) => (Some(max_fee_sat), Some(0.0)),
+            (None, Some(max_fee_percent)) => (Some(0), Some(max_fee_percent)),
+            (None, None) => (None, None),
+        };
+
+        let request = SendToNodeRequest {
+            node_id: params.destination,
+            amount_msat,
+            max_attempts: None,
+            max_fee_flat_sat,
+            max_fee_pct,
+            external_id: None,
+            path_finding_experiment_name: None,
+        };
+
+        Ok(request)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetSentInfoRequest {
+    pub id: Option<String>,
+    pub payment_hash: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SerializedInvoice {
+    pub serialized: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutgoingPayment {
+    pub id: String,
+    pub parent_id: String,
+    pub payment_hash: String,
+    pub amount: u64,
+    pub recipient_amount: u64,
+    pub recipient_node_id: String,
+    pub created_at: Timestamp,
+    // Renamed from `paymentRequest` in Eclair v0.7.
+    #[serde(alias = "paymentRequest")]
+    pub invoice: Option<SerializedInvoice>,
+    pub status: OutgoingPaymentStatus,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutgoingPaymentStatus {
+    #[serde(rename = "type")]
+    pub type_field: String,
+    pub payment_preimage: Option<String>,
+    pub fees_paid: Option<u64>,
+    pub failures: Option<Vec<FailureSummary>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureSummary {
+    pub failure_type: String,
+    pub failure_message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OnchainBalanceResponse {
+    pub confirmed: u64,
+    pub unconfirmed: u64,
+}
+
+impl Into<Balance> for OnchainBalanceResponse {
+    fn into(self) -> Balance {
+        Balance {
+            onchain_confirmed_msat: utils::sat_to_msat(self.confirmed),
+            onchain_unconfirmed_msat: utils::sat_to_msat(self.unconfirmed),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsableBalance {
+    pub remote_node_id: String,
+    pub short_channel_id: String,
+    pub can_send: u64,
+    pub can_receive: u64,
+    pub is_public: bool,
+}
+
+// Eclair looks the address of the node up when only given its id.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectRequest {
+    pub uri: Option<String>,
+    pub node_id: Option<String>,
+}
+
+impl From<ConnectPeerParams> for ConnectRequest {
+    fn from(params: ConnectPeerParams) -> Self {
+        match params.host {
+            Some(host) => ConnectRequest {
+                uri: Some(format!("{}@{}", params.pubkey, host)),
+                node_id: None,
+            },
+            None => ConnectRequest {
+                uri: None,
+                node_id: Some(params.pubkey),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisconnectRequest {
+    pub node_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerResponse {
+    pub node_id: String,
+    pub state: String,
+    pub address: Option<String>,
+}
+
+impl Into<Peer> for PeerResponse {
+    fn into(self) -> Peer {
+        Peer {
+            pubkey: self.node_id,
+            addresses: self.address.into_iter().collect(),
+            connected: self.state == "CONNECTED",
+            features: Vec::new(),
+            inbound: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenRequest {
+    pub node_id: String,
+    pub funding_satoshis: u64,
+    pub push_msat: Option<u64>,
+    pub funding_feerate_sat_byte: Option<u64>,
+    pub announce_channel: Option<bool>,
+}
+
+impl TryFrom<OpenChannelParams> for OpenRequest {
+    type Error = Error;
+
+    fn try_from(params: OpenChannelParams) -> Result<Self, Self::Error> {
+        // Eclair funds channels with the minimum depth of its own config.
+        if params.min_confs.is_some() {
+            return Err(Error::NotImplemented);
+        }
+
+        let request = OpenRequest {
+            node_id: params.peer_pubkey,
+            funding_satoshis: params.amount,
+            push_msat: params.push_amount.map(utils::sat_to_msat),
+            funding_feerate_sat_byte: params.fee_rate,
+            announce_channel: params.private.map(|private| !private),
+        };
+
+        Ok(request)
+    }
+}
+
+// Eclair only answers with a sentence such as `created channel <channel id>
+// with fundingTxId=<txid> and fees=<fees> sat`.
+#[derive(Debug, Deserialize)]
+pub struct OpenResponse(pub String);
+
+impl TryInto<OpenChannelResult> for OpenResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<OpenChannelResult, Self::Error> {
+        let invalid = || Error::ConversionError(format!("unexpected open response: {}", self.0));
+
+        let mut words = self.0.split_whitespace();
+        let channel_id = words
+            .by_ref()
+            .skip_while(|word| *word != "channel")
+            .nth(1)
+            .ok_or_else(invalid)?;
+        let funding_txid = words
+            .find_map(|word| word.strip_prefix("fundingTxId="))
+            .ok_or_else(invalid)?;
+
+        let result = OpenChannelResult {
+            channel_id: channel_id.to_string(),
+            funding_txid: funding_txid.to_string(),
+            output_index: utils::output_index_from_channel_id(channel_id, funding_txid)?,
+        };
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetChannelRequest {
+    pub channel_id: String,
+}
+
+impl TryInto<ChannelPolicy> for GetChannelsResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ChannelPolicy, Self::Error> {
+        let update = self
+            .data
+            .and_then(|data| data.channel_update)
+            .ok_or_else(|| Error::ApiError(String::from("channel policy not known yet")))?;
+
+        let policy = ChannelPolicy {
+            channel_id: self.channel_id,
+            base_fee_msat: update.fee_base_msat,
+            fee_rate_ppm: update.fee_proportional_millionths,
+            time_lock_delta: Some(update.cltv_expiry_delta),
+            min_htlc_msat: update.htlc_minimum_msat,
+            max_htlc_msat: update.htlc_maximum_msat,
+        };
+
+        Ok(policy)
+    }
+}
+
+// Eclair sets the relay fees by peer, both at once.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRelayFeeRequest {
+    /// Public keys of the peers, comma-separated.
+    pub node_ids: String,
+    pub fee_base_msat: u64,
+    pub fee_proportional_millionths: u64,
+}
+
+// The fees are updated channel by channel, with the outcome of each update as
+// either `ok` or the reason of the failure.
+#[derive(Debug, Deserialize)]
+pub struct UpdateRelayFeeResponse(pub HashMap<String, String>);
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloseRequest {
+    pub channel_id: String,
+    pub script_pub_key: Option<String>,
+    pub preferred_feerate_sat_byte: Option<u64>,
+}
+
+impl TryFrom<&CloseChannelParams> for CloseRequest {
+    type Error = Error;
+
+    fn try_from(params: &CloseChannelParams) -> Result<Self, Self::Error> {
+        let script_pub_key = match &params.delivery_address {
+            Some(address) => Some(hex::encode(utils::segwit_script_pubkey(address)?)),
+            None => None,
+        };
+
+        let request = CloseRequest {
+            channel_id: utils::get_channel_id(params)?,
+            script_pub_key,
+            preferred_feerate_sat_byte: params.fee_rate,
+        };
+
+        Ok(request)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForceCloseRequest {
+    pub channel_id: String,
+}
+
+// Channels are closed in bulk, with the outcome of each closing as either
+// `ok` or the reason of the failure.
+#[derive(Debug, Deserialize)]
+pub struct CloseResponse(pub HashMap<String, String>);
+
+// Eclair requires a confirmation target, this is the one LND defaults to.
+const DEFAULT_CONFIRMATION_TARGET: u32 = 6;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendOnchainRequest {
+    pub address: String,
+    pub amount_satoshis: u64,
+    pub confirmation_target: u32,
+}
+
+impl TryFrom<SendOnchainParams> for SendOnchainRequest {
+    type Error = Error;
+
+    fn try_from(params: SendOnchainParams) -> Result<Self, Self::Error> {
+        // Eclair can't send all the funds, take a fee rate or be told which
+        // outputs to spend.
+        if params.fee_rate.is_some() || params.utxos.is_some() {
+            return Err(Error::NotImplemented);
+        }
+        let amount = utils::get_onchain_amount(&params)?.ok_or(Error::NotImplemented)?;
+
+        let request = SendOnchainRequest {
+            address: params.address,
+            amount_satoshis: amount,
+            confirmation_target: params.target_conf.unwrap_or(DEFAULT_CONFIRMATION_TARGET),
+        };
+
+        Ok(request)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct OnchainTransactionsRequest {
+    pub count: u64,
+    pub skip: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WalletTransaction {
+    pub amount: i64,
+    pub fees: i64,
+    /// Negative for the transactions conflicting with the chain.
+    pub confirmations: i64,
+    pub txid: String,
+    pub timestamp: u64,
+}
+
+// The transactions come from the Bitcoin Core wallet, which lists them once
+// per output, the send amounts being given without the fee.
+pub fn onchain_transactions(
+    wallet_transactions: Vec<WalletTransaction>,
+    block_height: u32,
+) -> Result<Vec<OnchainTransaction>, Error> {
+    let mut transactions: Vec<(WalletTransaction, i64)> = Vec::new();
+    for wallet_transaction in wallet_transactions {
+        match transactions
+            .iter_mut()
+            .find(|(transaction, _)| transaction.txid == wallet_transaction.txid)
+        {
+            Some((transaction, amount)) => {
+                *amount += wallet_transaction.amount;
+                transaction.fees = transaction.fees.min(wallet_transaction.fees);
+            }
+            None => {
+                let amount = wallet_transaction.amount;
+                transactions.push((wallet_transaction, amount));
+            }
+        }
+    }
+
+    transactions
+        .into_iter()
+        .map(|(transaction, amount)| {
+            let fee = transaction.fees.unsigned_abs();
+            let amount = match amount < 0 {
+                true => amount - i64::try_from(fee)?,
+                false => amount,
+            };
+            let confirmations = u32::try_from(transaction.confirmations.max(0))?;
+            let block_height = match confirmations {
+                0 => None,
+                confirmations => Some(block_height.saturating_sub(confirmations) + 1),
+            };
+
+            Ok(OnchainTransaction {
+                txid: transaction.txid,
+                amount_received_msat: utils::sat_to_msat(u64::try_from(amount.max(0))?),
+                amount_sent_msat: utils::sat_to_msat(amount.min(0).unsigned_abs()),
+                fee_msat: Some(utils::sat_to_msat(fee)),
+                confirmations,
+                block_height,
+                timestamp: Some(transaction.timestamp),
+                label: None,
+                short_channel_id: None,
+            })
+        })
+        .collect()
+}
+
+fn outgoing_payment_status(status: &str) -> PaymentStatus {
+    match status {
+        "sent" => PaymentStatus::Succeeded,
+        "failed" => PaymentStatus::Failed,
+        _ => PaymentStatus::Pending,
+    }
+}
+
+// `getsentinfo` lists the parts of every attempt at a payment, the payment is
+// built from the parts of the last attempt.
+pub fn payment(parts: Vec<OutgoingPayment>) -> Option<Payment> {
+    let last = parts.iter().max_by_key(|part| part.created_at.unix)?;
+    let parent_id = last.parent_id.clone();
+    let created_at = parts.iter().map(|part| part.created_at.unix).min()?;
+    let attempt: Vec<OutgoingPayment> = parts
+        .into_iter()
+        .filter(|part| part.parent_id == parent_id)
+        .collect();
+
+    let payment_parts: Vec<crate::types::PaymentPart> = attempt
+        .iter()
+        .map(|part| crate::types::PaymentPart {
+            amount_msat: part.amount,
+            fee_msat: part.status.fees_paid.unwrap_or(0),
+            status: outgoing_payment_status(&part.status.type_field),
+        })
+        .collect();
+
+    let has_status = |status| payment_parts.iter().any(|part| part.status == status);
+    let status = if has_status(PaymentStatus::Succeeded) {
+        PaymentStatus::Succeeded
+    } else if has_status(PaymentStatus::Pending) {
+        PaymentStatus::Pending
+    } else {
+        PaymentStatus::Failed
+    };
+    let failure_reason = match status {
+        PaymentStatus::Failed => Some(
+            attempt
+                .iter()
+                .rev()
+                .filter_map(|part| part.status.failures.as_ref()?.last())
+                .map(|failure| failure.failure_message.clone())
+                .next()
+                .unwrap_or_else(|| String::from("payment failed")),
+        ),
+        _ => None,
+    };
+
+    let first = attempt.first()?;
+    let payment = Payment {
+        payment_hash: first.payment_hash.clone(),
+        payment_preimage: attempt
+            .iter()
+            .find_map(|part| part.status.payment_preimage.clone()),
+        amount_msat: first.recipient_amount,
+        fee_msat: match status {
+            PaymentStatus::Succeeded => Some(payment_parts.iter().map(|part| part.fee_msat).sum()),
+            _ => None,
+        },
+        status,
+        created_at: created_at as i64,
+        destination: Some(first.recipient_node_id.clone()),
+        bolt11: first
+            .invoice
+            .as_ref()
+            .map(|invoice| invoice.serialized.clone()),
+        failure_reason,
+        parts: payment_parts,
+    };
+
+    Some(payment)
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditRequest {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl From<&ListPaymentsParams> for AuditRequest {
+    fn from(params: &ListPaymentsParams) -> Self {
+        AuditRequest {
+            from: params.creation_date_start,
+            to: params.creation_date_end,
+        }
+    }
+}
+
+impl From<&ListForwardsParams> for AuditRequest {
+    fn from(params: &ListForwardsParams) -> Self {
+        AuditRequest {
+            from: params.date_start,
+            to: params.date_end,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditResponse {
+    pub sent: Vec<PayInvoiceResponse>,
+    pub received: Vec<ReceivedPaymentResponse>,
+    pub relayed: Vec<RelayedResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceivedPaymentResponse {
+    pub payment_hash: String,
+}
+
+// Trampoline relays aggregate several incoming and outgoing channels, they are
+// skipped as they can't be told apart per channel.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum RelayedResponse {
+    PaymentRelayed(ChannelPaymentRelayed),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelPaymentRelayed {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub from_channel_id: String,
+    pub to_channel_id: String,
+    pub timestamp: Timestamp,
+}
+
+// Eclair only records the relays once settled.
+impl Into<Forward> for ChannelPaymentRelayed {
+    fn into(self) -> Forward {
+        Forward {
+            incoming_channel_id: Some(self.from_channel_id),
+            incoming_short_channel_id: None,
+            outgoing_channel_id: Some(self.to_channel_id),
+            outgoing_short_channel_id: None,
+            amount_in_msat: self.amount_in,
+            amount_out_msat: Some(self.amount_out),
+            fee_msat: self.amount_in.saturating_sub(self.amount_out),
+            status: ForwardStatus::Settled,
+            received_at: None,
+            resolved_at: Some(self.timestamp.unix as i64),
+        }
+    }
+}
+
+// The audit only keeps the payments that succeeded, and only dates their
+// parts once settled.
+impl Into<Payment> for PayInvoiceResponse {
+    fn into(self) -> Payment {
+        let sent_parts = self.parts.unwrap_or_default();
+        let created_at = sent_parts
+            .iter()
+            .map(|part| part.timestamp.unix)
+            .min()
+            .unwrap_or(0);
+        let parts: Vec<crate::types::PaymentPart> = sent_parts
+            .into_iter()
+            .map(|part| crate::types::PaymentPart {
+                amount_msat: part.amount as u64,
+                fee_msat: part.fees_paid as u64,
+                status: PaymentStatus::Succeeded,
+            })
+            .collect();
+
+        Payment {
+            payment_hash: self.payment_hash,
+            payment_preimage: self.payment_preimage,
+            amount_msat: self.recipient_amount.unwrap_or(0),
+            fee_msat: Some(parts.iter().map(|part| part.fee_msat).sum()),
+            status: PaymentStatus::Succeeded,
+            created_at: created_at as i64,
+            destination: self.recipient_node_id,
+            bolt11: None,
+            failure_reason: None,
+            parts,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignMessageRequest {
+    /// Message in base64.
+    pub msg: String,
+}
+
+impl From<&str> for SignMessageRequest {
+    fn from(message: &str) -> Self {
+        SignMessageRequest {
+            msg: base64::encode(message),
+        }
+    }
+}
+
+// Eclair signs like LND and Core Lightning, but gives the signature in hex
+// instead of zbase32.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignMessageResponse {
+    pub node_id: String,
+    pub signature: String,
+}
+
+impl TryInto<String> for SignMessageResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<String, Self::Error> {
+        Ok(utils::zbase32_encode(&hex::decode(self.signature)?))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyMessageRequest {
+    /// Message in base64.
+    pub msg: String,
+    /// Signature in hex.
+    pub sig: String,
+}
+
+impl TryFrom<&VerifyMessageParams> for VerifyMessageRequest {
+    type Error = Error;
+
+    fn try_from(params: &VerifyMessageParams) -> Result<Self, Self::Error> {
+        let request = VerifyMessageRequest {
+            msg: base64::encode(&params.message),
+            sig: hex::encode(utils::zbase32_decode(&params.signature)?),
+        };
+
+        Ok(request)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyMessageResponse {
+    pub valid: bool,
+    pub public_key: String,
+}
+
+// Eclair only recovers the signer, so the signature is checked against the
+// expected one here.
+impl Into<VerifyMessageResult> for (&VerifyMessageParams, VerifyMessageResponse) {
+    fn into(self) -> VerifyMessageResult {
+        let (params, response) = self;
+
+        let valid = match &params.pubkey {
+            Some(pubkey) => response.valid && pubkey.eq_ignore_ascii_case(&response.public_key),
+            None => response.valid,
+        };
+
+        VerifyMessageResult {
+            valid,
+            pubkey: response.public_key,
+        }
+    }
+}
@@ -1,7 +1,53 @@
-/* TODO
+use http::uri::Uri;
+use std::convert::TryFrom;
+use std::time::Duration;
 
-pub struct LndGrpcConfig {
+use crate::error::{ConfigError, Error};
+use crate::types::NodeConfig;
+use crate::utils;
 
+#[derive(Clone, Debug)]
+pub struct LndGrpcConfig {
+    pub url: String,
+    pub macaroon: String,
+    pub tls_certificate: Vec<u8>,
+    pub connect_timeout: Duration,
+    pub request_timeout: Option<Duration>,
 }
 
-*/
+impl TryFrom<NodeConfig> for LndGrpcConfig {
+    type Error = Error;
+
+    fn try_from(config: NodeConfig) -> Result<Self, Self::Error> {
+        let url = config
+            .url
+            .ok_or_else(|| ConfigError::MissingField("url".to_string()))?;
+        let macaroon = config
+            .macaroon
+            .ok_or_else(|| ConfigError::MissingField("macaroon".to_string()))?;
+        let tls_certificate = config
+            .tls_certificate
+            .ok_or_else(|| ConfigError::MissingField("tls_certificate".to_string()))?;
+
+        // Verify URL
+        Uri::from_maybe_shared(url.clone())
+            .map_err(|_| ConfigError::InvalidField("url".to_string()))?;
+
+        // Verify macaroon, as it is sent as is in the request metadata
+        hex::decode(&macaroon).map_err(|_| ConfigError::ParsingHexError("macaroon".to_string()))?;
+
+        let config = LndGrpcConfig {
+            url,
+            macaroon,
+            tls_certificate: hex::decode(&tls_certificate)
+                .map_err(|_| ConfigError::ParsingHexError("tls_certificate".to_string()))?,
+            connect_timeout: config
+                .connect_timeout
+                .map(Duration::from_secs)
+                .unwrap_or(utils::DEFAULT_CONNECT_TIMEOUT),
+            request_timeout: config.request_timeout.map(Duration::from_secs),
+        };
+
+        Ok(config)
+    }
+}
@@ -1 +1,908 @@
-// TODO
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+
+use futures::StreamExt;
+use http::uri::{Scheme, Uri};
+use hyper::client::HttpConnector;
+use hyper::service::Service;
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, ClientConfig, ServerName};
+use tokio::net::TcpStream;
+use tokio::sync::OnceCell;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::TlsConnector;
+use tonic::codegen::InterceptedService;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::Interceptor;
+use tonic::transport::{self, Endpoint};
+use tonic::Status;
+
+use crate::error::{ConfigError, Error};
+use crate::node::{InvoiceStream, NodeMethods, PaymentStream};
+use crate::types::{
+    AddressType, Balance, Channel, ChannelPolicy, ChannelStatus, CloseChannelParams,
+    CloseChannelResult, ConnectPeerParams, CreateHoldInvoiceParams, CreateInvoiceParams,
+    CreateInvoiceResult, DecodedInvoice, Forward, Invoice, InvoiceEvent, InvoiceEventType,
+    KeysendParams, ListForwardsParams, ListForwardsResult, ListInvoicesParams, ListInvoicesResult,
+    ListPaymentsParams, ListPaymentsResult, NodeInfo, OnchainTransaction, OpenChannelParams,
+    OpenChannelResult, PayInvoiceParams, PayInvoiceResult, Payment, Peer, SendOnchainParams,
+    SubscribeInvoicesParams, UpdatePolicyParams, VerifyMessageParams, VerifyMessageResult,
+};
+use crate::utils;
+
+use super::config::LndGrpcConfig;
+use super::types::invoicesrpc::{
+    invoices_client::InvoicesClient, AddHoldInvoiceRequest, CancelInvoiceMsg, SettleInvoiceMsg,
+};
+use super::types::lnrpc::{
+    self, lightning_client::LightningClient, ChanInfoRequest, ChannelBalanceRequest,
+    ConnectPeerRequest, DisconnectPeerRequest, ForwardingHistoryRequest, GetInfoRequest,
+    InvoiceSubscription, LightningAddress, ListChannelsRequest, ListInvoiceRequest,
+    ListPaymentsRequest, ListPeersRequest, NodeInfoRequest, PayReqString, PaymentHash,
+    PendingChannelsRequest, PolicyUpdateRequest, SignMessageRequest, VerifyMessageRequest,
+    WalletBalanceRequest,
+};
+use super::types::routerrpc::{
+    router_client::RouterClient, SendPaymentRequest, TrackPaymentRequest,
+};
+
+type LndService = InterceptedService<transport::Channel, MacaroonInterceptor>;
+
+#[derive(Clone)]
+pub struct MacaroonInterceptor {
+    macaroon: MetadataValue<Ascii>,
+}
+
+impl Interceptor for MacaroonInterceptor {
+    fn call(&mut self, mut request: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
+        request
+            .metadata_mut()
+            .insert("macaroon", self.macaroon.clone());
+        Ok(request)
+    }
+}
+
+// LND generates a self-signed certificate which webpki refuses as a server
+// certificate, so the certificate from the config is pinned instead.
+struct PinnedCertificateVerifier {
+    certificates: Vec<Certificate>,
+}
+
+impl ServerCertVerifier for PinnedCertificateVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        match self.certificates.contains(end_entity) {
+            true => Ok(ServerCertVerified::assertion()),
+            false => Err(rustls::Error::InvalidCertificateData(String::from(
+                "server certificate doesn't match the configured certificate",
+            ))),
+        }
+    }
+}
+
+// tonic only does TLS with the webpki verifier, and refuses https URIs it
+// doesn't do TLS for. The endpoint is given as http instead, and the connector
+// does TLS with the pinned certificate whatever the scheme.
+#[derive(Clone)]
+struct PinnedTlsConnector {
+    http: HttpConnector,
+    tls: TlsConnector,
+}
+
+impl Service<Uri> for PinnedTlsConnector {
+    type Response = TlsStream<TcpStream>;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let mut http = self.http.clone();
+        let tls = self.tls.clone();
+
+        Box::pin(async move {
+            // The certificate is pinned, the server name is only sent to the
+            // node.
+            let server_name = ServerName::try_from(uri.host().unwrap_or("localhost"))
+                .unwrap_or_else(|_| ServerName::try_from("localhost").unwrap());
+            let stream = http.call(uri).await?;
+
+            Ok(tls.connect(server_name, stream).await?)
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct LndGrpc {
+    endpoint: Endpoint,
+    connector: PinnedTlsConnector,
+    channel: Arc<OnceCell<transport::Channel>>,
+    interceptor: MacaroonInterceptor,
+    request_timeout: Option<Duration>,
+}
+
+impl LndGrpc {
+    pub fn new(config: LndGrpcConfig) -> Result<Self, Error> {
+        let certificates = rustls_pemfile::certs(&mut config.tls_certificate.as_slice())
+            .map_err(|_| ConfigError::InvalidField("tls_certificate".to_string()))?;
+        if certificates.is_empty() {
+            return Err(ConfigError::InvalidField("tls_certificate".to_string()).into());
+        }
+
+        let verifier = PinnedCertificateVerifier {
+            certificates: certificates.into_iter().map(Certificate).collect(),
+        };
+        let mut tls = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(verifier))
+            .with_no_client_auth();
+        tls.alpn_protocols = vec![b"h2".to_vec()];
+
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        http.set_nodelay(true);
+        http.set_connect_timeout(Some(config.connect_timeout));
+        let connector = PinnedTlsConnector {
+            http,
+            tls: TlsConnector::from(Arc::new(tls)),
+        };
+
+        let interceptor = MacaroonInterceptor {
+            macaroon: MetadataValue::try_from(config.macaroon)
+                .expect("Already verified macaroon failed parsing"),
+        };
+
+        let mut url = Uri::from_maybe_shared(config.url)
+            .expect("Already verified URL failed parsing")
+            .into_parts();
+        url.scheme = Some(Scheme::HTTP);
+        let url = Uri::from_parts(url).expect("Already verified URL failed parsing");
+
+        let endpoint = Endpoint::from(url)
+            .tcp_keepalive(Some(Duration::from_secs(60)))
+            .http2_keep_alive_interval(Duration::from_secs(30))
+            .keep_alive_timeout(Duration::from_secs(10))
+            .keep_alive_while_idle(true);
+
+        Ok(LndGrpc {
+            endpoint,
+            connector,
+            channel: Arc::new(OnceCell::new()),
+            interceptor,
+            request_timeout: config.request_timeout,
+        })
+    }
+
+    // The channel is shared by all the clients and reconnects on its own when
+    // the connection drops. It is created on first use, as it needs a runtime.
+    async fn service(&self) -> LndService {
+        let channel = self
+            .channel
+            .get_or_init(|| async {
+                self.endpoint
+                    .connect_with_connector_lazy(self.connector.clone())
+            })
+            .await;
+
+        InterceptedService::new(channel.clone(), self.interceptor.clone())
+    }
+
+    async fn lightning(&self) -> LightningClient<LndService> {
+        LightningClient::new(self.service().await)
+    }
+
+    async fn router(&self) -> RouterClient<LndService> {
+        RouterClient::new(self.service().await)
+    }
+
+    async fn invoices(&self) -> InvoicesClient<LndService> {
+        InvoicesClient::new(self.service().await)
+    }
+
+    // Streaming calls last as long as the payment, the closing or the
+    // subscription, so they're sent without the request timeout.
+    fn request<T>(&self, message: T) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        if let Some(request_timeout) = self.request_timeout {
+            request.set_timeout(request_timeout);
+        }
+
+        request
+    }
+
+    async fn send_payment(&self, request: SendPaymentRequest) -> Result<PayInvoiceResult, Error> {
+        let mut client = self.router().await;
+
+        let mut payments = client.send_payment_v2(request).await?.into_inner();
+
+        // In-flight updates are disabled, so the first message is the outcome.
+        match payments.message().await? {
+            Some(payment) => payment.try_into(),
+            None => Err(Error::ApiError(String::from(
+                "payment stream closed without a result",
+            ))),
+        }
+    }
+
+    // LND only closes channels given by their channel point.
+    // Settled invoices in the order of their settlement.
+    async fn list_settled_invoices(&self) -> Result<Vec<lnrpc::Invoice>, Error> {
+        let mut client = self.lightning().await;
+        let mut request = ListInvoiceRequest {
+            pending_only: false,
+            index_offset: 0,
+            num_max_invoices: utils::DEFAULT_MAX_INVOICES,
+            reversed: false,
+        };
+
+        let mut settled = Vec::new();
+        loop {
+            let response = client
+                .list_invoices(self.request(request.clone()))
+                .await?
+                .into_inner();
+
+            let count = response.invoices.len() as u64;
+            settled.extend(
+                response
+                    .invoices
+                    .into_iter()
+                    .filter(|invoice| invoice.settle_index > 0),
+            );
+            if count < request.num_max_invoices {
+                break;
+            }
+            request.index_offset = response.last_index_offset;
+        }
+        settled.sort_by_key(|invoice| invoice.settle_index);
+
+        Ok(settled)
+    }
+
+    async fn get_channel_point(&self, params: &CloseChannelParams) -> Result<String, Error> {
+        if let Some(channel_point) = &params.channel_point {
+            return Ok(channel_point.clone());
+        }
+
+        let channel_id = utils::get_channel_id(params)?;
+        self.list_channels()
+            .await?
+            .into_iter()
+            .find(|channel| channel.channel_id == channel_id)
+            .and_then(|channel| channel.channel_point)
+            .ok_or_else(|| Error::ApiError(String::from("channel not found")))
+    }
+
+    async fn get_channel(&self, channel_id: &str) -> Result<Channel, Error> {
+        self.list_channels()
+            .await?
+            .into_iter()
+            .find(|channel| channel.channel_id == channel_id)
+            .ok_or_else(|| Error::ApiError(String::from("channel not found")))
+    }
+
+    // Policies are only known from the channel graph, by short channel id.
+    async fn get_policy(&self, channel: &Channel) -> Result<ChannelPolicy, Error> {
+        let mut client = self.lightning().await;
+
+        let short_channel_id = channel
+            .short_channel_id
+            .as_deref()
+            .ok_or_else(|| Error::ApiError(String::from("channel isn't confirmed yet")))?;
+        let request = ChanInfoRequest {
+            chan_id: utils::parse_short_channel_id(short_channel_id)?,
+        };
+        let edge = client
+            .get_chan_info(self.request(request))
+            .await?
+            .into_inner();
+
+        (channel, edge).try_into()
+    }
+
+    // LND only connects to peers given their address, which is otherwise taken
+    // from the network graph.
+    async fn get_node_address(&self, pubkey: &str) -> Result<String, Error> {
+        let mut client = self.lightning().await;
+
+        let request = NodeInfoRequest {
+            pub_key: pubkey.to_string(),
+            include_channels: false,
+        };
+        let info = client
+            .get_node_info(self.request(request))
+            .await?
+            .into_inner();
+
+        info.node
+            .and_then(|node| node.addresses.into_iter().next())
+            .map(|address| address.addr)
+            .ok_or_else(|| Error::ApiError(String::from("no address known for the peer")))
+    }
+}
+
+#[async_trait::async_trait]
+impl NodeMethods for LndGrpc {
+    async fn cancel_hold_invoice(&self, payment_hash: String) -> Result<(), Error> {
+        self.cancel_invoice(payment_hash).await
+    }
+
+    async fn cancel_invoice(&self, payment_hash: String) -> Result<(), Error> {
+        let mut client = self.invoices().await;
+
+        let request = CancelInvoiceMsg {
+            payment_hash: hex::decode(payment_hash)?,
+        };
+        client.cancel_invoice(self.request(request)).await?;
+
+        Ok(())
+    }
+
+    async fn close_channel(&self, params: CloseChannelParams) -> Result<CloseChannelResult, Error> {
+        let channel_point = self.get_channel_point(&params).await?;
+        let mut client = self.lightning().await;
+
+        let request: lnrpc::CloseChannelRequest = (&params, channel_point.as_str()).try_into()?;
+        let mut updates = client.close_channel(request).await?.into_inner();
+
+        // The first update comes once the closing transaction is broadcast,
+        // the channel is only closed after it confirms.
+        match updates.message().await? {
+            Some(update) => update.try_into(),
+            None => Err(Error::ApiError(String::from(
+                "close stream ended without an update",
+            ))),
+        }
+    }
+
+    async fn connect_peer(&self, params: ConnectPeerParams) -> Result<(), Error> {
+        let mut client = self.lightning().await;
+
+        let host = match params.host {
+            Some(host) => host,
+            None => self.get_node_address(&params.pubkey).await?,
+        };
+        let request = ConnectPeerRequest {
+            addr: Some(LightningAddress {
+                pubkey: params.pubkey,
+                host,
+            }),
+            ..Default::default()
+        };
+
+        // Connecting is idempotent, as on the other backends.
+        match client.connect_peer(self.request(request)).await {
+            Err(status) if status.message().contains("already connected") => Ok(()),
+            Err(status) => Err(status.into()),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    async fn create_hold_invoice(
+        &self,
+        invoice: CreateHoldInvoiceParams,
+    ) -> Result<CreateInvoiceResult, Error> {
+        let mut client = self.invoices().await;
+
+        let request = AddHoldInvoiceRequest::try_from(&invoice)?;
+        let response = client
+            .add_hold_invoice(self.request(request))
+            .await?
+            .into_inner();
+
+        Ok(CreateInvoiceResult {
+            payment_request: response.payment_request,
+            payment_hash: invoice.payment_hash,
+            label: None,
+        })
+    }
+
+    async fn create_invoice(
+        &self,
+        invoice: CreateInvoiceParams,
+    ) -> Result<CreateInvoiceResult, Error> {
+        let mut client = self.lightning().await;
+
+        let request: lnrpc::Invoice = invoice.try_into()?;
+        let response = client
+            .add_invoice(self.request(request))
+            .await?
+            .into_inner();
+
+        Ok(response.into())
+    }
+
+    async fn decode_invoice(&self, bolt11: String) -> Result<DecodedInvoice, Error> {
+        let mut client = self.lightning().await;
+
+        let request = PayReqString {
+            pay_req: bolt11.clone(),
+        };
+        let response = client
+            .decode_pay_req(self.request(request))
+            .await?
+            .into_inner();
+
+        (bolt11, response).try_into()
+    }
+
+    async fn delete_expired_invoices(&self, _before: Option<u64>) -> Result<(), Error> {
+        // LND has no call to delete invoices, it cancels the expired ones
+        // itself and can be configured to delete them.
+        Err(Error::NotImplemented)
+    }
+
+    async fn disconnect_peer(&self, pubkey: String) -> Result<(), Error> {
+        let mut client = self.lightning().await;
+
+        let request = DisconnectPeerRequest { pub_key: pubkey };
+        client.disconnect_peer(self.request(request)).await?;
+
+        Ok(())
+    }
+
+    async fn get_balance(&self) -> Result<Balance, Error> {
+        let mut client = self.lightning().await;
+
+        let request = WalletBalanceRequest {};
+        let wallet = client
+            .wallet_balance(self.request(request))
+            .await?
+            .into_inner();
+
+        let request = ChannelBalanceRequest {};
+        let channels = client
+            .channel_balance(self.request(request))
+            .await?
+            .into_inner();
+
+        // Funds of closing channels are only reported with the pending channels.
+        let request = PendingChannelsRequest {};
+        let pending = client
+            .pending_channels(self.request(request))
+            .await?
+            .into_inner();
+
+        let msat = |amount: Option<lnrpc::Amount>| amount.map(|amount| amount.msat).unwrap_or(0);
+
+        // The channel balance includes the reserves, which can't be sent.
+        let open_channels = self.list_channels().await?;
+        let (local_msat, remote_msat) = utils::spendable_balance_msat(&open_channels);
+
+        let mut balance: Balance = wallet.try_into()?;
+        balance.lightning_local_msat = local_msat;
+        balance.lightning_remote_msat = remote_msat;
+        balance.pending_open_msat = msat(channels.pending_open_local_balance);
+        balance.pending_close_msat =
+            utils::sat_to_msat(u64::try_from(pending.total_limbo_balance)?);
+
+        Ok(balance)
+    }
+
+    async fn get_channel_policy(&self, channel_id: String) -> Result<ChannelPolicy, Error> {
+        let channel = self.get_channel(&channel_id).await?;
+
+        self.get_policy(&channel).await
+    }
+
+    async fn get_info(&self) -> Result<NodeInfo, Error> {
+        let mut client = self.lightning().await;
+
+        let request = GetInfoRequest {};
+        let response = client.get_info(self.request(request)).await?.into_inner();
+
+        Ok(response.into())
+    }
+
+    async fn get_invoice(&self, payment_hash: String) -> Result<Invoice, Error> {
+        let mut client = self.lightning().await;
+
+        let request = PaymentHash {
+            r_hash: hex::decode(payment_hash)?,
+            ..Default::default()
+        };
+        let response = client
+            .lookup_invoice(self.request(request))
+            .await?
+            .into_inner();
+
+        response.try_into()
+    }
+
+    async fn get_payment(&self, payment_hash: String) -> Result<Payment, Error> {
+        // The first update of the tracking is the current state of the payment.
+        match self.track_payment(payment_hash).await?.next().await {
+            Some(payment) => payment,
+            None => Err(Error::ApiError(String::from("payment not found"))),
+        }
+    }
+
+    async fn keysend(&self, params: KeysendParams) -> Result<PayInvoiceResult, Error> {
+        let request: SendPaymentRequest = params.try_into()?;
+
+        self.send_payment(request).await
+    }
+
+    async fn list_channels(&self) -> Result<Vec<Channel>, Error> {
+        let mut client = self.lightning().await;
+
+        let request = ListChannelsRequest::default();
+        let open = client
+            .list_channels(self.request(request))
+            .await?
+            .into_inner();
+
+        let request = PendingChannelsRequest {};
+        let pending = client
+            .pending_channels(self.request(request))
+            .await?
+            .into_inner();
+
+        let mut channels = Vec::new();
+        for channel in pending.pending_open_channels {
+            if let Some(channel) = channel.channel {
+                channels.push((channel, ChannelStatus::Opening).try_into()?);
+            }
+        }
+        for channel in open.channels {
+            channels.push(channel.try_into()?);
+        }
+        let closing = pending
+            .waiting_close_channels
+            .into_iter()
+            .filter_map(|channel| channel.channel)
+            .chain(
+                pending
+                    .pending_force_closing_channels
+                    .into_iter()
+                    .filter_map(|channel| channel.channel),
+            );
+        for channel in closing {
+            channels.push((channel, ChannelStatus::Closing).try_into()?);
+        }
+
+        Ok(channels)
+    }
+
+    async fn list_forwards(&self, params: ListForwardsParams) -> Result<ListForwardsResult, Error> {
+        let mut client = self.lightning().await;
+
+        let request: ForwardingHistoryRequest = (&params).try_into()?;
+        let max_forwards = request.num_max_events;
+        let response = client
+            .forwarding_history(self.request(request))
+            .await?
+            .into_inner();
+
+        let has_more = !response.forwarding_events.is_empty()
+            && response.forwarding_events.len() as u32 == max_forwards;
+        let next_cursor = match has_more {
+            true => Some(response.last_offset_index.into()),
+            false => None,
+        };
+
+        let mut forwards: Vec<Forward> = response
+            .forwarding_events
+            .into_iter()
+            .map(|event| event.into())
+            .collect();
+
+        let channels = self.list_channels().await?;
+        utils::fill_forward_channels(&mut forwards, &channels);
+
+        Ok(ListForwardsResult {
+            forwards,
+            next_cursor,
+        })
+    }
+
+    async fn list_invoices(&self, params: ListInvoicesParams) -> Result<ListInvoicesResult, Error> {
+        let mut client = self.lightning().await;
+
+        let request: ListInvoiceRequest = (&params).into();
+        let num_max_invoices = request.num_max_invoices;
+        let response = client
+            .list_invoices(self.request(request))
+            .await?
+            .into_inner();
+
+        // LND pages on its own invoice index, so a page can come back with less
+        // than `max_invoices` invoices once the remaining filters are applied.
+        let has_more =
+            !response.invoices.is_empty() && response.invoices.len() as u64 == num_max_invoices;
+        let next_cursor = match has_more {
+            true => Some(response.last_index_offset),
+            false => None,
+        };
+
+        let mut invoices = Vec::new();
+        for invoice in response.invoices {
+            let invoice: Invoice = invoice.try_into()?;
+            if utils::invoice_matches(&invoice, &params) {
+                invoices.push(invoice);
+            }
+        }
+
+        Ok(ListInvoicesResult {
+            invoices,
+            next_cursor,
+        })
+    }
+
+    async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, Error> {
+        let mut client = self.lightning().await;
+
+        // Without an end height, the unconfirmed transactions are included.
+        let request = lnrpc::GetTransactionsRequest::default();
+        let response = client
+            .get_transactions(self.request(request))
+            .await?
+            .into_inner();
+
+        response
+            .transactions
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect()
+    }
+
+    async fn list_payments(&self, params: ListPaymentsParams) -> Result<ListPaymentsResult, Error> {
+        let mut client = self.lightning().await;
+
+        let request: ListPaymentsRequest = (&params).into();
+        let max_payments = request.max_payments;
+        let response = client
+            .list_payments(self.request(request))
+            .await?
+            .into_inner();
+
+        // LND v0.15 doesn't filter payments, so a page can come back with less
+        // than `max_payments` payments once the filters are applied.
+        let has_more =
+            !response.payments.is_empty() && response.payments.len() as u64 == max_payments;
+        let next_cursor = match has_more {
+            true => Some(response.last_index_offset),
+            false => None,
+        };
+
+        let mut payments = Vec::new();
+        for payment in response.payments {
+            let payment: Payment = payment.try_into()?;
+            if utils::payment_matches(&payment, &params) {
+                payments.push(payment);
+            }
+        }
+
+        Ok(ListPaymentsResult {
+            payments,
+            next_cursor,
+        })
+    }
+
+    async fn list_peers(&self) -> Result<Vec<Peer>, Error> {
+        let mut client = self.lightning().await;
+
+        let request = ListPeersRequest::default();
+        let response = client.list_peers(self.request(request)).await?.into_inner();
+
+        Ok(response.peers.into_iter().map(Into::into).collect())
+    }
+
+    async fn new_address(&self, address_type: AddressType) -> Result<String, Error> {
+        let mut client = self.lightning().await;
+
+        let request: lnrpc::NewAddressRequest = address_type.into();
+        let response = client
+            .new_address(self.request(request))
+            .await?
+            .into_inner();
+
+        Ok(response.address)
+    }
+
+    async fn open_channel(&self, params: OpenChannelParams) -> Result<OpenChannelResult, Error> {
+        // The params are checked before connecting to the peer, which isn't
+        // undone when they are invalid.
+        let peer = params.peer_host.clone().map(|host| ConnectPeerParams {
+            pubkey: params.peer_pubkey.clone(),
+            host: Some(host),
+        });
+        let request: lnrpc::OpenChannelRequest = params.try_into()?;
+        if let Some(peer) = peer {
+            self.connect_peer(peer).await?;
+        }
+
+        let mut client = self.lightning().await;
+        let response = client
+            .open_channel_sync(self.request(request))
+            .await?
+            .into_inner();
+
+        response.try_into()
+    }
+
+    async fn pay_invoice(&self, invoice: PayInvoiceParams) -> Result<PayInvoiceResult, Error> {
+        let request: SendPaymentRequest = invoice.try_into()?;
+
+        self.send_payment(request).await
+    }
+
+    async fn send_onchain(&self, params: SendOnchainParams) -> Result<String, Error> {
+        let mut client = self.lightning().await;
+
+        let request: lnrpc::SendCoinsRequest = params.try_into()?;
+        let response = client.send_coins(self.request(request)).await?.into_inner();
+
+        Ok(response.txid)
+    }
+
+    async fn send_payment_async(&self, invoice: PayInvoiceParams) -> Result<String, Error> {
+        let mut client = self.router().await;
+
+        let mut request: SendPaymentRequest = invoice.try_into()?;
+        request.no_inflight_updates = false;
+        let mut payments = client.send_payment_v2(request).await?.into_inner();
+
+        // The first update comes once the payment is registered, LND keeps
+        // paying after the stream is dropped.
+        match payments.message().await? {
+            Some(payment) => Ok(payment.payment_hash),
+            None => Err(Error::ApiError(String::from(
+                "payment stream closed without a result",
+            ))),
+        }
+    }
+
+    async fn settle_hold_invoice(&self, preimage: String) -> Result<(), Error> {
+        let mut client = self.invoices().await;
+
+        let request = SettleInvoiceMsg {
+            preimage: hex::decode(preimage)?,
+        };
+        client.settle_invoice(self.request(request)).await?;
+
+        Ok(())
+    }
+
+    async fn sign_message(&self, message: String) -> Result<String, Error> {
+        let mut client = self.lightning().await;
+
+        let request = SignMessageRequest {
+            msg: message.into_bytes(),
+            single_hash: false,
+        };
+        let response = client
+            .sign_message(self.request(request))
+            .await?
+            .into_inner();
+
+        Ok(response.signature)
+    }
+
+    async fn subscribe_invoices(
+        &self,
+        params: SubscribeInvoicesParams,
+    ) -> Result<InvoiceStream, Error> {
+        let mut client = self.lightning().await;
+
+        let request: InvoiceSubscription = (&params).into();
+        let mut invoices = client.subscribe_invoices(request).await?.into_inner();
+        let mut cursor = params.cursor.unwrap_or_default();
+
+        // LND doesn't replay any settlement without a settle index. A new
+        // subscription starts from the last one, while a resumed one first
+        // sends every settlement again.
+        let mut settled = Vec::new();
+        if cursor.settle_index.unwrap_or(0) == 0 {
+            let invoices = self.list_settled_invoices().await?;
+            match cursor.add_index {
+                Some(_) => settled = invoices,
+                None => cursor.settle_index = invoices.last().map(|invoice| invoice.settle_index),
+            }
+        }
+
+        let stream = async_stream::try_stream! {
+            for invoice in settled {
+                cursor.settle_index = cursor.settle_index.max(Some(invoice.settle_index));
+                let invoice: Invoice = invoice.try_into()?;
+
+                yield InvoiceEvent {
+                    event_type: InvoiceEventType::Settled,
+                    invoice,
+                    cursor: cursor.clone(),
+                };
+            }
+
+            while let Some(invoice) = invoices.message().await? {
+                // Open invoices have no settle index, so the cursor keeps the
+                // highest indexes seen to resume from.
+                cursor.add_index = cursor.add_index.max(Some(invoice.add_index));
+                if invoice.settle_index > 0 {
+                    cursor.settle_index = cursor.settle_index.max(Some(invoice.settle_index));
+                }
+
+                let event_type: InvoiceEventType = invoice.state().into();
+                let invoice: Invoice = invoice.try_into()?;
+
+                yield InvoiceEvent {
+                    event_type,
+                    invoice,
+                    cursor: cursor.clone(),
+                };
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn track_payment(&self, payment_hash: String) -> Result<PaymentStream, Error> {
+        let mut client = self.router().await;
+
+        let request = TrackPaymentRequest {
+            payment_hash: hex::decode(payment_hash)?,
+            no_inflight_updates: false,
+        };
+        let mut payments = client.track_payment_v2(request).await?.into_inner();
+
+        let stream = async_stream::try_stream! {
+            while let Some(payment) = payments.message().await? {
+                let payment: Payment = payment.try_into()?;
+                yield payment;
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn update_channel_policy(&self, params: UpdatePolicyParams) -> Result<(), Error> {
+        let request = match &params.channel_id {
+            Some(channel_id) => {
+                let channel = self.get_channel(channel_id).await?;
+                let policy = self.get_policy(&channel).await?;
+                let params = utils::fill_channel_policy(&params, &policy);
+                PolicyUpdateRequest::try_from((&params, channel.channel_point.as_deref()))?
+            }
+            None => PolicyUpdateRequest::try_from((&params, None))?,
+        };
+        let mut client = self.lightning().await;
+        let response = client
+            .update_channel_policy(self.request(request))
+            .await?
+            .into_inner();
+
+        match response.failed_updates.into_iter().next() {
+            Some(failed) => Err(Error::ApiError(failed.update_error)),
+            None => Ok(()),
+        }
+    }
+
+    async fn verify_message(
+        &self,
+        params: VerifyMessageParams,
+    ) -> Result<VerifyMessageResult, Error> {
+        let mut client = self.lightning().await;
+
+        let request = VerifyMessageRequest {
+            msg: params.message.clone().into_bytes(),
+            signature: params.signature.clone(),
+        };
+        let response = client
+            .verify_message(self.request(request))
+            .await?
+            .into_inner();
+
+        Ok((&params, response).into())
+    }
+}
@@ -1 +1,857 @@
+#![allow(clippy::from_over_into)]
 
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::{bolt11, types::*, utils};
+
+// Generated code, lints are not ours to fix.
+#[allow(clippy::all)]
+pub mod lnrpc {
+    include!(concat!(env!("PROTOBUFS_DIR"), "/lnrpc.rs"));
+}
+
+#[allow(clippy::all)]
+pub mod routerrpc {
+    include!(concat!(env!("PROTOBUFS_DIR"), "/routerrpc.rs"));
+}
+
+#[allow(clippy::all)]
+pub mod invoicesrpc {
+    include!(concat!(env!("PROTOBUFS_DIR"), "/invoicesrpc.rs"));
+}
+
+/// Seconds LND keeps looking for a route before failing a payment.
+pub const PAYMENT_TIMEOUT_SECONDS: i32 = 60;
+
+impl TryFrom<CreateInvoiceParams> for lnrpc::Invoice {
+    type Error = Error;
+
+    fn try_from(params: CreateInvoiceParams) -> Result<Self, Self::Error> {
+        let value_msat = match (params.amount, params.amount_msat) {
+            (Some(amount), _) => amount * 1000,
+            (_, Some(amount_msat)) => amount_msat,
+            (None, None) => 0,
+        };
+
+        let invoice = lnrpc::Invoice {
+            memo: params.description.unwrap_or_default(),
+            r_preimage: params
+                .payment_preimage
+                .map(hex::decode)
+                .transpose()?
+                .unwrap_or_default(),
+            value_msat: value_msat as i64,
+            description_hash: params
+                .description_hash
+                .map(hex::decode)
+                .transpose()?
+                .unwrap_or_default(),
+            expiry: params.expire_in.unwrap_or(3600).into(),
+            fallback_addr: params.fallback_address.unwrap_or_default(),
+            cltv_expiry: params.cltv_expiry.unwrap_or_default().into(),
+            ..Default::default()
+        };
+
+        Ok(invoice)
+    }
+}
+
+impl TryFrom<&CreateHoldInvoiceParams> for invoicesrpc::AddHoldInvoiceRequest {
+    type Error = Error;
+
+    fn try_from(params: &CreateHoldInvoiceParams) -> Result<Self, Self::Error> {
+        let request = invoicesrpc::AddHoldInvoiceRequest {
+            memo: params.description.clone().unwrap_or_default(),
+            hash: hex::decode(&params.payment_hash)?,
+            value_msat: i64::try_from(
+                utils::get_amount_msat(params.amount, params.amount_msat).unwrap_or(0),
+            )?,
+            description_hash: params
+                .description_hash
+                .as_ref()
+                .map(hex::decode)
+                .transpose()?
+                .unwrap_or_default(),
+            expiry: params.expire_in.unwrap_or(3600).into(),
+            fallback_addr: params.fallback_address.clone().unwrap_or_default(),
+            cltv_expiry: params.cltv_expiry.unwrap_or_default().into(),
+            ..Default::default()
+        };
+
+        Ok(request)
+    }
+}
+
+impl Into<CreateInvoiceResult> for lnrpc::AddInvoiceResponse {
+    fn into(self) -> CreateInvoiceResult {
+        CreateInvoiceResult {
+            payment_request: self.payment_request,
+            payment_hash: hex::encode(self.r_hash),
+            label: None,
+        }
+    }
+}
+
+impl TryInto<Balance> for lnrpc::WalletBalanceResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Balance, Self::Error> {
+        let balance = Balance {
+            onchain_confirmed_msat: utils::sat_to_msat(u64::try_from(self.confirmed_balance)?),
+            onchain_unconfirmed_msat: utils::sat_to_msat(u64::try_from(self.unconfirmed_balance)?),
+            onchain_locked_msat: utils::sat_to_msat(u64::try_from(self.locked_balance)?),
+            ..Default::default()
+        };
+
+        Ok(balance)
+    }
+}
+
+impl TryInto<Channel> for lnrpc::Channel {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Channel, Self::Error> {
+        let reserve_msat = |constraints: Option<lnrpc::ChannelConstraints>| {
+            utils::sat_to_msat(constraints.map(|c| c.chan_reserve_sat).unwrap_or(0))
+        };
+
+        let channel = Channel {
+            channel_id: utils::channel_id_from_channel_point(&self.channel_point)?,
+            channel_point: Some(self.channel_point),
+            short_channel_id: Some(utils::format_short_channel_id(self.chan_id)),
+            peer_pubkey: self.remote_pubkey,
+            capacity_msat: utils::sat_to_msat(u64::try_from(self.capacity)?),
+            local_balance_msat: utils::sat_to_msat(u64::try_from(self.local_balance)?),
+            remote_balance_msat: utils::sat_to_msat(u64::try_from(self.remote_balance)?),
+            local_reserve_msat: reserve_msat(self.local_constraints),
+            remote_reserve_msat: reserve_msat(self.remote_constraints),
+            active: self.active,
+            private: self.private,
+            initiator: self.initiator,
+            status: ChannelStatus::Open,
+        };
+
+        Ok(channel)
+    }
+}
+
+impl TryInto<Channel>
+    for (
+        lnrpc::pending_channels_response::PendingChannel,
+        ChannelStatus,
+    )
+{
+    type Error = Error;
+
+    fn try_into(self) -> Result<Channel, Self::Error> {
+        let (channel, status) = self;
+
+        let result = Channel {
+            channel_id: utils::channel_id_from_channel_point(&channel.channel_point)?,
+            initiator: channel.initiator() == lnrpc::Initiator::Local,
+            channel_point: Some(channel.channel_point),
+            short_channel_id: None,
+            peer_pubkey: channel.remote_node_pub,
+            capacity_msat: utils::sat_to_msat(u64::try_from(channel.capacity)?),
+            local_balance_msat: utils::sat_to_msat(u64::try_from(channel.local_balance)?),
+            remote_balance_msat: utils::sat_to_msat(u64::try_from(channel.remote_balance)?),
+            local_reserve_msat: utils::sat_to_msat(u64::try_from(channel.local_chan_reserve_sat)?),
+            remote_reserve_msat: utils::sat_to_msat(u64::try_from(
+                channel.remote_chan_reserve_sat,
+            )?),
+            active: false,
+            private: channel.private,
+            status,
+        };
+
+        Ok(result)
+    }
+}
+
+impl Into<NodeInfo> for lnrpc::GetInfoResponse {
+    fn into(self) -> NodeInfo {
+        let network = match self.chains.first() {
+            Some(chain) => match chain.network.as_ref() {
+                "mainnet" => Network::Mainnet,
+                "testnet" => Network::Testnet,
+                "regtest" => Network::Regtest,
+                _ => Network::Unknown(chain.network.clone()),
+            },
+            None => Network::Unknown("Unknown".to_string()),
+        };
+
+        NodeInfo {
+            backend: Backend::LndGrpc,
+            version: self.version,
+            network,
+            node_pubkey: self.identity_pubkey,
+            channels: ChannelStats {
+                active: self.num_active_channels.into(),
+                inactive: self.num_inactive_channels.into(),
+                pending: self.num_pending_channels.into(),
+            },
+        }
+    }
+}
+
+impl From<lnrpc::invoice::InvoiceState> for InvoiceEventType {
+    fn from(state: lnrpc::invoice::InvoiceState) -> Self {
+        match state {
+            lnrpc::invoice::InvoiceState::Open => InvoiceEventType::Created,
+            lnrpc::invoice::InvoiceState::Settled => InvoiceEventType::Settled,
+            lnrpc::invoice::InvoiceState::Canceled => InvoiceEventType::Cancelled,
+            lnrpc::invoice::InvoiceState::Accepted => InvoiceEventType::Accepted,
+        }
+    }
+}
+
+impl TryInto<Invoice> for lnrpc::Invoice {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Invoice, Self::Error> {
+        let status = match self.state() {
+            lnrpc::invoice::InvoiceState::Open => InvoiceStatus::Pending,
+            lnrpc::invoice::InvoiceState::Settled => InvoiceStatus::Settled,
+            lnrpc::invoice::InvoiceState::Canceled => InvoiceStatus::Cancelled,
+            lnrpc::invoice::InvoiceState::Accepted => InvoiceStatus::Accepted,
+        };
+        let settled = matches!(status, InvoiceStatus::Settled);
+
+        let pre_image = match settled && !self.r_preimage.is_empty() {
+            true => Some(hex::encode(&self.r_preimage)),
+            false => None,
+        };
+        let settle_date = match settled {
+            true => Some(self.settle_date),
+            false => None,
+        };
+
+        let invoice = Invoice {
+            bolt11: self.payment_request,
+            memo: self.memo,
+            amount: self.value.try_into()?,
+            amount_msat: self.value_msat.try_into()?,
+            pre_image,
+            payment_hash: hex::encode(self.r_hash),
+            settled,
+            settle_date,
+            creation_date: self.creation_date,
+            expiry: self.expiry.try_into()?,
+            status,
+        };
+
+        Ok(invoice)
+    }
+}
+
+impl From<&SubscribeInvoicesParams> for lnrpc::InvoiceSubscription {
+    fn from(params: &SubscribeInvoicesParams) -> Self {
+        let cursor = params.cursor.clone().unwrap_or_default();
+
+        lnrpc::InvoiceSubscription {
+            add_index: cursor.add_index.unwrap_or(0),
+            settle_index: cursor.settle_index.unwrap_or(0),
+        }
+    }
+}
+
+impl From<&ListInvoicesParams> for lnrpc::ListInvoiceRequest {
+    fn from(params: &ListInvoicesParams) -> Self {
+        let pending_only = params.pending_only.unwrap_or(false)
+            || matches!(
+                params.status,
+                Some(InvoiceStatus::Pending) | Some(InvoiceStatus::Accepted)
+            );
+
+        lnrpc::ListInvoiceRequest {
+            pending_only,
+            index_offset: params.cursor.unwrap_or(0),
+            num_max_invoices: utils::page_size(params.max_invoices, utils::DEFAULT_MAX_INVOICES),
+            reversed: false,
+        }
+    }
+}
+
+impl TryFrom<PayInvoiceParams> for routerrpc::SendPaymentRequest {
+    type Error = Error;
+
+    fn try_from(params: PayInvoiceParams) -> Result<Self, Self::Error> {
+        let amount_msat = utils::get_amount_msat(params.amount, params.amount_msat);
+        let max_fee_msat = utils::get_amount_msat(params.max_fee_sat, params.max_fee_msat);
+
+        // The router only takes an absolute fee limit, so a percentage is
+        // applied to the amount being paid. A zero limit would only allow
+        // zero-fee routes, so no limit is sent as the maximum value instead.
+        let fee_limit_msat = match (max_fee_msat, params.max_fee_percent) {
+            (Some(max_fee_msat), _) => max_fee_msat.try_into()?,
+            (None, Some(max_fee_percent)) => {
+                let invoice_amount_msat = match amount_msat {
+                    Some(amount_msat) => amount_msat,
+                    None => bolt11::decode_invoice(&params.payment_request)?
+                        .amount_msat
+                        .unwrap_or(0),
+                };
+                (invoice_amount_msat as f64 * max_fee_percent / 100.0) as i64
+            }
+            (None, None) => i64::MAX,
+        };
+
+        let request = routerrpc::SendPaymentRequest {
+            amt_msat: amount_msat.unwrap_or(0) as i64,
+            payment_request: params.payment_request,
+            timeout_seconds: PAYMENT_TIMEOUT_SECONDS,
+            fee_limit_msat,
+            allow_self_payment: false,
+            no_inflight_updates: true,
+            ..Default::default()
+        };
+
+        Ok(request)
+    }
+}
+
+impl TryFrom<KeysendParams> for routerrpc::SendPaymentRequest {
+    type Error = Error;
+
+    fn try_from(params: KeysendParams) -> Result<Self, Self::Error> {
+        let amount_msat = utils::get_amount_msat(params.amount, params.amount_msat)
+            .ok_or_else(|| Error::ApiError(String::from("amount is required")))?;
+        let fee_limit_msat = match utils::get_max_fee_msat(
+            amount_msat,
+            params.max_fee_sat,
+            params.max_fee_msat,
+            params.max_fee_percent,
+        ) {
+            Some(max_fee_msat) => max_fee_msat.try_into()?,
+            None => i64::MAX,
+        };
+
+        let mut dest_custom_records = HashMap::new();
+        for record in params.custom_records.unwrap_or_default() {
+            dest_custom_records.insert(record.record_type, hex::decode(record.value)?);
+        }
+
+        // The sender picks the preimage of a keysend payment, and sends it to
+        // the destination in a record.
+        let (preimage, payment_hash) = utils::keysend_preimage();
+        dest_custom_records.insert(utils::KEYSEND_PREIMAGE_RECORD, preimage.to_vec());
+
+        let request = routerrpc::SendPaymentRequest {
+            dest: hex::decode(params.destination)?,
+            amt_msat: amount_msat.try_into()?,
+            payment_hash: payment_hash.to_vec(),
+            timeout_seconds: PAYMENT_TIMEOUT_SECONDS,
+            fee_limit_msat,
+            dest_custom_records,
+            allow_self_payment: false,
+            no_inflight_updates: true,
+            ..Default::default()
+        };
+
+        Ok(request)
+    }
+}
+
+fn failure_message(reason: lnrpc::PaymentFailureReason) -> &'static str {
+    match reason {
+        lnrpc::PaymentFailureReason::FailureReasonNone => "payment failed",
+        lnrpc::PaymentFailureReason::FailureReasonTimeout => "payment timed out",
+        lnrpc::PaymentFailureReason::FailureReasonNoRoute => "no route found",
+        lnrpc::PaymentFailureReason::FailureReasonError => "payment failed",
+        lnrpc::PaymentFailureReason::FailureReasonIncorrectPaymentDetails => {
+            "incorrect payment details"
+        }
+        lnrpc::PaymentFailureReason::FailureReasonInsufficientBalance => "insufficient balance",
+    }
+}
+
+impl From<lnrpc::PaymentFailureReason> for Error {
+    fn from(reason: lnrpc::PaymentFailureReason) -> Self {
+        Error::ApiError(String::from(failure_message(reason)))
+    }
+}
+
+impl TryInto<PayInvoiceResult> for lnrpc::Payment {
+    type Error = Error;
+
+    fn try_into(self) -> Result<PayInvoiceResult, Self::Error> {
+        match self.status() {
+            lnrpc::payment::PaymentStatus::Succeeded => Ok(PayInvoiceResult {
+                payment_hash: self.payment_hash,
+                payment_preimage: self.payment_preimage,
+                fees_msat: Some(self.fee_msat.try_into()?),
+            }),
+            lnrpc::payment::PaymentStatus::Failed => Err(self.failure_reason().into()),
+            _ => Err(Error::ApiError(String::from("payment still in flight"))),
+        }
+    }
+}
+
+// LND names the features on its own, the BOLT 9 names are kept when known.
+fn features(features: HashMap<u32, lnrpc::Feature>) -> Vec<Feature> {
+    let mut result = Vec::new();
+    for (bit, lnd_feature) in features {
+        let mut feature = bolt11::feature(bit);
+        feature.name = feature.name.or(Some(lnd_feature.name));
+        feature.is_required = lnd_feature.is_required;
+        feature.is_known = lnd_feature.is_known;
+        result.push(feature);
+    }
+    result.sort_by_key(|feature| feature.bit);
+
+    result
+}
+
+impl TryInto<DecodedInvoice> for (String, lnrpc::PayReq) {
+    type Error = Error;
+
+    fn try_into(self) -> Result<DecodedInvoice, Self::Error> {
+        let (bolt11, payreq) = self;
+
+        let amount_msat = match payreq.num_msat {
+            0 => None,
+            amount_msat => Some(amount_msat.try_into()?),
+        };
+        let payment_secret = match payreq.payment_addr.is_empty() {
+            true => None,
+            false => Some(hex::encode(&payreq.payment_addr)),
+        };
+
+        let mut route_hints = Vec::new();
+        for route_hint in payreq.route_hints {
+            let mut hops = Vec::new();
+            for hop in route_hint.hop_hints {
+                hops.push(RouteHintHop {
+                    node_id: hop.node_id,
+                    short_channel_id: utils::format_short_channel_id(hop.chan_id),
+                    fee_base_msat: hop.fee_base_msat,
+                    fee_proportional_millionths: hop.fee_proportional_millionths,
+                    cltv_expiry_delta: hop.cltv_expiry_delta.try_into()?,
+                });
+            }
+            route_hints.push(RouteHint { hops });
+        }
+
+        let invoice = DecodedInvoice {
+            network: bolt11::get_network(&bolt11)?,
+            bolt11,
+            payee: payreq.destination,
+            amount: amount_msat.map(utils::msat_to_sat),
+            amount_msat,
+            payment_hash: payreq.payment_hash,
+            payment_secret,
+            description: Some(payreq.description).filter(|description| !description.is_empty()),
+            description_hash: Some(payreq.description_hash)
+                .filter(|description_hash| !description_hash.is_empty()),
+            creation_date: payreq.timestamp,
+            expiry: payreq.expiry.try_into()?,
+            min_final_cltv_expiry: payreq.cltv_expiry.try_into()?,
+            route_hints,
+            features: features(payreq.features),
+        };
+
+        Ok(invoice)
+    }
+}
+
+impl TryFrom<OpenChannelParams> for lnrpc::OpenChannelRequest {
+    type Error = Error;
+
+    fn try_from(params: OpenChannelParams) -> Result<Self, Self::Error> {
+        let request = lnrpc::OpenChannelRequest {
+            node_pubkey: hex::decode(params.peer_pubkey)?,
+            local_funding_amount: i64::try_from(params.amount)?,
+            push_sat: i64::try_from(params.push_amount.unwrap_or(0))?,
+            sat_per_vbyte: params.fee_rate.unwrap_or(0),
+            private: params.private.unwrap_or(false),
+            min_confs: i32::try_from(params.min_confs.unwrap_or(0))?,
+            // LND refuses to fund from unconfirmed outputs unless told so.
+            spend_unconfirmed: params.min_confs == Some(0),
+            ..Default::default()
+        };
+
+        Ok(request)
+    }
+}
+
+impl TryInto<OpenChannelResult> for lnrpc::ChannelPoint {
+    type Error = Error;
+
+    fn try_into(self) -> Result<OpenChannelResult, Self::Error> {
+        let funding_txid = match self.funding_txid {
+            Some(lnrpc::channel_point::FundingTxid::FundingTxidBytes(txid)) => {
+                utils::txid_to_hex(txid)
+            }
+            Some(lnrpc::channel_point::FundingTxid::FundingTxidStr(txid)) => txid,
+            None => {
+                return Err(Error::ConversionError(String::from(
+                    "missing funding transaction id",
+                )))
+            }
+        };
+
+        let channel_point = format!("{}:{}", funding_txid, self.output_index);
+        let result = OpenChannelResult {
+            channel_id: utils::channel_id_from_channel_point(&channel_point)?,
+            funding_txid,
+            output_index: self.output_index,
+        };
+
+        Ok(result)
+    }
+}
+
+impl TryFrom<(&CloseChannelParams, &str)> for lnrpc::CloseChannelRequest {
+    type Error = Error;
+
+    fn try_from((params, channel_point): (&CloseChannelParams, &str)) -> Result<Self, Self::Error> {
+        let (funding_txid, output_index) = channel_point.split_once(':').ok_or_else(|| {
+            Error::ConversionError(format!("invalid channel point {}", channel_point))
+        })?;
+
+        let request = lnrpc::CloseChannelRequest {
+            channel_point: Some(lnrpc::ChannelPoint {
+                funding_txid: Some(lnrpc::channel_point::FundingTxid::FundingTxidStr(
+                    funding_txid.to_string(),
+                )),
+                output_index: output_index.parse()?,
+            }),
+            force: params.force.unwrap_or(false),
+            sat_per_vbyte: params.fee_rate.unwrap_or(0),
+            delivery_address: params.delivery_address.clone().unwrap_or_default(),
+            ..Default::default()
+        };
+
+        Ok(request)
+    }
+}
+
+impl TryInto<CloseChannelResult> for lnrpc::CloseStatusUpdate {
+    type Error = Error;
+
+    fn try_into(self) -> Result<CloseChannelResult, Self::Error> {
+        let txid = match self.update {
+            Some(lnrpc::close_status_update::Update::ClosePending(pending)) => pending.txid,
+            Some(lnrpc::close_status_update::Update::ChanClose(close)) => close.closing_txid,
+            None => {
+                return Err(Error::ApiError(String::from(
+                    "close update without a transaction",
+                )))
+            }
+        };
+
+        Ok(CloseChannelResult {
+            closing_txids: vec![utils::txid_to_hex(txid)],
+        })
+    }
+}
+
+impl Into<Peer> for lnrpc::Peer {
+    fn into(self) -> Peer {
+        // LND only lists the connected peers.
+        Peer {
+            pubkey: self.pub_key,
+            addresses: vec![self.address],
+            connected: true,
+            features: features(self.features),
+            inbound: Some(self.inbound),
+        }
+    }
+}
+
+impl From<AddressType> for lnrpc::NewAddressRequest {
+    fn from(address_type: AddressType) -> Self {
+        let address_type = match address_type {
+            AddressType::P2wpkh => lnrpc::AddressType::WitnessPubkeyHash,
+            AddressType::P2tr => lnrpc::AddressType::TaprootPubkey,
+            AddressType::NestedP2wpkh => lnrpc::AddressType::NestedPubkeyHash,
+        };
+
+        lnrpc::NewAddressRequest {
+            r#type: address_type as i32,
+            ..Default::default()
+        }
+    }
+}
+
+impl TryFrom<SendOnchainParams> for lnrpc::SendCoinsRequest {
+    type Error = Error;
+
+    fn try_from(params: SendOnchainParams) -> Result<Self, Self::Error> {
+        // LND v0.15 picks the outputs to spend on its own.
+        if params.utxos.is_some() {
+            return Err(Error::NotImplemented);
+        }
+
+        let amount = utils::get_onchain_amount(&params)?;
+        let request = lnrpc::SendCoinsRequest {
+            addr: params.address,
+            amount: amount.map(i64::try_from).transpose()?.unwrap_or(0),
+            target_conf: params
+                .target_conf
+                .map(i32::try_from)
+                .transpose()?
+                .unwrap_or(0),
+            sat_per_vbyte: params.fee_rate.unwrap_or(0),
+            send_all: amount.is_none(),
+            ..Default::default()
+        };
+
+        Ok(request)
+    }
+}
+
+impl TryInto<OnchainTransaction> for lnrpc::Transaction {
+    type Error = Error;
+
+    fn try_into(self) -> Result<OnchainTransaction, Self::Error> {
+        // The amount is the net change of the wallet, with the fee for sends.
+        let block_height = match self.block_height {
+            0 => None,
+            block_height => Some(u32::try_from(block_height)?),
+        };
+        let label = match self.label.is_empty() {
+            true => None,
+            false => Some(self.label),
+        };
+
+        let transaction = OnchainTransaction {
+            txid: self.tx_hash,
+            amount_received_msat: utils::sat_to_msat(u64::try_from(self.amount.max(0))?),
+            amount_sent_msat: utils::sat_to_msat(self.amount.min(0).unsigned_abs()),
+            fee_msat: Some(utils::sat_to_msat(u64::try_from(self.total_fees)?)),
+            confirmations: u32::try_from(self.num_confirmations)?,
+            block_height,
+            timestamp: Some(u64::try_from(self.time_stamp)?),
+            short_channel_id: label
+                .as_deref()
+                .and_then(utils::short_channel_id_from_lnd_label),
+            label,
+        };
+
+        Ok(transaction)
+    }
+}
+
+impl From<lnrpc::payment::PaymentStatus> for PaymentStatus {
+    fn from(status: lnrpc::payment::PaymentStatus) -> Self {
+        match status {
+            lnrpc::payment::PaymentStatus::Unknown => PaymentStatus::Pending,
+            lnrpc::payment::PaymentStatus::InFlight => PaymentStatus::Pending,
+            lnrpc::payment::PaymentStatus::Succeeded => PaymentStatus::Succeeded,
+            lnrpc::payment::PaymentStatus::Failed => PaymentStatus::Failed,
+        }
+    }
+}
+
+impl From<lnrpc::htlc_attempt::HtlcStatus> for PaymentStatus {
+    fn from(status: lnrpc::htlc_attempt::HtlcStatus) -> Self {
+        match status {
+            lnrpc::htlc_attempt::HtlcStatus::InFlight => PaymentStatus::Pending,
+            lnrpc::htlc_attempt::HtlcStatus::Succeeded => PaymentStatus::Succeeded,
+            lnrpc::htlc_attempt::HtlcStatus::Failed => PaymentStatus::Failed,
+        }
+    }
+}
+
+impl TryInto<Payment> for lnrpc::Payment {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Payment, Self::Error> {
+        let status: PaymentStatus = self.status().into();
+        let failure_reason = match status {
+            PaymentStatus::Failed => Some(String::from(failure_message(self.failure_reason()))),
+            _ => None,
+        };
+        let payment_preimage = match status {
+            PaymentStatus::Succeeded => Some(self.payment_preimage),
+            _ => None,
+        };
+
+        let mut parts = Vec::new();
+        let mut destination = None;
+        for htlc in self.htlcs {
+            let status: PaymentStatus = htlc.status().into();
+            if let Some(route) = htlc.route {
+                destination = route.hops.last().map(|hop| hop.pub_key.clone());
+                parts.push(PaymentPart {
+                    amount_msat: (route.total_amt_msat - route.total_fees_msat).try_into()?,
+                    fee_msat: route.total_fees_msat.try_into()?,
+                    status,
+                });
+            }
+        }
+        // Payments that never got a route only have the payee in the invoice.
+        if destination.is_none() && !self.payment_request.is_empty() {
+            destination = bolt11::decode_invoice(&self.payment_request)
+                .ok()
+                .map(|invoice| invoice.payee);
+        }
+
+        let payment = Payment {
+            payment_hash: self.payment_hash,
+            payment_preimage,
+            amount_msat: self.value_msat.try_into()?,
+            fee_msat: Some(self.fee_msat.try_into()?),
+            status,
+            created_at: self.creation_time_ns / 1_000_000_000,
+            destination,
+            bolt11: match self.payment_request.is_empty() {
+                true => None,
+                false => Some(self.payment_request),
+            },
+            failure_reason,
+            parts,
+        };
+
+        Ok(payment)
+    }
+}
+
+impl From<&ListPaymentsParams> for lnrpc::ListPaymentsRequest {
+    fn from(params: &ListPaymentsParams) -> Self {
+        lnrpc::ListPaymentsRequest {
+            include_incomplete: true,
+            index_offset: params.cursor.unwrap_or_default(),
+            max_payments: utils::page_size(params.max_payments, utils::DEFAULT_MAX_PAYMENTS),
+            ..Default::default()
+        }
+    }
+}
+
+// The edge holds the policies of both ends of the channel, the one of the node
+// is the one not set by the peer.
+impl TryInto<ChannelPolicy> for (&Channel, lnrpc::ChannelEdge) {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ChannelPolicy, Self::Error> {
+        let (channel, edge) = self;
+
+        let policy = match edge.node1_pub == channel.peer_pubkey {
+            true => edge.node2_policy,
+            false => edge.node1_policy,
+        };
+        let policy = policy
+            .ok_or_else(|| Error::ApiError(String::from("channel policy not announced yet")))?;
+
+        let channel_policy = ChannelPolicy {
+            channel_id: channel.channel_id.clone(),
+            base_fee_msat: policy.fee_base_msat.try_into()?,
+            fee_rate_ppm: policy.fee_rate_milli_msat.try_into()?,
+            time_lock_delta: Some(policy.time_lock_delta),
+            min_htlc_msat: policy.min_htlc.try_into()?,
+            max_htlc_msat: Some(policy.max_htlc_msat).filter(|max_htlc_msat| *max_htlc_msat > 0),
+        };
+
+        Ok(channel_policy)
+    }
+}
+
+// LND sets the base fee, fee rate and time lock delta all at once, so they are
+// required when updating every channel. Otherwise the missing ones are filled
+// from the current policy of the channel.
+impl TryFrom<(&UpdatePolicyParams, Option<&str>)> for lnrpc::PolicyUpdateRequest {
+    type Error = Error;
+
+    fn try_from(
+        (params, channel_point): (&UpdatePolicyParams, Option<&str>),
+    ) -> Result<Self, Self::Error> {
+        use lnrpc::policy_update_request::Scope;
+
+        let scope = match channel_point {
+            Some(channel_point) => {
+                let (funding_txid, output_index) =
+                    channel_point.split_once(':').ok_or_else(|| {
+                        Error::ConversionError(format!("invalid channel point {}", channel_point))
+                    })?;
+                Scope::ChanPoint(lnrpc::ChannelPoint {
+                    funding_txid: Some(lnrpc::channel_point::FundingTxid::FundingTxidStr(
+                        funding_txid.to_string(),
+                    )),
+                    output_index: output_index.parse()?,
+                })
+            }
+            None => Scope::Global(true),
+        };
+
+        let (base_fee_msat, fee_rate_ppm, time_lock_delta) =
+            match (params.base_fee_msat, params.fee_rate_ppm, params.time_lock_delta) {
+                (Some(base_fee_msat), Some(fee_rate_ppm), Some(time_lock_delta)) => {
+                    (base_fee_msat, fee_rate_ppm, time_lock_delta)
+                }
+                _ => {
+                    return Err(Error::ApiError(String::from(
+                        "base_fee_msat, fee_rate_ppm and time_lock_delta are required to update every channel",
+                    )))
+                }
+            };
+
+        let request = lnrpc::PolicyUpdateRequest {
+            scope: Some(scope),
+            base_fee_msat: base_fee_msat.try_into()?,
+            fee_rate_ppm: fee_rate_ppm.try_into()?,
+            time_lock_delta,
+            // Zero keeps the current maximum.
+            max_htlc_msat: params.max_htlc_msat.unwrap_or(0),
+            min_htlc_msat: params.min_htlc_msat.unwrap_or(0),
+            min_htlc_msat_specified: params.min_htlc_msat.is_some(),
+            ..Default::default()
+        };
+
+        Ok(request)
+    }
+}
+
+impl TryFrom<&ListForwardsParams> for lnrpc::ForwardingHistoryRequest {
+    type Error = Error;
+
+    fn try_from(params: &ListForwardsParams) -> Result<Self, Self::Error> {
+        let request = lnrpc::ForwardingHistoryRequest {
+            start_time: params.date_start.unwrap_or(0).try_into()?,
+            end_time: params.date_end.unwrap_or(0).try_into()?,
+            index_offset: params.cursor.unwrap_or(0).try_into()?,
+            num_max_events: utils::page_size(params.max_forwards, utils::DEFAULT_MAX_FORWARDS)
+                .try_into()?,
+        };
+
+        Ok(request)
+    }
+}
+
+// LND only records the forwards once settled, identified by the short channel
+// ids of their channels.
+impl Into<Forward> for lnrpc::ForwardingEvent {
+    fn into(self) -> Forward {
+        Forward {
+            incoming_channel_id: None,
+            incoming_short_channel_id: Some(utils::format_short_channel_id(self.chan_id_in)),
+            outgoing_channel_id: None,
+            outgoing_short_channel_id: Some(utils::format_short_channel_id(self.chan_id_out)),
+            amount_in_msat: self.amt_in_msat,
+            amount_out_msat: Some(self.amt_out_msat),
+            fee_msat: self.fee_msat,
+            status: ForwardStatus::Settled,
+            received_at: None,
+            resolved_at: Some((self.timestamp_ns / 1_000_000_000) as i64),
+        }
+    }
+}
+
+// LND only deems valid the signatures of the nodes in its graph, which doesn't
+// matter when the signer is known.
+impl Into<VerifyMessageResult> for (&VerifyMessageParams, lnrpc::VerifyMessageResponse) {
+    fn into(self) -> VerifyMessageResult {
+        let (params, response) = self;
+
+        let valid = match &params.pubkey {
+            Some(pubkey) => pubkey.eq_ignore_ascii_case(&response.pubkey),
+            None => response.valid,
+        };
+
+        VerifyMessageResult {
+            valid,
+            pubkey: response.pubkey,
+        }
+    }
+}
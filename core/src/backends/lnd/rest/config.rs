@@ -1,13 +1,17 @@
 use std::convert::TryFrom;
+use std::time::Duration;
 
 use crate::error::{ConfigError, Error};
 use crate::types::NodeConfig;
+use crate::utils;
 
 #[derive(Clone, Debug)]
 pub struct LndRestConfig {
     pub url: String,
     pub macaroon: String,
     pub tls_certificate: Vec<u8>,
+    pub connect_timeout: Duration,
+    pub request_timeout: Option<Duration>,
 }
 
 impl TryFrom<NodeConfig> for LndRestConfig {
@@ -29,6 +33,11 @@ impl TryFrom<NodeConfig> for LndRestConfig {
             macaroon,
             tls_certificate: hex::decode(&tls_certificate)
                 .map_err(|_| ConfigError::ParsingHexError("tls_certificate".to_string()))?,
+            connect_timeout: config
+                .connect_timeout
+                .map(Duration::from_secs)
+                .unwrap_or(utils::DEFAULT_CONNECT_TIMEOUT),
+            request_timeout: config.request_timeout.map(Duration::from_secs),
         };
 
         Ok(config)
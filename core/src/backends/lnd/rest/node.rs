@@ -1,15 +1,36 @@
+use futures::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+
 use crate::error::Error;
-use crate::node::NodeMethods;
+use crate::node::{InvoiceStream, NodeMethods, PaymentStream};
 use crate::types::{
-    CreateInvoiceParams, CreateInvoiceResult, NodeInfo, PayInvoiceParams, PayInvoiceResult,
+    AddressType, Balance, Channel, ChannelPolicy, ChannelStatus, CloseChannelParams,
+    CloseChannelResult, ConnectPeerParams, CreateHoldInvoiceParams, CreateInvoiceParams,
+    CreateInvoiceResult, DecodedInvoice, Invoice, InvoiceEvent, InvoiceEventType, KeysendParams,
+    ListForwardsParams, ListForwardsResult, ListInvoicesParams, ListInvoicesResult,
+    ListPaymentsParams, ListPaymentsResult, NodeInfo, OnchainTransaction, OpenChannelParams,
+    OpenChannelResult, PayInvoiceParams, PayInvoiceResult, Payment, Peer, SendOnchainParams,
+    SubscribeInvoicesParams, UpdatePolicyParams, VerifyMessageParams, VerifyMessageResult,
 };
+use crate::utils;
 
 use super::config::LndRestConfig;
 use super::types::{
-    ApiError, CreateInvoiceRequest, CreateInvoiceResponse, GetInfoResponse, SendPaymentSyncRequest,
-    SendPaymentSyncResponse,
+    AddHoldInvoiceRequest, AddHoldInvoiceResponse, ApiError, CancelInvoiceRequest,
+    ChannelBalanceResponse, ChannelEdgeResponse, ChannelPoint, CloseChannelRequest,
+    CloseStatusUpdate, ConnectPeerRequest, CreateInvoiceRequest, CreateInvoiceResponse,
+    ForwardingHistoryRequest, ForwardingHistoryResponse, GetInfoResponse, InvoiceResponse,
+    LightningAddress, ListChannelsResponse, ListInvoiceRequest, ListInvoiceResponse,
+    ListPaymentsRequest, ListPaymentsResponse, ListPeersResponse, NewAddressRequest,
+    NewAddressResponse, NodeInfoResponse, OpenChannelRequest, PayReqResponse, PaymentResponse,
+    PendingChannelsResponse, PolicyUpdateRequest, PolicyUpdateResponse, RouterSendPaymentRequest,
+    SendCoinsRequest, SendCoinsResponse, SendPaymentSyncRequest, SendPaymentSyncResponse,
+    SettleInvoiceRequest, SignMessageRequest, SignMessageResponse, StreamResponse,
+    SubscribeInvoicesRequest, TransactionDetails, VerifyMessageRequest, VerifyMessageResponse,
+    WalletBalanceResponse,
 };
 
+#[derive(Clone)]
 pub struct LndRest {
     config: LndRestConfig,
     client: reqwest::Client,
@@ -24,13 +45,25 @@ impl LndRest {
         macaroon_value.set_sensitive(true);
         headers.insert("Grpc-Metadata-macaroon", macaroon_value);
 
-        Ok(LndRest {
-            config,
-            client: reqwest::Client::builder()
-                .add_root_certificate(tls_certificate)
-                .default_headers(headers)
-                .build()?,
-        })
+        let client = reqwest::Client::builder()
+            .add_root_certificate(tls_certificate)
+            .default_headers(headers)
+            .connect_timeout(config.connect_timeout)
+            .build()?;
+
+        Ok(LndRest { config, client })
+    }
+
+    // Payments, closes and subscriptions are streamed for as long as they
+    // last, so they're sent with the client directly, without the request
+    // timeout.
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, url);
+
+        match self.config.request_timeout {
+            Some(request_timeout) => request.timeout(request_timeout),
+            None => request,
+        }
     }
 
     pub async fn on_response(response: reqwest::Response) -> Result<reqwest::Response, Error> {
@@ -38,7 +71,7 @@ impl LndRest {
 
         match status {
             reqwest::StatusCode::OK => Ok(response),
-            reqwest::StatusCode::INTERNAL_SERVER_ERROR => {
+            reqwest::StatusCode::INTERNAL_SERVER_ERROR | reqwest::StatusCode::NOT_FOUND => {
                 let error: ApiError = response.json().await?;
 
                 match error.message.as_str() {
@@ -52,10 +85,231 @@ impl LndRest {
             },
         }
     }
+
+    pub fn on_stream_response<T>(
+        response: reqwest::Response,
+    ) -> impl Stream<Item = Result<T, Error>> + Send
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        async_stream::try_stream! {
+            let mut chunks = response.bytes_stream();
+            let mut buffer: Vec<u8> = Vec::new();
+
+            while let Some(chunk) = chunks.next().await {
+                buffer.extend_from_slice(&chunk?);
+
+                while let Some(position) = buffer.iter().position(|byte| *byte == b'\n') {
+                    let line: Vec<u8> = buffer.drain(..=position).collect();
+                    if line.iter().all(u8::is_ascii_whitespace) {
+                        continue;
+                    }
+
+                    let message: StreamResponse<T> = serde_json::from_slice(&line)?;
+                    match (message.result, message.error) {
+                        (_, Some(error)) => Err(Error::ApiError(error.message))?,
+                        (Some(result), None) => yield result,
+                        (None, None) => continue,
+                    }
+                }
+            }
+        }
+    }
+
+    // Settled invoices in the order of their settlement.
+    async fn list_settled_invoices(&self) -> Result<Vec<InvoiceResponse>, Error> {
+        let url = format!("{}/v1/invoices", self.config.url);
+        let mut request = ListInvoiceRequest {
+            pending_only: false,
+            index_offset: None,
+            num_max_invoices: utils::DEFAULT_MAX_INVOICES,
+            reversed: false,
+        };
+
+        let mut settled = Vec::new();
+        loop {
+            let mut response = self
+                .request(reqwest::Method::GET, &url)
+                .query(&request)
+                .send()
+                .await?;
+            response = Self::on_response(response).await?;
+            let data: ListInvoiceResponse = response.json().await?;
+
+            let count = data.invoices.len() as u64;
+            for invoice in data.invoices {
+                let settle_index: u64 = invoice.settle_index.parse()?;
+                if settle_index > 0 {
+                    settled.push((settle_index, invoice));
+                }
+            }
+            if count < request.num_max_invoices {
+                break;
+            }
+            request.index_offset = Some(data.last_index_offset.parse()?);
+        }
+        settled.sort_by_key(|(settle_index, _)| *settle_index);
+
+        Ok(settled.into_iter().map(|(_, invoice)| invoice).collect())
+    }
+
+    // LND only closes channels given by their channel point.
+    async fn get_channel_point(&self, params: &CloseChannelParams) -> Result<String, Error> {
+        if let Some(channel_point) = &params.channel_point {
+            return Ok(channel_point.clone());
+        }
+
+        let channel_id = utils::get_channel_id(params)?;
+        self.list_channels()
+            .await?
+            .into_iter()
+            .find(|channel| channel.channel_id == channel_id)
+            .and_then(|channel| channel.channel_point)
+            .ok_or_else(|| Error::ApiError(String::from("channel not found")))
+    }
+
+    async fn get_channel(&self, channel_id: &str) -> Result<Channel, Error> {
+        self.list_channels()
+            .await?
+            .into_iter()
+            .find(|channel| channel.channel_id == channel_id)
+            .ok_or_else(|| Error::ApiError(String::from("channel not found")))
+    }
+
+    // Policies are only known from the channel graph, by short channel id.
+    async fn get_policy(&self, channel: &Channel) -> Result<ChannelPolicy, Error> {
+        let short_channel_id = channel
+            .short_channel_id
+            .as_deref()
+            .ok_or_else(|| Error::ApiError(String::from("channel isn't confirmed yet")))?;
+        let url = format!(
+            "{}/v1/graph/edge/{}",
+            self.config.url,
+            utils::parse_short_channel_id(short_channel_id)?
+        );
+        let mut response = self.request(reqwest::Method::GET, &url).send().await?;
+        response = Self::on_response(response).await?;
+        let edge: ChannelEdgeResponse = response.json().await?;
+
+        (channel, edge).try_into()
+    }
+
+    // LND only connects to peers given their address, which is otherwise taken
+    // from the network graph.
+    async fn get_node_address(&self, pubkey: &str) -> Result<String, Error> {
+        let url = format!("{}/v1/graph/node/{}", self.config.url, pubkey);
+        let mut response = self.request(reqwest::Method::GET, &url).send().await?;
+        response = Self::on_response(response).await?;
+        let info: NodeInfoResponse = response.json().await?;
+
+        info.node
+            .addresses
+            .into_iter()
+            .next()
+            .map(|address| address.addr)
+            .ok_or_else(|| Error::ApiError(String::from("no address known for the peer")))
+    }
 }
 
 #[async_trait::async_trait]
 impl NodeMethods for LndRest {
+    async fn cancel_hold_invoice(&self, payment_hash: String) -> Result<(), Error> {
+        self.cancel_invoice(payment_hash).await
+    }
+
+    async fn cancel_invoice(&self, payment_hash: String) -> Result<(), Error> {
+        let url = format!("{}/v2/invoices/cancel", self.config.url);
+
+        let request = CancelInvoiceRequest {
+            payment_hash: base64::encode(hex::decode(payment_hash)?),
+        };
+        let response = self
+            .request(reqwest::Method::POST, &url)
+            .json(&request)
+            .send()
+            .await?;
+
+        Self::on_response(response).await?;
+
+        Ok(())
+    }
+
+    async fn close_channel(&self, params: CloseChannelParams) -> Result<CloseChannelResult, Error> {
+        let channel_point = self.get_channel_point(&params).await?;
+        let url = format!(
+            "{}/v1/channels/{}",
+            self.config.url,
+            channel_point.replace(':', "/")
+        );
+
+        let request: CloseChannelRequest = (&params).into();
+        let mut response = self.client.delete(&url).query(&request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        // The first update comes once the closing transaction is broadcast,
+        // the channel is only closed after it confirms.
+        let mut updates = Box::pin(Self::on_stream_response::<CloseStatusUpdate>(response));
+        match updates.next().await {
+            Some(update) => update?.try_into(),
+            None => Err(Error::ApiError(String::from(
+                "close stream ended without an update",
+            ))),
+        }
+    }
+
+    async fn connect_peer(&self, params: ConnectPeerParams) -> Result<(), Error> {
+        let url = format!("{}/v1/peers", self.config.url);
+
+        let host = match params.host {
+            Some(host) => host,
+            None => self.get_node_address(&params.pubkey).await?,
+        };
+        let request = ConnectPeerRequest {
+            addr: LightningAddress {
+                pubkey: params.pubkey,
+                host,
+            },
+            perm: false,
+        };
+        let response = self
+            .request(reqwest::Method::POST, &url)
+            .json(&request)
+            .send()
+            .await?;
+
+        // Connecting is idempotent, as on the other backends.
+        match Self::on_response(response).await {
+            Err(Error::ApiError(message)) if message.contains("already connected") => Ok(()),
+            Err(err) => Err(err),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    async fn create_hold_invoice(
+        &self,
+        invoice: CreateHoldInvoiceParams,
+    ) -> Result<CreateInvoiceResult, Error> {
+        let url = format!("{}/v2/invoices/hodl", self.config.url);
+
+        let request: AddHoldInvoiceRequest = (&invoice).try_into()?;
+        let mut response = self
+            .request(reqwest::Method::POST, &url)
+            .json(&request)
+            .send()
+            .await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: AddHoldInvoiceResponse = response.json().await?;
+
+        Ok(CreateInvoiceResult {
+            payment_request: data.payment_request,
+            payment_hash: invoice.payment_hash,
+            label: None,
+        })
+    }
+
     async fn create_invoice(
         &self,
         invoice: CreateInvoiceParams,
@@ -63,7 +317,11 @@ impl NodeMethods for LndRest {
         let url = format!("{}/v1/invoices", self.config.url);
 
         let request: CreateInvoiceRequest = invoice.into();
-        let mut response = self.client.post(&url).json(&request).send().await?;
+        let mut response = self
+            .request(reqwest::Method::POST, &url)
+            .json(&request)
+            .send()
+            .await?;
 
         response = Self::on_response(response).await?;
 
@@ -72,10 +330,75 @@ impl NodeMethods for LndRest {
         Ok(data.try_into()?)
     }
 
+    async fn decode_invoice(&self, bolt11: String) -> Result<DecodedInvoice, Error> {
+        let url = format!("{}/v1/payreq/{}", self.config.url, bolt11);
+
+        let mut response = self.request(reqwest::Method::GET, &url).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: PayReqResponse = response.json().await?;
+
+        Ok((bolt11, data).try_into()?)
+    }
+
+    async fn delete_expired_invoices(&self, _before: Option<u64>) -> Result<(), Error> {
+        // LND has no call to delete invoices, it cancels the expired ones
+        // itself and can be configured to delete them.
+        Err(Error::NotImplemented)
+    }
+
+    async fn disconnect_peer(&self, pubkey: String) -> Result<(), Error> {
+        let url = format!("{}/v1/peers/{}", self.config.url, pubkey);
+
+        let response = self.request(reqwest::Method::DELETE, &url).send().await?;
+
+        Self::on_response(response).await?;
+
+        Ok(())
+    }
+
+    async fn get_balance(&self) -> Result<Balance, Error> {
+        let url = format!("{}/v1/balance/blockchain", self.config.url);
+        let mut response = self.request(reqwest::Method::GET, &url).send().await?;
+        response = Self::on_response(response).await?;
+        let wallet: WalletBalanceResponse = response.json().await?;
+
+        let url = format!("{}/v1/balance/channels", self.config.url);
+        let mut response = self.request(reqwest::Method::GET, &url).send().await?;
+        response = Self::on_response(response).await?;
+        let channels: ChannelBalanceResponse = response.json().await?;
+
+        // Funds of closing channels are only reported with the pending channels.
+        let url = format!("{}/v1/channels/pending", self.config.url);
+        let mut response = self.request(reqwest::Method::GET, &url).send().await?;
+        response = Self::on_response(response).await?;
+        let pending: PendingChannelsResponse = response.json().await?;
+
+        // The channel balance includes the reserves, which can't be sent.
+        let open_channels = self.list_channels().await?;
+        let (local_msat, remote_msat) = utils::spendable_balance_msat(&open_channels);
+
+        let mut balance: Balance = wallet.try_into()?;
+        balance.lightning_local_msat = local_msat;
+        balance.lightning_remote_msat = remote_msat;
+        balance.pending_open_msat = channels.pending_open_local_balance.msat.parse()?;
+        balance.pending_close_msat =
+            utils::sat_to_msat(pending.total_limbo_balance.parse::<u64>()?);
+
+        Ok(balance)
+    }
+
+    async fn get_channel_policy(&self, channel_id: String) -> Result<ChannelPolicy, Error> {
+        let channel = self.get_channel(&channel_id).await?;
+
+        self.get_policy(&channel).await
+    }
+
     async fn get_info(&self) -> Result<NodeInfo, Error> {
         let url = format!("{}/v1/getinfo", self.config.url);
 
-        let mut response = self.client.get(&url).send().await?;
+        let mut response = self.request(reqwest::Method::GET, &url).send().await?;
 
         response = Self::on_response(response).await?;
 
@@ -84,6 +407,247 @@ impl NodeMethods for LndRest {
         Ok(data.into())
     }
 
+    async fn get_invoice(&self, payment_hash: String) -> Result<Invoice, Error> {
+        let url = format!("{}/v1/invoice/{}", self.config.url, payment_hash);
+
+        let mut response = self.request(reqwest::Method::GET, &url).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: InvoiceResponse = response.json().await?;
+
+        Ok(data.try_into()?)
+    }
+
+    async fn get_payment(&self, payment_hash: String) -> Result<Payment, Error> {
+        // The first update of the tracking is the current state of the payment.
+        match self.track_payment(payment_hash).await?.next().await {
+            Some(payment) => payment,
+            None => Err(Error::ApiError(String::from("payment not found"))),
+        }
+    }
+
+    async fn keysend(&self, params: KeysendParams) -> Result<PayInvoiceResult, Error> {
+        let url = format!("{}/v1/channels/transactions", self.config.url);
+
+        let request: SendPaymentSyncRequest = params.try_into()?;
+        let mut response = self.client.post(&url).json(&request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: SendPaymentSyncResponse = response.json().await?;
+
+        Ok(data.try_into()?)
+    }
+
+    async fn list_channels(&self) -> Result<Vec<Channel>, Error> {
+        let url = format!("{}/v1/channels", self.config.url);
+        let mut response = self.request(reqwest::Method::GET, &url).send().await?;
+        response = Self::on_response(response).await?;
+        let open: ListChannelsResponse = response.json().await?;
+
+        let url = format!("{}/v1/channels/pending", self.config.url);
+        let mut response = self.request(reqwest::Method::GET, &url).send().await?;
+        response = Self::on_response(response).await?;
+        let pending: PendingChannelsResponse = response.json().await?;
+
+        let mut channels = Vec::new();
+        for channel in pending.pending_open_channels {
+            channels.push((channel.channel, ChannelStatus::Opening).try_into()?);
+        }
+        for channel in open.channels {
+            channels.push(channel.try_into()?);
+        }
+        let closing = pending
+            .waiting_close_channels
+            .into_iter()
+            .chain(pending.pending_force_closing_channels);
+        for channel in closing {
+            channels.push((channel.channel, ChannelStatus::Closing).try_into()?);
+        }
+
+        Ok(channels)
+    }
+
+    async fn list_forwards(&self, params: ListForwardsParams) -> Result<ListForwardsResult, Error> {
+        let url = format!("{}/v1/switch", self.config.url);
+
+        let request: ForwardingHistoryRequest = (&params).try_into()?;
+        let mut response = self
+            .request(reqwest::Method::POST, &url)
+            .json(&request)
+            .send()
+            .await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: ForwardingHistoryResponse = response.json().await?;
+
+        let has_more = !data.forwarding_events.is_empty()
+            && data.forwarding_events.len() as u32 == request.num_max_events;
+        let next_cursor = match has_more {
+            true => Some(data.last_offset_index.into()),
+            false => None,
+        };
+
+        let mut forwards = Vec::new();
+        for event in data.forwarding_events {
+            forwards.push(event.try_into()?);
+        }
+
+        let channels = self.list_channels().await?;
+        utils::fill_forward_channels(&mut forwards, &channels);
+
+        Ok(ListForwardsResult {
+            forwards,
+            next_cursor,
+        })
+    }
+
+    async fn list_invoices(&self, params: ListInvoicesParams) -> Result<ListInvoicesResult, Error> {
+        let url = format!("{}/v1/invoices", self.config.url);
+
+        let request: ListInvoiceRequest = (&params).into();
+        let mut response = self
+            .request(reqwest::Method::GET, &url)
+            .query(&request)
+            .send()
+            .await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: ListInvoiceResponse = response.json().await?;
+
+        // LND pages on its own invoice index, so a page can come back with less
+        // than `max_invoices` invoices once the remaining filters are applied.
+        let has_more =
+            !data.invoices.is_empty() && data.invoices.len() as u64 == request.num_max_invoices;
+        let next_cursor = match has_more {
+            true => Some(data.last_index_offset.parse()?),
+            false => None,
+        };
+
+        let mut invoices = Vec::new();
+        for invoice in data.invoices {
+            let invoice: Invoice = invoice.try_into()?;
+            if utils::invoice_matches(&invoice, &params) {
+                invoices.push(invoice);
+            }
+        }
+
+        Ok(ListInvoicesResult {
+            invoices,
+            next_cursor,
+        })
+    }
+
+    async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, Error> {
+        let url = format!("{}/v1/transactions", self.config.url);
+
+        let mut response = self.request(reqwest::Method::GET, &url).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: TransactionDetails = response.json().await?;
+
+        data.transactions
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect()
+    }
+
+    async fn list_payments(&self, params: ListPaymentsParams) -> Result<ListPaymentsResult, Error> {
+        let url = format!("{}/v1/payments", self.config.url);
+
+        let request: ListPaymentsRequest = (&params).into();
+        let mut response = self
+            .request(reqwest::Method::GET, &url)
+            .query(&request)
+            .send()
+            .await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: ListPaymentsResponse = response.json().await?;
+
+        // LND v0.15 doesn't filter payments, so a page can come back with less
+        // than `max_payments` payments once the filters are applied.
+        let has_more =
+            !data.payments.is_empty() && data.payments.len() as u64 == request.max_payments;
+        let next_cursor = match has_more {
+            true => Some(data.last_index_offset.parse()?),
+            false => None,
+        };
+
+        let mut payments = Vec::new();
+        for payment in data.payments {
+            let payment: Payment = payment.try_into()?;
+            if utils::payment_matches(&payment, &params) {
+                payments.push(payment);
+            }
+        }
+
+        Ok(ListPaymentsResult {
+            payments,
+            next_cursor,
+        })
+    }
+
+    async fn list_peers(&self) -> Result<Vec<Peer>, Error> {
+        let url = format!("{}/v1/peers", self.config.url);
+
+        let mut response = self.request(reqwest::Method::GET, &url).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: ListPeersResponse = response.json().await?;
+
+        data.peers.into_iter().map(TryInto::try_into).collect()
+    }
+
+    async fn new_address(&self, address_type: AddressType) -> Result<String, Error> {
+        let url = format!("{}/v1/newaddress", self.config.url);
+
+        let request: NewAddressRequest = address_type.into();
+        let mut response = self
+            .request(reqwest::Method::GET, &url)
+            .query(&request)
+            .send()
+            .await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: NewAddressResponse = response.json().await?;
+
+        Ok(data.address)
+    }
+
+    async fn open_channel(&self, params: OpenChannelParams) -> Result<OpenChannelResult, Error> {
+        // The params are checked before connecting to the peer, which isn't
+        // undone when they are invalid.
+        let peer = params.peer_host.clone().map(|host| ConnectPeerParams {
+            pubkey: params.peer_pubkey.clone(),
+            host: Some(host),
+        });
+        let request: OpenChannelRequest = params.try_into()?;
+        if let Some(peer) = peer {
+            self.connect_peer(peer).await?;
+        }
+
+        let url = format!("{}/v1/channels", self.config.url);
+        let mut response = self
+            .request(reqwest::Method::POST, &url)
+            .json(&request)
+            .send()
+            .await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: ChannelPoint = response.json().await?;
+
+        data.try_into()
+    }
+
     async fn pay_invoice(&self, invoice: PayInvoiceParams) -> Result<PayInvoiceResult, Error> {
         let url = format!("{}/v1/channels/transactions", self.config.url);
 
@@ -96,4 +660,211 @@ impl NodeMethods for LndRest {
 
         Ok(data.try_into()?)
     }
+
+    async fn send_onchain(&self, params: SendOnchainParams) -> Result<String, Error> {
+        let url = format!("{}/v1/transactions", self.config.url);
+
+        let request: SendCoinsRequest = params.try_into()?;
+        let mut response = self
+            .request(reqwest::Method::POST, &url)
+            .json(&request)
+            .send()
+            .await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: SendCoinsResponse = response.json().await?;
+
+        Ok(data.txid)
+    }
+
+    async fn send_payment_async(&self, invoice: PayInvoiceParams) -> Result<String, Error> {
+        let url = format!("{}/v2/router/send", self.config.url);
+
+        let request: RouterSendPaymentRequest = invoice.try_into()?;
+        let mut response = self.client.post(&url).json(&request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        // The first update comes once the payment is registered, LND keeps
+        // paying after the stream is dropped.
+        let mut payments = Box::pin(Self::on_stream_response::<PaymentResponse>(response));
+        match payments.next().await {
+            Some(payment) => Ok(payment?.payment_hash),
+            None => Err(Error::ApiError(String::from(
+                "payment stream closed without a result",
+            ))),
+        }
+    }
+
+    async fn settle_hold_invoice(&self, preimage: String) -> Result<(), Error> {
+        let url = format!("{}/v2/invoices/settle", self.config.url);
+
+        let request = SettleInvoiceRequest {
+            preimage: base64::encode(hex::decode(preimage)?),
+        };
+        let response = self
+            .request(reqwest::Method::POST, &url)
+            .json(&request)
+            .send()
+            .await?;
+
+        Self::on_response(response).await?;
+
+        Ok(())
+    }
+
+    async fn sign_message(&self, message: String) -> Result<String, Error> {
+        let url = format!("{}/v1/signmessage", self.config.url);
+
+        let request: SignMessageRequest = message.as_str().into();
+        let mut response = self
+            .request(reqwest::Method::POST, &url)
+            .json(&request)
+            .send()
+            .await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: SignMessageResponse = response.json().await?;
+
+        Ok(data.signature)
+    }
+
+    async fn subscribe_invoices(
+        &self,
+        params: SubscribeInvoicesParams,
+    ) -> Result<InvoiceStream, Error> {
+        let url = format!("{}/v1/invoices/subscribe", self.config.url);
+
+        let request: SubscribeInvoicesRequest = (&params).into();
+        let mut response = self.client.get(&url).query(&request).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let invoices = Self::on_stream_response::<InvoiceResponse>(response);
+        let mut cursor = params.cursor.unwrap_or_default();
+
+        // LND doesn't replay any settlement without a settle index. A new
+        // subscription starts from the last one, while a resumed one first
+        // sends every settlement again.
+        let mut settled = Vec::new();
+        if cursor.settle_index.unwrap_or(0) == 0 {
+            let invoices = self.list_settled_invoices().await?;
+            match cursor.add_index {
+                Some(_) => settled = invoices,
+                None => {
+                    cursor.settle_index = invoices
+                        .last()
+                        .map(|invoice| invoice.settle_index.parse())
+                        .transpose()?
+                }
+            }
+        }
+
+        let stream = async_stream::try_stream! {
+            for invoice in settled {
+                let settle_index: u64 = invoice.settle_index.parse()?;
+                cursor.settle_index = cursor.settle_index.max(Some(settle_index));
+                let invoice: Invoice = invoice.try_into()?;
+
+                yield InvoiceEvent {
+                    event_type: InvoiceEventType::Settled,
+                    invoice,
+                    cursor: cursor.clone(),
+                };
+            }
+
+            for await invoice in invoices {
+                let invoice = invoice?;
+
+                // Open invoices have no settle index, so the cursor keeps the
+                // highest indexes seen to resume from.
+                let add_index: u64 = invoice.add_index.parse()?;
+                let settle_index: u64 = invoice.settle_index.parse()?;
+                cursor.add_index = cursor.add_index.max(Some(add_index));
+                if settle_index > 0 {
+                    cursor.settle_index = cursor.settle_index.max(Some(settle_index));
+                }
+
+                let event_type: InvoiceEventType = (&invoice.state).into();
+                let invoice: Invoice = invoice.try_into()?;
+
+                yield InvoiceEvent {
+                    event_type,
+                    invoice,
+                    cursor: cursor.clone(),
+                };
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn track_payment(&self, payment_hash: String) -> Result<PaymentStream, Error> {
+        // The hash is a bytes field in the path, so it's sent as URL-safe base64.
+        let payment_hash = base64::encode_config(hex::decode(payment_hash)?, base64::URL_SAFE);
+        let url = format!("{}/v2/router/track/{}", self.config.url, payment_hash);
+
+        let mut response = self.client.get(&url).send().await?;
+
+        response = Self::on_response(response).await?;
+
+        let payments = Self::on_stream_response::<PaymentResponse>(response);
+
+        let stream = async_stream::try_stream! {
+            for await payment in payments {
+                let payment: Payment = payment?.try_into()?;
+                yield payment;
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn update_channel_policy(&self, params: UpdatePolicyParams) -> Result<(), Error> {
+        let url = format!("{}/v1/chanpolicy", self.config.url);
+
+        let request = match &params.channel_id {
+            Some(channel_id) => {
+                let channel = self.get_channel(channel_id).await?;
+                let policy = self.get_policy(&channel).await?;
+                let params = utils::fill_channel_policy(&params, &policy);
+                PolicyUpdateRequest::try_from((&params, channel.channel_point.as_deref()))?
+            }
+            None => PolicyUpdateRequest::try_from((&params, None))?,
+        };
+        let mut response = self
+            .request(reqwest::Method::POST, &url)
+            .json(&request)
+            .send()
+            .await?;
+        response = Self::on_response(response).await?;
+        let data: PolicyUpdateResponse = response.json().await?;
+
+        match data.failed_updates.into_iter().next() {
+            Some(failed) => Err(Error::ApiError(failed.update_error)),
+            None => Ok(()),
+        }
+    }
+
+    async fn verify_message(
+        &self,
+        params: VerifyMessageParams,
+    ) -> Result<VerifyMessageResult, Error> {
+        let url = format!("{}/v1/verifymessage", self.config.url);
+
+        let request: VerifyMessageRequest = (&params).into();
+        let mut response = self
+            .request(reqwest::Method::POST, &url)
+            .json(&request)
+            .send()
+            .await?;
+
+        response = Self::on_response(response).await?;
+
+        let data: VerifyMessageResponse = response.json().await?;
+
+        Ok((&params, data).into())
+    }
 }
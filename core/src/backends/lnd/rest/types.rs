@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::error::Error;
-use crate::{types::*, utils};
+use crate::{bolt11, types::*, utils};
 
 pub type Base64String = String;
 
@@ -15,6 +15,19 @@ pub struct ApiError {
     pub details: Vec<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct StreamError {
+    pub message: String,
+}
+
+// Streaming endpoints send one JSON object per line, wrapping either a result
+// or an error.
+#[derive(Debug, Deserialize)]
+pub struct StreamResponse<T> {
+    pub result: Option<T>,
+    pub error: Option<StreamError>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct CreateInvoiceRequest {
     pub memo: Option<String>,
@@ -108,7 +121,7 @@ pub struct Feature {
 
 impl Into<NodeInfo> for GetInfoResponse {
     fn into(self) -> NodeInfo {
-        let network = match self.chains.get(0) {
+        let network = match self.chains.first() {
             Some(chain) => match chain.network.as_ref() {
                 "mainnet" => Network::Mainnet,
                 "testnet" => Network::Testnet,
@@ -132,6 +145,139 @@ impl Into<NodeInfo> for GetInfoResponse {
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct InvoiceResponse {
+    pub memo: String,
+    pub r_preimage: Option<Base64String>,
+    pub r_hash: Base64String,
+    pub value: String,
+    pub value_msat: String,
+    pub settled: bool,
+    pub creation_date: String,
+    pub settle_date: String,
+    pub payment_request: String,
+    pub description_hash: Option<Base64String>,
+    pub expiry: String,
+    pub fallback_addr: String,
+    pub cltv_expiry: String,
+    pub private: bool,
+    pub add_index: String,
+    pub settle_index: String,
+    pub amt_paid_sat: String,
+    pub amt_paid_msat: String,
+    pub state: InvoiceState,
+    pub is_keysend: bool,
+    pub payment_addr: Option<Base64String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum InvoiceState {
+    Open,
+    Settled,
+    Canceled,
+    Accepted,
+}
+
+impl From<&InvoiceState> for InvoiceEventType {
+    fn from(state: &InvoiceState) -> Self {
+        match state {
+            InvoiceState::Open => InvoiceEventType::Created,
+            InvoiceState::Settled => InvoiceEventType::Settled,
+            InvoiceState::Canceled => InvoiceEventType::Cancelled,
+            InvoiceState::Accepted => InvoiceEventType::Accepted,
+        }
+    }
+}
+
+impl TryInto<Invoice> for InvoiceResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Invoice, Self::Error> {
+        let status = match self.state {
+            InvoiceState::Open => InvoiceStatus::Pending,
+            InvoiceState::Settled => InvoiceStatus::Settled,
+            InvoiceState::Canceled => InvoiceStatus::Cancelled,
+            InvoiceState::Accepted => InvoiceStatus::Accepted,
+        };
+        let settled = matches!(status, InvoiceStatus::Settled);
+
+        let pre_image = match (settled, self.r_preimage) {
+            (true, Some(r_preimage)) => Some(utils::b64_to_hex(&r_preimage)?),
+            _ => None,
+        };
+        let settle_date = match settled {
+            true => Some(self.settle_date.parse()?),
+            false => None,
+        };
+
+        let invoice = Invoice {
+            bolt11: self.payment_request,
+            memo: self.memo,
+            amount: self.value.parse()?,
+            amount_msat: self.value_msat.parse()?,
+            pre_image,
+            payment_hash: utils::b64_to_hex(&self.r_hash)?,
+            settled,
+            settle_date,
+            creation_date: self.creation_date.parse()?,
+            expiry: self.expiry.parse()?,
+            status,
+        };
+
+        Ok(invoice)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubscribeInvoicesRequest {
+    pub add_index: u64,
+    pub settle_index: u64,
+}
+
+impl From<&SubscribeInvoicesParams> for SubscribeInvoicesRequest {
+    fn from(params: &SubscribeInvoicesParams) -> Self {
+        let cursor = params.cursor.clone().unwrap_or_default();
+
+        SubscribeInvoicesRequest {
+            add_index: cursor.add_index.unwrap_or(0),
+            settle_index: cursor.settle_index.unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListInvoiceRequest {
+    pub pending_only: bool,
+    pub index_offset: Option<u64>,
+    pub num_max_invoices: u64,
+    pub reversed: bool,
+}
+
+impl From<&ListInvoicesParams> for ListInvoiceRequest {
+    fn from(params: &ListInvoicesParams) -> Self {
+        let pending_only = params.pending_only.unwrap_or(false)
+            || matches!(
+                params.status,
+                Some(InvoiceStatus::Pending) | Some(InvoiceStatus::Accepted)
+            );
+
+        ListInvoiceRequest {
+            pending_only,
+            index_offset: params.cursor,
+            num_max_invoices: utils::page_size(params.max_invoices, utils::DEFAULT_MAX_INVOICES),
+            reversed: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListInvoiceResponse {
+    pub invoices: Vec<InvoiceResponse>,
+    pub last_index_offset: String,
+    pub first_index_offset: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct FeeLimit {
     pub fixed: Option<String>,
@@ -154,6 +300,7 @@ pub struct SendPaymentSyncRequest {
     pub allow_self_payment: Option<bool>,
     pub dest_features: Option<Vec<u8>>,
     pub payment_addr: Option<Base64String>,
+    pub dest_custom_records: Option<HashMap<String, Base64String>>,
 }
 
 impl From<PayInvoiceParams> for SendPaymentSyncRequest {
@@ -179,7 +326,103 @@ impl From<PayInvoiceParams> for SendPaymentSyncRequest {
             allow_self_payment: Some(false),
             dest_features: None,
             payment_addr: None,
+            dest_custom_records: None,
+        }
+    }
+}
+
+impl TryFrom<KeysendParams> for SendPaymentSyncRequest {
+    type Error = Error;
+
+    fn try_from(params: KeysendParams) -> Result<Self, Self::Error> {
+        let amount_msat = utils::get_amount_msat(params.amount, params.amount_msat)
+            .ok_or_else(|| Error::ApiError(String::from("amount is required")))?;
+        let max_fee_msat = utils::get_amount_msat(params.max_fee_sat, params.max_fee_msat);
+
+        let mut dest_custom_records = HashMap::new();
+        for record in params.custom_records.unwrap_or_default() {
+            let value = hex::decode(record.value)?;
+            dest_custom_records.insert(record.record_type.to_string(), base64::encode(value));
         }
+
+        // The sender picks the preimage of a keysend payment, and sends it to
+        // the destination in a record.
+        let (preimage, payment_hash) = utils::keysend_preimage();
+        dest_custom_records.insert(
+            utils::KEYSEND_PREIMAGE_RECORD.to_string(),
+            base64::encode(preimage),
+        );
+
+        let request = SendPaymentSyncRequest {
+            dest: Some(base64::encode(hex::decode(params.destination)?)),
+            amt: None,
+            amt_msat: Some(amount_msat.to_string()),
+            payment_hash: Some(base64::encode(payment_hash)),
+            payment_request: String::new(),
+            final_cltv_delta: None,
+            fee_limit: Some(FeeLimit {
+                fixed: None,
+                fixed_msat: max_fee_msat.map(|v| v.to_string()),
+                percent: params.max_fee_percent.map(|v| v.to_string()),
+            }),
+            outgoing_chan_id: None,
+            last_hop_pubkey: None,
+            cltv_limit: None,
+            allow_self_payment: Some(false),
+            dest_features: None,
+            payment_addr: None,
+            dest_custom_records: Some(dest_custom_records),
+        };
+
+        Ok(request)
+    }
+}
+
+/// Seconds LND keeps looking for a route before failing a payment.
+pub const PAYMENT_TIMEOUT_SECONDS: i32 = 60;
+
+// Request of the router, which unlike `SendPaymentSync` streams the updates of
+// the payment.
+#[derive(Debug, Serialize)]
+pub struct RouterSendPaymentRequest {
+    pub payment_request: String,
+    pub amt_msat: Option<String>,
+    pub fee_limit_msat: Option<String>,
+    pub timeout_seconds: i32,
+    pub no_inflight_updates: bool,
+}
+
+impl TryFrom<PayInvoiceParams> for RouterSendPaymentRequest {
+    type Error = Error;
+
+    fn try_from(params: PayInvoiceParams) -> Result<Self, Self::Error> {
+        let amount_msat = utils::get_amount_msat(params.amount, params.amount_msat);
+
+        // The router only takes an absolute fee limit, so a percentage is
+        // applied to the amount being paid.
+        let invoice_amount_msat = match (amount_msat, params.max_fee_percent) {
+            (Some(amount_msat), _) => amount_msat,
+            (None, Some(_)) => bolt11::decode_invoice(&params.payment_request)?
+                .amount_msat
+                .unwrap_or(0),
+            (None, None) => 0,
+        };
+        let fee_limit_msat = utils::get_max_fee_msat(
+            invoice_amount_msat,
+            params.max_fee_sat,
+            params.max_fee_msat,
+            params.max_fee_percent,
+        );
+
+        let request = RouterSendPaymentRequest {
+            payment_request: params.payment_request,
+            amt_msat: amount_msat.map(|v| v.to_string()),
+            fee_limit_msat: fee_limit_msat.map(|v| v.to_string()),
+            timeout_seconds: PAYMENT_TIMEOUT_SECONDS,
+            no_inflight_updates: false,
+        };
+
+        Ok(request)
     }
 }
 
@@ -253,3 +496,956 @@ impl TryInto<PayInvoiceResult> for SendPaymentSyncResponse {
         Ok(result)
     }
 }
+
+#[derive(Debug, Deserialize)]
+pub struct PayReqResponse {
+    pub destination: String,
+    pub payment_hash: String,
+    pub num_satoshis: String,
+    pub timestamp: String,
+    pub expiry: String,
+    pub description: String,
+    pub description_hash: String,
+    pub fallback_addr: String,
+    pub cltv_expiry: String,
+    pub route_hints: Vec<RouteHintResponse>,
+    pub payment_addr: Base64String,
+    pub num_msat: String,
+    pub features: HashMap<String, FeatureResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RouteHintResponse {
+    pub hop_hints: Vec<HopHintResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HopHintResponse {
+    pub node_id: String,
+    pub chan_id: String,
+    pub fee_base_msat: u32,
+    pub fee_proportional_millionths: u32,
+    pub cltv_expiry_delta: u16,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeatureResponse {
+    pub name: String,
+    pub is_required: bool,
+    pub is_known: bool,
+}
+
+// LND names the features on its own, the BOLT 9 names are kept when known.
+fn features(
+    features: HashMap<String, FeatureResponse>,
+) -> Result<Vec<crate::types::Feature>, Error> {
+    let mut result = Vec::new();
+    for (bit, lnd_feature) in features {
+        let mut feature = bolt11::feature(bit.parse()?);
+        feature.name = feature.name.or(Some(lnd_feature.name));
+        feature.is_required = lnd_feature.is_required;
+        feature.is_known = lnd_feature.is_known;
+        result.push(feature);
+    }
+    result.sort_by_key(|feature| feature.bit);
+
+    Ok(result)
+}
+
+impl TryInto<DecodedInvoice> for (String, PayReqResponse) {
+    type Error = Error;
+
+    fn try_into(self) -> Result<DecodedInvoice, Self::Error> {
+        let (bolt11, payreq) = self;
+
+        let amount_msat: u64 = payreq.num_msat.parse()?;
+        let amount_msat = match amount_msat {
+            0 => None,
+            amount_msat => Some(amount_msat),
+        };
+        let payment_secret = match payreq.payment_addr.is_empty() {
+            true => None,
+            false => Some(utils::b64_to_hex(&payreq.payment_addr)?),
+        };
+
+        let mut route_hints = Vec::new();
+        for route_hint in payreq.route_hints {
+            let mut hops = Vec::new();
+            for hop in route_hint.hop_hints {
+                hops.push(RouteHintHop {
+                    node_id: hop.node_id,
+                    short_channel_id: utils::format_short_channel_id(hop.chan_id.parse()?),
+                    fee_base_msat: hop.fee_base_msat,
+                    fee_proportional_millionths: hop.fee_proportional_millionths,
+                    cltv_expiry_delta: hop.cltv_expiry_delta,
+                });
+            }
+            route_hints.push(RouteHint { hops });
+        }
+
+        let invoice = DecodedInvoice {
+            network: bolt11::get_network(&bolt11)?,
+            bolt11,
+            payee: payreq.destination,
+            amount: amount_msat.map(utils::msat_to_sat),
+            amount_msat,
+            payment_hash: payreq.payment_hash,
+            payment_secret,
+            description: Some(payreq.description).filter(|description| !description.is_empty()),
+            description_hash: Some(payreq.description_hash)
+                .filter(|description_hash| !description_hash.is_empty()),
+            creation_date: payreq.timestamp.parse()?,
+            expiry: payreq.expiry.parse()?,
+            min_final_cltv_expiry: payreq.cltv_expiry.parse()?,
+            route_hints,
+            features: features(payreq.features)?,
+        };
+
+        Ok(invoice)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WalletBalanceResponse {
+    pub confirmed_balance: String,
+    pub unconfirmed_balance: String,
+    pub locked_balance: String,
+}
+
+impl TryInto<Balance> for WalletBalanceResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Balance, Self::Error> {
+        let balance = Balance {
+            onchain_confirmed_msat: utils::sat_to_msat(self.confirmed_balance.parse::<u64>()?),
+            onchain_unconfirmed_msat: utils::sat_to_msat(self.unconfirmed_balance.parse::<u64>()?),
+            onchain_locked_msat: utils::sat_to_msat(self.locked_balance.parse::<u64>()?),
+            ..Default::default()
+        };
+
+        Ok(balance)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Amount {
+    pub sat: String,
+    pub msat: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChannelBalanceResponse {
+    pub local_balance: Amount,
+    pub remote_balance: Amount,
+    pub pending_open_local_balance: Amount,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PendingChannelsResponse {
+    pub total_limbo_balance: String,
+    pub pending_open_channels: Vec<PendingChannelEntry>,
+    pub waiting_close_channels: Vec<PendingChannelEntry>,
+    pub pending_force_closing_channels: Vec<PendingChannelEntry>,
+}
+
+// The pending channels are listed along with details depending on why they are
+// pending, only the channel itself is used.
+#[derive(Debug, Deserialize)]
+pub struct PendingChannelEntry {
+    pub channel: PendingChannel,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PendingChannel {
+    pub remote_node_pub: String,
+    pub channel_point: String,
+    pub capacity: String,
+    pub local_balance: String,
+    pub remote_balance: String,
+    pub local_chan_reserve_sat: String,
+    pub remote_chan_reserve_sat: String,
+    pub initiator: String,
+    pub private: bool,
+}
+
+impl TryInto<Channel> for (PendingChannel, ChannelStatus) {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Channel, Self::Error> {
+        let (channel, status) = self;
+
+        let result = Channel {
+            channel_id: utils::channel_id_from_channel_point(&channel.channel_point)?,
+            channel_point: Some(channel.channel_point),
+            short_channel_id: None,
+            peer_pubkey: channel.remote_node_pub,
+            capacity_msat: utils::sat_to_msat(channel.capacity.parse::<u64>()?),
+            local_balance_msat: utils::sat_to_msat(channel.local_balance.parse::<u64>()?),
+            remote_balance_msat: utils::sat_to_msat(channel.remote_balance.parse::<u64>()?),
+            local_reserve_msat: utils::sat_to_msat(channel.local_chan_reserve_sat.parse::<u64>()?),
+            remote_reserve_msat: utils::sat_to_msat(
+                channel.remote_chan_reserve_sat.parse::<u64>()?,
+            ),
+            active: false,
+            private: channel.private,
+            initiator: channel.initiator == "INITIATOR_LOCAL",
+            status,
+        };
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListChannelsResponse {
+    pub channels: Vec<ChannelResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChannelResponse {
+    pub active: bool,
+    pub remote_pubkey: String,
+    pub channel_point: String,
+    pub chan_id: String,
+    pub capacity: String,
+    pub local_balance: String,
+    pub remote_balance: String,
+    pub private: bool,
+    pub initiator: bool,
+    pub local_constraints: ChannelConstraints,
+    pub remote_constraints: ChannelConstraints,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChannelConstraints {
+    pub chan_reserve_sat: String,
+}
+
+impl TryInto<Channel> for ChannelResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Channel, Self::Error> {
+        let short_channel_id = self.chan_id.parse::<u64>()?;
+
+        let channel = Channel {
+            channel_id: utils::channel_id_from_channel_point(&self.channel_point)?,
+            channel_point: Some(self.channel_point),
+            short_channel_id: Some(utils::format_short_channel_id(short_channel_id)),
+            peer_pubkey: self.remote_pubkey,
+            capacity_msat: utils::sat_to_msat(self.capacity.parse::<u64>()?),
+            local_balance_msat: utils::sat_to_msat(self.local_balance.parse::<u64>()?),
+            remote_balance_msat: utils::sat_to_msat(self.remote_balance.parse::<u64>()?),
+            local_reserve_msat: utils::sat_to_msat(
+                self.local_constraints.chan_reserve_sat.parse::<u64>()?,
+            ),
+            remote_reserve_msat: utils::sat_to_msat(
+                self.remote_constraints.chan_reserve_sat.parse::<u64>()?,
+            ),
+            active: self.active,
+            private: self.private,
+            initiator: self.initiator,
+            status: ChannelStatus::Open,
+        };
+
+        Ok(channel)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LightningAddress {
+    pub pubkey: String,
+    pub host: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConnectPeerRequest {
+    pub addr: LightningAddress,
+    pub perm: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OpenChannelRequest {
+    pub node_pubkey: Base64String,
+    pub local_funding_amount: String,
+    pub push_sat: Option<String>,
+    pub sat_per_vbyte: Option<String>,
+    pub private: bool,
+    pub min_confs: Option<i32>,
+    pub spend_unconfirmed: bool,
+}
+
+impl TryFrom<OpenChannelParams> for OpenChannelRequest {
+    type Error = Error;
+
+    fn try_from(params: OpenChannelParams) -> Result<Self, Self::Error> {
+        let request = OpenChannelRequest {
+            node_pubkey: base64::encode(hex::decode(params.peer_pubkey)?),
+            local_funding_amount: params.amount.to_string(),
+            push_sat: params.push_amount.map(|v| v.to_string()),
+            sat_per_vbyte: params.fee_rate.map(|v| v.to_string()),
+            private: params.private.unwrap_or(false),
+            min_confs: params.min_confs.map(i32::try_from).transpose()?,
+            // LND refuses to fund from unconfirmed outputs unless told so.
+            spend_unconfirmed: params.min_confs == Some(0),
+        };
+
+        Ok(request)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChannelPoint {
+    pub funding_txid_bytes: Base64String,
+    pub output_index: u32,
+}
+
+impl TryInto<OpenChannelResult> for ChannelPoint {
+    type Error = Error;
+
+    fn try_into(self) -> Result<OpenChannelResult, Self::Error> {
+        let funding_txid = utils::txid_to_hex(base64::decode(&self.funding_txid_bytes)?);
+
+        let channel_point = format!("{}:{}", funding_txid, self.output_index);
+        let result = OpenChannelResult {
+            channel_id: utils::channel_id_from_channel_point(&channel_point)?,
+            funding_txid,
+            output_index: self.output_index,
+        };
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CloseChannelRequest {
+    pub force: bool,
+    pub sat_per_vbyte: Option<String>,
+    pub delivery_address: Option<String>,
+}
+
+impl From<&CloseChannelParams> for CloseChannelRequest {
+    fn from(params: &CloseChannelParams) -> Self {
+        CloseChannelRequest {
+            force: params.force.unwrap_or(false),
+            sat_per_vbyte: params.fee_rate.map(|v| v.to_string()),
+            delivery_address: params.delivery_address.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PendingUpdate {
+    pub txid: Base64String,
+    pub output_index: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChannelCloseUpdate {
+    pub closing_txid: Base64String,
+    pub success: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CloseStatusUpdate {
+    pub close_pending: Option<PendingUpdate>,
+    pub chan_close: Option<ChannelCloseUpdate>,
+}
+
+impl TryInto<CloseChannelResult> for CloseStatusUpdate {
+    type Error = Error;
+
+    fn try_into(self) -> Result<CloseChannelResult, Self::Error> {
+        let txid = match (self.close_pending, self.chan_close) {
+            (Some(pending), _) => pending.txid,
+            (None, Some(close)) => close.closing_txid,
+            (None, None) => {
+                return Err(Error::ApiError(String::from(
+                    "close update without a transaction",
+                )))
+            }
+        };
+
+        let result = CloseChannelResult {
+            closing_txids: vec![utils::txid_to_hex(base64::decode(txid)?)],
+        };
+
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListPeersResponse {
+    pub peers: Vec<PeerResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PeerResponse {
+    pub pub_key: String,
+    pub address: String,
+    pub inbound: bool,
+    pub features: HashMap<String, FeatureResponse>,
+}
+
+impl TryInto<Peer> for PeerResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Peer, Self::Error> {
+        // LND only lists the connected peers.
+        let peer = Peer {
+            pubkey: self.pub_key,
+            addresses: vec![self.address],
+            connected: true,
+            features: features(self.features)?,
+            inbound: Some(self.inbound),
+        };
+
+        Ok(peer)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct NewAddressRequest {
+    #[serde(rename = "type")]
+    pub address_type: String,
+}
+
+impl From<AddressType> for NewAddressRequest {
+    fn from(address_type: AddressType) -> Self {
+        let address_type = match address_type {
+            AddressType::P2wpkh => "WITNESS_PUBKEY_HASH",
+            AddressType::P2tr => "TAPROOT_PUBKEY",
+            AddressType::NestedP2wpkh => "NESTED_PUBKEY_HASH",
+        };
+
+        NewAddressRequest {
+            address_type: address_type.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewAddressResponse {
+    pub address: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SendCoinsRequest {
+    pub addr: String,
+    pub amount: Option<String>,
+    pub target_conf: Option<i32>,
+    pub sat_per_vbyte: Option<String>,
+    pub send_all: bool,
+}
+
+impl TryFrom<SendOnchainParams> for SendCoinsRequest {
+    type Error = Error;
+
+    fn try_from(params: SendOnchainParams) -> Result<Self, Self::Error> {
+        // LND v0.15 picks the outputs to spend on its own.
+        if params.utxos.is_some() {
+            return Err(Error::NotImplemented);
+        }
+
+        let amount = utils::get_onchain_amount(&params)?;
+        let request = SendCoinsRequest {
+            addr: params.address,
+            amount: amount.map(|v| v.to_string()),
+            target_conf: params.target_conf.map(i32::try_from).transpose()?,
+            sat_per_vbyte: params.fee_rate.map(|v| v.to_string()),
+            send_all: amount.is_none(),
+        };
+
+        Ok(request)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SendCoinsResponse {
+    pub txid: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransactionDetails {
+    pub transactions: Vec<TransactionResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransactionResponse {
+    pub tx_hash: String,
+    pub amount: String,
+    pub num_confirmations: i32,
+    pub block_height: i32,
+    pub time_stamp: String,
+    pub total_fees: String,
+    pub label: String,
+}
+
+impl TryInto<OnchainTransaction> for TransactionResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<OnchainTransaction, Self::Error> {
+        // The amount is the net change of the wallet, with the fee for sends.
+        let amount: i64 = self.amount.parse()?;
+        let block_height = match self.block_height {
+            0 => None,
+            block_height => Some(u32::try_from(block_height)?),
+        };
+        let label = match self.label.is_empty() {
+            true => None,
+            false => Some(self.label),
+        };
+
+        let transaction = OnchainTransaction {
+            txid: self.tx_hash,
+            amount_received_msat: utils::sat_to_msat(u64::try_from(amount.max(0))?),
+            amount_sent_msat: utils::sat_to_msat(amount.min(0).unsigned_abs()),
+            fee_msat: Some(utils::sat_to_msat(self.total_fees.parse()?)),
+            confirmations: u32::try_from(self.num_confirmations)?,
+            block_height,
+            timestamp: Some(self.time_stamp.parse()?),
+            short_channel_id: label
+                .as_deref()
+                .and_then(utils::short_channel_id_from_lnd_label),
+            label,
+        };
+
+        Ok(transaction)
+    }
+}
+
+fn payment_status(status: &str) -> PaymentStatus {
+    match status {
+        "SUCCEEDED" => PaymentStatus::Succeeded,
+        "FAILED" => PaymentStatus::Failed,
+        _ => PaymentStatus::Pending,
+    }
+}
+
+fn failure_message(reason: &str) -> &'static str {
+    match reason {
+        "FAILURE_REASON_TIMEOUT" => "payment timed out",
+        "FAILURE_REASON_NO_ROUTE" => "no route found",
+        "FAILURE_REASON_INCORRECT_PAYMENT_DETAILS" => "incorrect payment details",
+        "FAILURE_REASON_INSUFFICIENT_BALANCE" => "insufficient balance",
+        _ => "payment failed",
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HtlcAttemptResponse {
+    pub status: String,
+    pub route: Option<Route>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PaymentResponse {
+    pub payment_hash: String,
+    pub value_msat: String,
+    pub payment_preimage: String,
+    pub payment_request: String,
+    pub status: String,
+    pub fee_msat: String,
+    pub creation_time_ns: String,
+    pub htlcs: Vec<HtlcAttemptResponse>,
+    pub failure_reason: String,
+}
+
+impl TryInto<Payment> for PaymentResponse {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Payment, Self::Error> {
+        let status = payment_status(&self.status);
+        let failure_reason = match status {
+            PaymentStatus::Failed => Some(String::from(failure_message(&self.failure_reason))),
+            _ => None,
+        };
+        let payment_preimage = match status {
+            PaymentStatus::Succeeded => Some(self.payment_preimage),
+            _ => None,
+        };
+
+        let mut parts = Vec::new();
+        let mut destination = None;
+        for htlc in self.htlcs {
+            if let Some(route) = htlc.route {
+                let total_amt_msat: u64 = route.total_amt_msat.parse()?;
+                let total_fees_msat: u64 = route.total_fees_msat.parse()?;
+                destination = route.hops.last().and_then(|hop| hop.pub_key.clone());
+                parts.push(PaymentPart {
+                    amount_msat: total_amt_msat - total_fees_msat,
+                    fee_msat: total_fees_msat,
+                    status: payment_status(&htlc.status),
+                });
+            }
+        }
+        // Payments that never got a route only have the payee in the invoice.
+        if destination.is_none() && !self.payment_request.is_empty() {
+            destination = bolt11::decode_invoice(&self.payment_request)
+                .ok()
+                .map(|invoice| invoice.payee);
+        }
+
+        let creation_time_ns: i64 = self.creation_time_ns.parse()?;
+
+        let payment = Payment {
+            payment_hash: self.payment_hash,
+            payment_preimage,
+            amount_msat: self.value_msat.parse()?,
+            fee_msat: Some(self.fee_msat.parse()?),
+            status,
+            created_at: creation_time_ns / 1_000_000_000,
+            destination,
+            bolt11: match self.payment_request.is_empty() {
+                true => None,
+                false => Some(self.payment_request),
+            },
+            failure_reason,
+            parts,
+        };
+
+        Ok(payment)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListPaymentsRequest {
+    pub include_incomplete: bool,
+    pub index_offset: Option<u64>,
+    pub max_payments: u64,
+}
+
+impl From<&ListPaymentsParams> for ListPaymentsRequest {
+    fn from(params: &ListPaymentsParams) -> Self {
+        ListPaymentsRequest {
+            include_incomplete: true,
+            index_offset: params.cursor,
+            max_payments: utils::page_size(params.max_payments, utils::DEFAULT_MAX_PAYMENTS),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListPaymentsResponse {
+    pub payments: Vec<PaymentResponse>,
+    pub last_index_offset: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NodeInfoResponse {
+    pub node: LightningNode,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LightningNode {
+    #[serde(default)]
+    pub addresses: Vec<NodeAddress>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NodeAddress {
+    pub addr: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChannelEdgeResponse {
+    pub node1_pub: String,
+    pub node2_pub: String,
+    pub node1_policy: Option<RoutingPolicy>,
+    pub node2_policy: Option<RoutingPolicy>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RoutingPolicy {
+    pub time_lock_delta: u32,
+    pub min_htlc: String,
+    pub fee_base_msat: String,
+    pub fee_rate_milli_msat: String,
+    pub max_htlc_msat: String,
+}
+
+// The edge holds the policies of both ends of the channel, the one of the node
+// is the one not set by the peer.
+impl TryInto<ChannelPolicy> for (&Channel, ChannelEdgeResponse) {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ChannelPolicy, Self::Error> {
+        let (channel, edge) = self;
+
+        let policy = match edge.node1_pub == channel.peer_pubkey {
+            true => edge.node2_policy,
+            false => edge.node1_policy,
+        };
+        let policy = policy
+            .ok_or_else(|| Error::ApiError(String::from("channel policy not announced yet")))?;
+
+        let max_htlc_msat = policy.max_htlc_msat.parse::<u64>()?;
+        let channel_policy = ChannelPolicy {
+            channel_id: channel.channel_id.clone(),
+            base_fee_msat: policy.fee_base_msat.parse()?,
+            fee_rate_ppm: policy.fee_rate_milli_msat.parse()?,
+            time_lock_delta: Some(policy.time_lock_delta),
+            min_htlc_msat: policy.min_htlc.parse()?,
+            max_htlc_msat: Some(max_htlc_msat).filter(|max_htlc_msat| *max_htlc_msat > 0),
+        };
+
+        Ok(channel_policy)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PolicyChannelPoint {
+    pub funding_txid_str: String,
+    pub output_index: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PolicyUpdateRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub global: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chan_point: Option<PolicyChannelPoint>,
+    pub base_fee_msat: String,
+    pub fee_rate_ppm: u32,
+    pub time_lock_delta: u32,
+    pub max_htlc_msat: String,
+    pub min_htlc_msat: String,
+    pub min_htlc_msat_specified: bool,
+}
+
+// LND sets the base fee, fee rate and time lock delta all at once, so they are
+// required when updating every channel. Otherwise the missing ones are filled
+// from the current policy of the channel.
+impl TryFrom<(&UpdatePolicyParams, Option<&str>)> for PolicyUpdateRequest {
+    type Error = Error;
+
+    fn try_from(
+        (params, channel_point): (&UpdatePolicyParams, Option<&str>),
+    ) -> Result<Self, Self::Error> {
+        let chan_point = match channel_point {
+            Some(channel_point) => {
+                let (funding_txid, output_index) =
+                    channel_point.split_once(':').ok_or_else(|| {
+                        Error::ConversionError(format!("invalid channel point {}", channel_point))
+                    })?;
+                Some(PolicyChannelPoint {
+                    funding_txid_str: funding_txid.to_string(),
+                    output_index: output_index.parse()?,
+                })
+            }
+            None => None,
+        };
+
+        let (base_fee_msat, fee_rate_ppm, time_lock_delta) =
+            match (params.base_fee_msat, params.fee_rate_ppm, params.time_lock_delta) {
+                (Some(base_fee_msat), Some(fee_rate_ppm), Some(time_lock_delta)) => {
+                    (base_fee_msat, fee_rate_ppm, time_lock_delta)
+                }
+                _ => {
+                    return Err(Error::ApiError(String::from(
+                        "base_fee_msat, fee_rate_ppm and time_lock_delta are required to update every channel",
+                    )))
+                }
+            };
+
+        let request = PolicyUpdateRequest {
+            global: Some(true).filter(|_| chan_point.is_none()),
+            chan_point,
+            base_fee_msat: base_fee_msat.to_string(),
+            fee_rate_ppm: fee_rate_ppm.try_into()?,
+            time_lock_delta,
+            // Zero keeps the current maximum.
+            max_htlc_msat: params.max_htlc_msat.unwrap_or(0).to_string(),
+            min_htlc_msat: params.min_htlc_msat.unwrap_or(0).to_string(),
+            min_htlc_msat_specified: params.min_htlc_msat.is_some(),
+        };
+
+        Ok(request)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PolicyUpdateResponse {
+    #[serde(default)]
+    pub failed_updates: Vec<FailedUpdate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FailedUpdate {
+    pub update_error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ForwardingHistoryRequest {
+    pub start_time: u64,
+    pub end_time: u64,
+    pub index_offset: u32,
+    pub num_max_events: u32,
+}
+
+impl TryFrom<&ListForwardsParams> for ForwardingHistoryRequest {
+    type Error = Error;
+
+    fn try_from(params: &ListForwardsParams) -> Result<Self, Self::Error> {
+        let request = ForwardingHistoryRequest {
+            start_time: params.date_start.unwrap_or(0).try_into()?,
+            end_time: params.date_end.unwrap_or(0).try_into()?,
+            index_offset: params.cursor.unwrap_or(0).try_into()?,
+            num_max_events: utils::page_size(params.max_forwards, utils::DEFAULT_MAX_FORWARDS)
+                .try_into()?,
+        };
+
+        Ok(request)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForwardingHistoryResponse {
+    pub forwarding_events: Vec<ForwardingEvent>,
+    pub last_offset_index: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForwardingEvent {
+    pub chan_id_in: String,
+    pub chan_id_out: String,
+    pub amt_in_msat: String,
+    pub amt_out_msat: String,
+    pub fee_msat: String,
+    pub timestamp_ns: String,
+}
+
+// LND only records the forwards once settled, identified by the short channel
+// ids of their channels.
+impl TryInto<Forward> for ForwardingEvent {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Forward, Self::Error> {
+        let timestamp_ns: i64 = self.timestamp_ns.parse()?;
+
+        let forward = Forward {
+            incoming_channel_id: None,
+            incoming_short_channel_id: Some(utils::format_short_channel_id(
+                self.chan_id_in.parse()?,
+            )),
+            outgoing_channel_id: None,
+            outgoing_short_channel_id: Some(utils::format_short_channel_id(
+                self.chan_id_out.parse()?,
+            )),
+            amount_in_msat: self.amt_in_msat.parse()?,
+            amount_out_msat: Some(self.amt_out_msat.parse()?),
+            fee_msat: self.fee_msat.parse()?,
+            status: ForwardStatus::Settled,
+            received_at: None,
+            resolved_at: Some(timestamp_ns / 1_000_000_000),
+        };
+
+        Ok(forward)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignMessageRequest {
+    pub msg: Base64String,
+}
+
+impl From<&str> for SignMessageRequest {
+    fn from(message: &str) -> Self {
+        SignMessageRequest {
+            msg: base64::encode(message),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignMessageResponse {
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyMessageRequest {
+    pub msg: Base64String,
+    pub signature: String,
+}
+
+impl From<&VerifyMessageParams> for VerifyMessageRequest {
+    fn from(params: &VerifyMessageParams) -> Self {
+        VerifyMessageRequest {
+            msg: base64::encode(&params.message),
+            signature: params.signature.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyMessageResponse {
+    pub valid: bool,
+    pub pubkey: String,
+}
+
+// LND only deems valid the signatures of the nodes in its graph, which doesn't
+// matter when the signer is known.
+impl Into<VerifyMessageResult> for (&VerifyMessageParams, VerifyMessageResponse) {
+    fn into(self) -> VerifyMessageResult {
+        let (params, response) = self;
+
+        let valid = match &params.pubkey {
+            Some(pubkey) => pubkey.eq_ignore_ascii_case(&response.pubkey),
+            None => response.valid,
+        };
+
+        VerifyMessageResult {
+            valid,
+            pubkey: response.pubkey,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AddHoldInvoiceRequest {
+    pub hash: Base64String,
+    pub memo: Option<String>,
+    pub value_msat: u64,
+    pub description_hash: Option<Base64String>,
+    pub expiry: u32,
+    pub fallback_addr: Option<String>,
+    pub cltv_expiry: Option<u32>,
+}
+
+impl TryFrom<&CreateHoldInvoiceParams> for AddHoldInvoiceRequest {
+    type Error = Error;
+
+    fn try_from(params: &CreateHoldInvoiceParams) -> Result<Self, Self::Error> {
+        let description_hash = match &params.description_hash {
+            Some(description_hash) => Some(base64::encode(hex::decode(description_hash)?)),
+            None => None,
+        };
+
+        let request = AddHoldInvoiceRequest {
+            hash: base64::encode(hex::decode(&params.payment_hash)?),
+            memo: params.description.clone(),
+            value_msat: utils::get_amount_msat(params.amount, params.amount_msat).unwrap_or(0),
+            description_hash,
+            expiry: params.expire_in.unwrap_or(3600),
+            fallback_addr: params.fallback_address.clone(),
+            cltv_expiry: params.cltv_expiry,
+        };
+
+        Ok(request)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddHoldInvoiceResponse {
+    pub payment_request: String,
+    pub add_index: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SettleInvoiceRequest {
+    pub preimage: Base64String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancelInvoiceRequest {
+    pub payment_hash: Base64String,
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use crate::types::{Balance, Channel, Network};
+
+#[derive(Clone, Debug)]
+pub struct MockConfig {
+    /// Seed of the node key and of the invoice preimages, so that the same
+    /// config always produces the same invoices.
+    pub seed: u64,
+    pub network: Network,
+    /// Fixed UNIX time used as the invoices creation date, the system time
+    /// otherwise.
+    pub time: Option<u64>,
+    pub payment_fee_msat: u64,
+    pub payment_latency: Duration,
+    /// Makes every payment fail with this error message.
+    pub payment_failure: Option<String>,
+    /// Funds of the node, as reported by `get_balance`.
+    pub balance: Balance,
+    /// Channels of the node, as listed by `list_channels`.
+    pub channels: Vec<Channel>,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        MockConfig {
+            seed: 0,
+            network: Network::Regtest,
+            time: None,
+            payment_fee_msat: 0,
+            payment_latency: Duration::ZERO,
+            payment_failure: None,
+            balance: Balance::default(),
+            channels: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,1133 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bech32::ToBase32;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+use sha2::{Digest, Sha256};
+use tokio::sync::broadcast;
+
+use crate::bolt11::{self, InvoiceFields};
+use crate::error::Error;
+use crate::node::{InvoiceStream, NodeMethods, PaymentStream};
+use crate::types::{
+    AddressType, Backend, Balance, Channel, ChannelPolicy, ChannelStats, ChannelStatus,
+    CloseChannelParams, CloseChannelResult, ConnectPeerParams, CreateHoldInvoiceParams,
+    CreateInvoiceParams, CreateInvoiceResult, DecodedInvoice, Forward, Invoice, InvoiceCursor,
+    InvoiceEvent, InvoiceEventType, InvoiceStatus, KeysendParams, ListForwardsParams,
+    ListForwardsResult, ListInvoicesParams, ListInvoicesResult, ListPaymentsParams,
+    ListPaymentsResult, Network, NodeInfo, OnchainTransaction, OpenChannelParams,
+    OpenChannelResult, PayInvoiceParams, PayInvoiceResult, Payment, PaymentPart, PaymentStatus,
+    Peer, SendOnchainParams, SubscribeInvoicesParams, UpdatePolicyParams, VerifyMessageParams,
+    VerifyMessageResult,
+};
+use crate::utils;
+
+use super::config::MockConfig;
+
+const DEFAULT_MIN_FINAL_CLTV_EXPIRY: u64 = 18;
+const EVENTS_CAPACITY: usize = 1024;
+/// Interval at which a pending payment is looked up again while tracking it.
+const PAYMENT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+/// Prefix of the signed messages, so that they can't be mistaken for
+/// anything else the node signs.
+const SIGNED_MESSAGE_PREFIX: &str = "Lightning Signed Message:";
+
+// Messages are signed like LND and Core Lightning do, over the double SHA-256
+// of the prefixed message, with the recovery id in the header byte.
+fn message_digest(message: &str) -> Result<Message, Error> {
+    let digest = Sha256::digest(Sha256::digest(
+        format!("{}{}", SIGNED_MESSAGE_PREFIX, message).as_bytes(),
+    ));
+
+    Ok(Message::from_slice(&digest)?)
+}
+
+struct MockInvoice {
+    invoice: Invoice,
+    /// Unknown for hold invoices until they're settled.
+    preimage: Option<[u8; 32]>,
+    add_index: u64,
+    settle_index: Option<u64>,
+}
+
+#[derive(Default)]
+struct MockState {
+    invoices: Vec<MockInvoice>,
+    // Kept apart from the invoices, which can be deleted.
+    add_index: u64,
+    settle_index: u64,
+    payments: Vec<Payment>,
+    payment_failure: Option<String>,
+    balance: Balance,
+    channels: Vec<Channel>,
+    peers: Vec<Peer>,
+    address_index: u64,
+    transactions: Vec<OnchainTransaction>,
+    policies: Vec<ChannelPolicy>,
+    forwards: Vec<Forward>,
+}
+
+/// In-memory node, to test code using `Node` without a Lightning node.
+///
+/// Clones share the same state, so a test can keep a clone to settle invoices
+/// or make payments fail while the code under test uses the node, made with
+/// `Node::from`.
+#[derive(Clone)]
+pub struct Mock {
+    config: MockConfig,
+    secret_key: SecretKey,
+    public_key: PublicKey,
+    state: Arc<Mutex<MockState>>,
+    events: broadcast::Sender<InvoiceEvent>,
+}
+
+impl Mock {
+    pub fn new(config: MockConfig) -> Result<Self, Error> {
+        let mut seed = b"una-mock-node".to_vec();
+        seed.extend(config.seed.to_be_bytes());
+        let secret_key = SecretKey::from_slice(&Sha256::digest(&seed))?;
+        let public_key = secret_key.public_key(&Secp256k1::signing_only());
+
+        let state = MockState {
+            payment_failure: config.payment_failure.clone(),
+            balance: config.balance.clone(),
+            channels: config.channels.clone(),
+            ..Default::default()
+        };
+        let (events, _) = broadcast::channel(EVENTS_CAPACITY);
+
+        Ok(Mock {
+            config,
+            secret_key,
+            public_key,
+            state: Arc::new(Mutex::new(state)),
+            events,
+        })
+    }
+
+    /// Settles an invoice as if it had been paid, returning it updated.
+    pub fn settle_invoice(&self, payment_hash: &str) -> Result<Invoice, Error> {
+        let mut state = self.lock();
+        self.settle(&mut state, payment_hash)
+    }
+
+    /// Makes the next payments fail with `failure`, or succeed if `None`.
+    pub fn set_payment_failure(&self, failure: Option<String>) {
+        self.lock().payment_failure = failure;
+    }
+
+    /// Replaces the funds reported by `get_balance`.
+    pub fn set_balance(&self, balance: Balance) {
+        self.lock().balance = balance;
+    }
+
+    /// Records a payment as forwarded by the node, to be listed by
+    /// `list_forwards`.
+    pub fn add_forward(&self, forward: Forward) {
+        self.lock().forwards.push(forward);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().expect("Mock state lock is poisoned")
+    }
+
+    fn now(&self) -> u64 {
+        self.config.time.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0)
+        })
+    }
+
+    fn derive(&self, label: &[u8], index: u64) -> [u8; 32] {
+        let mut data = self.secret_key.secret_bytes().to_vec();
+        data.extend(label);
+        data.extend(index.to_be_bytes());
+
+        Sha256::digest(&data).into()
+    }
+
+    /// Records a pending payment of `invoice`, returning its index.
+    fn start_payment(&self, invoice: &PayInvoiceParams) -> Result<usize, Error> {
+        let decoded = bolt11::decode_invoice(&invoice.payment_request)?;
+        let amount_msat = utils::get_amount_msat(invoice.amount, invoice.amount_msat)
+            .or(decoded.amount_msat)
+            .ok_or_else(|| Error::ApiError(String::from("amount is required")))?;
+
+        let max_fee_msat = utils::get_max_fee_msat(
+            amount_msat,
+            invoice.max_fee_sat,
+            invoice.max_fee_msat,
+            invoice.max_fee_percent,
+        );
+        let fees_msat = match decoded.payee == self.public_key.to_string() {
+            true => 0,
+            false => self.config.payment_fee_msat,
+        };
+        if max_fee_msat.is_some_and(|max_fee_msat| fees_msat > max_fee_msat) {
+            return Err(Error::ApiError(String::from(
+                "fee exceeds the maximum fee of the payment",
+            )));
+        }
+
+        let mut state = self.lock();
+        let already_paid = state.payments.iter().any(|payment| {
+            payment.payment_hash == decoded.payment_hash && payment.status != PaymentStatus::Failed
+        });
+        if already_paid {
+            return Err(Error::ApiError(String::from("invoice is already paid")));
+        }
+
+        state.payments.push(Payment {
+            payment_hash: decoded.payment_hash,
+            payment_preimage: None,
+            amount_msat,
+            fee_msat: Some(fees_msat),
+            status: PaymentStatus::Pending,
+            created_at: self.now() as i64,
+            destination: Some(decoded.payee),
+            bolt11: Some(invoice.payment_request.clone()),
+            failure_reason: None,
+            parts: Vec::new(),
+        });
+        Ok(state.payments.len() - 1)
+    }
+
+    /// Makes the pending payment at `index` succeed or fail, returning it.
+    fn complete_payment(&self, index: usize) -> Payment {
+        let mut state = self.lock();
+        let payment_failure = state.payment_failure.clone();
+        let own = state.payments[index].destination == Some(self.public_key.to_string());
+
+        // Own hold invoices only get accepted, the payment being held until
+        // the invoice is settled or cancelled.
+        let payment_hash = state.payments[index].payment_hash.clone();
+        let held = own
+            && payment_failure.is_none()
+            && state.invoices.iter().any(|invoice| {
+                invoice.invoice.payment_hash == payment_hash && invoice.preimage.is_none()
+            });
+        if held {
+            if let Err(err) =
+                self.set_invoice_status(&mut state, &payment_hash, InvoiceStatus::Accepted)
+            {
+                Self::resolve_payment(&mut state.payments[index], Err(err.to_string()));
+            }
+            return state.payments[index].clone();
+        }
+
+        // Own invoices get settled, other ones can't be, so their preimage is
+        // made up.
+        let payment_preimage = match (payment_failure, own) {
+            (Some(failure), _) => Err(failure),
+            (None, true) => self
+                .settle(&mut state, &payment_hash)
+                .map(|invoice| invoice.pre_image.unwrap_or_default())
+                .map_err(|err| err.to_string()),
+            (None, false) => Ok(hex::encode(
+                self.derive(payment_hash.as_bytes(), index as u64),
+            )),
+        };
+
+        let payment = &mut state.payments[index];
+        Self::resolve_payment(payment, payment_preimage);
+        payment.clone()
+    }
+
+    // Invoices are added with their payment hash, their preimage being
+    // unknown for hold invoices.
+    fn add_invoice(
+        &self,
+        invoice: CreateInvoiceParams,
+        preimage: Option<[u8; 32]>,
+        payment_hash: [u8; 32],
+    ) -> Result<CreateInvoiceResult, Error> {
+        let mut state = self.lock();
+        let add_index = state.add_index + 1;
+
+        if state
+            .invoices
+            .iter()
+            .any(|existing| existing.invoice.payment_hash == hex::encode(payment_hash))
+        {
+            return Err(Error::ApiError(String::from(
+                "invoice with payment hash already exists",
+            )));
+        }
+
+        let description_hash = match invoice.description_hash {
+            Some(description_hash) => {
+                Some(hex::decode(description_hash)?.try_into().map_err(|_| {
+                    Error::ApiError(String::from("description hash must be 32 bytes"))
+                })?)
+            }
+            None => None,
+        };
+        let amount_msat = utils::get_amount_msat(invoice.amount, invoice.amount_msat);
+        let creation_date = self.now();
+        let expiry = invoice.expire_in.unwrap_or(3600);
+
+        let fields = InvoiceFields {
+            network: self.config.network.clone(),
+            amount_msat,
+            creation_date,
+            payment_hash,
+            payment_secret: self.derive(b"payment_secret", add_index),
+            description: invoice.description.clone(),
+            description_hash,
+            expiry,
+            min_final_cltv_expiry: invoice
+                .cltv_expiry
+                .map(u64::from)
+                .unwrap_or(DEFAULT_MIN_FINAL_CLTV_EXPIRY),
+        };
+        let bolt11 = bolt11::encode_invoice(&fields, &self.secret_key)?;
+
+        let created = Invoice {
+            bolt11: bolt11.clone(),
+            memo: invoice.description.unwrap_or_default(),
+            amount: amount_msat.map(utils::msat_to_sat).unwrap_or(0),
+            amount_msat: amount_msat.unwrap_or(0),
+            pre_image: None,
+            payment_hash: hex::encode(payment_hash),
+            settled: false,
+            settle_date: None,
+            creation_date: creation_date as i64,
+            expiry: expiry.try_into()?,
+            status: InvoiceStatus::Pending,
+        };
+        state.invoices.push(MockInvoice {
+            invoice: created.clone(),
+            preimage,
+            add_index,
+            settle_index: None,
+        });
+        state.add_index = add_index;
+
+        let event = InvoiceEvent {
+            event_type: InvoiceEventType::Created,
+            invoice: created,
+            cursor: Self::cursor(&state),
+        };
+        let _ = self.events.send(event);
+
+        Ok(CreateInvoiceResult {
+            payment_request: bolt11,
+            payment_hash: hex::encode(payment_hash),
+            label: invoice.label,
+        })
+    }
+
+    fn settle(&self, state: &mut MockState, payment_hash: &str) -> Result<Invoice, Error> {
+        let now = self.now();
+        let settle_index = state.settle_index + 1;
+
+        let invoice = state
+            .invoices
+            .iter_mut()
+            .find(|invoice| invoice.invoice.payment_hash == payment_hash)
+            .ok_or_else(|| Error::ApiError(String::from("invoice not found")))?;
+        if invoice.invoice.settled {
+            return Err(Error::ApiError(String::from("invoice is already paid")));
+        }
+        if invoice.invoice.status == InvoiceStatus::Cancelled {
+            return Err(Error::ApiError(String::from("invoice is cancelled")));
+        }
+        let preimage = invoice
+            .preimage
+            .ok_or_else(|| Error::ApiError(String::from("hold invoice has no preimage yet")))?;
+
+        invoice.invoice.settled = true;
+        invoice.invoice.settle_date = Some(now as i64);
+        invoice.invoice.status = InvoiceStatus::Settled;
+        invoice.invoice.pre_image = Some(hex::encode(preimage));
+        invoice.settle_index = Some(settle_index);
+        state.settle_index = settle_index;
+
+        let invoice = invoice.invoice.clone();
+        let event = InvoiceEvent {
+            event_type: InvoiceEventType::Settled,
+            invoice: invoice.clone(),
+            cursor: Self::cursor(state),
+        };
+        // Nobody listening is fine, events are only kept for subscribers.
+        let _ = self.events.send(event);
+
+        Ok(invoice)
+    }
+
+    // Hold invoices are only accepted or cancelled, without any index to
+    // resume from.
+    fn set_invoice_status(
+        &self,
+        state: &mut MockState,
+        payment_hash: &str,
+        status: InvoiceStatus,
+    ) -> Result<(), Error> {
+        let invoice = state
+            .invoices
+            .iter_mut()
+            .find(|invoice| invoice.invoice.payment_hash == payment_hash)
+            .ok_or_else(|| Error::ApiError(String::from("invoice not found")))?;
+        if invoice.invoice.settled {
+            return Err(Error::ApiError(String::from("invoice is already paid")));
+        }
+        if invoice.invoice.status == InvoiceStatus::Cancelled {
+            return Err(Error::ApiError(String::from("invoice is cancelled")));
+        }
+
+        let event_type = match status {
+            InvoiceStatus::Accepted => InvoiceEventType::Accepted,
+            _ => InvoiceEventType::Cancelled,
+        };
+        invoice.invoice.status = status;
+
+        let event = InvoiceEvent {
+            event_type,
+            invoice: invoice.invoice.clone(),
+            cursor: Self::cursor(state),
+        };
+        let _ = self.events.send(event);
+
+        Ok(())
+    }
+
+    // Payments held by a hold invoice complete once it is settled or
+    // cancelled.
+    fn resolve_held_payments(
+        state: &mut MockState,
+        payment_hash: &str,
+        outcome: Result<String, String>,
+    ) {
+        let held = state.payments.iter_mut().filter(|payment| {
+            payment.payment_hash == payment_hash && payment.status == PaymentStatus::Pending
+        });
+        for payment in held {
+            Self::resolve_payment(payment, outcome.clone());
+        }
+    }
+
+    fn resolve_payment(payment: &mut Payment, outcome: Result<String, String>) {
+        match outcome {
+            Ok(payment_preimage) => {
+                payment.payment_preimage = Some(payment_preimage);
+                payment.status = PaymentStatus::Succeeded;
+                payment.parts.push(PaymentPart {
+                    amount_msat: payment.amount_msat,
+                    fee_msat: payment.fee_msat.unwrap_or_default(),
+                    status: PaymentStatus::Succeeded,
+                });
+            }
+            Err(failure) => {
+                payment.fee_msat = None;
+                payment.status = PaymentStatus::Failed;
+                payment.failure_reason = Some(failure);
+            }
+        }
+    }
+
+    // Channels without an updated policy get LND's default one.
+    fn policy(state: &MockState, channel: &Channel) -> ChannelPolicy {
+        state
+            .policies
+            .iter()
+            .find(|policy| policy.channel_id == channel.channel_id)
+            .cloned()
+            .unwrap_or_else(|| ChannelPolicy {
+                channel_id: channel.channel_id.clone(),
+                base_fee_msat: 1000,
+                fee_rate_ppm: 1,
+                time_lock_delta: Some(40),
+                min_htlc_msat: 1000,
+                max_htlc_msat: Some(channel.capacity_msat),
+            })
+    }
+
+    fn cursor(state: &MockState) -> InvoiceCursor {
+        InvoiceCursor {
+            add_index: Some(state.add_index).filter(|index| *index > 0),
+            settle_index: Some(state.settle_index).filter(|index| *index > 0),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl NodeMethods for Mock {
+    async fn cancel_hold_invoice(&self, payment_hash: String) -> Result<(), Error> {
+        self.cancel_invoice(payment_hash).await
+    }
+
+    async fn cancel_invoice(&self, payment_hash: String) -> Result<(), Error> {
+        let mut state = self.lock();
+        self.set_invoice_status(&mut state, &payment_hash, InvoiceStatus::Cancelled)?;
+        Self::resolve_held_payments(
+            &mut state,
+            &payment_hash,
+            Err(String::from("invoice was cancelled")),
+        );
+
+        Ok(())
+    }
+
+    async fn close_channel(&self, params: CloseChannelParams) -> Result<CloseChannelResult, Error> {
+        let channel_id = utils::get_channel_id(&params)?;
+
+        let mut state = self.lock();
+        let index = state
+            .channels
+            .iter()
+            .position(|channel| channel.channel_id == channel_id)
+            .ok_or_else(|| Error::ApiError(String::from("channel not found")))?;
+
+        let channel = state.channels[index].clone();
+        let balance = &mut state.balance;
+        match channel.status {
+            ChannelStatus::Opening => {
+                balance.pending_open_msat = balance
+                    .pending_open_msat
+                    .saturating_sub(channel.local_balance_msat);
+            }
+            ChannelStatus::Open => {
+                let (local_msat, remote_msat) =
+                    utils::spendable_balance_msat(std::slice::from_ref(&channel));
+                balance.lightning_local_msat =
+                    balance.lightning_local_msat.saturating_sub(local_msat);
+                balance.lightning_remote_msat =
+                    balance.lightning_remote_msat.saturating_sub(remote_msat);
+            }
+            ChannelStatus::Closing | ChannelStatus::Closed => {
+                return Err(Error::ApiError(String::from("channel is already closing")));
+            }
+        }
+        balance.pending_close_msat += channel.local_balance_msat;
+
+        let channel = &mut state.channels[index];
+        channel.status = ChannelStatus::Closing;
+        channel.active = false;
+
+        Ok(CloseChannelResult {
+            closing_txids: vec![hex::encode(self.derive(b"closing", index as u64))],
+        })
+    }
+
+    async fn connect_peer(&self, params: ConnectPeerParams) -> Result<(), Error> {
+        let mut state = self.lock();
+
+        let peer = match state
+            .peers
+            .iter_mut()
+            .find(|peer| peer.pubkey == params.pubkey)
+        {
+            Some(peer) => peer,
+            None => {
+                state.peers.push(Peer {
+                    pubkey: params.pubkey,
+                    addresses: Vec::new(),
+                    connected: true,
+                    features: Vec::new(),
+                    inbound: Some(false),
+                });
+                state.peers.last_mut().expect("Peer was just added")
+            }
+        };
+        if let Some(host) = params.host {
+            if !peer.addresses.contains(&host) {
+                peer.addresses.push(host);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn create_hold_invoice(
+        &self,
+        invoice: CreateHoldInvoiceParams,
+    ) -> Result<CreateInvoiceResult, Error> {
+        let payment_hash: [u8; 32] = hex::decode(&invoice.payment_hash)?
+            .try_into()
+            .map_err(|_| Error::ApiError(String::from("payment hash must be 32 bytes")))?;
+
+        let params = CreateInvoiceParams {
+            amount: invoice.amount,
+            amount_msat: invoice.amount_msat,
+            description: invoice.description,
+            description_hash: invoice.description_hash,
+            label: None,
+            expire_in: invoice.expire_in,
+            fallback_address: invoice.fallback_address,
+            payment_preimage: None,
+            cltv_expiry: invoice.cltv_expiry,
+        };
+        self.add_invoice(params, None, payment_hash)
+    }
+
+    async fn create_invoice(
+        &self,
+        invoice: CreateInvoiceParams,
+    ) -> Result<CreateInvoiceResult, Error> {
+        let preimage: [u8; 32] = match &invoice.payment_preimage {
+            Some(preimage) => hex::decode(preimage)?
+                .try_into()
+                .map_err(|_| Error::ApiError(String::from("payment preimage must be 32 bytes")))?,
+            None => self.derive(b"preimage", self.lock().add_index + 1),
+        };
+        let payment_hash: [u8; 32] = Sha256::digest(preimage).into();
+
+        self.add_invoice(invoice, Some(preimage), payment_hash)
+    }
+
+    async fn decode_invoice(&self, bolt11: String) -> Result<DecodedInvoice, Error> {
+        bolt11::decode_invoice(&bolt11)
+    }
+
+    async fn delete_expired_invoices(&self, before: Option<u64>) -> Result<(), Error> {
+        let now = self.now() as i64;
+        let before = before.map_or(now, |before| now.min(before as i64));
+
+        self.lock().invoices.retain(|invoice| {
+            let expires_at = invoice.invoice.creation_date + i64::from(invoice.invoice.expiry);
+            invoice.invoice.settled || expires_at > before
+        });
+
+        Ok(())
+    }
+
+    async fn disconnect_peer(&self, pubkey: String) -> Result<(), Error> {
+        let mut state = self.lock();
+
+        let count = state.peers.len();
+        state.peers.retain(|peer| peer.pubkey != pubkey);
+        if state.peers.len() == count {
+            return Err(Error::ApiError(String::from("peer not connected")));
+        }
+
+        Ok(())
+    }
+
+    async fn get_balance(&self) -> Result<Balance, Error> {
+        Ok(self.lock().balance.clone())
+    }
+
+    async fn get_channel_policy(&self, channel_id: String) -> Result<ChannelPolicy, Error> {
+        let state = self.lock();
+        let channel = state
+            .channels
+            .iter()
+            .find(|channel| channel.channel_id == channel_id)
+            .ok_or_else(|| Error::ApiError(String::from("channel not found")))?;
+
+        Ok(Self::policy(&state, channel))
+    }
+
+    async fn get_info(&self) -> Result<NodeInfo, Error> {
+        let state = self.lock();
+        let count = |filter: fn(&Channel) -> bool| {
+            state.channels.iter().filter(|c| filter(c)).count() as i64
+        };
+
+        Ok(NodeInfo {
+            backend: Backend::Mock,
+            version: String::from("mock"),
+            network: self.config.network.clone(),
+            node_pubkey: self.public_key.to_string(),
+            channels: ChannelStats {
+                active: count(|c| c.active),
+                inactive: count(|c| c.status == ChannelStatus::Open && !c.active),
+                pending: count(|c| c.status == ChannelStatus::Opening),
+            },
+        })
+    }
+
+    async fn get_invoice(&self, payment_hash: String) -> Result<Invoice, Error> {
+        self.lock()
+            .invoices
+            .iter()
+            .find(|invoice| invoice.invoice.payment_hash == payment_hash)
+            .map(|invoice| invoice.invoice.clone())
+            .ok_or_else(|| Error::ApiError(String::from("invoice not found")))
+    }
+
+    async fn get_payment(&self, payment_hash: String) -> Result<Payment, Error> {
+        // Failed attempts are kept, the last attempt is the one reported.
+        self.lock()
+            .payments
+            .iter()
+            .rev()
+            .find(|payment| payment.payment_hash == payment_hash)
+            .cloned()
+            .ok_or_else(|| Error::ApiError(String::from("payment not found")))
+    }
+
+    async fn keysend(&self, params: KeysendParams) -> Result<PayInvoiceResult, Error> {
+        tokio::time::sleep(self.config.payment_latency).await;
+
+        let amount_msat = utils::get_amount_msat(params.amount, params.amount_msat)
+            .ok_or_else(|| Error::ApiError(String::from("amount is required")))?;
+        let max_fee_msat = utils::get_max_fee_msat(
+            amount_msat,
+            params.max_fee_sat,
+            params.max_fee_msat,
+            params.max_fee_percent,
+        );
+        if max_fee_msat.is_some_and(|max_fee_msat| self.config.payment_fee_msat > max_fee_msat) {
+            return Err(Error::ApiError(String::from(
+                "fee exceeds the maximum fee of the payment",
+            )));
+        }
+
+        let mut state = self.lock();
+        let index = state.payments.len() as u64;
+        let payment_preimage = self.derive(b"keysend", index);
+        let payment_hash = hex::encode(Sha256::digest(payment_preimage));
+
+        let mut payment = Payment {
+            payment_hash: payment_hash.clone(),
+            payment_preimage: None,
+            amount_msat,
+            fee_msat: None,
+            status: PaymentStatus::Failed,
+            created_at: self.now() as i64,
+            destination: Some(params.destination),
+            bolt11: None,
+            failure_reason: state.payment_failure.clone(),
+            parts: Vec::new(),
+        };
+        if let Some(failure) = &state.payment_failure {
+            let failure = failure.clone();
+            state.payments.push(payment);
+            return Err(Error::ApiError(failure));
+        }
+
+        payment.payment_preimage = Some(hex::encode(payment_preimage));
+        payment.fee_msat = Some(self.config.payment_fee_msat);
+        payment.status = PaymentStatus::Succeeded;
+        payment.parts.push(PaymentPart {
+            amount_msat,
+            fee_msat: self.config.payment_fee_msat,
+            status: PaymentStatus::Succeeded,
+        });
+        state.payments.push(payment);
+
+        Ok(PayInvoiceResult {
+            payment_hash,
+            payment_preimage: hex::encode(payment_preimage),
+            fees_msat: Some(self.config.payment_fee_msat),
+        })
+    }
+
+    async fn list_channels(&self) -> Result<Vec<Channel>, Error> {
+        Ok(self.lock().channels.clone())
+    }
+
+    async fn list_forwards(&self, params: ListForwardsParams) -> Result<ListForwardsResult, Error> {
+        let forwards = self
+            .lock()
+            .forwards
+            .iter()
+            .filter(|forward| utils::forward_matches(forward, &params))
+            .cloned()
+            .collect();
+
+        let (forwards, next_cursor) = utils::paginate(
+            forwards,
+            params.cursor,
+            params.max_forwards,
+            utils::DEFAULT_MAX_FORWARDS,
+        );
+
+        Ok(ListForwardsResult {
+            forwards,
+            next_cursor,
+        })
+    }
+
+    async fn list_invoices(&self, params: ListInvoicesParams) -> Result<ListInvoicesResult, Error> {
+        let invoices = self
+            .lock()
+            .invoices
+            .iter()
+            .map(|invoice| invoice.invoice.clone())
+            .filter(|invoice| utils::invoice_matches(invoice, &params))
+            .collect();
+
+        let (invoices, next_cursor) = utils::paginate(
+            invoices,
+            params.cursor,
+            params.max_invoices,
+            utils::DEFAULT_MAX_INVOICES,
+        );
+
+        Ok(ListInvoicesResult {
+            invoices,
+            next_cursor,
+        })
+    }
+
+    async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, Error> {
+        Ok(self.lock().transactions.clone())
+    }
+
+    async fn list_payments(&self, params: ListPaymentsParams) -> Result<ListPaymentsResult, Error> {
+        let payments = self
+            .lock()
+            .payments
+            .iter()
+            .filter(|payment| utils::payment_matches(payment, &params))
+            .cloned()
+            .collect();
+
+        let (payments, next_cursor) = utils::paginate(
+            payments,
+            params.cursor,
+            params.max_payments,
+            utils::DEFAULT_MAX_PAYMENTS,
+        );
+
+        Ok(ListPaymentsResult {
+            payments,
+            next_cursor,
+        })
+    }
+
+    async fn list_peers(&self) -> Result<Vec<Peer>, Error> {
+        Ok(self.lock().peers.clone())
+    }
+
+    async fn new_address(&self, address_type: AddressType) -> Result<String, Error> {
+        let hrp = match &self.config.network {
+            Network::Mainnet => "bc",
+            Network::Testnet => "tb",
+            Network::Regtest => "bcrt",
+            Network::Unknown(network) if network == "signet" => "tb",
+            Network::Unknown(_) => return Err(Error::NotImplemented),
+        };
+
+        let mut state = self.lock();
+        state.address_index += 1;
+        let program = self.derive(b"address", state.address_index);
+
+        // Nested addresses are base58 encoded, which the mock doesn't do.
+        let (version, program, variant) = match address_type {
+            AddressType::P2wpkh => (0, &program[..20], bech32::Variant::Bech32),
+            AddressType::P2tr => (1, &program[..], bech32::Variant::Bech32m),
+            AddressType::NestedP2wpkh => return Err(Error::NotImplemented),
+        };
+        let mut data = vec![bech32::u5::try_from_u8(version)?];
+        data.extend(program.to_base32());
+
+        Ok(bech32::encode(hrp, data, variant)?)
+    }
+
+    async fn open_channel(&self, params: OpenChannelParams) -> Result<OpenChannelResult, Error> {
+        if let Some(host) = &params.peer_host {
+            let peer = ConnectPeerParams {
+                pubkey: params.peer_pubkey.clone(),
+                host: Some(host.clone()),
+            };
+            self.connect_peer(peer).await?;
+        }
+
+        let amount_msat = utils::sat_to_msat(params.amount);
+        let push_amount_msat = utils::sat_to_msat(params.push_amount.unwrap_or(0));
+        if push_amount_msat > amount_msat {
+            return Err(Error::ApiError(String::from(
+                "push amount exceeds the funding amount",
+            )));
+        }
+
+        let mut state = self.lock();
+        if state.balance.onchain_confirmed_msat < amount_msat {
+            return Err(Error::ApiError(String::from("insufficient funds")));
+        }
+
+        // The funding transaction is made up, with the channel as its only output.
+        let funding_txid = hex::encode(self.derive(b"funding", state.channels.len() as u64));
+        let channel_point = format!("{}:0", funding_txid);
+        let channel_id = utils::channel_id_from_channel_point(&channel_point)?;
+
+        state.balance.onchain_confirmed_msat -= amount_msat;
+        state.balance.pending_open_msat += amount_msat - push_amount_msat;
+        state.channels.push(Channel {
+            channel_id: channel_id.clone(),
+            channel_point: Some(channel_point),
+            short_channel_id: None,
+            peer_pubkey: params.peer_pubkey,
+            capacity_msat: amount_msat,
+            local_balance_msat: amount_msat - push_amount_msat,
+            remote_balance_msat: push_amount_msat,
+            local_reserve_msat: 0,
+            remote_reserve_msat: 0,
+            active: false,
+            private: params.private.unwrap_or(false),
+            initiator: true,
+            status: ChannelStatus::Opening,
+        });
+
+        Ok(OpenChannelResult {
+            channel_id,
+            funding_txid,
+            output_index: 0,
+        })
+    }
+
+    async fn pay_invoice(&self, invoice: PayInvoiceParams) -> Result<PayInvoiceResult, Error> {
+        let index = self.start_payment(&invoice)?;
+        tokio::time::sleep(self.config.payment_latency).await;
+
+        let mut payment = self.complete_payment(index);
+        while payment.status == PaymentStatus::Pending {
+            tokio::time::sleep(PAYMENT_POLL_INTERVAL).await;
+            payment = self.lock().payments[index].clone();
+        }
+        match payment.status {
+            PaymentStatus::Succeeded => Ok(PayInvoiceResult {
+                payment_hash: payment.payment_hash,
+                payment_preimage: payment.payment_preimage.unwrap_or_default(),
+                fees_msat: payment.fee_msat,
+            }),
+            _ => Err(Error::ApiError(payment.failure_reason.unwrap_or_default())),
+        }
+    }
+
+    async fn send_onchain(&self, params: SendOnchainParams) -> Result<String, Error> {
+        let amount = utils::get_onchain_amount(&params)?;
+
+        let mut state = self.lock();
+        // The mock sends without fees.
+        let amount_msat = match amount {
+            Some(amount) => utils::sat_to_msat(amount),
+            None => state.balance.onchain_confirmed_msat,
+        };
+        if state.balance.onchain_confirmed_msat < amount_msat {
+            return Err(Error::ApiError(String::from("insufficient funds")));
+        }
+
+        let txid = hex::encode(self.derive(b"transaction", state.transactions.len() as u64));
+        state.balance.onchain_confirmed_msat -= amount_msat;
+        state.transactions.push(OnchainTransaction {
+            txid: txid.clone(),
+            amount_received_msat: 0,
+            amount_sent_msat: amount_msat,
+            fee_msat: Some(0),
+            confirmations: 0,
+            block_height: None,
+            timestamp: Some(self.now()),
+            label: None,
+            short_channel_id: None,
+        });
+
+        Ok(txid)
+    }
+
+    async fn send_payment_async(&self, invoice: PayInvoiceParams) -> Result<String, Error> {
+        let index = self.start_payment(&invoice)?;
+        let payment_hash = self.lock().payments[index].payment_hash.clone();
+
+        let node = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(node.config.payment_latency).await;
+            node.complete_payment(index);
+        });
+
+        Ok(payment_hash)
+    }
+
+    async fn settle_hold_invoice(&self, preimage: String) -> Result<(), Error> {
+        let preimage: [u8; 32] = hex::decode(preimage)?
+            .try_into()
+            .map_err(|_| Error::ApiError(String::from("preimage must be 32 bytes")))?;
+        let payment_hash = hex::encode(Sha256::digest(preimage));
+
+        let mut state = self.lock();
+        let invoice = state
+            .invoices
+            .iter_mut()
+            .find(|invoice| invoice.invoice.payment_hash == payment_hash)
+            .ok_or_else(|| Error::ApiError(String::from("invoice not found")))?;
+        if invoice.invoice.status != InvoiceStatus::Accepted {
+            return Err(Error::ApiError(String::from("invoice is not accepted")));
+        }
+        invoice.preimage = Some(preimage);
+
+        self.settle(&mut state, &payment_hash)?;
+        Self::resolve_held_payments(&mut state, &payment_hash, Ok(hex::encode(preimage)));
+
+        Ok(())
+    }
+
+    async fn sign_message(&self, message: String) -> Result<String, Error> {
+        let signature = Secp256k1::signing_only()
+            .sign_ecdsa_recoverable(&message_digest(&message)?, &self.secret_key);
+        let (recovery_id, signature) = signature.serialize_compact();
+
+        let mut data = vec![31 + recovery_id.to_i32() as u8];
+        data.extend(signature);
+
+        Ok(utils::zbase32_encode(&data))
+    }
+
+    async fn subscribe_invoices(
+        &self,
+        params: SubscribeInvoicesParams,
+    ) -> Result<InvoiceStream, Error> {
+        let cursor = params.cursor.unwrap_or_default();
+
+        // Subscribing with the state locked makes sure no event is missed nor
+        // sent twice between the replayed ones and the live ones.
+        let state = self.lock();
+        let mut events = self.events.subscribe();
+
+        let mut created: Vec<&MockInvoice> = state
+            .invoices
+            .iter()
+            .filter(|invoice| {
+                cursor
+                    .add_index
+                    .is_some_and(|index| invoice.add_index > index)
+            })
+            .collect();
+        created.sort_by_key(|invoice| invoice.add_index);
+        let mut settled: Vec<&MockInvoice> = state
+            .invoices
+            .iter()
+            .filter(
+                |invoice| match (invoice.settle_index, cursor.settle_index) {
+                    (Some(settle_index), Some(index)) => settle_index > index,
+                    _ => false,
+                },
+            )
+            .collect();
+        settled.sort_by_key(|invoice| invoice.settle_index);
+
+        // Replayed events carry the cursor as of each event, so a subscription
+        // stopped halfway resumes from there.
+        let mut replayed = Vec::new();
+        let mut replay_cursor = cursor;
+        for invoice in created {
+            replay_cursor.add_index = Some(invoice.add_index);
+            replayed.push(InvoiceEvent {
+                event_type: InvoiceEventType::Created,
+                invoice: invoice.invoice.clone(),
+                cursor: replay_cursor.clone(),
+            });
+        }
+        for invoice in settled {
+            replay_cursor.settle_index = invoice.settle_index;
+            replayed.push(InvoiceEvent {
+                event_type: InvoiceEventType::Settled,
+                invoice: invoice.invoice.clone(),
+                cursor: replay_cursor.clone(),
+            });
+        }
+        drop(state);
+
+        let stream = async_stream::try_stream! {
+            for event in replayed {
+                yield event;
+            }
+
+            loop {
+                match events.recv().await {
+                    Ok(event) => yield event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        Err(Error::ApiError(String::from("invoice events were missed")))?
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn track_payment(&self, payment_hash: String) -> Result<PaymentStream, Error> {
+        let node = self.clone();
+
+        let stream = async_stream::try_stream! {
+            let mut status = None;
+            loop {
+                let payment = node.get_payment(payment_hash.clone()).await?;
+                let pending = payment.status == PaymentStatus::Pending;
+                if status.as_ref() != Some(&payment.status) {
+                    status = Some(payment.status.clone());
+                    yield payment;
+                }
+                if !pending {
+                    break;
+                }
+
+                tokio::time::sleep(PAYMENT_POLL_INTERVAL).await;
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn update_channel_policy(&self, params: UpdatePolicyParams) -> Result<(), Error> {
+        let mut state = self.lock();
+
+        let channels: Vec<&Channel> = state
+            .channels
+            .iter()
+            .filter(|channel| {
+                params
+                    .channel_id
+                    .as_ref()
+                    .is_none_or(|channel_id| channel.channel_id == *channel_id)
+            })
+            .collect();
+        if channels.is_empty() && params.channel_id.is_some() {
+            return Err(Error::ApiError(String::from("channel not found")));
+        }
+
+        let policies: Vec<ChannelPolicy> = channels
+            .into_iter()
+            .map(|channel| {
+                let policy = Self::policy(&state, channel);
+                let params = utils::fill_channel_policy(&params, &policy);
+                ChannelPolicy {
+                    channel_id: policy.channel_id,
+                    base_fee_msat: params.base_fee_msat.unwrap_or_default(),
+                    fee_rate_ppm: params.fee_rate_ppm.unwrap_or_default(),
+                    time_lock_delta: params.time_lock_delta,
+                    min_htlc_msat: params.min_htlc_msat.unwrap_or_default(),
+                    max_htlc_msat: params.max_htlc_msat,
+                }
+            })
+            .collect();
+        state.policies.retain(|policy| {
+            !policies
+                .iter()
+                .any(|updated| updated.channel_id == policy.channel_id)
+        });
+        state.policies.extend(policies);
+
+        Ok(())
+    }
+
+    async fn verify_message(
+        &self,
+        params: VerifyMessageParams,
+    ) -> Result<VerifyMessageResult, Error> {
+        let data = utils::zbase32_decode(&params.signature)?;
+        if data.len() != 65 || !(27..=34).contains(&data[0]) {
+            return Err(Error::ApiError(String::from("invalid signature")));
+        }
+
+        let recovery_id = RecoveryId::from_i32(((data[0] - 27) & 3) as i32)?;
+        let signature = RecoverableSignature::from_compact(&data[1..], recovery_id)?;
+        let pubkey = Secp256k1::verification_only()
+            .recover_ecdsa(&message_digest(&params.message)?, &signature)?
+            .to_string();
+
+        Ok(VerifyMessageResult {
+            valid: params
+                .pubkey
+                .as_ref()
+                .is_none_or(|expected| expected.eq_ignore_ascii_case(&pubkey)),
+            pubkey,
+        })
+    }
+}
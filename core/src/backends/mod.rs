@@ -1,3 +1,5 @@
 pub mod cln;
 pub mod eclair;
 pub mod lnd;
+#[cfg(feature = "mock")]
+pub mod mock;
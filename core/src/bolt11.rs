@@ -0,0 +1,632 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bech32::u5;
+use secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use secp256k1::{Message, PublicKey, Secp256k1};
+use sha2::{Digest, Sha256};
+
+use crate::error::Error;
+use crate::types::{DecodedInvoice, Feature, Network, RouteHint, RouteHintHop};
+use crate::utils;
+
+const DEFAULT_EXPIRY: i32 = 3600;
+const DEFAULT_MIN_FINAL_CLTV_EXPIRY: u64 = 18;
+
+const TIMESTAMP_LENGTH: usize = 7;
+const SIGNATURE_LENGTH: usize = 104;
+const ROUTE_HINT_HOP_LENGTH: usize = 51;
+
+const TAG_PAYMENT_HASH: u8 = 1;
+const TAG_ROUTE_HINT: u8 = 3;
+const TAG_FEATURES: u8 = 5;
+const TAG_EXPIRY: u8 = 6;
+const TAG_DESCRIPTION: u8 = 13;
+const TAG_PAYMENT_SECRET: u8 = 16;
+const TAG_PAYEE: u8 = 19;
+const TAG_DESCRIPTION_HASH: u8 = 23;
+const TAG_MIN_FINAL_CLTV_EXPIRY: u8 = 24;
+
+// Feature bits that can be set in invoices, as named in BOLT 9. Odd bits are
+// the optional variants of the even ones.
+const KNOWN_FEATURES: [(u32, &str); 7] = [
+    (8, "var_onion_optin"),
+    (14, "payment_secret"),
+    (16, "basic_mpp"),
+    (24, "option_route_blinding"),
+    (26, "option_shutdown_anysegwit"),
+    (46, "option_scid_alias"),
+    (48, "option_payment_metadata"),
+];
+
+fn invalid_invoice(message: &str) -> Error {
+    Error::InvalidInvoice(String::from(message))
+}
+
+/// Decodes a BOLT11 invoice locally, without asking the node, and checks its
+/// signature against the payee public key.
+pub fn decode_invoice(bolt11: &str) -> Result<DecodedInvoice, Error> {
+    let bolt11 = bolt11.trim();
+    let bolt11 = bolt11
+        .strip_prefix("lightning:")
+        .or_else(|| bolt11.strip_prefix("LIGHTNING:"))
+        .unwrap_or(bolt11);
+
+    let (hrp, data, variant) = bech32::decode(bolt11)?;
+    if variant != bech32::Variant::Bech32 {
+        return Err(invalid_invoice("invoice must be bech32 encoded"));
+    }
+    if data.len() < TIMESTAMP_LENGTH + SIGNATURE_LENGTH {
+        return Err(invalid_invoice("invoice is too short"));
+    }
+
+    let (network, amount_msat) = parse_hrp(&hrp)?;
+
+    let (data, signature) = data.split_at(data.len() - SIGNATURE_LENGTH);
+    let creation_date = read_int(&data[..TIMESTAMP_LENGTH]);
+
+    let mut invoice = DecodedInvoice {
+        bolt11: bolt11.to_lowercase(),
+        network,
+        payee: String::new(),
+        amount: amount_msat.map(utils::msat_to_sat),
+        amount_msat,
+        payment_hash: String::new(),
+        payment_secret: None,
+        description: None,
+        description_hash: None,
+        creation_date: creation_date as i64,
+        expiry: DEFAULT_EXPIRY,
+        min_final_cltv_expiry: DEFAULT_MIN_FINAL_CLTV_EXPIRY,
+        route_hints: Vec::new(),
+        features: Vec::new(),
+    };
+    let mut payee = None;
+
+    let mut fields = &data[TIMESTAMP_LENGTH..];
+    while !fields.is_empty() {
+        if fields.len() < 3 {
+            return Err(invalid_invoice("invalid tagged field"));
+        }
+
+        let tag = fields[0].to_u8();
+        let length = read_int(&fields[1..3]) as usize;
+        if fields.len() < 3 + length {
+            return Err(invalid_invoice("invalid tagged field length"));
+        }
+
+        let value = &fields[3..3 + length];
+        fields = &fields[3 + length..];
+
+        // Fields with an unexpected length must be skipped, as well as the
+        // unknown ones.
+        match (tag, length) {
+            (TAG_PAYMENT_HASH, 52) => invoice.payment_hash = hex::encode(to_bytes(value, false)),
+            (TAG_PAYMENT_SECRET, 52) => {
+                invoice.payment_secret = Some(hex::encode(to_bytes(value, false)))
+            }
+            (TAG_DESCRIPTION_HASH, 52) => {
+                invoice.description_hash = Some(hex::encode(to_bytes(value, false)))
+            }
+            (TAG_PAYEE, 53) => payee = Some(PublicKey::from_slice(&to_bytes(value, false))?),
+            (TAG_DESCRIPTION, _) => {
+                let description = String::from_utf8(to_bytes(value, false))
+                    .map_err(|_| invalid_invoice("description is not valid utf-8"))?;
+                invoice.description = Some(description);
+            }
+            (TAG_EXPIRY, _) => {
+                invoice.expiry = i32::try_from(read_int(value))
+                    .map_err(|_| invalid_invoice("expiry is too large"))?
+            }
+            (TAG_MIN_FINAL_CLTV_EXPIRY, _) => invoice.min_final_cltv_expiry = read_int(value),
+            (TAG_ROUTE_HINT, _) => invoice.route_hints.push(parse_route_hint(value)?),
+            (TAG_FEATURES, _) => invoice.features = parse_features(value),
+            _ => continue,
+        }
+    }
+
+    if invoice.payment_hash.is_empty() {
+        return Err(invalid_invoice("missing payment hash"));
+    }
+
+    let signature = to_bytes(signature, false);
+    let recovery_id = RecoveryId::from_i32(signature[64] as i32)?;
+    let signature = RecoverableSignature::from_compact(&signature[..64], recovery_id)?;
+
+    let mut preimage = hrp.into_bytes();
+    preimage.extend(to_bytes(data, true));
+    let message = Message::from_slice(&Sha256::digest(&preimage))?;
+
+    let secp = Secp256k1::verification_only();
+    let payee = match payee {
+        Some(payee) => {
+            secp.verify_ecdsa(&message, &signature.to_standard(), &payee)?;
+            payee
+        }
+        None => secp.recover_ecdsa(&message, &signature)?,
+    };
+    invoice.payee = payee.to_string();
+
+    Ok(invoice)
+}
+
+/// Checks that an invoice can be paid by a node on `network`, for the given
+/// amount, before sending the payment.
+pub fn validate_invoice(
+    invoice: &DecodedInvoice,
+    network: &Network,
+    amount_msat: Option<u64>,
+) -> Result<(), Error> {
+    if invoice.network != *network {
+        return Err(Error::InvalidInvoice(format!(
+            "invoice is for network {:?}, node is on {:?}",
+            invoice.network, network
+        )));
+    }
+
+    if is_expired(invoice) {
+        return Err(invalid_invoice("invoice is expired"));
+    }
+
+    match (invoice.amount_msat, amount_msat) {
+        (None, None) => Err(invalid_invoice(
+            "amount is required for zero-amount invoices",
+        )),
+        // Paying more than asked is allowed, as the spec permits.
+        (Some(invoice_amount), Some(amount)) if amount < invoice_amount => {
+            Err(invalid_invoice("amount is less than the invoice amount"))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn is_expired(invoice: &DecodedInvoice) -> bool {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0);
+
+    invoice.creation_date + invoice.expiry as i64 <= now
+}
+
+/// Reads the network of an invoice from its prefix.
+pub fn get_network(bolt11: &str) -> Result<Network, Error> {
+    let bolt11 = bolt11.trim().to_lowercase();
+    let separator = bolt11
+        .rfind('1')
+        .ok_or_else(|| invalid_invoice("missing separator"))?;
+    let (network, _) = parse_hrp(&bolt11[..separator])?;
+
+    Ok(network)
+}
+
+/// Parses the human readable part of an invoice, made of `ln`, the network
+/// prefix and an optional amount with its multiplier.
+fn parse_hrp(hrp: &str) -> Result<(Network, Option<u64>), Error> {
+    let hrp = hrp
+        .strip_prefix("ln")
+        .ok_or_else(|| invalid_invoice("invalid prefix"))?;
+
+    // Longer prefixes go first, as `bc` is a prefix of `bcrt`.
+    let networks = [
+        ("bcrt", Network::Regtest),
+        ("bc", Network::Mainnet),
+        ("tbs", Network::Unknown(String::from("signet"))),
+        ("tb", Network::Testnet),
+        ("sb", Network::Unknown(String::from("simnet"))),
+    ];
+    let (prefix, network) = networks
+        .into_iter()
+        .find(|(prefix, _)| hrp.starts_with(prefix))
+        .ok_or_else(|| invalid_invoice("unknown network"))?;
+
+    let amount = &hrp[prefix.len()..];
+    if amount.is_empty() {
+        return Ok((network, None));
+    }
+
+    let (value, multiplier) = match amount.char_indices().last() {
+        Some((index, c)) if c.is_ascii_alphabetic() => (&amount[..index], Some(c)),
+        _ => (amount, None),
+    };
+    if value.is_empty() || !value.chars().all(|c| c.is_ascii_digit()) || value.starts_with('0') {
+        return Err(invalid_invoice("invalid amount"));
+    }
+    let value: u64 = value.parse()?;
+
+    // Amounts are in bitcoin, the multiplier giving the unit.
+    let amount_msat = match multiplier {
+        None => value.checked_mul(100_000_000_000),
+        Some('m') => value.checked_mul(100_000_000),
+        Some('u') => value.checked_mul(100_000),
+        Some('n') => value.checked_mul(100),
+        Some('p') if value.is_multiple_of(10) => Some(value / 10),
+        Some('p') => return Err(invalid_invoice("amount is not a whole number of msat")),
+        Some(_) => return Err(invalid_invoice("invalid amount multiplier")),
+    }
+    .ok_or_else(|| invalid_invoice("amount is too large"))?;
+
+    Ok((network, Some(amount_msat)))
+}
+
+fn parse_route_hint(value: &[u5]) -> Result<RouteHint, Error> {
+    let bytes = to_bytes(value, false);
+    if !bytes.len().is_multiple_of(ROUTE_HINT_HOP_LENGTH) {
+        return Err(invalid_invoice("invalid route hint"));
+    }
+
+    let hops = bytes
+        .chunks(ROUTE_HINT_HOP_LENGTH)
+        .map(|hop| {
+            let short_channel_id = u64::from_be_bytes(hop[33..41].try_into().unwrap());
+
+            RouteHintHop {
+                node_id: hex::encode(&hop[..33]),
+                short_channel_id: utils::format_short_channel_id(short_channel_id),
+                fee_base_msat: u32::from_be_bytes(hop[41..45].try_into().unwrap()),
+                fee_proportional_millionths: u32::from_be_bytes(hop[45..49].try_into().unwrap()),
+                cltv_expiry_delta: u16::from_be_bytes(hop[49..51].try_into().unwrap()),
+            }
+        })
+        .collect();
+
+    Ok(RouteHint { hops })
+}
+
+fn parse_features(value: &[u5]) -> Vec<Feature> {
+    let mut features = Vec::new();
+
+    // The bit field is big endian, the least significant bit being at the end.
+    for (index, word) in value.iter().rev().enumerate() {
+        for offset in 0..5 {
+            if word.to_u8() & (1 << offset) != 0 {
+                features.push(feature((index * 5 + offset) as u32));
+            }
+        }
+    }
+
+    features
+}
+
+/// Reads the features of a big endian bit field, as used in the Lightning
+/// messages.
+pub fn features_from_bytes(bytes: &[u8]) -> Vec<Feature> {
+    let mut features = Vec::new();
+
+    for (index, byte) in bytes.iter().rev().enumerate() {
+        for offset in 0..8 {
+            if byte & (1 << offset) != 0 {
+                features.push(feature((index * 8 + offset) as u32));
+            }
+        }
+    }
+
+    features
+}
+
+/// Finds the bit of a feature from its BOLT 9 name.
+pub fn feature_bit(name: &str, is_required: bool) -> Option<u32> {
+    KNOWN_FEATURES
+        .iter()
+        .find(|(_, known_name)| *known_name == name)
+        .map(|(bit, _)| if is_required { *bit } else { bit + 1 })
+}
+
+/// Describes a feature bit, named after its even (required) variant.
+pub fn feature(bit: u32) -> Feature {
+    let name = KNOWN_FEATURES
+        .iter()
+        .find(|(known_bit, _)| *known_bit == bit - bit % 2)
+        .map(|(_, name)| name.to_string());
+
+    Feature {
+        bit,
+        is_known: name.is_some(),
+        name,
+        is_required: bit.is_multiple_of(2),
+    }
+}
+
+/// Fields of an invoice to encode with [`encode_invoice`].
+#[cfg(feature = "mock")]
+pub(crate) struct InvoiceFields {
+    pub network: Network,
+    pub amount_msat: Option<u64>,
+    pub creation_date: u64,
+    pub payment_hash: [u8; 32],
+    pub payment_secret: [u8; 32],
+    pub description: Option<String>,
+    pub description_hash: Option<[u8; 32]>,
+    pub expiry: u32,
+    pub min_final_cltv_expiry: u64,
+}
+
+/// Encodes and signs an invoice with the payee secret key. The invoice
+/// requires `var_onion_optin` and `payment_secret`, like current nodes do.
+#[cfg(feature = "mock")]
+pub(crate) fn encode_invoice(
+    fields: &InvoiceFields,
+    secret_key: &secp256k1::SecretKey,
+) -> Result<String, Error> {
+    use bech32::ToBase32;
+
+    let prefix = match &fields.network {
+        Network::Mainnet => "bc",
+        Network::Testnet => "tb",
+        Network::Regtest => "bcrt",
+        Network::Unknown(network) if network == "signet" => "tbs",
+        Network::Unknown(network) if network == "simnet" => "sb",
+        Network::Unknown(_) => return Err(invalid_invoice("unknown network")),
+    };
+    let amount = match fields.amount_msat {
+        None => String::new(),
+        Some(msat) if msat.is_multiple_of(100_000_000_000) => (msat / 100_000_000_000).to_string(),
+        Some(msat) if msat.is_multiple_of(100_000_000) => format!("{}m", msat / 100_000_000),
+        Some(msat) if msat.is_multiple_of(100_000) => format!("{}u", msat / 100_000),
+        Some(msat) if msat.is_multiple_of(100) => format!("{}n", msat / 100),
+        Some(msat) => format!("{}p", msat * 10),
+    };
+    let hrp = format!("ln{}{}", prefix, amount);
+
+    let mut data = write_int(fields.creation_date, TIMESTAMP_LENGTH);
+    push_field(&mut data, TAG_PAYMENT_HASH, fields.payment_hash.to_base32());
+    push_field(
+        &mut data,
+        TAG_PAYMENT_SECRET,
+        fields.payment_secret.to_base32(),
+    );
+    match (&fields.description, &fields.description_hash) {
+        (_, Some(description_hash)) => push_field(
+            &mut data,
+            TAG_DESCRIPTION_HASH,
+            description_hash.to_base32(),
+        ),
+        (description, None) => push_field(
+            &mut data,
+            TAG_DESCRIPTION,
+            description
+                .clone()
+                .unwrap_or_default()
+                .as_bytes()
+                .to_base32(),
+        ),
+    }
+    if fields.expiry != DEFAULT_EXPIRY as u32 {
+        push_field(&mut data, TAG_EXPIRY, write_int(fields.expiry.into(), 0));
+    }
+    push_field(
+        &mut data,
+        TAG_MIN_FINAL_CLTV_EXPIRY,
+        write_int(fields.min_final_cltv_expiry, 0),
+    );
+    let features = [
+        feature_bit("var_onion_optin", true),
+        feature_bit("payment_secret", true),
+        feature_bit("basic_mpp", false),
+    ];
+    let mut feature_words = vec![0u8; 4];
+    for bit in features.into_iter().flatten() {
+        let index = feature_words.len() - 1 - bit as usize / 5;
+        feature_words[index] |= 1 << (bit % 5);
+    }
+    push_field(
+        &mut data,
+        TAG_FEATURES,
+        feature_words
+            .into_iter()
+            .map(|word| u5::try_from_u8(word).expect("Feature words are 5 bits"))
+            .collect(),
+    );
+
+    let mut preimage = hrp.clone().into_bytes();
+    preimage.extend(to_bytes(&data, true));
+    let message = Message::from_slice(&Sha256::digest(&preimage))?;
+
+    let signature = Secp256k1::signing_only().sign_ecdsa_recoverable(&message, secret_key);
+    let (recovery_id, signature) = signature.serialize_compact();
+    let mut signature = signature.to_vec();
+    signature.push(recovery_id.to_i32() as u8);
+    data.extend(signature.to_base32());
+
+    Ok(bech32::encode(&hrp, data, bech32::Variant::Bech32)?)
+}
+
+#[cfg(feature = "mock")]
+fn push_field(data: &mut Vec<u5>, tag: u8, value: Vec<u5>) {
+    data.push(u5::try_from_u8(tag).expect("Tags are 5 bits"));
+    data.extend(write_int(value.len() as u64, 2));
+    data.extend(value);
+}
+
+/// Converts an integer to big endian 5-bit words, on `length` words or on as
+/// few words as possible if `length` is 0.
+#[cfg(feature = "mock")]
+fn write_int(value: u64, length: usize) -> Vec<u5> {
+    let mut words = Vec::new();
+    let mut value = value;
+
+    while value > 0 || words.len() < length.max(1) {
+        words.push(u5::try_from_u8((value & 31) as u8).expect("Masked to 5 bits"));
+        value >>= 5;
+        if length > 0 && words.len() == length {
+            break;
+        }
+    }
+    words.reverse();
+
+    words
+}
+
+fn read_int(data: &[u5]) -> u64 {
+    data.iter()
+        .fold(0u64, |value, word| value << 5 | word.to_u8() as u64)
+}
+
+/// Converts 5-bit words to bytes. Leftover bits are dropped, unless `pad` is
+/// set, in which case they are padded with zeros to a last byte.
+fn to_bytes(data: &[u5], pad: bool) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(data.len() * 5 / 8 + 1);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for word in data {
+        buffer = buffer << 5 | word.to_u8() as u32;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    if pad && bits > 0 {
+        bytes.push((buffer << (8 - bits)) as u8);
+    }
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors of BOLT 11, signed by the key of PAYEE.
+    const PAYEE: &str = "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad";
+    const PAYMENT_HASH: &str = "0001020304050607080900010203040506070809000102030405060708090102";
+    const PAYMENT_SECRET: &str = "1111111111111111111111111111111111111111111111111111111111111111";
+    const TIMESTAMP: i64 = 1496314658;
+
+    const DONATION: &str = "lnbc1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdpl2pkx2ctnv5sxxmmwwd5kgetjypeh2ursdae8g6twvus8g6rfwvs8qun0dfjkxaq9qrsgq357wnc5r2ueh7ck6q93dj32dlqnls087fxdwk8qakdyafkq3yap9us6v52vjjsrvywa6rt52cm9r9zqt8r2t7mlcwspyetp5h2tztugp9lfyql";
+    const DONATION_WITHOUT_SECRET: &str = "lnbc1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdpl2pkx2ctnv5sxxmmwwd5kgetjypeh2ursdae8g6twvus8g6rfwvs8qun0dfjkxaq8rkx3yf5tcsyz3d73gafnh3cax9rn449d9p5uxz9ezhhypd0elx87sjle52x86fux2ypatgddc6k63n7erqz25le42c4u4ecky03ylcqca784w";
+    const COFFEE: &str = "lnbc2500u1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqdq5xysxxatsyp3k7enxv4jsxqzpu9qrsgquk0rl77nj30yxdy8j9vdx85fkpmdla2087ne0xh8nhedh8w27kyke0lp53ut353s06fv3qfegext0eh0ymjpf39tuven09sam30g4vgpfna3rh";
+    const TESTNET_DESCRIPTION_HASH: &str = "lntb20m1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygshp58yjmdan79s6qqdhdzgynm4zwqd5d7xmw5fk98klysy043l2ahrqspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqfpp3x9et2e20v6pu37c5d9vax37wxq72un989qrsgqdj545axuxtnfemtpwkc45hx9d2ft7x04mt8q7y6t0k2dge9e7h8kpy9p34ytyslj3yu569aalz2xdk8xkd7ltxqld94u8h2esmsmacgpghe9k8";
+
+    fn feature_bits(invoice: &DecodedInvoice) -> Vec<u32> {
+        invoice.features.iter().map(|feature| feature.bit).collect()
+    }
+
+    // Moves the invoice creation date to now, so that it can be validated.
+    fn unexpired(bolt11: &str) -> DecodedInvoice {
+        let mut invoice = decode_invoice(bolt11).unwrap();
+        invoice.creation_date = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        invoice
+    }
+
+    #[test]
+    fn decode_donation() {
+        let invoice = decode_invoice(DONATION).unwrap();
+
+        assert_eq!(invoice.network, Network::Mainnet);
+        assert_eq!(invoice.payee, PAYEE);
+        assert_eq!(invoice.amount_msat, None);
+        assert_eq!(invoice.payment_hash, PAYMENT_HASH);
+        assert_eq!(invoice.payment_secret.as_deref(), Some(PAYMENT_SECRET));
+        assert_eq!(
+            invoice.description.as_deref(),
+            Some("Please consider supporting this project")
+        );
+        assert_eq!(invoice.creation_date, TIMESTAMP);
+        assert_eq!(invoice.expiry, DEFAULT_EXPIRY);
+        assert_eq!(invoice.min_final_cltv_expiry, DEFAULT_MIN_FINAL_CLTV_EXPIRY);
+        assert_eq!(feature_bits(&invoice), vec![8, 14]);
+        assert!(invoice.features.iter().all(|feature| feature.is_required));
+    }
+
+    #[test]
+    fn decode_donation_without_secret() {
+        let invoice = decode_invoice(DONATION_WITHOUT_SECRET).unwrap();
+
+        assert_eq!(invoice.payee, PAYEE);
+        assert_eq!(invoice.payment_hash, PAYMENT_HASH);
+        assert_eq!(invoice.payment_secret, None);
+        assert!(invoice.features.is_empty());
+    }
+
+    #[test]
+    fn decode_coffee() {
+        let invoice = decode_invoice(COFFEE).unwrap();
+
+        assert_eq!(invoice.amount, Some(250_000));
+        assert_eq!(invoice.amount_msat, Some(250_000_000));
+        assert_eq!(invoice.description.as_deref(), Some("1 cup coffee"));
+        assert_eq!(invoice.expiry, 60);
+    }
+
+    #[test]
+    fn decode_testnet_description_hash() {
+        let invoice = decode_invoice(TESTNET_DESCRIPTION_HASH).unwrap();
+
+        assert_eq!(invoice.network, Network::Testnet);
+        assert_eq!(invoice.amount_msat, Some(2_000_000_000));
+        assert_eq!(invoice.description, None);
+        assert_eq!(
+            invoice.description_hash.as_deref(),
+            Some("3925b6f67e2c340036ed12093dd44e0368df1b6ea26c53dbe4811f58fd5db8c1")
+        );
+    }
+
+    #[test]
+    fn decode_uppercase_and_uri() {
+        let invoice = decode_invoice(&COFFEE.to_uppercase()).unwrap();
+        assert_eq!(invoice.bolt11, COFFEE);
+
+        let invoice = decode_invoice(&format!("lightning:{}", COFFEE)).unwrap();
+        assert_eq!(invoice.bolt11, COFFEE);
+    }
+
+    #[test]
+    fn decode_invalid_checksum() {
+        let bolt11 = format!("{}q", &COFFEE[..COFFEE.len() - 1]);
+
+        assert!(decode_invoice(&bolt11).is_err());
+    }
+
+    #[test]
+    fn decode_mixed_case() {
+        let bolt11 = format!("LNBC{}", &COFFEE[4..]);
+
+        assert!(decode_invoice(&bolt11).is_err());
+    }
+
+    #[test]
+    fn network_from_prefix() {
+        assert_eq!(get_network(COFFEE).unwrap(), Network::Mainnet);
+        assert_eq!(
+            get_network(TESTNET_DESCRIPTION_HASH).unwrap(),
+            Network::Testnet
+        );
+    }
+
+    #[test]
+    fn validate_expired() {
+        let invoice = decode_invoice(COFFEE).unwrap();
+
+        assert!(is_expired(&invoice));
+        assert!(validate_invoice(&invoice, &Network::Mainnet, None).is_err());
+    }
+
+    #[test]
+    fn validate_network() {
+        let invoice = unexpired(COFFEE);
+
+        assert!(validate_invoice(&invoice, &Network::Mainnet, None).is_ok());
+        assert!(validate_invoice(&invoice, &Network::Testnet, None).is_err());
+    }
+
+    #[test]
+    fn validate_amount() {
+        let invoice = unexpired(COFFEE);
+
+        assert!(validate_invoice(&invoice, &Network::Mainnet, Some(250_000_000)).is_ok());
+        assert!(validate_invoice(&invoice, &Network::Mainnet, Some(300_000_000)).is_ok());
+        assert!(validate_invoice(&invoice, &Network::Mainnet, Some(249_999_999)).is_err());
+
+        let invoice = unexpired(DONATION);
+
+        assert!(validate_invoice(&invoice, &Network::Mainnet, Some(1_000)).is_ok());
+        assert!(validate_invoice(&invoice, &Network::Mainnet, None).is_err());
+    }
+}
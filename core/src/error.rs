@@ -1,7 +1,4 @@
-use std::{
-    fmt::{self, Display},
-    io::ErrorKind,
-};
+use std::fmt::{self, Display};
 
 #[derive(Debug)]
 pub enum ConfigError {
@@ -33,6 +30,7 @@ pub enum Error {
     ApiError(String),
     UnknownError(String),
     ConversionError(String),
+    InvalidInvoice(String),
 }
 
 impl Display for Error {
@@ -47,6 +45,7 @@ impl Display for Error {
             Error::ApiError(err) => err.clone(),
             Error::ConversionError(err) => err.clone(),
             Error::UnknownError(err) => err.clone(),
+            Error::InvalidInvoice(err) => format!("invalid invoice: {}", err),
         };
 
         write!(f, "{}", str)
@@ -81,6 +80,12 @@ impl From<tonic::transport::Error> for Error {
     }
 }
 
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(err: tokio_tungstenite::tungstenite::Error) -> Self {
+        Error::ConnectionError(err.to_string())
+    }
+}
+
 impl From<reqwest::header::InvalidHeaderValue> for Error {
     fn from(err: reqwest::header::InvalidHeaderValue) -> Self {
         Error::ApiError(err.to_string())
@@ -91,6 +96,14 @@ impl From<tonic::Status> for Error {
     fn from(status: tonic::Status) -> Self {
         let status_message = status.message().to_string();
 
+        if matches!(
+            status.code(),
+            tonic::Code::Unauthenticated | tonic::Code::PermissionDenied
+        ) || status_message == "permission denied"
+        {
+            return Error::Unauthorized;
+        }
+
         let rpc_error_regex =
             regex::Regex::new(r"RpcError").expect("Hardcoded regex should be valid.");
         if rpc_error_regex.is_match(&status_message) {
@@ -121,14 +134,44 @@ impl From<base64::DecodeError> for Error {
     }
 }
 
+impl From<hex::FromHexError> for Error {
+    fn from(_: hex::FromHexError) -> Self {
+        Error::ConversionError(String::from("couldn't convert hex to bytes"))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(_: serde_json::Error) -> Self {
+        Error::ConversionError(String::from("couldn't parse json"))
+    }
+}
+
+impl From<bech32::Error> for Error {
+    fn from(err: bech32::Error) -> Self {
+        Error::InvalidInvoice(err.to_string())
+    }
+}
+
+impl From<secp256k1::Error> for Error {
+    fn from(err: secp256k1::Error) -> Self {
+        Error::InvalidInvoice(err.to_string())
+    }
+}
+
 impl From<std::num::ParseIntError> for Error {
     fn from(_: std::num::ParseIntError) -> Self {
         Error::ConversionError(String::from("couldn't convert string to integer"))
     }
 }
 
+impl From<std::num::TryFromIntError> for Error {
+    fn from(_: std::num::TryFromIntError) -> Self {
+        Error::ConversionError(String::from("couldn't convert integer"))
+    }
+}
+
 impl From<Error> for std::io::Error {
     fn from(e: Error) -> std::io::Error {
-        std::io::Error::new(ErrorKind::Other, e.to_string())
+        std::io::Error::other(e.to_string())
     }
 }
@@ -1,4 +1,5 @@
 pub mod backends;
+pub mod bolt11;
 pub mod error;
 pub mod node;
 pub mod types;
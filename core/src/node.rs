@@ -1,25 +1,94 @@
+use std::pin::Pin;
+
+use futures::Stream;
+use tokio::sync::OnceCell;
+
 use crate::backends::cln::grpc::node::ClnGrpc;
 use crate::backends::eclair::rest::node::EclairRest;
+use crate::backends::lnd::grpc::node::LndGrpc;
 use crate::backends::lnd::rest::node::LndRest;
+#[cfg(feature = "mock")]
+use crate::backends::mock::node::Mock;
+use crate::bolt11;
 use crate::error::Error;
 use crate::types::{
-    Backend, CreateInvoiceParams, CreateInvoiceResult, NodeConfig, NodeInfo, PayInvoiceParams,
-    PayInvoiceResult,
+    AddressType, Backend, Balance, Channel, ChannelPolicy, CloseChannelParams, CloseChannelResult,
+    ConnectPeerParams, CreateHoldInvoiceParams, CreateInvoiceParams, CreateInvoiceResult,
+    DecodedInvoice, Invoice, InvoiceEvent, KeysendParams, ListForwardsParams, ListForwardsResult,
+    ListInvoicesParams, ListInvoicesResult, ListPaymentsParams, ListPaymentsResult, Network,
+    NodeConfig, NodeInfo, OnchainTransaction, OpenChannelParams, OpenChannelResult,
+    PayInvoiceParams, PayInvoiceResult, Payment, Peer, SendOnchainParams, SubscribeInvoicesParams,
+    UpdatePolicyParams, VerifyMessageParams, VerifyMessageResult,
 };
+use crate::utils;
+
+pub type InvoiceStream = Pin<Box<dyn Stream<Item = Result<InvoiceEvent, Error>> + Send>>;
+pub type PaymentStream = Pin<Box<dyn Stream<Item = Result<Payment, Error>> + Send>>;
 
 #[async_trait::async_trait]
 pub trait NodeMethods {
+    /// Cancels a hold invoice, failing the payments held for it.
+    async fn cancel_hold_invoice(&self, payment_hash: String) -> Result<(), Error>;
+    /// Cancels an unpaid invoice so that it can't be paid anymore.
+    async fn cancel_invoice(&self, payment_hash: String) -> Result<(), Error>;
+    async fn close_channel(&self, params: CloseChannelParams) -> Result<CloseChannelResult, Error>;
+    async fn connect_peer(&self, params: ConnectPeerParams) -> Result<(), Error>;
+    async fn create_hold_invoice(
+        &self,
+        invoice: CreateHoldInvoiceParams,
+    ) -> Result<CreateInvoiceResult, Error>;
     async fn create_invoice(
         &self,
         invoice: CreateInvoiceParams,
     ) -> Result<CreateInvoiceResult, Error>;
+    async fn decode_invoice(&self, bolt11: String) -> Result<DecodedInvoice, Error>;
+    /// Deletes the invoices which expired unpaid, only those which expired
+    /// before `before` (UNIX time) if given.
+    async fn delete_expired_invoices(&self, before: Option<u64>) -> Result<(), Error>;
+    async fn disconnect_peer(&self, pubkey: String) -> Result<(), Error>;
+    async fn get_balance(&self) -> Result<Balance, Error>;
+    async fn get_channel_policy(&self, channel_id: String) -> Result<ChannelPolicy, Error>;
     async fn get_info(&self) -> Result<NodeInfo, Error>;
+    async fn get_invoice(&self, payment_hash: String) -> Result<Invoice, Error>;
+    async fn get_payment(&self, payment_hash: String) -> Result<Payment, Error>;
+    async fn keysend(&self, params: KeysendParams) -> Result<PayInvoiceResult, Error>;
+    async fn list_channels(&self) -> Result<Vec<Channel>, Error>;
+    async fn list_forwards(&self, params: ListForwardsParams) -> Result<ListForwardsResult, Error>;
+    async fn list_invoices(&self, params: ListInvoicesParams) -> Result<ListInvoicesResult, Error>;
+    async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, Error>;
+    async fn list_payments(&self, params: ListPaymentsParams) -> Result<ListPaymentsResult, Error>;
+    async fn list_peers(&self) -> Result<Vec<Peer>, Error>;
+    async fn new_address(&self, address_type: AddressType) -> Result<String, Error>;
+    async fn open_channel(&self, params: OpenChannelParams) -> Result<OpenChannelResult, Error>;
     async fn pay_invoice(&self, invoice: PayInvoiceParams) -> Result<PayInvoiceResult, Error>;
+    async fn send_onchain(&self, params: SendOnchainParams) -> Result<String, Error>;
+    /// Starts paying an invoice without waiting for the outcome, returning
+    /// the payment hash to track it with.
+    async fn send_payment_async(&self, invoice: PayInvoiceParams) -> Result<String, Error>;
+    /// Settles a hold invoice with the preimage of its payment hash, in hex.
+    async fn settle_hold_invoice(&self, preimage: String) -> Result<(), Error>;
+    /// Signs a message with the key of the node, returning the signature in
+    /// zbase32.
+    async fn sign_message(&self, message: String) -> Result<String, Error>;
+    async fn subscribe_invoices(
+        &self,
+        params: SubscribeInvoicesParams,
+    ) -> Result<InvoiceStream, Error>;
+    /// Streams the updates of a payment, starting with its current state and
+    /// ending once it succeeded or failed.
+    async fn track_payment(&self, payment_hash: String) -> Result<PaymentStream, Error>;
+    async fn update_channel_policy(&self, params: UpdatePolicyParams) -> Result<(), Error>;
+    async fn verify_message(
+        &self,
+        params: VerifyMessageParams,
+    ) -> Result<VerifyMessageResult, Error>;
 }
 
 pub struct Node {
     pub backend: Backend,
     pub node: Box<dyn NodeMethods + Send + Sync>,
+    // The network of the node, looked up once to validate the invoices paid.
+    network: OnceCell<Network>,
 }
 
 impl Node {
@@ -30,6 +99,15 @@ impl Node {
                 Ok(Node {
                     backend,
                     node: Box::new(node),
+                    network: OnceCell::new(),
+                })
+            }
+            Backend::LndGrpc => {
+                let node = LndGrpc::new(config.try_into()?)?;
+                Ok(Node {
+                    backend,
+                    node: Box::new(node),
+                    network: OnceCell::new(),
                 })
             }
             Backend::ClnGrpc => {
@@ -37,6 +115,7 @@ impl Node {
                 Ok(Node {
                     backend,
                     node: Box::new(node),
+                    network: OnceCell::new(),
                 })
             }
             Backend::EclairRest => {
@@ -44,15 +123,68 @@ impl Node {
                 Ok(Node {
                     backend,
                     node: Box::new(node),
+                    network: OnceCell::new(),
                 })
             }
+            // Nothing in `NodeConfig` applies to the mock, which is configured
+            // with a `MockConfig` instead.
+            #[cfg(feature = "mock")]
+            Backend::Mock => Err(crate::error::ConfigError::InvalidField(String::from(
+                "backend, a mock node is built from a `Mock` with `Node::from`",
+            ))
+            .into()),
             _ => Err(Error::InvalidBackend),
         }
     }
+
+    async fn validate_invoice(&self, invoice: &PayInvoiceParams) -> Result<(), Error> {
+        let decoded_invoice = bolt11::decode_invoice(&invoice.payment_request)?;
+        let network = self
+            .network
+            .get_or_try_init(|| async { Ok::<_, Error>(self.node.get_info().await?.network) })
+            .await?;
+        let amount_msat = utils::get_amount_msat(invoice.amount, invoice.amount_msat);
+
+        bolt11::validate_invoice(&decoded_invoice, network, amount_msat)
+    }
+}
+
+#[cfg(feature = "mock")]
+impl From<Mock> for Node {
+    fn from(node: Mock) -> Self {
+        Node {
+            backend: Backend::Mock,
+            node: Box::new(node),
+            network: OnceCell::new(),
+        }
+    }
 }
 
 #[async_trait::async_trait]
 impl NodeMethods for Node {
+    async fn cancel_hold_invoice(&self, payment_hash: String) -> Result<(), Error> {
+        self.node.cancel_hold_invoice(payment_hash).await
+    }
+
+    async fn cancel_invoice(&self, payment_hash: String) -> Result<(), Error> {
+        self.node.cancel_invoice(payment_hash).await
+    }
+
+    async fn close_channel(&self, params: CloseChannelParams) -> Result<CloseChannelResult, Error> {
+        self.node.close_channel(params).await
+    }
+
+    async fn connect_peer(&self, params: ConnectPeerParams) -> Result<(), Error> {
+        self.node.connect_peer(params).await
+    }
+
+    async fn create_hold_invoice(
+        &self,
+        invoice: CreateHoldInvoiceParams,
+    ) -> Result<CreateInvoiceResult, Error> {
+        self.node.create_hold_invoice(invoice).await
+    }
+
     async fn create_invoice(
         &self,
         invoice: CreateInvoiceParams,
@@ -60,11 +192,117 @@ impl NodeMethods for Node {
         self.node.create_invoice(invoice).await
     }
 
+    async fn decode_invoice(&self, bolt11: String) -> Result<DecodedInvoice, Error> {
+        self.node.decode_invoice(bolt11).await
+    }
+
+    async fn delete_expired_invoices(&self, before: Option<u64>) -> Result<(), Error> {
+        self.node.delete_expired_invoices(before).await
+    }
+
+    async fn disconnect_peer(&self, pubkey: String) -> Result<(), Error> {
+        self.node.disconnect_peer(pubkey).await
+    }
+
+    async fn get_balance(&self) -> Result<Balance, Error> {
+        self.node.get_balance().await
+    }
+
+    async fn get_channel_policy(&self, channel_id: String) -> Result<ChannelPolicy, Error> {
+        self.node.get_channel_policy(channel_id).await
+    }
+
     async fn get_info(&self) -> Result<NodeInfo, Error> {
         self.node.get_info().await
     }
 
+    async fn get_invoice(&self, payment_hash: String) -> Result<Invoice, Error> {
+        self.node.get_invoice(payment_hash).await
+    }
+
+    async fn get_payment(&self, payment_hash: String) -> Result<Payment, Error> {
+        self.node.get_payment(payment_hash).await
+    }
+
+    async fn keysend(&self, params: KeysendParams) -> Result<PayInvoiceResult, Error> {
+        self.node.keysend(params).await
+    }
+
+    async fn list_channels(&self) -> Result<Vec<Channel>, Error> {
+        self.node.list_channels().await
+    }
+
+    async fn list_forwards(&self, params: ListForwardsParams) -> Result<ListForwardsResult, Error> {
+        self.node.list_forwards(params).await
+    }
+
+    async fn list_invoices(&self, params: ListInvoicesParams) -> Result<ListInvoicesResult, Error> {
+        self.node.list_invoices(params).await
+    }
+
+    async fn list_onchain_transactions(&self) -> Result<Vec<OnchainTransaction>, Error> {
+        self.node.list_onchain_transactions().await
+    }
+
+    async fn list_payments(&self, params: ListPaymentsParams) -> Result<ListPaymentsResult, Error> {
+        self.node.list_payments(params).await
+    }
+
+    async fn list_peers(&self) -> Result<Vec<Peer>, Error> {
+        self.node.list_peers().await
+    }
+
+    async fn new_address(&self, address_type: AddressType) -> Result<String, Error> {
+        self.node.new_address(address_type).await
+    }
+
+    async fn open_channel(&self, params: OpenChannelParams) -> Result<OpenChannelResult, Error> {
+        self.node.open_channel(params).await
+    }
+
     async fn pay_invoice(&self, invoice: PayInvoiceParams) -> Result<PayInvoiceResult, Error> {
+        self.validate_invoice(&invoice).await?;
+
         self.node.pay_invoice(invoice).await
     }
+
+    async fn send_onchain(&self, params: SendOnchainParams) -> Result<String, Error> {
+        self.node.send_onchain(params).await
+    }
+
+    async fn send_payment_async(&self, invoice: PayInvoiceParams) -> Result<String, Error> {
+        self.validate_invoice(&invoice).await?;
+
+        self.node.send_payment_async(invoice).await
+    }
+
+    async fn settle_hold_invoice(&self, preimage: String) -> Result<(), Error> {
+        self.node.settle_hold_invoice(preimage).await
+    }
+
+    async fn sign_message(&self, message: String) -> Result<String, Error> {
+        self.node.sign_message(message).await
+    }
+
+    async fn subscribe_invoices(
+        &self,
+        params: SubscribeInvoicesParams,
+    ) -> Result<InvoiceStream, Error> {
+        self.node.subscribe_invoices(params).await
+    }
+
+    async fn track_payment(&self, payment_hash: String) -> Result<PaymentStream, Error> {
+        self.node.track_payment(payment_hash).await
+    }
+
+    async fn update_channel_policy(&self, params: UpdatePolicyParams) -> Result<(), Error> {
+        self.node.update_channel_policy(params).await
+    }
+
+    async fn verify_message(
+        &self,
+        params: VerifyMessageParams,
+    ) -> Result<VerifyMessageResult, Error> {
+        self.node.verify_message(params).await
+    }
 }
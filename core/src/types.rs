@@ -11,6 +11,11 @@ pub struct NodeConfig {
     pub tls_client_certificate: Option<String>,
     pub username: Option<String>,
     pub password: Option<String>,
+    /// Seconds to wait for a connection to the node.
+    pub connect_timeout: Option<u64>,
+    /// Seconds to wait for the node to answer a request. Payments, channel
+    /// closes and subscriptions aren't bound by it.
+    pub request_timeout: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
@@ -19,10 +24,14 @@ pub enum Backend {
     LndGrpc,
     ClnGrpc,
     EclairRest,
+    /// In-memory node, built from a `Mock` with `Node::from`. `Node::new`
+    /// refuses it, as it can't be configured with a `NodeConfig`.
+    #[cfg(feature = "mock")]
+    Mock,
     InvalidBackend,
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Network {
     Mainnet,
@@ -38,6 +47,8 @@ impl Display for Backend {
             Backend::LndGrpc => String::from("LndGrpc"),
             Backend::ClnGrpc => String::from("ClnGrpc"),
             Backend::EclairRest => String::from("EclairRest"),
+            #[cfg(feature = "mock")]
+            Backend::Mock => String::from("Mock"),
             Backend::InvalidBackend => String::from("InvalidBackend"),
         };
 
@@ -52,6 +63,8 @@ impl From<&str> for Backend {
             "LndGrpc" => Backend::LndGrpc,
             "ClnGrpc" => Backend::ClnGrpc,
             "EclairRest" => Backend::EclairRest,
+            #[cfg(feature = "mock")]
+            "Mock" => Backend::Mock,
             // etc.
             _ => Backend::InvalidBackend,
         }
@@ -71,6 +84,22 @@ pub struct CreateInvoiceParams {
     pub cltv_expiry: Option<u32>,
 }
 
+/// Invoice whose payments are held until settled with the preimage, or
+/// cancelled.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct CreateHoldInvoiceParams {
+    /// Payment hash of the invoice, in hex, the preimage being kept by the
+    /// caller.
+    pub payment_hash: String,
+    pub amount: Option<u64>,
+    pub amount_msat: Option<u64>,
+    pub description: Option<String>,
+    pub description_hash: Option<String>,
+    pub expire_in: Option<u32>,
+    pub fallback_address: Option<String>,
+    pub cltv_expiry: Option<u32>,
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct CreateInvoiceResult {
     pub payment_request: String,
@@ -78,7 +107,7 @@ pub struct CreateInvoiceResult {
     pub label: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct Invoice {
     pub bolt11: String,
     pub memo: String,
@@ -93,7 +122,7 @@ pub struct Invoice {
     pub status: InvoiceStatus,
 }
 
-#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 pub enum InvoiceStatus {
     Pending,
     Settled,
@@ -101,6 +130,99 @@ pub enum InvoiceStatus {
     Accepted,
 }
 
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ListInvoicesParams {
+    pub status: Option<InvoiceStatus>,
+    pub pending_only: Option<bool>,
+    pub creation_date_start: Option<i64>,
+    pub creation_date_end: Option<i64>,
+    /// Opaque pagination cursor, as returned in `ListInvoicesResult::next_cursor`.
+    pub cursor: Option<u64>,
+    /// Size of the pages, 100 if not set or 0. Eclair looks up the settled
+    /// invoices of the page one by one, so at most this many calls are made.
+    pub max_invoices: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ListInvoicesResult {
+    pub invoices: Vec<Invoice>,
+    /// Cursor of the next page, or `None` once all invoices have been listed.
+    pub next_cursor: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub enum InvoiceEventType {
+    Created,
+    Settled,
+    Cancelled,
+    Accepted,
+}
+
+/// Position of an invoice event, to resume a subscription without missing events.
+///
+/// Eclair has no event index, so its events have an empty cursor and its
+/// subscriptions only send the events from now on.
+///
+/// LND subscriptions resumed without a settle index send every settlement again.
+#[derive(Debug, Default, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct InvoiceCursor {
+    pub add_index: Option<u64>,
+    pub settle_index: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
+pub struct SubscribeInvoicesParams {
+    /// Cursor of the last processed event. Only new events are sent when missing.
+    pub cursor: Option<InvoiceCursor>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct InvoiceEvent {
+    pub event_type: InvoiceEventType,
+    pub invoice: Invoice,
+    pub cursor: InvoiceCursor,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct DecodedInvoice {
+    pub bolt11: String,
+    pub network: Network,
+    pub payee: String,
+    pub amount: Option<u64>,
+    pub amount_msat: Option<u64>,
+    pub payment_hash: String,
+    pub payment_secret: Option<String>,
+    pub description: Option<String>,
+    pub description_hash: Option<String>,
+    pub creation_date: i64,
+    pub expiry: i32,
+    pub min_final_cltv_expiry: u64,
+    pub route_hints: Vec<RouteHint>,
+    pub features: Vec<Feature>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct RouteHint {
+    pub hops: Vec<RouteHintHop>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct RouteHintHop {
+    pub node_id: String,
+    pub short_channel_id: String,
+    pub fee_base_msat: u32,
+    pub fee_proportional_millionths: u32,
+    pub cltv_expiry_delta: u16,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct Feature {
+    pub bit: u32,
+    pub name: Option<String>,
+    pub is_required: bool,
+    pub is_known: bool,
+}
+
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct ChannelStats {
     pub active: i64,
@@ -133,3 +255,358 @@ pub struct PayInvoiceResult {
     pub payment_preimage: String,
     pub fees_msat: Option<u64>,
 }
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct KeysendParams {
+    /// Public key of the node to pay, in hex.
+    pub destination: String,
+    pub amount: Option<u64>,
+    pub amount_msat: Option<u64>,
+    /// Records sent to the destination along with the payment. Not supported
+    /// by Core Lightning before v22.11.
+    pub custom_records: Option<Vec<CustomRecord>>,
+    pub max_fee_sat: Option<u64>,
+    pub max_fee_msat: Option<u64>,
+    pub max_fee_percent: Option<f64>,
+}
+
+/// TLV record of a payment, such as the podcast metadata of a boostagram.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct CustomRecord {
+    pub record_type: u64,
+    /// Value of the record, in hex.
+    pub value: String,
+}
+
+/// Funds of the node, all amounts in msat.
+#[derive(Debug, Default, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct Balance {
+    pub onchain_confirmed_msat: u64,
+    pub onchain_unconfirmed_msat: u64,
+    /// On-chain funds reserved or leased, which can't be spent for now.
+    pub onchain_locked_msat: u64,
+    /// Local funds of the open channels which can be sent, without the
+    /// channel reserves.
+    pub lightning_local_msat: u64,
+    /// Remote funds of the open channels which can be received, without the
+    /// channel reserves.
+    pub lightning_remote_msat: u64,
+    /// Local funds of the channels being opened.
+    pub pending_open_msat: u64,
+    /// Local funds of the channels being closed, not back in the wallet yet.
+    pub pending_close_msat: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub enum ChannelStatus {
+    Opening,
+    Open,
+    Closing,
+    Closed,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct Channel {
+    /// Channel id of BOLT 2, in hex.
+    pub channel_id: String,
+    /// Funding outpoint of the channel, as `txid:output_index`.
+    pub channel_point: Option<String>,
+    /// Short channel id as `block x transaction x output`, once the funding
+    /// transaction is confirmed.
+    pub short_channel_id: Option<String>,
+    pub peer_pubkey: String,
+    pub capacity_msat: u64,
+    pub local_balance_msat: u64,
+    pub remote_balance_msat: u64,
+    /// Amount the node must keep on its side of the channel.
+    pub local_reserve_msat: u64,
+    /// Amount the peer must keep on its side of the channel.
+    pub remote_reserve_msat: u64,
+    /// Whether the channel can be used for payments, open with the peer online.
+    pub active: bool,
+    /// Whether the channel isn't announced to the network.
+    pub private: bool,
+    /// Whether the node opened the channel.
+    pub initiator: bool,
+    pub status: ChannelStatus,
+}
+
+/// Fees and limits the node applies to the payments it forwards through a
+/// channel.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ChannelPolicy {
+    /// Channel id of BOLT 2, in hex.
+    pub channel_id: String,
+    pub base_fee_msat: u64,
+    /// Proportional fee, in millionths of the forwarded amount.
+    pub fee_rate_ppm: u64,
+    /// CLTV delta required to forward a payment, unknown to Core Lightning
+    /// until the channel is announced.
+    pub time_lock_delta: Option<u32>,
+    pub min_htlc_msat: u64,
+    pub max_htlc_msat: Option<u64>,
+}
+
+/// Changes of the forwarding policy, the fields left to `None` are kept.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct UpdatePolicyParams {
+    /// Channel id of BOLT 2 of the channel to update, every channel if `None`.
+    /// Eclair sets the fees by peer, and fails when the peer has other channels.
+    pub channel_id: Option<String>,
+    pub base_fee_msat: Option<u64>,
+    /// Proportional fee, in millionths of the forwarded amount.
+    pub fee_rate_ppm: Option<u64>,
+    pub time_lock_delta: Option<u32>,
+    pub min_htlc_msat: Option<u64>,
+    pub max_htlc_msat: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct OpenChannelParams {
+    /// Public key of the peer to open the channel with, in hex.
+    pub peer_pubkey: String,
+    /// Address of the peer as `host:port`, to connect to it first when given.
+    pub peer_host: Option<String>,
+    /// Funding amount of the channel, in sat.
+    pub amount: u64,
+    /// Amount given to the peer on opening, in sat.
+    pub push_amount: Option<u64>,
+    /// Fee rate of the funding transaction, in sat/vbyte.
+    pub fee_rate: Option<u64>,
+    /// Whether to keep the channel unannounced to the network.
+    pub private: Option<bool>,
+    /// Minimum confirmations of the outputs funding the channel.
+    pub min_confs: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct OpenChannelResult {
+    /// Channel id of BOLT 2, in hex.
+    pub channel_id: String,
+    pub funding_txid: String,
+    pub output_index: u32,
+}
+
+/// Channel to close, given by either its channel point or its channel id.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct CloseChannelParams {
+    /// Funding outpoint of the channel, as `txid:output_index`.
+    pub channel_point: Option<String>,
+    /// Channel id of BOLT 2, in hex.
+    pub channel_id: Option<String>,
+    /// Whether to publish the commitment transaction instead of negotiating
+    /// the close with the peer, without a fee rate or delivery address. Core
+    /// Lightning still negotiates for a second first, so the close can end up
+    /// a mutual one with a peer online.
+    pub force: Option<bool>,
+    /// Fee rate of the closing transaction, in sat/vbyte.
+    pub fee_rate: Option<u64>,
+    /// Address the local funds are sent to, a wallet address otherwise.
+    pub delivery_address: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct CloseChannelResult {
+    /// Transactions closing the channel, empty while the backend doesn't know
+    /// them yet.
+    pub closing_txids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ConnectPeerParams {
+    /// Public key of the peer, in hex.
+    pub pubkey: String,
+    /// Address of the peer as `host:port`. When missing, LND takes the first
+    /// address of the peer in the network graph, while Core Lightning and
+    /// Eclair look it up on their own.
+    pub host: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct Peer {
+    pub pubkey: String,
+    /// Network addresses of the peer, as `host:port`.
+    pub addresses: Vec<String>,
+    pub connected: bool,
+    /// Features the peer advertised, empty when the backend doesn't tell them.
+    pub features: Vec<Feature>,
+    /// Whether the peer opened the connection, `None` when the backend
+    /// doesn't tell.
+    pub inbound: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub enum AddressType {
+    /// Native segwit v0 address (bech32).
+    P2wpkh,
+    /// Taproot address (bech32m).
+    P2tr,
+    /// Segwit v0 address nested in P2SH, for wallets without bech32 support.
+    NestedP2wpkh,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct SendOnchainParams {
+    pub address: String,
+    /// Amount to send in sat, required unless `send_all` is set.
+    pub amount: Option<u64>,
+    /// Sends all the funds of the wallet, the fee being taken out of them.
+    pub send_all: Option<bool>,
+    /// Fee rate of the transaction, in sat/vbyte.
+    pub fee_rate: Option<u64>,
+    /// Number of blocks the transaction should confirm within, used to
+    /// estimate the fee rate when `fee_rate` is missing.
+    pub target_conf: Option<u32>,
+    /// Outputs to spend, as `txid:output_index`, picked by the wallet when
+    /// missing.
+    pub utxos: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct OnchainTransaction {
+    pub txid: String,
+    /// Funds the transaction brought to the wallet, in msat.
+    pub amount_received_msat: u64,
+    /// Funds the transaction took out of the wallet, fee included, in msat.
+    pub amount_sent_msat: u64,
+    /// Fee paid by the wallet, in msat, `None` when the backend doesn't tell.
+    pub fee_msat: Option<u64>,
+    pub confirmations: u32,
+    /// Height of the block including the transaction, `None` while
+    /// unconfirmed.
+    pub block_height: Option<u32>,
+    /// UNIX time the transaction was first seen, `None` when the backend
+    /// doesn't tell.
+    pub timestamp: Option<u64>,
+    pub label: Option<String>,
+    /// Short channel id of the channel the transaction opens or closes, as
+    /// `block x transaction x output`.
+    pub short_channel_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub enum PaymentStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct PaymentPart {
+    /// Amount delivered by the part, in msat.
+    pub amount_msat: u64,
+    pub fee_msat: u64,
+    pub status: PaymentStatus,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct Payment {
+    pub payment_hash: String,
+    /// Preimage of the payment, `None` until it succeeds.
+    pub payment_preimage: Option<String>,
+    /// Amount delivered to the destination, in msat.
+    pub amount_msat: u64,
+    /// Fee paid to the routing nodes, in msat, `None` when the backend
+    /// doesn't tell.
+    pub fee_msat: Option<u64>,
+    pub status: PaymentStatus,
+    /// UNIX time the payment was started.
+    pub created_at: i64,
+    /// Public key of the node paid, in hex.
+    pub destination: Option<String>,
+    /// Invoice paid, `None` for keysend payments.
+    pub bolt11: Option<String>,
+    pub failure_reason: Option<String>,
+    /// Parts the payment was split into, empty when the backend doesn't tell
+    /// them.
+    pub parts: Vec<PaymentPart>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ListPaymentsParams {
+    /// Eclair only lists the payments that succeeded, and doesn't support
+    /// the other statuses.
+    pub status: Option<PaymentStatus>,
+    pub creation_date_start: Option<i64>,
+    pub creation_date_end: Option<i64>,
+    /// Opaque pagination cursor, as returned in `ListPaymentsResult::next_cursor`.
+    pub cursor: Option<u64>,
+    pub max_payments: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ListPaymentsResult {
+    pub payments: Vec<Payment>,
+    /// Cursor of the next page, or `None` once all payments have been listed.
+    pub next_cursor: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub enum ForwardStatus {
+    Offered,
+    Settled,
+    Failed,
+}
+
+/// Payment forwarded by the node from one of its channels to another.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct Forward {
+    /// Channel id of BOLT 2 of the incoming channel, in hex, `None` when the
+    /// backend only tells its short channel id and the channel is closed.
+    pub incoming_channel_id: Option<String>,
+    /// Short channel id of the incoming channel, as
+    /// `block x transaction x output`.
+    pub incoming_short_channel_id: Option<String>,
+    /// Channel id of BOLT 2 of the outgoing channel, in hex, `None` when the
+    /// backend only tells its short channel id and the channel is closed.
+    pub outgoing_channel_id: Option<String>,
+    /// Short channel id of the outgoing channel, as
+    /// `block x transaction x output`.
+    pub outgoing_short_channel_id: Option<String>,
+    pub amount_in_msat: u64,
+    /// Amount sent on the outgoing channel, `None` when the payment failed
+    /// before an outgoing channel was picked.
+    pub amount_out_msat: Option<u64>,
+    /// Fee earned by the node, zero unless the forward is settled.
+    pub fee_msat: u64,
+    pub status: ForwardStatus,
+    /// UNIX time the incoming HTLC was received, `None` when the backend
+    /// doesn't tell.
+    pub received_at: Option<i64>,
+    /// UNIX time the forward was settled or failed, `None` while it is
+    /// pending or when the backend doesn't tell.
+    pub resolved_at: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ListForwardsParams {
+    pub date_start: Option<i64>,
+    pub date_end: Option<i64>,
+    /// Opaque pagination cursor, as returned in `ListForwardsResult::next_cursor`.
+    pub cursor: Option<u64>,
+    pub max_forwards: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ListForwardsResult {
+    pub forwards: Vec<Forward>,
+    /// Cursor of the next page, or `None` once all forwards have been listed.
+    pub next_cursor: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct VerifyMessageParams {
+    pub message: String,
+    /// Signature of the message, in zbase32 as returned by `sign_message`.
+    pub signature: String,
+    /// Public key the signature must be made by, in hex. Without it, LND and
+    /// Core Lightning only accept the signatures of nodes in their graph.
+    pub pubkey: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct VerifyMessageResult {
+    pub valid: bool,
+    /// Public key recovered from the signature, in hex.
+    pub pubkey: String,
+}
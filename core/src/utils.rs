@@ -1,6 +1,24 @@
 use std::ops::{Div, Mul};
+use std::time::Duration;
+
+use bech32::FromBase32;
+use sha2::{Digest, Sha256};
 
 use crate::error::Error;
+use crate::types::{
+    Channel, ChannelPolicy, ChannelStatus, CloseChannelParams, Forward, Invoice, InvoiceStatus,
+    ListForwardsParams, ListInvoicesParams, ListPaymentsParams, Payment, SendOnchainParams,
+    UpdatePolicyParams,
+};
+
+pub const DEFAULT_MAX_INVOICES: u64 = 100;
+pub const DEFAULT_MAX_PAYMENTS: u64 = 100;
+pub const DEFAULT_MAX_FORWARDS: u64 = 100;
+
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// TLV record type of the preimage in keysend payments.
+pub const KEYSEND_PREIMAGE_RECORD: u64 = 5482373484;
 
 pub fn sat_to_msat<T>(sat: T) -> T
 where
@@ -38,7 +56,385 @@ where
     }
 }
 
+/// Resolves the fee limit of a payment of `amount_msat` to an absolute amount.
+pub fn get_max_fee_msat(
+    amount_msat: u64,
+    max_fee_sat: Option<u64>,
+    max_fee_msat: Option<u64>,
+    max_fee_percent: Option<f64>,
+) -> Option<u64> {
+    match (get_amount_msat(max_fee_sat, max_fee_msat), max_fee_percent) {
+        (Some(max_fee_msat), _) => Some(max_fee_msat),
+        (None, Some(max_fee_percent)) => {
+            Some((amount_msat as f64 * max_fee_percent / 100.0) as u64)
+        }
+        (None, None) => None,
+    }
+}
+
+/// Generates the preimage of a keysend payment, along with its payment hash.
+pub fn keysend_preimage() -> ([u8; 32], [u8; 32]) {
+    let preimage: [u8; 32] = rand::random();
+    let payment_hash: [u8; 32] = Sha256::digest(preimage).into();
+
+    (preimage, payment_hash)
+}
+
 pub fn b64_to_hex(b64: &str) -> Result<String, Error> {
     let bytes = base64::decode(b64)?;
     Ok(hex::encode(&bytes))
 }
+
+/// Formats a transaction id given in its internal byte order, which is
+/// reversed from how transaction ids are displayed.
+pub fn txid_to_hex(mut txid: Vec<u8>) -> String {
+    txid.reverse();
+    hex::encode(txid)
+}
+
+/// Formats a short channel id as `block x transaction x output`.
+pub fn format_short_channel_id(short_channel_id: u64) -> String {
+    format!(
+        "{}x{}x{}",
+        short_channel_id >> 40,
+        (short_channel_id >> 16) & 0xFFFFFF,
+        short_channel_id & 0xFFFF
+    )
+}
+
+/// Reads a short channel id formatted as `block x transaction x output`.
+pub fn parse_short_channel_id(short_channel_id: &str) -> Result<u64, Error> {
+    let invalid =
+        || Error::ConversionError(format!("invalid short channel id {}", short_channel_id));
+
+    let parts = short_channel_id
+        .split('x')
+        .map(str::parse::<u64>)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| invalid())?;
+    match parts[..] {
+        [block, transaction, output] => Ok(block << 40 | transaction << 16 | output),
+        _ => Err(invalid()),
+    }
+}
+
+/// Reads the short channel id from the label LND gives to the transactions
+/// of its channels, such as `0:openchannel:shortchanid-<id>`.
+pub fn short_channel_id_from_lnd_label(label: &str) -> Option<String> {
+    let (_, short_channel_id) = label.split_once("shortchanid-")?;
+
+    short_channel_id.parse().ok().map(format_short_channel_id)
+}
+
+/// Derives the channel id of BOLT 2 from the funding outpoint of a channel,
+/// given as `txid:output_index`.
+pub fn channel_id_from_channel_point(channel_point: &str) -> Result<String, Error> {
+    let invalid = || Error::ConversionError(format!("invalid channel point {}", channel_point));
+
+    let (txid, output_index) = channel_point.split_once(':').ok_or_else(invalid)?;
+    let output_index: u16 = output_index.parse().map_err(|_| invalid())?;
+    // Transaction ids are displayed in reverse byte order.
+    let mut channel_id = hex::decode(txid)?;
+    channel_id.reverse();
+    if channel_id.len() != 32 {
+        return Err(invalid());
+    }
+
+    let [high, low] = output_index.to_be_bytes();
+    channel_id[30] ^= high;
+    channel_id[31] ^= low;
+
+    Ok(hex::encode(channel_id))
+}
+
+/// Fills the fields of `params` left to `None` from the current `policy` of
+/// the channel, for the backends setting the whole policy at once.
+pub fn fill_channel_policy(
+    params: &UpdatePolicyParams,
+    policy: &ChannelPolicy,
+) -> UpdatePolicyParams {
+    UpdatePolicyParams {
+        channel_id: params.channel_id.clone(),
+        base_fee_msat: params.base_fee_msat.or(Some(policy.base_fee_msat)),
+        fee_rate_ppm: params.fee_rate_ppm.or(Some(policy.fee_rate_ppm)),
+        time_lock_delta: params.time_lock_delta.or(policy.time_lock_delta),
+        min_htlc_msat: params.min_htlc_msat.or(Some(policy.min_htlc_msat)),
+        max_htlc_msat: params.max_htlc_msat.or(policy.max_htlc_msat),
+    }
+}
+
+/// Resolves the channel id of the channel to close from either of the ways
+/// it can be given.
+pub fn get_channel_id(params: &CloseChannelParams) -> Result<String, Error> {
+    match (&params.channel_id, &params.channel_point) {
+        (Some(channel_id), _) => Ok(channel_id.clone()),
+        (None, Some(channel_point)) => channel_id_from_channel_point(channel_point),
+        (None, None) => Err(Error::ApiError(String::from(
+            "channel_point or channel_id is required",
+        ))),
+    }
+}
+
+/// Recovers the funding output index of a channel from its channel id and
+/// funding transaction id, reversing `channel_id_from_channel_point`.
+pub fn output_index_from_channel_id(channel_id: &str, funding_txid: &str) -> Result<u32, Error> {
+    let channel_id = hex::decode(channel_id)?;
+    let mut txid = hex::decode(funding_txid)?;
+    txid.reverse();
+    if channel_id.len() != 32 || txid.len() != 32 || channel_id[..30] != txid[..30] {
+        return Err(Error::ConversionError(String::from(
+            "channel id doesn't match the funding transaction",
+        )));
+    }
+
+    let output_index = u16::from_be_bytes([channel_id[30] ^ txid[30], channel_id[31] ^ txid[31]]);
+
+    Ok(output_index.into())
+}
+
+/// Builds the output script paying to a segwit address, for the APIs taking
+/// scripts instead of addresses.
+pub fn segwit_script_pubkey(address: &str) -> Result<Vec<u8>, Error> {
+    let invalid = || Error::ConversionError(format!("unsupported address {}", address));
+
+    let (_, data, _) = bech32::decode(address).map_err(|_| invalid())?;
+    let (version, program) = data.split_first().ok_or_else(invalid)?;
+    let program = Vec::<u8>::from_base32(program).map_err(|_| invalid())?;
+    let version = version.to_u8();
+    if version > 16 || !(2..=40).contains(&program.len()) {
+        return Err(invalid());
+    }
+
+    // Version 0 is pushed as `OP_0`, the next ones as `OP_1` to `OP_16`.
+    let mut script = match version {
+        0 => vec![0x00],
+        version => vec![0x50 + version],
+    };
+    script.push(program.len() as u8);
+    script.extend(program);
+
+    Ok(script)
+}
+
+/// Resolves the amount of an on-chain send in sat, `None` meaning all the
+/// funds of the wallet.
+pub fn get_onchain_amount(params: &SendOnchainParams) -> Result<Option<u64>, Error> {
+    match (params.amount, params.send_all.unwrap_or(false)) {
+        (Some(amount), false) => Ok(Some(amount)),
+        (None, true) => Ok(None),
+        (Some(_), true) => Err(Error::ApiError(String::from(
+            "amount and send_all are exclusive",
+        ))),
+        (None, false) => Err(Error::ApiError(String::from(
+            "amount or send_all is required",
+        ))),
+    }
+}
+
+/// Checks an invoice against the filters of `params`, for backends that
+/// can't filter invoices on their side.
+pub fn invoice_matches(invoice: &Invoice, params: &ListInvoicesParams) -> bool {
+    let is_pending = matches!(
+        invoice.status,
+        InvoiceStatus::Pending | InvoiceStatus::Accepted
+    );
+
+    if params.pending_only.unwrap_or(false) && !is_pending {
+        return false;
+    }
+
+    if let Some(status) = &params.status {
+        if *status != invoice.status {
+            return false;
+        }
+    }
+
+    if let Some(creation_date_start) = params.creation_date_start {
+        if invoice.creation_date < creation_date_start {
+            return false;
+        }
+    }
+
+    if let Some(creation_date_end) = params.creation_date_end {
+        if invoice.creation_date > creation_date_end {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Checks a payment against the filters of `params`, for backends that
+/// can't filter payments on their side.
+pub fn payment_matches(payment: &Payment, params: &ListPaymentsParams) -> bool {
+    if let Some(status) = &params.status {
+        if *status != payment.status {
+            return false;
+        }
+    }
+
+    if let Some(creation_date_start) = params.creation_date_start {
+        if payment.created_at < creation_date_start {
+            return false;
+        }
+    }
+
+    if let Some(creation_date_end) = params.creation_date_end {
+        if payment.created_at > creation_date_end {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Checks a forward against the dates of `params`, for backends that can't
+/// filter forwards on their side. Forwards are dated by the time their incoming
+/// HTLC was received, or resolved when the backend doesn't tell.
+pub fn forward_matches(forward: &Forward, params: &ListForwardsParams) -> bool {
+    let date = match forward.received_at.or(forward.resolved_at) {
+        Some(date) => date,
+        None => return true,
+    };
+
+    if let Some(date_start) = params.date_start {
+        if date < date_start {
+            return false;
+        }
+    }
+
+    if let Some(date_end) = params.date_end {
+        if date > date_end {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Fills the channel ids or short channel ids the backend doesn't tell in
+/// forwards, from the channels of the node. Closed channels are left as is.
+pub fn fill_forward_channels(forwards: &mut [Forward], channels: &[Channel]) {
+    let find = |channel_id: &Option<String>, short_channel_id: &Option<String>| {
+        channels.iter().find(|channel| {
+            channel_id.as_ref() == Some(&channel.channel_id)
+                || (short_channel_id.is_some() && channel.short_channel_id == *short_channel_id)
+        })
+    };
+
+    for forward in forwards.iter_mut() {
+        if let Some(channel) = find(
+            &forward.incoming_channel_id,
+            &forward.incoming_short_channel_id,
+        ) {
+            forward.incoming_channel_id = Some(channel.channel_id.clone());
+            forward.incoming_short_channel_id = channel.short_channel_id.clone();
+        }
+
+        if let Some(channel) = find(
+            &forward.outgoing_channel_id,
+            &forward.outgoing_short_channel_id,
+        ) {
+            forward.outgoing_channel_id = Some(channel.channel_id.clone());
+            forward.outgoing_short_channel_id = channel.short_channel_id.clone();
+        }
+    }
+}
+
+/// Funds of the open channels which can be sent and received, the reserves
+/// both sides must keep being left out.
+pub fn spendable_balance_msat(channels: &[Channel]) -> (u64, u64) {
+    let mut local_msat = 0;
+    let mut remote_msat = 0;
+    for channel in channels {
+        if channel.status == ChannelStatus::Open {
+            local_msat += channel
+                .local_balance_msat
+                .saturating_sub(channel.local_reserve_msat);
+            remote_msat += channel
+                .remote_balance_msat
+                .saturating_sub(channel.remote_reserve_msat);
+        }
+    }
+
+    (local_msat, remote_msat)
+}
+
+/// Size of the pages, `default` unless `max_items` asks for another one. A
+/// size of 0 also gets the default, as empty pages would never move the
+/// cursor forward.
+pub fn page_size(max_items: Option<u64>, default: u64) -> u64 {
+    max_items
+        .filter(|max_items| *max_items > 0)
+        .unwrap_or(default)
+}
+
+/// Returns the page of `items` starting at `cursor`, along with the cursor of
+/// the next page if there are items left.
+pub fn paginate<T>(
+    items: Vec<T>,
+    cursor: Option<u64>,
+    max_items: Option<u64>,
+    default_max_items: u64,
+) -> (Vec<T>, Option<u64>) {
+    let offset = cursor.unwrap_or(0) as usize;
+    let max_items = page_size(max_items, default_max_items) as usize;
+    let total = items.len();
+
+    let page: Vec<T> = items.into_iter().skip(offset).take(max_items).collect();
+
+    let next_offset = offset + page.len();
+    let next_cursor = match next_offset < total {
+        true => Some(next_offset as u64),
+        false => None,
+    };
+
+    (page, next_cursor)
+}
+
+const ZBASE32_CHARSET: &str = "ybndrfg8ejkmcpqxot1uwisza345h769";
+
+/// Encodes bytes in zbase32, the encoding of the message signatures of LND and
+/// Core Lightning.
+pub fn zbase32_encode(data: &[u8]) -> String {
+    let charset = ZBASE32_CHARSET.as_bytes();
+    let mut encoded = String::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for byte in data {
+        buffer = buffer << 8 | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(charset[(buffer >> bits) as usize & 0x1F] as char);
+        }
+    }
+
+    if bits > 0 {
+        encoded.push(charset[(buffer << (5 - bits)) as usize & 0x1F] as char);
+    }
+
+    encoded
+}
+
+/// Decodes zbase32, dropping the bits left over after the last full byte.
+pub fn zbase32_decode(encoded: &str) -> Result<Vec<u8>, Error> {
+    let mut data = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for c in encoded.chars() {
+        let value = ZBASE32_CHARSET
+            .find(c)
+            .ok_or_else(|| Error::ConversionError(String::from("invalid zbase32 string")))?;
+        buffer = buffer << 5 | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            data.push((buffer >> bits) as u8);
+        }
+    }
+
+    Ok(data)
+}
@@ -0,0 +1,1604 @@
+mod common;
+
+use futures::StreamExt;
+use tonic::{Code, Status};
+
+use common::{fixture, ClnStub, RunningClnStub};
+use una_core::backends::cln::grpc::node::ClnGrpc;
+use una_core::backends::cln::grpc::pb;
+use una_core::error::Error;
+use una_core::node::NodeMethods;
+use una_core::types::{
+    AddressType, ChannelStatus, CloseChannelParams, ConnectPeerParams, CreateHoldInvoiceParams,
+    CreateInvoiceParams, CustomRecord, ForwardStatus, InvoiceCursor, InvoiceEventType,
+    InvoiceStatus, KeysendParams, ListForwardsParams, ListInvoicesParams, ListPaymentsParams,
+    Network, OpenChannelParams, PayInvoiceParams, PaymentStatus, SendOnchainParams,
+    SubscribeInvoicesParams, UpdatePolicyParams, VerifyMessageParams,
+};
+
+const NODE_ID: &str = "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad";
+const PAYMENT_HASH: &str = "0001020304050607080900010203040506070809000102030405060708090102";
+const PAYMENT_HASH_2: &str = "44590ee42f1b6224fe0e0ec9e5ab41ee2dc795fe727f690f1c0881ca9126909d";
+const PAYMENT_PREIMAGE: &str = "5e2b9c0bb4d0f0a6c5e3b1f7c2d8a9e0f1a2b3c4d5e6f708192a3b4c5d6e7f80";
+
+fn cln(stub: &RunningClnStub) -> ClnGrpc {
+    ClnGrpc::new(stub.config().try_into().unwrap()).unwrap()
+}
+
+fn bolt11() -> String {
+    fixture("bolt11.txt").trim().to_string()
+}
+
+fn invoice(payment_hash: &str, paid: bool) -> pb::ListinvoicesInvoices {
+    let status = match paid {
+        true => pb::listinvoices_invoices::ListinvoicesInvoicesStatus::Paid,
+        false => pb::listinvoices_invoices::ListinvoicesInvoicesStatus::Unpaid,
+    };
+
+    pb::ListinvoicesInvoices {
+        label: format!("label-{}", &payment_hash[..8]),
+        description: Some(String::from("1 cup coffee")),
+        payment_hash: hex::decode(payment_hash).unwrap(),
+        status: status as i32,
+        expires_at: 1496314718,
+        amount_msat: Some(pb::Amount { msat: 250000000 }),
+        bolt11: Some(bolt11()),
+        pay_index: paid.then_some(5),
+        amount_received_msat: paid.then_some(pb::Amount { msat: 250000000 }),
+        paid_at: paid.then_some(1496314700),
+        payment_preimage: paid.then(|| hex::decode(PAYMENT_PREIMAGE).unwrap()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn get_info() {
+    let stub = ClnStub::new()
+        .reply(
+            "Getinfo",
+            pb::GetinfoResponse {
+                id: hex::decode(NODE_ID).unwrap(),
+                alias: String::from("carol"),
+                num_active_channels: 3,
+                num_inactive_channels: 2,
+                num_pending_channels: 1,
+                version: String::from("v0.11.2"),
+                network: String::from("bitcoin"),
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    let info = cln(&stub).get_info().await.unwrap();
+
+    assert_eq!(info.node_pubkey, NODE_ID);
+    assert_eq!(info.version, "v0.11.2");
+    assert!(matches!(info.network, Network::Mainnet));
+    assert_eq!(info.channels.active, 3);
+    assert_eq!(info.channels.inactive, 2);
+    assert_eq!(info.channels.pending, 1);
+}
+
+#[tokio::test]
+async fn get_balance() {
+    use pb::listfunds_outputs::ListfundsOutputsStatus;
+    use pb::listpeers_peers_channels::ListpeersPeersChannelsState as State;
+
+    let output =
+        |amount_msat: u64, status: ListfundsOutputsStatus, reserved: bool| pb::ListfundsOutputs {
+            amount_msat: Some(pb::Amount { msat: amount_msat }),
+            status: status as i32,
+            reserved,
+            ..Default::default()
+        };
+    let channel =
+        |our_amount_msat: u64, amount_msat: u64, state: pb::ChannelState| pb::ListfundsChannels {
+            our_amount_msat: Some(pb::Amount {
+                msat: our_amount_msat,
+            }),
+            amount_msat: Some(pb::Amount { msat: amount_msat }),
+            state: state as i32,
+            ..Default::default()
+        };
+    let peer_channel =
+        |state: State, spendable_msat: u64, receivable_msat: u64| pb::ListpeersPeersChannels {
+            state: state as i32,
+            spendable_msat: Some(pb::Amount {
+                msat: spendable_msat,
+            }),
+            receivable_msat: Some(pb::Amount {
+                msat: receivable_msat,
+            }),
+            ..Default::default()
+        };
+    let stub = ClnStub::new()
+        .reply(
+            "ListFunds",
+            pb::ListfundsResponse {
+                outputs: vec![
+                    output(1000000000, ListfundsOutputsStatus::Confirmed, false),
+                    output(200000000, ListfundsOutputsStatus::Confirmed, false),
+                    output(300000000, ListfundsOutputsStatus::Unconfirmed, false),
+                    output(50000000, ListfundsOutputsStatus::Confirmed, true),
+                    output(70000000, ListfundsOutputsStatus::Spent, false),
+                ],
+                channels: vec![
+                    channel(700000500, 950000500, pb::ChannelState::ChanneldNormal),
+                    channel(
+                        200000000,
+                        200000000,
+                        pb::ChannelState::ChanneldAwaitingLockin,
+                    ),
+                    channel(80000000, 100000000, pb::ChannelState::ClosingdComplete),
+                    channel(90000000, 90000000, pb::ChannelState::Onchain),
+                ],
+            },
+        )
+        .reply(
+            "ListPeers",
+            pb::ListpeersResponse {
+                peers: vec![pb::ListpeersPeers {
+                    id: hex::decode(NODE_ID).unwrap(),
+                    channels: vec![
+                        peer_channel(State::ChanneldNormal, 693000500, 247500000),
+                        peer_channel(State::ChanneldAwaitingLockin, 198000000, 0),
+                    ],
+                    ..Default::default()
+                }],
+            },
+        )
+        .start()
+        .await;
+
+    let balance = cln(&stub).get_balance().await.unwrap();
+
+    assert_eq!(balance.onchain_confirmed_msat, 1200000000);
+    assert_eq!(balance.onchain_unconfirmed_msat, 300000000);
+    assert_eq!(balance.onchain_locked_msat, 50000000);
+    // The spendable amounts leave the channel reserves out.
+    assert_eq!(balance.lightning_local_msat, 693000500);
+    assert_eq!(balance.lightning_remote_msat, 247500000);
+    assert_eq!(balance.pending_open_msat, 200000000);
+    assert_eq!(balance.pending_close_msat, 80000000);
+}
+
+#[tokio::test]
+async fn list_channels() {
+    use pb::listpeers_peers_channels::ListpeersPeersChannelsState as State;
+
+    let channel = |state: State, funding_txid: Option<&str>| pb::ListpeersPeersChannels {
+        state: state as i32,
+        short_channel_id: funding_txid.map(|_| String::from("761300x1x0")),
+        channel_id: Some(vec![0x4d; 32]),
+        funding_txid: funding_txid.map(|txid| hex::decode(txid).unwrap()),
+        funding_outnum: funding_txid.map(|_| 0),
+        private: Some(false),
+        opener: pb::ChannelSide::In as i32,
+        to_us_msat: Some(pb::Amount { msat: 700000500 }),
+        total_msat: Some(pb::Amount { msat: 950000500 }),
+        our_reserve_msat: Some(pb::Amount { msat: 9500000 }),
+        their_reserve_msat: Some(pb::Amount { msat: 9500000 }),
+        ..Default::default()
+    };
+    let funding_txid = "c3f7e8f7d1a2b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d";
+    let stub = ClnStub::new()
+        .reply(
+            "ListPeers",
+            pb::ListpeersResponse {
+                peers: vec![
+                    pb::ListpeersPeers {
+                        id: hex::decode(NODE_ID).unwrap(),
+                        connected: true,
+                        channels: vec![
+                            channel(State::ChanneldNormal, Some(funding_txid)),
+                            channel(State::Openingd, None),
+                        ],
+                        ..Default::default()
+                    },
+                    pb::ListpeersPeers {
+                        id: hex::decode(NODE_ID).unwrap(),
+                        connected: false,
+                        channels: vec![channel(State::ChanneldNormal, Some(funding_txid))],
+                        ..Default::default()
+                    },
+                ],
+            },
+        )
+        .start()
+        .await;
+
+    let channels = cln(&stub).list_channels().await.unwrap();
+
+    assert_eq!(channels.len(), 3);
+    let open = &channels[0];
+    assert_eq!(open.status, ChannelStatus::Open);
+    assert_eq!(open.channel_id, "4d".repeat(32));
+    assert_eq!(open.channel_point, Some(format!("{}:0", funding_txid)));
+    assert_eq!(open.peer_pubkey, NODE_ID);
+    assert_eq!(open.local_balance_msat, 700000500);
+    assert_eq!(open.remote_balance_msat, 250000000);
+    assert!(open.active);
+    assert!(open.initiator);
+
+    assert_eq!(channels[1].status, ChannelStatus::Opening);
+    assert_eq!(channels[1].channel_point, None);
+    assert!(!channels[1].active);
+    // Open channels with a disconnected peer can't be used.
+    assert!(!channels[2].active);
+}
+
+#[tokio::test]
+async fn get_channel_policy() {
+    let stub = ClnStub::new()
+        .reply(
+            "ListPeers",
+            pb::ListpeersResponse {
+                peers: vec![pb::ListpeersPeers {
+                    id: hex::decode(NODE_ID).unwrap(),
+                    channels: vec![pb::ListpeersPeersChannels {
+                        short_channel_id: Some(String::from("761300x1x0")),
+                        channel_id: Some(vec![0x4d; 32]),
+                        fee_base_msat: Some(pb::Amount { msat: 1000 }),
+                        fee_proportional_millionths: Some(10),
+                        minimum_htlc_out_msat: Some(pb::Amount { msat: 0 }),
+                        maximum_htlc_out_msat: Some(pb::Amount { msat: 990000000 }),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+            },
+        )
+        .reply(
+            "ListChannels",
+            pb::ListchannelsResponse {
+                channels: vec![pb::ListchannelsChannels {
+                    destination: hex::decode(NODE_ID).unwrap(),
+                    short_channel_id: String::from("761300x1x0"),
+                    delay: 34,
+                    ..Default::default()
+                }],
+            },
+        )
+        .start()
+        .await;
+
+    let policy = cln(&stub)
+        .get_channel_policy("4d".repeat(32))
+        .await
+        .unwrap();
+
+    assert_eq!(policy.channel_id, "4d".repeat(32));
+    assert_eq!(policy.base_fee_msat, 1000);
+    assert_eq!(policy.fee_rate_ppm, 10);
+    assert_eq!(policy.time_lock_delta, Some(34));
+    assert_eq!(policy.min_htlc_msat, 0);
+    assert_eq!(policy.max_htlc_msat, Some(990000000));
+
+    // The direction of the channel set by the node is the one to the peer.
+    let request: pb::ListchannelsRequest = stub.request("ListChannels");
+    assert_eq!(request.short_channel_id.as_deref(), Some("761300x1x0"));
+    assert_eq!(request.destination, Some(hex::decode(NODE_ID).unwrap()));
+}
+
+#[tokio::test]
+async fn update_channel_policy() {
+    let stub = ClnStub::new()
+        .reply("SetChannel", pb::SetchannelResponse { channels: vec![] })
+        .start()
+        .await;
+
+    let params = UpdatePolicyParams {
+        fee_rate_ppm: Some(250),
+        min_htlc_msat: Some(1000),
+        ..Default::default()
+    };
+    cln(&stub).update_channel_policy(params).await.unwrap();
+
+    let request: pb::SetchannelRequest = stub.request("SetChannel");
+    assert_eq!(request.id, "all");
+    assert_eq!(request.feebase, None);
+    assert_eq!(request.feeppm, Some(250));
+    assert_eq!(request.htlcmin, Some(pb::Amount { msat: 1000 }));
+
+    // The time lock delta is set for the whole node.
+    let params = UpdatePolicyParams {
+        channel_id: Some("4d".repeat(32)),
+        time_lock_delta: Some(80),
+        ..Default::default()
+    };
+    let error = cln(&stub).update_channel_policy(params).await.unwrap_err();
+    assert!(matches!(error, Error::NotImplemented));
+}
+
+#[tokio::test]
+async fn open_channel() {
+    let funding_txid = "0d1a30974ed79c68a6d05bc2c0d29d9d62bdee3a56b1eb6d58ccd0ddbed5ff98";
+    let channel_id = "98ffd5beddd0cc586debb1563aeebd629d9dd2c0c25bd0a6689cd74e97301a0c";
+    let stub = ClnStub::new()
+        .reply(
+            "ConnectPeer",
+            pb::ConnectResponse {
+                id: hex::decode(NODE_ID).unwrap(),
+                ..Default::default()
+            },
+        )
+        .reply(
+            "FundChannel",
+            pb::FundchannelResponse {
+                txid: hex::decode(funding_txid).unwrap(),
+                outnum: 1,
+                channel_id: hex::decode(channel_id).unwrap(),
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    let params = OpenChannelParams {
+        peer_pubkey: NODE_ID.to_string(),
+        peer_host: Some(String::from("127.0.0.1:9735")),
+        amount: 1000000,
+        push_amount: Some(10000),
+        fee_rate: Some(5),
+        private: Some(true),
+        min_confs: Some(3),
+    };
+    let result = cln(&stub).open_channel(params).await.unwrap();
+
+    assert_eq!(result.funding_txid, funding_txid);
+    assert_eq!(result.output_index, 1);
+    assert_eq!(result.channel_id, channel_id);
+
+    let request: pb::ConnectRequest = stub.request("ConnectPeer");
+    assert_eq!(request.id, NODE_ID);
+    assert_eq!(request.host.as_deref(), Some("127.0.0.1"));
+    assert_eq!(request.port, Some(9735));
+
+    let request: pb::FundchannelRequest = stub.request("FundChannel");
+    assert_eq!(request.id, hex::decode(NODE_ID).unwrap());
+    assert_eq!(
+        request.amount.unwrap().value,
+        Some(pb::amount_or_all::Value::Amount(pb::Amount {
+            msat: 1000000000
+        }))
+    );
+    assert_eq!(request.push_msat, Some(pb::Amount { msat: 10000000 }));
+    assert_eq!(
+        request.feerate.unwrap().style,
+        Some(pb::feerate::Style::Perkw(1250))
+    );
+    assert_eq!(request.announce, Some(false));
+    assert_eq!(request.minconf, Some(3));
+}
+
+#[tokio::test]
+async fn open_channel_fee_rate_overflow() {
+    let stub = ClnStub::new().start().await;
+
+    let params = OpenChannelParams {
+        peer_pubkey: NODE_ID.to_string(),
+        peer_host: Some(String::from("127.0.0.1:9735")),
+        amount: 1000000,
+        push_amount: None,
+        fee_rate: Some(u64::MAX),
+        private: None,
+        min_confs: None,
+    };
+    let result = cln(&stub).open_channel(params).await;
+
+    assert!(matches!(result, Err(Error::ConversionError(_))));
+    // Invalid params don't leave the node connected to the peer.
+    assert_eq!(stub.count("ConnectPeer"), 0);
+}
+
+#[tokio::test]
+async fn close_channel() {
+    let closing_txid = "a85aeb8057eea358e16d7afe1d31776ae015722faf2bb44b0a771f9fdd924839";
+    let stub = ClnStub::new()
+        .reply(
+            "Close",
+            pb::CloseResponse {
+                item_type: pb::close_response::CloseType::Unilateral as i32,
+                tx: Some(vec![0x02, 0x00, 0x00, 0x00]),
+                txid: Some(hex::decode(closing_txid).unwrap()),
+            },
+        )
+        .start()
+        .await;
+
+    let params = CloseChannelParams {
+        channel_point: Some(String::from(
+            "2348f998744212575d85959674f9607ab26f67708a917157472832386337c904:1",
+        )),
+        channel_id: None,
+        force: Some(true),
+        fee_rate: Some(2),
+        delivery_address: Some(String::from("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kyd6q0j")),
+    };
+    let result = cln(&stub).close_channel(params).await.unwrap();
+
+    assert_eq!(result.closing_txids, vec![closing_txid]);
+
+    let request: pb::CloseRequest = stub.request("Close");
+    assert_eq!(
+        request.id,
+        "04c93763383228475771918a70676fb27a60f9749695855d5712427498f94822"
+    );
+    assert_eq!(request.unilateraltimeout, Some(1));
+    assert_eq!(
+        request.destination.as_deref(),
+        Some("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kyd6q0j")
+    );
+    let perkw = Some(pb::feerate::Style::Perkw(500));
+    assert_eq!(request.feerange.len(), 2);
+    assert!(request
+        .feerange
+        .iter()
+        .all(|feerate| feerate.style == perkw));
+}
+
+#[tokio::test]
+async fn list_peers() {
+    let stub = ClnStub::new()
+        .reply(
+            "ListPeers",
+            pb::ListpeersResponse {
+                peers: vec![
+                    pb::ListpeersPeers {
+                        id: hex::decode(NODE_ID).unwrap(),
+                        connected: true,
+                        netaddr: vec![String::from("172.18.0.3:9735")],
+                        features: Some(vec![0x02, 0x00, 0x01]),
+                        ..Default::default()
+                    },
+                    pb::ListpeersPeers {
+                        id: hex::decode(NODE_ID).unwrap(),
+                        connected: false,
+                        ..Default::default()
+                    },
+                ],
+            },
+        )
+        .start()
+        .await;
+
+    let peers = cln(&stub).list_peers().await.unwrap();
+
+    assert_eq!(peers.len(), 2);
+    assert_eq!(peers[0].pubkey, NODE_ID);
+    assert_eq!(peers[0].addresses, vec!["172.18.0.3:9735"]);
+    assert!(peers[0].connected);
+    let bits: Vec<u32> = peers[0]
+        .features
+        .iter()
+        .map(|feature| feature.bit)
+        .collect();
+    assert_eq!(bits, vec![0, 17]);
+    assert_eq!(peers[0].inbound, None);
+    assert!(!peers[1].connected);
+    assert!(peers[1].features.is_empty());
+}
+
+#[tokio::test]
+async fn connect_and_disconnect_peer() {
+    let stub = ClnStub::new()
+        .reply(
+            "ConnectPeer",
+            pb::ConnectResponse {
+                id: hex::decode(NODE_ID).unwrap(),
+                ..Default::default()
+            },
+        )
+        .reply("Disconnect", pb::DisconnectResponse {})
+        .start()
+        .await;
+    let node = cln(&stub);
+
+    let params = ConnectPeerParams {
+        pubkey: NODE_ID.to_string(),
+        host: None,
+    };
+    node.connect_peer(params).await.unwrap();
+    node.disconnect_peer(NODE_ID.to_string()).await.unwrap();
+
+    // Without a host, Core Lightning looks the address up in the network graph.
+    let request: pb::ConnectRequest = stub.request("ConnectPeer");
+    assert_eq!(request.id, NODE_ID);
+    assert_eq!(request.host, None);
+    assert_eq!(request.port, None);
+
+    let request: pb::DisconnectRequest = stub.request("Disconnect");
+    assert_eq!(request.id, hex::decode(NODE_ID).unwrap());
+}
+
+#[tokio::test]
+async fn new_address() {
+    let stub = ClnStub::new()
+        .reply(
+            "NewAddr",
+            pb::NewaddrResponse {
+                bech32: None,
+                p2sh_segwit: Some(String::from("2N1SP7r92ZZJvYKG2oNtzPwYnzw62up7mTo")),
+            },
+        )
+        .start()
+        .await;
+    let node = cln(&stub);
+
+    let address = node.new_address(AddressType::NestedP2wpkh).await.unwrap();
+    assert_eq!(address, "2N1SP7r92ZZJvYKG2oNtzPwYnzw62up7mTo");
+
+    let request: pb::NewaddrRequest = stub.request("NewAddr");
+    assert_eq!(
+        request.addresstype,
+        Some(pb::newaddr_request::NewaddrAddresstype::P2shSegwit as i32)
+    );
+
+    let err = node.new_address(AddressType::P2tr).await.unwrap_err();
+    assert!(matches!(err, Error::NotImplemented));
+}
+
+#[tokio::test]
+async fn send_onchain() {
+    let txid = "6d4cfdbd0e5d9e0c7d9cd6e1a37c2f4a1b76b0e1df6be2d9ab3a05d0f17a2c93";
+    let utxo = "2348f998744212575d85959674f9607ab26f67708a917157472832386337c904";
+    let stub = ClnStub::new()
+        .reply(
+            "Withdraw",
+            pb::WithdrawResponse {
+                txid: hex::decode(txid).unwrap(),
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    let params = SendOnchainParams {
+        address: String::from("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kyd6q0j"),
+        amount: Some(150000),
+        send_all: None,
+        fee_rate: None,
+        target_conf: Some(6),
+        utxos: Some(vec![format!("{}:1", utxo)]),
+    };
+    let result = cln(&stub).send_onchain(params).await.unwrap();
+
+    assert_eq!(result, txid);
+
+    let request: pb::WithdrawRequest = stub.request("Withdraw");
+    assert_eq!(
+        request.satoshi.unwrap().value,
+        Some(pb::amount_or_all::Value::Amount(pb::Amount {
+            msat: 150000000
+        }))
+    );
+    // Confirmation targets are mapped to the named fee rates.
+    assert_eq!(
+        request.feerate.unwrap().style,
+        Some(pb::feerate::Style::Normal(true))
+    );
+    assert_eq!(
+        request.utxos,
+        vec![pb::Outpoint {
+            txid: hex::decode(utxo).unwrap(),
+            outnum: 1
+        }]
+    );
+}
+
+#[tokio::test]
+async fn list_onchain_transactions() {
+    let deposit = "a85aeb8057eea358e16d7afe1d31776ae015722faf2bb44b0a771f9fdd924839";
+    let funding = "0d1a30974ed79c68a6d05bc2c0d29d9d62bdee3a56b1eb6d58ccd0ddbed5ff98";
+    let output = |index, msat| pb::ListtransactionsTransactionsOutputs {
+        index,
+        msat: Some(pb::Amount { msat }),
+        ..Default::default()
+    };
+    let wallet_output = |txid: &str, output, msat| pb::ListfundsOutputs {
+        txid: hex::decode(txid).unwrap(),
+        output,
+        amount_msat: Some(pb::Amount { msat }),
+        ..Default::default()
+    };
+    let stub = ClnStub::new()
+        .reply(
+            "Getinfo",
+            pb::GetinfoResponse {
+                blockheight: 160,
+                ..Default::default()
+            },
+        )
+        .reply(
+            "ListTransactions",
+            pb::ListtransactionsResponse {
+                transactions: vec![
+                    pb::ListtransactionsTransactions {
+                        hash: hex::decode(deposit).unwrap(),
+                        blockheight: 140,
+                        outputs: vec![output(0, 2000000000), output(1, 500000000)],
+                        ..Default::default()
+                    },
+                    pb::ListtransactionsTransactions {
+                        hash: hex::decode(funding).unwrap(),
+                        blockheight: 0,
+                        channel: Some(String::from("152x1x0")),
+                        inputs: vec![pb::ListtransactionsTransactionsInputs {
+                            txid: hex::decode(deposit).unwrap(),
+                            index: 0,
+                            ..Default::default()
+                        }],
+                        outputs: vec![output(0, 1000000000), output(1, 999859000)],
+                        ..Default::default()
+                    },
+                ],
+            },
+        )
+        .reply(
+            "ListFunds",
+            pb::ListfundsResponse {
+                outputs: vec![
+                    wallet_output(deposit, 0, 2000000000),
+                    wallet_output(funding, 1, 999859000),
+                ],
+                channels: vec![],
+            },
+        )
+        .start()
+        .await;
+
+    let transactions = cln(&stub).list_onchain_transactions().await.unwrap();
+
+    assert_eq!(transactions[0].txid, deposit);
+    assert_eq!(transactions[0].amount_received_msat, 2000000000);
+    assert_eq!(transactions[0].fee_msat, None);
+    assert_eq!(transactions[0].confirmations, 21);
+    assert_eq!(transactions[0].block_height, Some(140));
+
+    // The outputs of the wallet are looked up in the spent ones too, the
+    // funding output belonging to the channel.
+    assert_eq!(transactions[1].amount_sent_msat, 1000141000);
+    assert_eq!(transactions[1].fee_msat, Some(141000));
+    assert_eq!(transactions[1].confirmations, 0);
+    assert_eq!(transactions[1].short_channel_id.as_deref(), Some("152x1x0"));
+
+    let request: pb::ListfundsRequest = stub.request("ListFunds");
+    assert_eq!(request.spent, Some(true));
+}
+
+#[tokio::test]
+async fn create_invoice() {
+    let stub = ClnStub::new()
+        .reply(
+            "Invoice",
+            pb::InvoiceResponse {
+                bolt11: bolt11(),
+                payment_hash: hex::decode(PAYMENT_HASH).unwrap(),
+                expires_at: 1496314718,
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    let params = CreateInvoiceParams {
+        amount: Some(250000),
+        amount_msat: None,
+        description: Some(String::from("1 cup coffee")),
+        description_hash: None,
+        label: Some(String::from("coffee")),
+        expire_in: Some(60),
+        fallback_address: None,
+        payment_preimage: None,
+        cltv_expiry: None,
+    };
+    let result = cln(&stub).create_invoice(params).await.unwrap();
+
+    assert_eq!(result.payment_hash, PAYMENT_HASH);
+    assert_eq!(result.label.as_deref(), Some("coffee"));
+
+    let request: pb::InvoiceRequest = stub.request("Invoice");
+    assert_eq!(request.label, "coffee");
+    assert_eq!(request.expiry, Some(60));
+    assert_eq!(
+        request.msatoshi.and_then(|amount| amount.value),
+        Some(pb::amount_or_any::Value::Amount(pb::Amount {
+            msat: 250000000
+        }))
+    );
+}
+
+#[tokio::test]
+async fn hold_invoices_not_implemented() {
+    let stub = ClnStub::new().start().await;
+    let node = cln(&stub);
+
+    let params = CreateHoldInvoiceParams {
+        payment_hash: PAYMENT_HASH.to_string(),
+        amount: Some(250000),
+        amount_msat: None,
+        description: None,
+        description_hash: None,
+        expire_in: None,
+        fallback_address: None,
+        cltv_expiry: None,
+    };
+    let error = node.create_hold_invoice(params).await.unwrap_err();
+    assert!(matches!(error, Error::NotImplemented));
+
+    let error = node
+        .settle_hold_invoice(PAYMENT_PREIMAGE.to_string())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::NotImplemented));
+
+    let error = node
+        .cancel_hold_invoice(PAYMENT_HASH.to_string())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::NotImplemented));
+}
+
+#[tokio::test]
+async fn cancel_invoice() {
+    let stub = ClnStub::new()
+        .reply(
+            "ListInvoices",
+            pb::ListinvoicesResponse {
+                invoices: vec![invoice(PAYMENT_HASH, false)],
+            },
+        )
+        .reply(
+            "DelInvoice",
+            pb::DelinvoiceResponse {
+                label: format!("label-{}", &PAYMENT_HASH[..8]),
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    cln(&stub)
+        .cancel_invoice(PAYMENT_HASH.to_string())
+        .await
+        .unwrap();
+
+    let request: pb::DelinvoiceRequest = stub.request("DelInvoice");
+    assert_eq!(request.label, "label-00010203");
+    assert_eq!(
+        request.status,
+        pb::delinvoice_request::DelinvoiceStatus::Unpaid as i32
+    );
+}
+
+#[tokio::test]
+async fn cancel_invoice_paid() {
+    let stub = ClnStub::new()
+        .reply(
+            "ListInvoices",
+            pb::ListinvoicesResponse {
+                invoices: vec![invoice(PAYMENT_HASH, true)],
+            },
+        )
+        .start()
+        .await;
+
+    let error = cln(&stub)
+        .cancel_invoice(PAYMENT_HASH.to_string())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::ApiError(message) if message == "invoice is already paid"));
+}
+
+#[tokio::test]
+async fn delete_expired_invoices() {
+    let stub = ClnStub::new()
+        .reply("DelExpiredInvoice", pb::DelexpiredinvoiceResponse {})
+        .start()
+        .await;
+
+    cln(&stub)
+        .delete_expired_invoices(Some(1496314718))
+        .await
+        .unwrap();
+
+    let request: pb::DelexpiredinvoiceRequest = stub.request("DelExpiredInvoice");
+    assert_eq!(request.maxexpirytime, Some(1496314718));
+}
+
+#[tokio::test]
+async fn get_invoice() {
+    let stub = ClnStub::new()
+        .reply(
+            "ListInvoices",
+            pb::ListinvoicesResponse {
+                invoices: vec![invoice(PAYMENT_HASH, true)],
+            },
+        )
+        .start()
+        .await;
+
+    let invoice = cln(&stub)
+        .get_invoice(PAYMENT_HASH.to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(invoice.payment_hash, PAYMENT_HASH);
+    assert_eq!(invoice.status, InvoiceStatus::Settled);
+    assert_eq!(invoice.pre_image.as_deref(), Some(PAYMENT_PREIMAGE));
+    assert_eq!(invoice.creation_date, 1496314658);
+    assert_eq!(invoice.expiry, 60);
+
+    let request: pb::ListinvoicesRequest = stub.request("ListInvoices");
+    assert_eq!(
+        request.payment_hash,
+        Some(hex::decode(PAYMENT_HASH).unwrap())
+    );
+}
+
+#[tokio::test]
+async fn get_invoice_not_found() {
+    let stub = ClnStub::new()
+        .reply(
+            "ListInvoices",
+            pb::ListinvoicesResponse { invoices: vec![] },
+        )
+        .start()
+        .await;
+
+    let error = cln(&stub)
+        .get_invoice(PAYMENT_HASH.to_string())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::ApiError(message) if message == "invoice not found"));
+}
+
+#[tokio::test]
+async fn list_invoices() {
+    let stub = ClnStub::new()
+        .reply(
+            "ListInvoices",
+            pb::ListinvoicesResponse {
+                invoices: vec![invoice(PAYMENT_HASH, true), invoice(PAYMENT_HASH_2, false)],
+            },
+        )
+        .start()
+        .await;
+
+    let params = ListInvoicesParams {
+        status: None,
+        pending_only: None,
+        creation_date_start: None,
+        creation_date_end: None,
+        cursor: None,
+        max_invoices: Some(1),
+    };
+    let node = cln(&stub);
+    let first = node.list_invoices(params).await.unwrap();
+
+    assert_eq!(first.invoices.len(), 1);
+    assert_eq!(first.invoices[0].payment_hash, PAYMENT_HASH);
+    assert!(first.next_cursor.is_some());
+
+    let params = ListInvoicesParams {
+        status: None,
+        pending_only: None,
+        creation_date_start: None,
+        creation_date_end: None,
+        cursor: first.next_cursor,
+        max_invoices: Some(1),
+    };
+    let second = node.list_invoices(params).await.unwrap();
+
+    assert_eq!(second.invoices.len(), 1);
+    assert_eq!(second.invoices[0].payment_hash, PAYMENT_HASH_2);
+    assert_eq!(second.invoices[0].status, InvoiceStatus::Pending);
+    assert_eq!(second.next_cursor, None);
+
+    // A page size of 0 gets the default one instead of an empty page.
+    let params = ListInvoicesParams {
+        status: None,
+        pending_only: None,
+        creation_date_start: None,
+        creation_date_end: None,
+        cursor: None,
+        max_invoices: Some(0),
+    };
+    let all = node.list_invoices(params).await.unwrap();
+
+    assert_eq!(all.invoices.len(), 2);
+    assert_eq!(all.next_cursor, None);
+}
+
+#[tokio::test]
+async fn pay_invoice() {
+    let stub = ClnStub::new()
+        .reply(
+            "Pay",
+            pb::PayResponse {
+                payment_preimage: hex::decode(PAYMENT_PREIMAGE).unwrap(),
+                payment_hash: hex::decode(PAYMENT_HASH).unwrap(),
+                amount_msat: Some(pb::Amount { msat: 250000000 }),
+                amount_sent_msat: Some(pb::Amount { msat: 250001250 }),
+                parts: 1,
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    let params = PayInvoiceParams {
+        payment_request: bolt11(),
+        amount: None,
+        amount_msat: None,
+        max_fee_sat: None,
+        max_fee_msat: Some(5000),
+        max_fee_percent: None,
+    };
+    let result = cln(&stub).pay_invoice(params).await.unwrap();
+
+    assert_eq!(result.payment_hash, PAYMENT_HASH);
+    assert_eq!(result.payment_preimage, PAYMENT_PREIMAGE);
+    assert_eq!(result.fees_msat, Some(1250));
+
+    let request: pb::PayRequest = stub.request("Pay");
+    assert_eq!(request.bolt11, bolt11());
+    assert_eq!(request.maxfee, Some(pb::Amount { msat: 5000 }));
+}
+
+#[tokio::test]
+async fn request_timeout() {
+    let stub = ClnStub::new()
+        .reply(
+            "Getinfo",
+            pb::GetinfoResponse {
+                id: hex::decode(NODE_ID).unwrap(),
+                network: String::from("bitcoin"),
+                ..Default::default()
+            },
+        )
+        .reply(
+            "Pay",
+            pb::PayResponse {
+                payment_preimage: hex::decode(PAYMENT_PREIMAGE).unwrap(),
+                payment_hash: hex::decode(PAYMENT_HASH).unwrap(),
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+    let config = una_core::types::NodeConfig {
+        request_timeout: Some(30),
+        ..stub.config()
+    };
+    let cln = ClnGrpc::new(config.try_into().unwrap()).unwrap();
+
+    cln.get_info().await.unwrap();
+    let params = PayInvoiceParams {
+        payment_request: bolt11(),
+        amount: None,
+        amount_msat: None,
+        max_fee_sat: None,
+        max_fee_msat: None,
+        max_fee_percent: None,
+    };
+    cln.pay_invoice(params).await.unwrap();
+
+    assert!(stub.metadata("Getinfo").get("grpc-timeout").is_some());
+    // Payments last longer than a request, they aren't timed out.
+    assert!(stub.metadata("Pay").get("grpc-timeout").is_none());
+}
+
+#[tokio::test]
+async fn keysend() {
+    let stub = ClnStub::new()
+        .reply(
+            "Getinfo",
+            pb::GetinfoResponse {
+                version: String::from("v22.11"),
+                ..Default::default()
+            },
+        )
+        .reply(
+            "KeySend",
+            pb::KeysendResponse {
+                payment_preimage: hex::decode(PAYMENT_PREIMAGE).unwrap(),
+                payment_hash: hex::decode(PAYMENT_HASH).unwrap(),
+                amount_msat: Some(pb::Amount { msat: 1000000 }),
+                amount_sent_msat: Some(pb::Amount { msat: 1001001 }),
+                parts: 1,
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    let params = KeysendParams {
+        destination: NODE_ID.to_string(),
+        amount: None,
+        amount_msat: Some(1000000),
+        custom_records: Some(vec![CustomRecord {
+            record_type: 7629169,
+            value: String::from("cafe"),
+        }]),
+        max_fee_sat: Some(2),
+        max_fee_msat: None,
+        max_fee_percent: None,
+    };
+    let result = cln(&stub).keysend(params).await.unwrap();
+
+    assert_eq!(result.payment_hash, PAYMENT_HASH);
+    assert_eq!(result.payment_preimage, PAYMENT_PREIMAGE);
+    assert_eq!(result.fees_msat, Some(1001));
+
+    let request: pb::KeysendRequest = stub.request("KeySend");
+    assert_eq!(request.destination, hex::decode(NODE_ID).unwrap());
+    assert_eq!(request.msatoshi, Some(pb::Amount { msat: 1000000 }));
+    // The absolute fee limit is enforced by exempting it from the percentage.
+    assert_eq!(request.maxfeepercent, Some(0.0));
+    assert_eq!(request.exemptfee, Some(pb::Amount { msat: 2000 }));
+    let entries = request.extratlvs.unwrap().entries;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].r#type, 7629169);
+    assert_eq!(entries[0].value, vec![0xca, 0xfe]);
+}
+
+#[tokio::test]
+async fn keysend_custom_records_unsupported() {
+    let stub = ClnStub::new()
+        .reply(
+            "Getinfo",
+            pb::GetinfoResponse {
+                version: String::from("v0.11.2"),
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    let params = KeysendParams {
+        destination: NODE_ID.to_string(),
+        amount: None,
+        amount_msat: Some(1000000),
+        custom_records: Some(vec![CustomRecord {
+            record_type: 7629169,
+            value: String::from("cafe"),
+        }]),
+        max_fee_sat: None,
+        max_fee_msat: None,
+        max_fee_percent: None,
+    };
+    let result = cln(&stub).keysend(params).await;
+
+    // The node would drop the records, so the payment isn't made.
+    assert!(matches!(result, Err(Error::NotImplemented)));
+    assert_eq!(stub.count("KeySend"), 0);
+}
+
+fn sendpay(
+    id: u64,
+    groupid: u64,
+    payment_hash: &str,
+    status: pb::listsendpays_payments::ListsendpaysPaymentsStatus,
+    amount_msat: u64,
+) -> pb::ListsendpaysPayments {
+    let complete = status == pb::listsendpays_payments::ListsendpaysPaymentsStatus::Complete;
+
+    pb::ListsendpaysPayments {
+        id,
+        groupid: Some(groupid),
+        payment_hash: hex::decode(payment_hash).unwrap(),
+        status: status as i32,
+        amount_msat: Some(pb::Amount { msat: amount_msat }),
+        destination: Some(hex::decode(NODE_ID).unwrap()),
+        created_at: 1496314600 + id,
+        amount_sent_msat: Some(pb::Amount {
+            msat: amount_msat + 500,
+        }),
+        label: None,
+        bolt11: Some(bolt11()),
+        description: None,
+        bolt12: None,
+        payment_preimage: complete.then(|| hex::decode(PAYMENT_PREIMAGE).unwrap()),
+        erroronion: None,
+    }
+}
+
+#[tokio::test]
+async fn get_payment() {
+    use pb::listsendpays_payments::ListsendpaysPaymentsStatus;
+
+    let stub = ClnStub::new()
+        .reply(
+            "ListSendPays",
+            pb::ListsendpaysResponse {
+                payments: vec![
+                    sendpay(
+                        1,
+                        1,
+                        PAYMENT_HASH,
+                        ListsendpaysPaymentsStatus::Failed,
+                        250000000,
+                    ),
+                    sendpay(
+                        2,
+                        2,
+                        PAYMENT_HASH,
+                        ListsendpaysPaymentsStatus::Complete,
+                        150000000,
+                    ),
+                    sendpay(
+                        3,
+                        2,
+                        PAYMENT_HASH,
+                        ListsendpaysPaymentsStatus::Complete,
+                        100000000,
+                    ),
+                ],
+            },
+        )
+        .start()
+        .await;
+
+    let payment = cln(&stub)
+        .get_payment(PAYMENT_HASH.to_string())
+        .await
+        .unwrap();
+
+    // Only the parts of the last attempt are counted.
+    assert_eq!(payment.status, PaymentStatus::Succeeded);
+    assert_eq!(payment.payment_preimage.as_deref(), Some(PAYMENT_PREIMAGE));
+    assert_eq!(payment.amount_msat, 250000000);
+    assert_eq!(payment.fee_msat, Some(1000));
+    assert_eq!(payment.parts.len(), 2);
+    assert_eq!(payment.created_at, 1496314601);
+    assert_eq!(payment.destination.as_deref(), Some(NODE_ID));
+
+    let request: pb::ListsendpaysRequest = stub.request("ListSendPays");
+    assert_eq!(
+        request.payment_hash,
+        Some(hex::decode(PAYMENT_HASH).unwrap())
+    );
+}
+
+#[tokio::test]
+async fn list_forwards() {
+    use pb::listforwards_forwards::ListforwardsForwardsStatus;
+
+    let forward =
+        |status: ListforwardsForwardsStatus, received_time: f64| pb::ListforwardsForwards {
+            in_channel: String::from("761300x1x0"),
+            in_msat: Some(pb::Amount { msat: 100001100 }),
+            status: status as i32,
+            received_time,
+            out_channel: Some(String::from("761234x1021x0")),
+            fee_msat: Some(pb::Amount { msat: 1100 }),
+            out_msat: Some(pb::Amount { msat: 100000000 }),
+            ..Default::default()
+        };
+
+    let stub = ClnStub::new()
+        .reply(
+            "ListForwards",
+            pb::ListforwardsResponse {
+                forwards: vec![
+                    forward(ListforwardsForwardsStatus::Settled, 1664570000.5),
+                    forward(ListforwardsForwardsStatus::Settled, 1664575261.2),
+                    forward(ListforwardsForwardsStatus::LocalFailed, 1664578842.9),
+                    forward(ListforwardsForwardsStatus::Offered, 1664579000.1),
+                ],
+            },
+        )
+        .reply(
+            "ListPeers",
+            pb::ListpeersResponse {
+                peers: vec![pb::ListpeersPeers {
+                    id: hex::decode(NODE_ID).unwrap(),
+                    channels: vec![pb::ListpeersPeersChannels {
+                        short_channel_id: Some(String::from("761300x1x0")),
+                        channel_id: Some(vec![0x4d; 32]),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+            },
+        )
+        .start()
+        .await;
+
+    let params = ListForwardsParams {
+        date_start: Some(1664575200),
+        date_end: None,
+        cursor: None,
+        max_forwards: Some(2),
+    };
+    let result = cln(&stub).list_forwards(params).await.unwrap();
+
+    // The forward received before `date_start` is filtered out.
+    assert_eq!(result.forwards.len(), 2);
+    assert_eq!(result.next_cursor, Some(2));
+
+    let settled = &result.forwards[0];
+    assert_eq!(settled.status, ForwardStatus::Settled);
+    assert_eq!(settled.amount_in_msat, 100001100);
+    assert_eq!(settled.amount_out_msat, Some(100000000));
+    assert_eq!(settled.fee_msat, 1100);
+    assert_eq!(settled.received_at, Some(1664575261));
+    assert_eq!(settled.resolved_at, None);
+    assert_eq!(settled.incoming_channel_id, Some("4d".repeat(32)));
+    assert_eq!(settled.outgoing_channel_id, None);
+    assert_eq!(
+        settled.outgoing_short_channel_id.as_deref(),
+        Some("761234x1021x0")
+    );
+
+    // Failed forwards earn nothing.
+    let failed = &result.forwards[1];
+    assert_eq!(failed.status, ForwardStatus::Failed);
+    assert_eq!(failed.fee_msat, 0);
+}
+
+#[tokio::test]
+async fn list_payments() {
+    use pb::listsendpays_payments::ListsendpaysPaymentsStatus;
+
+    let stub = ClnStub::new()
+        .reply(
+            "ListSendPays",
+            pb::ListsendpaysResponse {
+                payments: vec![
+                    sendpay(
+                        1,
+                        1,
+                        PAYMENT_HASH,
+                        ListsendpaysPaymentsStatus::Complete,
+                        250000000,
+                    ),
+                    sendpay(
+                        2,
+                        1,
+                        PAYMENT_HASH_2,
+                        ListsendpaysPaymentsStatus::Failed,
+                        1000000,
+                    ),
+                    sendpay(
+                        3,
+                        2,
+                        PAYMENT_HASH_2,
+                        ListsendpaysPaymentsStatus::Pending,
+                        1000000,
+                    ),
+                ],
+            },
+        )
+        .start()
+        .await;
+
+    let params = ListPaymentsParams {
+        status: Some(PaymentStatus::Pending),
+        creation_date_start: None,
+        creation_date_end: None,
+        cursor: None,
+        max_payments: None,
+    };
+    let result = cln(&stub).list_payments(params).await.unwrap();
+
+    assert_eq!(result.payments.len(), 1);
+    assert_eq!(result.payments[0].payment_hash, PAYMENT_HASH_2);
+    assert_eq!(result.payments[0].payment_preimage, None);
+    assert_eq!(result.next_cursor, None);
+}
+
+#[tokio::test]
+async fn decode_invoice() {
+    let stub = ClnStub::new()
+        .reply(
+            "DecodePay",
+            pb::DecodepayResponse {
+                currency: String::from("bc"),
+                created_at: 1496314658,
+                expiry: 60,
+                payee: hex::decode(NODE_ID).unwrap(),
+                amount_msat: Some(pb::Amount { msat: 250000000 }),
+                payment_hash: hex::decode(PAYMENT_HASH).unwrap(),
+                description: Some(String::from("1 cup coffee")),
+                min_final_cltv_expiry: 18,
+                payment_secret: Some(vec![0x11; 32]),
+                features: Some(vec![0x02, 0x42, 0x00]),
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    let invoice = cln(&stub).decode_invoice(bolt11()).await.unwrap();
+
+    assert!(matches!(invoice.network, Network::Mainnet));
+    assert_eq!(invoice.payee, NODE_ID);
+    assert_eq!(invoice.amount, Some(250000));
+    assert_eq!(invoice.payment_secret, Some("11".repeat(32)));
+    assert_eq!(invoice.min_final_cltv_expiry, 18);
+    let bits: Vec<u32> = invoice.features.iter().map(|feature| feature.bit).collect();
+    assert_eq!(bits, vec![9, 14, 17]);
+}
+
+#[tokio::test]
+async fn rpc_error_message_is_extracted() {
+    let message = "Error calling method Pay: RpcError { code: Some(210), message: \"Ran out of routes to try after 3 attempts: see `paystatus`\", data: None }";
+    let stub = ClnStub::new()
+        .fail("Pay", Code::Unknown, message)
+        .start()
+        .await;
+
+    let params = PayInvoiceParams {
+        payment_request: bolt11(),
+        amount: None,
+        amount_msat: None,
+        max_fee_sat: None,
+        max_fee_msat: None,
+        max_fee_percent: None,
+    };
+    let error = cln(&stub).pay_invoice(params).await.unwrap_err();
+
+    assert!(
+        matches!(error, Error::ApiError(message) if message == "Ran out of routes to try after 3 attempts: see `paystatus`")
+    );
+}
+
+#[tokio::test]
+async fn plain_status_message_is_api_error() {
+    let stub = ClnStub::new()
+        .fail("Getinfo", Code::Internal, "lightningd is shutting down")
+        .start()
+        .await;
+
+    let error = cln(&stub).get_info().await.unwrap_err();
+
+    assert!(matches!(error, Error::ApiError(message) if message == "lightningd is shutting down"));
+}
+
+#[tokio::test]
+async fn unauthenticated_is_unauthorized() {
+    let stub = ClnStub::new()
+        .fail(
+            "Getinfo",
+            Code::Unauthenticated,
+            "invalid client certificate",
+        )
+        .start()
+        .await;
+
+    let error = cln(&stub).get_info().await.unwrap_err();
+
+    assert!(matches!(error, Error::Unauthorized));
+}
+
+#[tokio::test]
+async fn missing_method_is_api_error() {
+    let stub = ClnStub::new().start().await;
+
+    let error = cln(&stub).get_info().await.unwrap_err();
+
+    assert!(matches!(error, Error::ApiError(message) if message == "no stub for Getinfo"));
+}
+
+#[test]
+fn status_conversion() {
+    let unauthorized = [
+        Status::new(Code::Unauthenticated, "bad macaroon"),
+        Status::new(Code::PermissionDenied, "verification failed"),
+        Status::new(Code::Unknown, "permission denied"),
+    ];
+    for status in unauthorized {
+        assert!(matches!(Error::from(status), Error::Unauthorized));
+    }
+
+    let status = Status::new(
+        Code::Unknown,
+        "RpcError { code: Some(-1), message: \"Unknown command 'foo'\", data: None }",
+    );
+    assert!(
+        matches!(Error::from(status), Error::ApiError(message) if message == "Unknown command 'foo'")
+    );
+
+    // An RpcError without a message is passed through as is.
+    let status = Status::new(Code::Unknown, "RpcError { code: Some(-1) }");
+    assert!(
+        matches!(Error::from(status), Error::ApiError(message) if message == "RpcError { code: Some(-1) }")
+    );
+
+    let status = Status::new(Code::NotFound, "invoice not found");
+    assert!(
+        matches!(Error::from(status), Error::ApiError(message) if message == "invoice not found")
+    );
+}
+
+fn waitanyinvoice(pay_index: u64) -> pb::WaitanyinvoiceResponse {
+    pb::WaitanyinvoiceResponse {
+        label: String::from("coffee"),
+        description: String::from("1 cup coffee"),
+        payment_hash: hex::decode(PAYMENT_HASH).unwrap(),
+        status: pb::waitanyinvoice_response::WaitanyinvoiceStatus::Paid as i32,
+        expires_at: 1496314718,
+        amount_msat: Some(pb::Amount { msat: 250000000 }),
+        bolt11: Some(bolt11()),
+        pay_index: Some(pay_index),
+        amount_received_msat: Some(pb::Amount { msat: 250000000 }),
+        paid_at: Some(1496314700),
+        payment_preimage: Some(hex::decode(PAYMENT_PREIMAGE).unwrap()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn subscribe_invoices() {
+    let stub = ClnStub::new()
+        .reply(
+            "ListInvoices",
+            pb::ListinvoicesResponse {
+                invoices: vec![invoice(PAYMENT_HASH_2, true), invoice(PAYMENT_HASH, false)],
+            },
+        )
+        .reply("WaitAnyInvoice", waitanyinvoice(6))
+        .start()
+        .await;
+
+    let mut events = cln(&stub)
+        .subscribe_invoices(SubscribeInvoicesParams { cursor: None })
+        .await
+        .unwrap();
+    let event = events.next().await.unwrap().unwrap();
+
+    assert!(matches!(event.event_type, InvoiceEventType::Settled));
+    assert_eq!(event.invoice.payment_hash, PAYMENT_HASH);
+    assert_eq!(event.invoice.status, InvoiceStatus::Settled);
+    assert_eq!(event.invoice.pre_image.as_deref(), Some(PAYMENT_PREIMAGE));
+    assert_eq!(event.cursor.settle_index, Some(6));
+    // Without a cursor, the subscription starts after the last paid invoice.
+    let request: pb::WaitanyinvoiceRequest = stub.request("WaitAnyInvoice");
+    assert_eq!(request.lastpay_index, Some(5));
+
+    events.next().await.unwrap().unwrap();
+    let request: pb::WaitanyinvoiceRequest = stub.request("WaitAnyInvoice");
+    assert_eq!(request.lastpay_index, Some(6));
+}
+
+#[tokio::test]
+async fn subscribe_invoices_cursor() {
+    let stub = ClnStub::new()
+        .reply("WaitAnyInvoice", waitanyinvoice(4))
+        .start()
+        .await;
+
+    let params = SubscribeInvoicesParams {
+        cursor: Some(InvoiceCursor {
+            add_index: None,
+            settle_index: Some(3),
+        }),
+    };
+    let mut events = cln(&stub).subscribe_invoices(params).await.unwrap();
+    let event = events.next().await.unwrap().unwrap();
+
+    assert_eq!(event.cursor.settle_index, Some(4));
+    let request: pb::WaitanyinvoiceRequest = stub.request("WaitAnyInvoice");
+    assert_eq!(request.lastpay_index, Some(3));
+    assert_eq!(stub.count("ListInvoices"), 0);
+}
+
+#[tokio::test]
+async fn subscribe_invoices_error() {
+    let stub = ClnStub::new()
+        .reply(
+            "ListInvoices",
+            pb::ListinvoicesResponse { invoices: vec![] },
+        )
+        .fail("WaitAnyInvoice", Code::Unavailable, "connection lost")
+        .start()
+        .await;
+
+    let events: Vec<_> = cln(&stub)
+        .subscribe_invoices(SubscribeInvoicesParams { cursor: None })
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    // The subscription ends on the first error.
+    assert_eq!(events.len(), 1);
+    assert!(events[0].is_err());
+    let request: pb::WaitanyinvoiceRequest = stub.request("WaitAnyInvoice");
+    assert_eq!(request.lastpay_index, Some(0));
+}
+
+#[tokio::test]
+async fn track_payment() {
+    use pb::listsendpays_payments::ListsendpaysPaymentsStatus;
+
+    let stub = ClnStub::new()
+        .reply(
+            "ListSendPays",
+            pb::ListsendpaysResponse {
+                payments: vec![sendpay(
+                    1,
+                    1,
+                    PAYMENT_HASH,
+                    ListsendpaysPaymentsStatus::Complete,
+                    250000000,
+                )],
+            },
+        )
+        .start()
+        .await;
+
+    let payments: Vec<_> = cln(&stub)
+        .track_payment(PAYMENT_HASH.to_string())
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    // The payment is already final, so the tracking ends right away.
+    assert_eq!(payments.len(), 1);
+    let payment = payments[0].as_ref().unwrap();
+    assert_eq!(payment.status, PaymentStatus::Succeeded);
+    assert_eq!(payment.payment_preimage.as_deref(), Some(PAYMENT_PREIMAGE));
+}
+
+#[tokio::test]
+async fn sign_message() {
+    let stub = ClnStub::new()
+        .reply(
+            "SignMessage",
+            pb::SignmessageResponse {
+                signature: vec![0xca; 64],
+                recid: vec![0],
+                zbase: String::from("d9fjxye13ep551z4aea58gtd5tg4xbzx9yk8auu1zgy8xbpx73rmsxtd"),
+            },
+        )
+        .start()
+        .await;
+
+    let signature = cln(&stub)
+        .sign_message(String::from("I own this node"))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        signature,
+        "d9fjxye13ep551z4aea58gtd5tg4xbzx9yk8auu1zgy8xbpx73rmsxtd"
+    );
+    let request = stub.request::<pb::SignmessageRequest>("SignMessage");
+    assert_eq!(request.message, "I own this node");
+}
+
+#[tokio::test]
+async fn verify_message() {
+    let stub = ClnStub::new()
+        .reply(
+            "CheckMessage",
+            pb::CheckmessageResponse {
+                verified: true,
+                pubkey: Some(hex::decode(NODE_ID).unwrap()),
+            },
+        )
+        .start()
+        .await;
+
+    let params = VerifyMessageParams {
+        message: String::from("I own this node"),
+        signature: String::from("d9fjxye13ep551z4aea58gtd5tg4xbzx9yk8auu1zgy8xbpx73rmsxtd"),
+        pubkey: Some(NODE_ID.to_string()),
+    };
+    let result = cln(&stub).verify_message(params).await.unwrap();
+
+    assert!(result.valid);
+    assert_eq!(result.pubkey, NODE_ID);
+
+    // The expected signer is checked by Core Lightning.
+    let request = stub.request::<pb::CheckmessageRequest>("CheckMessage");
+    assert_eq!(request.message, "I own this node");
+    assert_eq!(request.pubkey, Some(hex::decode(NODE_ID).unwrap()));
+}
@@ -0,0 +1,617 @@
+//! Stand-in servers answering with canned node responses, so the backends can
+//! be tested without running nodes.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::{SinkExt, Stream};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::upgrade::OnUpgrade;
+use hyper::{Body, HeaderMap, Request, Response, Server};
+use prost::bytes::{Buf, BufMut};
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use tonic::body::BoxBody;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::metadata::MetadataMap;
+use tonic::transport::{Certificate, Identity, NamedService, ServerTlsConfig};
+use tonic::{Code, Status};
+
+use una_core::types::NodeConfig;
+
+pub fn fixture(path: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(path);
+
+    std::fs::read_to_string(&path).unwrap_or_else(|_| panic!("missing fixture {:?}", path))
+}
+
+/// Reads a fixture as hex, as the certificates are passed in `NodeConfig`.
+pub fn fixture_hex(path: &str) -> String {
+    hex::encode(fixture(path))
+}
+
+pub fn node_config(url: &str) -> NodeConfig {
+    NodeConfig {
+        url: Some(url.to_string()),
+        macaroon: None,
+        tls_certificate: None,
+        tls_client_key: None,
+        tls_client_certificate: None,
+        username: None,
+        password: None,
+        connect_timeout: None,
+        request_timeout: None,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+struct Route {
+    method: &'static str,
+    path: String,
+    status: u16,
+    body: String,
+    delay: Option<Duration>,
+    /// Messages sent over a websocket, for routes upgrading the connection.
+    messages: Option<Vec<String>>,
+}
+
+/// HTTP server answering each route with a fixed response, recording the
+/// requests it receives. A route given several times answers with each
+/// response in turn, repeating the last one.
+pub struct RestStub {
+    routes: Vec<Route>,
+}
+
+pub struct RunningRestStub {
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl RestStub {
+    pub fn new() -> Self {
+        RestStub { routes: Vec::new() }
+    }
+
+    pub fn route(mut self, method: &'static str, path: &str, status: u16, body: String) -> Self {
+        self.routes.push(Route {
+            method,
+            path: path.to_string(),
+            status,
+            body,
+            delay: None,
+            messages: None,
+        });
+        self
+    }
+
+    /// Upgrades the requests of `path` to a websocket, sending `messages`
+    /// before closing it.
+    pub fn websocket(mut self, path: &str, messages: Vec<String>) -> Self {
+        self.routes.push(Route {
+            method: "GET",
+            path: path.to_string(),
+            status: 101,
+            body: String::new(),
+            delay: None,
+            messages: Some(messages),
+        });
+        self
+    }
+
+    /// Delays the response of the last route, as a slow node would.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        if let Some(route) = self.routes.last_mut() {
+            route.delay = Some(delay);
+        }
+        self
+    }
+
+    pub async fn start(self) -> RunningRestStub {
+        let routes = Arc::new(self.routes);
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let service_requests = requests.clone();
+        let make_service = make_service_fn(move |_| {
+            let routes = routes.clone();
+            let requests = service_requests.clone();
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    respond(request, routes.clone(), requests.clone())
+                }))
+            }
+        });
+
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let url = format!("http://{}", server.local_addr());
+        tokio::spawn(server);
+
+        RunningRestStub { url, requests }
+    }
+}
+
+async fn respond(
+    mut request: Request<Body>,
+    routes: Arc<Vec<Route>>,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+) -> Result<Response<Body>, Infallible> {
+    let upgrade = hyper::upgrade::on(&mut request);
+    let (parts, body) = request.into_parts();
+    let body = hyper::body::to_bytes(body).await.unwrap_or_default();
+
+    let recorded = RecordedRequest {
+        method: parts.method.to_string(),
+        path: parts.uri.path().to_string(),
+        query: parts.uri.query().map(str::to_string),
+        headers: parts.headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    };
+
+    let matching: Vec<&Route> = routes
+        .iter()
+        .filter(|route| route.method == recorded.method && route.path == recorded.path)
+        .collect();
+    let answered = requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|request| request.method == recorded.method && request.path == recorded.path)
+        .count();
+    let route = matching.get(answered).or_else(|| matching.last()).copied();
+    if let Some(delay) = route.and_then(|route| route.delay) {
+        tokio::time::sleep(delay).await;
+    }
+
+    let response = match route {
+        Some(Route {
+            messages: Some(messages),
+            ..
+        }) => {
+            let key = recorded
+                .headers
+                .get("sec-websocket-key")
+                .map(|key| derive_accept_key(key.as_bytes()))
+                .unwrap_or_default();
+            tokio::spawn(send_messages(upgrade, messages.clone()));
+
+            Response::builder()
+                .status(101)
+                .header("connection", "upgrade")
+                .header("upgrade", "websocket")
+                .header("sec-websocket-accept", key)
+                .body(Body::empty())
+        }
+        Some(route) => Response::builder()
+            .status(route.status)
+            .header("content-type", "application/json")
+            .body(Body::from(route.body.clone())),
+        None => Response::builder().status(501).body(Body::from(format!(
+            "no stub for {} {}",
+            recorded.method, recorded.path
+        ))),
+    };
+
+    requests.lock().unwrap().push(recorded);
+
+    Ok(response.unwrap())
+}
+
+async fn send_messages(upgrade: OnUpgrade, messages: Vec<String>) {
+    let upgraded = upgrade.await.unwrap();
+    let mut socket = WebSocketStream::from_raw_socket(upgraded, Role::Server, None).await;
+
+    for message in messages {
+        socket.send(Message::Text(message)).await.unwrap();
+    }
+    socket.close(None).await.unwrap();
+}
+
+impl RunningRestStub {
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn request(&self, path: &str) -> RecordedRequest {
+        self.requests()
+            .into_iter()
+            .find(|request| request.path == path)
+            .unwrap_or_else(|| panic!("no request to {}", path))
+    }
+}
+
+/// Parses an urlencoded form body, as sent to Eclair.
+pub fn form(body: &str) -> HashMap<String, String> {
+    body.split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.to_string(), urldecode(value)))
+        .collect()
+}
+
+fn urldecode(value: &str) -> String {
+    let value = value.replace('+', " ");
+    let mut bytes = Vec::new();
+    let mut chars = value.bytes();
+    while let Some(byte) = chars.next() {
+        match byte {
+            b'%' => {
+                let high = chars.next().unwrap_or(b'0') as char;
+                let low = chars.next().unwrap_or(b'0') as char;
+                let hex = format!("{}{}", high, low);
+                bytes.push(u8::from_str_radix(&hex, 16).unwrap_or(0));
+            }
+            byte => bytes.push(byte),
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+type GrpcReply = Result<Vec<Vec<u8>>, (Code, String)>;
+type GrpcRequests = Arc<Mutex<Vec<(String, MetadataMap, Vec<u8>)>>>;
+
+/// Core Lightning gRPC server answering each method of `node.proto` with an
+/// encoded message or an error status, recording the encoded requests.
+pub struct ClnStub {
+    replies: HashMap<String, GrpcReply>,
+}
+
+/// LND gRPC server answering each method of the `Lightning`, `Router` and
+/// `Invoices` services with encoded messages or an error status, recording the
+/// encoded requests.
+pub struct LndStub {
+    replies: HashMap<String, GrpcReply>,
+}
+
+pub struct RunningClnStub {
+    pub url: String,
+    requests: GrpcRequests,
+}
+
+pub struct RunningLndStub {
+    pub url: String,
+    requests: GrpcRequests,
+}
+
+impl ClnStub {
+    pub fn new() -> Self {
+        ClnStub {
+            replies: HashMap::new(),
+        }
+    }
+
+    pub fn reply<M: prost::Message>(mut self, method: &str, message: M) -> Self {
+        self.replies
+            .insert(method.to_string(), Ok(vec![message.encode_to_vec()]));
+        self
+    }
+
+    pub fn fail(mut self, method: &str, code: Code, message: &str) -> Self {
+        self.replies
+            .insert(method.to_string(), Err((code, message.to_string())));
+        self
+    }
+
+    pub async fn start(self) -> RunningClnStub {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let replies = Arc::new(self.replies);
+
+        let identity = Identity::from_pem(fixture("tls/server.pem"), fixture("tls/server-key.pem"));
+        let tls = ServerTlsConfig::new()
+            .identity(identity)
+            .client_ca_root(Certificate::from_pem(fixture("tls/ca.pem")));
+
+        let (url, incoming) = listen().await;
+        let server = tonic::transport::Server::builder()
+            .tls_config(tls)
+            .unwrap()
+            .add_service(GrpcService::<ClnNode>::new(&replies, &requests))
+            .serve_with_incoming(incoming);
+        tokio::spawn(server);
+
+        RunningClnStub { url, requests }
+    }
+}
+
+impl LndStub {
+    pub fn new() -> Self {
+        LndStub {
+            replies: HashMap::new(),
+        }
+    }
+
+    pub fn reply<M: prost::Message>(self, method: &str, message: M) -> Self {
+        self.stream(method, vec![message])
+    }
+
+    /// Answers a streaming method with the messages, then ends the stream.
+    pub fn stream<M: prost::Message>(mut self, method: &str, messages: Vec<M>) -> Self {
+        let messages = messages.iter().map(|message| message.encode_to_vec());
+        self.replies
+            .insert(method.to_string(), Ok(messages.collect()));
+        self
+    }
+
+    pub fn fail(mut self, method: &str, code: Code, message: &str) -> Self {
+        self.replies
+            .insert(method.to_string(), Err((code, message.to_string())));
+        self
+    }
+
+    pub async fn start(self) -> RunningLndStub {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let replies = Arc::new(self.replies);
+
+        let identity = Identity::from_pem(fixture("tls/server.pem"), fixture("tls/server-key.pem"));
+        let tls = ServerTlsConfig::new().identity(identity);
+
+        let (url, incoming) = listen().await;
+        let server = tonic::transport::Server::builder()
+            .tls_config(tls)
+            .unwrap()
+            .add_service(GrpcService::<LndLightning>::new(&replies, &requests))
+            .add_service(GrpcService::<LndRouter>::new(&replies, &requests))
+            .add_service(GrpcService::<LndInvoices>::new(&replies, &requests))
+            .serve_with_incoming(incoming);
+        tokio::spawn(server);
+
+        RunningLndStub { url, requests }
+    }
+}
+
+async fn listen() -> (
+    String,
+    impl Stream<Item = std::io::Result<tokio::net::TcpStream>>,
+) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("https://{}", listener.local_addr().unwrap());
+    let incoming = async_stream::stream! {
+        loop {
+            yield listener.accept().await.map(|(stream, _)| stream);
+        }
+    };
+
+    (url, incoming)
+}
+
+/// Decodes the last request received by `method`.
+fn last_request<M: prost::Message + Default>(requests: &GrpcRequests, method: &str) -> M {
+    let requests = requests.lock().unwrap();
+    let (_, _, bytes) = requests
+        .iter()
+        .rev()
+        .find(|(name, _, _)| name == method)
+        .unwrap_or_else(|| panic!("no request to {}", method));
+
+    M::decode(bytes.as_slice()).unwrap()
+}
+
+fn last_metadata(requests: &GrpcRequests, method: &str) -> MetadataMap {
+    let requests = requests.lock().unwrap();
+    let (_, metadata, _) = requests
+        .iter()
+        .rev()
+        .find(|(name, _, _)| name == method)
+        .unwrap_or_else(|| panic!("no request to {}", method));
+
+    metadata.clone()
+}
+
+impl RunningClnStub {
+    pub fn config(&self) -> NodeConfig {
+        NodeConfig {
+            tls_certificate: Some(fixture_hex("tls/ca.pem")),
+            tls_client_certificate: Some(fixture_hex("tls/client.pem")),
+            tls_client_key: Some(fixture_hex("tls/client-key.pem")),
+            ..node_config(&self.url)
+        }
+    }
+
+    /// Decodes the last request received by `method`.
+    pub fn request<M: prost::Message + Default>(&self, method: &str) -> M {
+        last_request(&self.requests, method)
+    }
+
+    pub fn metadata(&self, method: &str) -> MetadataMap {
+        last_metadata(&self.requests, method)
+    }
+
+    pub fn count(&self, method: &str) -> usize {
+        let requests = self.requests.lock().unwrap();
+        requests
+            .iter()
+            .filter(|(name, _, _)| name == method)
+            .count()
+    }
+}
+
+impl RunningLndStub {
+    pub fn config(&self) -> NodeConfig {
+        NodeConfig {
+            macaroon: Some(String::from(MACAROON)),
+            tls_certificate: Some(fixture_hex("tls/server.pem")),
+            ..node_config(&self.url)
+        }
+    }
+
+    /// Decodes the last request received by `method`.
+    pub fn request<M: prost::Message + Default>(&self, method: &str) -> M {
+        last_request(&self.requests, method)
+    }
+
+    pub fn metadata(&self, method: &str) -> MetadataMap {
+        last_metadata(&self.requests, method)
+    }
+}
+
+pub const MACAROON: &str = "0201036c6e640224030a10";
+
+struct ClnNode;
+struct LndLightning;
+struct LndRouter;
+struct LndInvoices;
+
+impl NamedService for ClnNode {
+    const NAME: &'static str = "cln.Node";
+}
+
+impl NamedService for LndLightning {
+    const NAME: &'static str = "lnrpc.Lightning";
+}
+
+impl NamedService for LndRouter {
+    const NAME: &'static str = "routerrpc.Router";
+}
+
+impl NamedService for LndInvoices {
+    const NAME: &'static str = "invoicesrpc.Invoices";
+}
+
+/// Service of a node, replaying the replies of its methods.
+struct GrpcService<N> {
+    replies: Arc<HashMap<String, GrpcReply>>,
+    requests: GrpcRequests,
+    name: PhantomData<N>,
+}
+
+impl<N> GrpcService<N> {
+    fn new(replies: &Arc<HashMap<String, GrpcReply>>, requests: &GrpcRequests) -> Self {
+        GrpcService {
+            replies: replies.clone(),
+            requests: requests.clone(),
+            name: PhantomData,
+        }
+    }
+}
+
+impl<N> Clone for GrpcService<N> {
+    fn clone(&self) -> Self {
+        GrpcService::new(&self.replies, &self.requests)
+    }
+}
+
+impl<N: NamedService> NamedService for GrpcService<N> {
+    const NAME: &'static str = N::NAME;
+}
+
+impl<N> tonic::codegen::Service<http::Request<hyper::Body>> for GrpcService<N> {
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<hyper::Body>) -> Self::Future {
+        let method = request.uri().path().rsplit('/').next().unwrap().to_string();
+        let replay = Replay {
+            method,
+            replies: self.replies.clone(),
+            requests: self.requests.clone(),
+        };
+
+        // Unary answers are sent as a stream of a single message, which is the
+        // same on the wire.
+        Box::pin(async move {
+            let mut grpc = tonic::server::Grpc::new(RawCodec);
+            Ok(grpc.server_streaming(replay, request).await)
+        })
+    }
+}
+
+struct Replay {
+    method: String,
+    replies: Arc<HashMap<String, GrpcReply>>,
+    requests: GrpcRequests,
+}
+
+type ReplayStream = futures::stream::Iter<std::vec::IntoIter<Result<Vec<u8>, Status>>>;
+
+impl tonic::server::ServerStreamingService<Vec<u8>> for Replay {
+    type Response = Vec<u8>;
+    type ResponseStream = ReplayStream;
+    type Future = std::future::Ready<Result<tonic::Response<ReplayStream>, Status>>;
+
+    fn call(&mut self, request: tonic::Request<Vec<u8>>) -> Self::Future {
+        let metadata = request.metadata().clone();
+        self.requests
+            .lock()
+            .unwrap()
+            .push((self.method.clone(), metadata, request.into_inner()));
+
+        let reply = match self.replies.get(&self.method) {
+            Some(Ok(messages)) => {
+                let messages: Vec<_> = messages.iter().cloned().map(Ok).collect();
+                Ok(tonic::Response::new(futures::stream::iter(messages)))
+            }
+            Some(Err((code, message))) => Err(Status::new(*code, message)),
+            None => Err(Status::unimplemented(format!(
+                "no stub for {}",
+                self.method
+            ))),
+        };
+
+        std::future::ready(reply)
+    }
+}
+
+// Passes the messages through as bytes, they are encoded and decoded with the
+// generated types by the tests.
+#[derive(Default)]
+struct RawCodec;
+
+impl Codec for RawCodec {
+    type Encode = Vec<u8>;
+    type Decode = Vec<u8>;
+    type Encoder = RawCodec;
+    type Decoder = RawCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        RawCodec
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        RawCodec
+    }
+}
+
+impl Encoder for RawCodec {
+    type Item = Vec<u8>;
+    type Error = Status;
+
+    fn encode(&mut self, item: Vec<u8>, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        dst.put_slice(&item);
+        Ok(())
+    }
+}
+
+impl Decoder for RawCodec {
+    type Item = Vec<u8>;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Vec<u8>>, Status> {
+        Ok(Some(src.copy_to_bytes(src.remaining()).to_vec()))
+    }
+}
@@ -0,0 +1,1436 @@
+mod common;
+
+use std::time::Duration;
+
+use futures::StreamExt;
+
+use common::{fixture, form, node_config, RestStub, RunningRestStub};
+use una_core::backends::eclair::rest::node::EclairRest;
+use una_core::error::Error;
+use una_core::node::NodeMethods;
+use una_core::types::{
+    AddressType, ChannelStatus, CloseChannelParams, ConnectPeerParams, CreateHoldInvoiceParams,
+    CreateInvoiceParams, CustomRecord, ForwardStatus, InvoiceEventType, InvoiceStatus,
+    KeysendParams, ListForwardsParams, ListInvoicesParams, ListPaymentsParams, Network, NodeConfig,
+    OpenChannelParams, PayInvoiceParams, PaymentStatus, SendOnchainParams, SubscribeInvoicesParams,
+    UpdatePolicyParams, VerifyMessageParams,
+};
+
+const CHANNEL_ID: &str = "4d3c2b1a09f8e7d6c5b4a39281706f5e4d3c2b1a09f8e7d6c5b4a2d1f7e8f7c3";
+const PAYMENT_HASH: &str = "0001020304050607080900010203040506070809000102030405060708090102";
+const PAYMENT_PREIMAGE: &str = "5e2b9c0bb4d0f0a6c5e3b1f7c2d8a9e0f1a2b3c4d5e6f708192a3b4c5d6e7f80";
+const SIGNATURE: &str = "d9fjxye13ep551z4aea58gtd5tg4xbzx9yk8auu1zgy8xbpx73rmsxtd7ymyyqk3je3a1u5fcuo5nprmzi7ybngwftfcsh9qi5k3ayr7";
+const SIGNATURE_HEX: &str = "1fca978112ca1bbdcafac231b39a23dc4da786eff8147c4e72b9807785afee48bb3e23e8160039594a33894f6564e1b1348bbd7a0088d42c4acb73eeaed59c009d";
+const SIGNER_PUBKEY: &str = "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad";
+
+fn eclair(stub: &RunningRestStub) -> EclairRest {
+    let config = NodeConfig {
+        username: Some(String::from("eclair")),
+        password: Some(String::from("hunter2")),
+        ..node_config(&stub.url)
+    };
+
+    EclairRest::new(config.try_into().unwrap()).unwrap()
+}
+
+fn pay_params() -> PayInvoiceParams {
+    PayInvoiceParams {
+        payment_request: fixture("bolt11.txt").trim().to_string(),
+        amount: None,
+        amount_msat: None,
+        max_fee_sat: Some(5),
+        max_fee_msat: None,
+        max_fee_percent: None,
+    }
+}
+
+#[tokio::test]
+async fn get_info() {
+    let stub = RestStub::new()
+        .route("POST", "/getinfo", 200, fixture("eclair/getinfo.json"))
+        .route("POST", "/channels", 200, fixture("eclair/channels.json"))
+        .start()
+        .await;
+
+    let info = eclair(&stub).get_info().await.unwrap();
+
+    assert_eq!(info.version, "0.7.0-a804905");
+    assert!(matches!(info.network, Network::Mainnet));
+    assert_eq!(info.channels.active, 2);
+    assert_eq!(info.channels.inactive, 1);
+    assert_eq!(info.channels.pending, 0);
+
+    // "eclair:hunter2"
+    let request = stub.request("/getinfo");
+    assert_eq!(
+        request.headers["authorization"],
+        "Basic ZWNsYWlyOmh1bnRlcjI="
+    );
+}
+
+#[tokio::test]
+async fn get_info_unknown_channel_state() {
+    let channels = r#"[
+        {
+            "nodeId": "029e03a901b85534ff1e92c43c74431f7ce72046060fcf7a95c37e148f78c77255",
+            "channelId": "c3f7e8f7d1a2b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d",
+            "state": "WAIT_FOR_DUAL_FUNDING_CONFIRMED"
+        },
+        {
+            "nodeId": "029e03a901b85534ff1e92c43c74431f7ce72046060fcf7a95c37e148f78c77255",
+            "channelId": "c3f7e8f7d1a2b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d",
+            "state": "WAIT_FOR_CHANNEL_READY"
+        }
+    ]"#;
+    let stub = RestStub::new()
+        .route("POST", "/getinfo", 200, fixture("eclair/getinfo.json"))
+        .route("POST", "/channels", 200, channels.to_string())
+        .start()
+        .await;
+
+    let info = eclair(&stub).get_info().await.unwrap();
+
+    // States of later releases don't fail the call.
+    assert_eq!(info.channels.active, 0);
+    assert_eq!(info.channels.pending, 2);
+}
+
+#[tokio::test]
+async fn get_balance() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/onchainbalance",
+            200,
+            fixture("eclair/onchainbalance.json"),
+        )
+        .route(
+            "POST",
+            "/usablebalances",
+            200,
+            fixture("eclair/usablebalances.json"),
+        )
+        .route(
+            "POST",
+            "/channels",
+            200,
+            fixture("eclair/channels_full.json"),
+        )
+        .start()
+        .await;
+
+    let balance = eclair(&stub).get_balance().await.unwrap();
+
+    assert_eq!(balance.onchain_confirmed_msat, 1200000000);
+    assert_eq!(balance.onchain_unconfirmed_msat, 300000000);
+    assert_eq!(balance.onchain_locked_msat, 0);
+    assert_eq!(balance.lightning_local_msat, 700000500);
+    assert_eq!(balance.lightning_remote_msat, 250000000);
+    assert_eq!(balance.pending_open_msat, 200000000);
+    // Negotiating and closing channels, the closed one is already settled.
+    assert_eq!(balance.pending_close_msat, 80000000);
+}
+
+#[tokio::test]
+async fn list_channels() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/channels",
+            200,
+            fixture("eclair/channels_full.json"),
+        )
+        .start()
+        .await;
+
+    let channels = eclair(&stub).list_channels().await.unwrap();
+
+    let statuses: Vec<ChannelStatus> = channels.iter().map(|c| c.status.clone()).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            ChannelStatus::Open,
+            ChannelStatus::Opening,
+            ChannelStatus::Opening,
+            ChannelStatus::Closing,
+            ChannelStatus::Closing,
+            ChannelStatus::Closed,
+        ]
+    );
+
+    let normal = &channels[0];
+    assert_eq!(
+        normal.channel_id,
+        "4d3c2b1a09f8e7d6c5b4a39281706f5e4d3c2b1a09f8e7d6c5b4a2d1f7e8f7c3"
+    );
+    assert_eq!(normal.short_channel_id.as_deref(), Some("761300x1x0"));
+    assert_eq!(normal.capacity_msat, 960000000);
+    assert_eq!(normal.local_balance_msat, 700000500);
+    assert_eq!(normal.remote_balance_msat, 250000000);
+    assert_eq!(normal.local_reserve_msat, 9600000);
+    assert!(normal.active);
+    assert!(normal.initiator);
+    assert!(!normal.private);
+
+    // Nothing is known about a channel before its funding transaction.
+    assert_eq!(channels[1].capacity_msat, 0);
+    assert_eq!(channels[1].channel_point, None);
+    assert!(channels[2].private);
+    assert!(!channels[3].initiator);
+}
+
+#[tokio::test]
+async fn open_channel() {
+    let stub = RestStub::new()
+        .route("POST", "/connect", 200, String::from("\"connected\""))
+        .route(
+            "POST",
+            "/open",
+            200,
+            String::from(
+                "\"created channel 98ffd5beddd0cc586debb1563aeebd629d9dd2c0c25bd0a6689cd74e97301a0c \
+                 with fundingTxId=0d1a30974ed79c68a6d05bc2c0d29d9d62bdee3a56b1eb6d58ccd0ddbed5ff98 \
+                 and fees=720 sat\"",
+            ),
+        )
+        .start()
+        .await;
+
+    let params = OpenChannelParams {
+        peer_pubkey: String::from(
+            "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad",
+        ),
+        peer_host: Some(String::from("127.0.0.1:9735")),
+        amount: 1000000,
+        push_amount: Some(10000),
+        fee_rate: Some(5),
+        private: Some(true),
+        min_confs: None,
+    };
+    let result = eclair(&stub).open_channel(params).await.unwrap();
+
+    assert_eq!(
+        result.funding_txid,
+        "0d1a30974ed79c68a6d05bc2c0d29d9d62bdee3a56b1eb6d58ccd0ddbed5ff98"
+    );
+    // Eclair doesn't tell the output index, it is recovered from the channel id.
+    assert_eq!(result.output_index, 1);
+
+    let request = form(&stub.request("/connect").body);
+    assert_eq!(
+        request["uri"],
+        "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad@127.0.0.1:9735"
+    );
+    let request = form(&stub.request("/open").body);
+    assert_eq!(request["fundingSatoshis"], "1000000");
+    assert_eq!(request["pushMsat"], "10000000");
+    assert_eq!(request["fundingFeerateSatByte"], "5");
+    assert_eq!(request["announceChannel"], "false");
+}
+
+#[tokio::test]
+async fn open_channel_min_confs() {
+    let stub = RestStub::new()
+        .route("POST", "/connect", 200, String::from("\"connected\""))
+        .start()
+        .await;
+
+    let params = OpenChannelParams {
+        peer_pubkey: String::from(
+            "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad",
+        ),
+        peer_host: Some(String::from("127.0.0.1:9735")),
+        amount: 1000000,
+        push_amount: None,
+        fee_rate: None,
+        private: None,
+        min_confs: Some(3),
+    };
+    let result = eclair(&stub).open_channel(params).await;
+
+    assert!(matches!(result, Err(Error::NotImplemented)));
+    // Unsupported params don't leave the node connected to the peer.
+    assert!(stub.requests().is_empty());
+}
+
+#[tokio::test]
+async fn force_close_channel() {
+    let channel_id = "4d3c2b1a09f8e7d6c5b4a39281706f5e4d3c2b1a09f8e7d6c5b4a2d1f7e8f7c0";
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/forceclose",
+            200,
+            format!("{{\"{}\": \"ok\"}}", channel_id),
+        )
+        .route(
+            "POST",
+            "/channel",
+            200,
+            fixture("eclair/channel_closing.json"),
+        )
+        .start()
+        .await;
+
+    let params = CloseChannelParams {
+        channel_point: Some(String::from(
+            "c3f7e8f7d1a2b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d:3",
+        )),
+        channel_id: None,
+        force: Some(true),
+        fee_rate: None,
+        delivery_address: None,
+    };
+    let result = eclair(&stub).close_channel(params).await.unwrap();
+
+    assert_eq!(
+        result.closing_txids,
+        vec!["0cab4b19224a80ce0e8ac10ad453744239a1b612d832c77cb916259996ea282e"]
+    );
+
+    let request = form(&stub.request("/forceclose").body);
+    assert_eq!(request["channelId"], channel_id);
+}
+
+#[tokio::test]
+async fn close_channel_force_fee_rate() {
+    let stub = RestStub::new().start().await;
+
+    let params = CloseChannelParams {
+        channel_point: None,
+        channel_id: Some(CHANNEL_ID.to_string()),
+        force: Some(true),
+        fee_rate: Some(5),
+        delivery_address: None,
+    };
+    let result = eclair(&stub).close_channel(params).await;
+
+    // The force close would ignore them.
+    assert!(matches!(result, Err(Error::ApiError(_))));
+    assert!(stub.requests().is_empty());
+}
+
+#[tokio::test]
+async fn close_channel_to_address() {
+    let channel_id = "4d3c2b1a09f8e7d6c5b4a39281706f5e4d3c2b1a09f8e7d6c5b4a2d1f7e8f7c0";
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/close",
+            200,
+            format!("{{\"{}\": \"closing already in progress\"}}", channel_id),
+        )
+        .start()
+        .await;
+
+    let params = CloseChannelParams {
+        channel_point: None,
+        channel_id: Some(channel_id.to_string()),
+        force: None,
+        fee_rate: Some(5),
+        delivery_address: Some(String::from("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")),
+    };
+    let error = eclair(&stub).close_channel(params).await.unwrap_err();
+
+    assert!(matches!(error, Error::ApiError(message) if message == "closing already in progress"));
+
+    // Eclair takes the output script of the address.
+    let request = form(&stub.request("/close").body);
+    assert_eq!(
+        request["scriptPubKey"],
+        "0014751e76e8199196d454941c45d1b3a323f1433bd6"
+    );
+    assert_eq!(request["preferredFeerateSatByte"], "5");
+}
+
+#[tokio::test]
+async fn get_channel_policy() {
+    let stub = RestStub::new()
+        .route("POST", "/channel", 200, fixture("eclair/channel.json"))
+        .start()
+        .await;
+
+    let policy = eclair(&stub)
+        .get_channel_policy(CHANNEL_ID.to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(policy.channel_id, CHANNEL_ID);
+    assert_eq!(policy.base_fee_msat, 1000);
+    assert_eq!(policy.fee_rate_ppm, 100);
+    assert_eq!(policy.time_lock_delta, Some(144));
+    assert_eq!(policy.min_htlc_msat, 1);
+    assert_eq!(policy.max_htlc_msat, Some(950000000));
+
+    let request = form(&stub.request("/channel").body);
+    assert_eq!(request["channelId"], CHANNEL_ID);
+}
+
+#[tokio::test]
+async fn update_channel_policy() {
+    let stub = RestStub::new()
+        .route("POST", "/channel", 200, fixture("eclair/channel.json"))
+        .route(
+            "POST",
+            "/channels",
+            200,
+            format!("[{}]", fixture("eclair/channel.json")),
+        )
+        .route(
+            "POST",
+            "/updaterelayfee",
+            200,
+            format!("{{\"{}\": \"ok\"}}", CHANNEL_ID),
+        )
+        .start()
+        .await;
+
+    let params = UpdatePolicyParams {
+        channel_id: Some(CHANNEL_ID.to_string()),
+        fee_rate_ppm: Some(250),
+        ..Default::default()
+    };
+    eclair(&stub).update_channel_policy(params).await.unwrap();
+
+    // Relay fees are set by peer, the base fee is kept from the channel.
+    let request = form(&stub.request("/updaterelayfee").body);
+    assert_eq!(
+        request["nodeIds"],
+        "029e03a901b85534ff1e92c43c74431f7ce72046060fcf7a95c37e148f78c77255"
+    );
+    assert_eq!(request["feeBaseMsat"], "1000");
+    assert_eq!(request["feeProportionalMillionths"], "250");
+}
+
+#[tokio::test]
+async fn update_channel_policy_several_channels() {
+    let stub = RestStub::new()
+        .route("POST", "/channel", 200, fixture("eclair/channel.json"))
+        .route("POST", "/channels", 200, fixture("eclair/channels.json"))
+        .start()
+        .await;
+
+    let params = UpdatePolicyParams {
+        channel_id: Some(CHANNEL_ID.to_string()),
+        fee_rate_ppm: Some(250),
+        ..Default::default()
+    };
+    let error = eclair(&stub)
+        .update_channel_policy(params)
+        .await
+        .unwrap_err();
+
+    // The relay fees would change for every channel with the peer.
+    assert!(matches!(error, Error::ApiError(_)));
+    assert!(stub
+        .requests()
+        .iter()
+        .all(|request| request.path != "/updaterelayfee"));
+}
+
+#[tokio::test]
+async fn update_channel_policy_not_implemented() {
+    let stub = RestStub::new().start().await;
+
+    let params = UpdatePolicyParams {
+        channel_id: Some(CHANNEL_ID.to_string()),
+        time_lock_delta: Some(80),
+        ..Default::default()
+    };
+    let error = eclair(&stub)
+        .update_channel_policy(params)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::NotImplemented));
+}
+
+#[tokio::test]
+async fn list_peers() {
+    let stub = RestStub::new()
+        .route("POST", "/peers", 200, fixture("eclair/peers.json"))
+        .start()
+        .await;
+
+    let peers = eclair(&stub).list_peers().await.unwrap();
+
+    assert_eq!(peers.len(), 2);
+    assert_eq!(peers[0].addresses, vec!["172.18.0.3:9735"]);
+    assert!(peers[0].connected);
+    assert!(peers[0].features.is_empty());
+    assert_eq!(peers[0].inbound, None);
+    // Eclair also lists the peers it has channels with while disconnected.
+    assert!(!peers[1].connected);
+    assert!(peers[1].addresses.is_empty());
+}
+
+#[tokio::test]
+async fn connect_peer_by_node_id() {
+    let stub = RestStub::new()
+        .route("POST", "/connect", 200, String::from("\"connected\""))
+        .start()
+        .await;
+
+    let params = ConnectPeerParams {
+        pubkey: String::from("03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad"),
+        host: None,
+    };
+    eclair(&stub).connect_peer(params).await.unwrap();
+
+    // Without a host, Eclair looks the address up in the network graph.
+    let request = form(&stub.request("/connect").body);
+    assert_eq!(
+        request["nodeId"],
+        "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad"
+    );
+    assert!(!request.contains_key("uri"));
+}
+
+#[tokio::test]
+async fn new_address() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/getnewaddress",
+            200,
+            String::from("\"bcrt1qaq3qqc8g5ywzj8mxfclzzjkdpemvjlvvmqzk7n\""),
+        )
+        .start()
+        .await;
+    let node = eclair(&stub);
+
+    let address = node.new_address(AddressType::P2wpkh).await.unwrap();
+    assert_eq!(address, "bcrt1qaq3qqc8g5ywzj8mxfclzzjkdpemvjlvvmqzk7n");
+
+    // The Bitcoin Core wallet of Eclair only gives native segwit addresses.
+    let err = node.new_address(AddressType::P2tr).await.unwrap_err();
+    assert!(matches!(err, Error::NotImplemented));
+    assert_eq!(stub.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn send_onchain() {
+    let txid = "6d4cfdbd0e5d9e0c7d9cd6e1a37c2f4a1b76b0e1df6be2d9ab3a05d0f17a2c93";
+    let stub = RestStub::new()
+        .route("POST", "/sendonchain", 200, format!("\"{}\"", txid))
+        .start()
+        .await;
+    let node = eclair(&stub);
+
+    let params = SendOnchainParams {
+        address: String::from("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kyd6q0j"),
+        amount: Some(150000),
+        send_all: None,
+        fee_rate: None,
+        target_conf: None,
+        utxos: None,
+    };
+    assert_eq!(node.send_onchain(params).await.unwrap(), txid);
+
+    // Eclair requires a confirmation target, 6 blocks by default.
+    let request = form(&stub.request("/sendonchain").body);
+    assert_eq!(request["amountSatoshis"], "150000");
+    assert_eq!(request["confirmationTarget"], "6");
+
+    let params = SendOnchainParams {
+        address: String::from("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kyd6q0j"),
+        amount: None,
+        send_all: Some(true),
+        fee_rate: None,
+        target_conf: None,
+        utxos: None,
+    };
+    let err = node.send_onchain(params).await.unwrap_err();
+    assert!(matches!(err, Error::NotImplemented));
+}
+
+#[tokio::test]
+async fn list_onchain_transactions() {
+    let stub = RestStub::new()
+        .route("POST", "/getinfo", 200, fixture("eclair/getinfo.json"))
+        .route(
+            "POST",
+            "/onchaintransactions",
+            200,
+            fixture("eclair/onchaintransactions.json"),
+        )
+        .start()
+        .await;
+
+    let transactions = eclair(&stub).list_onchain_transactions().await.unwrap();
+
+    // Bitcoin Core lists a transaction once per output, the fee being
+    // counted once.
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(
+        transactions[0].txid,
+        "6d4cfdbd0e5d9e0c7d9cd6e1a37c2f4a1b76b0e1df6be2d9ab3a05d0f17a2c93"
+    );
+    assert_eq!(transactions[0].amount_sent_msat, 200141000);
+    assert_eq!(transactions[0].fee_msat, Some(141000));
+    assert_eq!(transactions[0].block_height, None);
+
+    assert_eq!(transactions[1].amount_received_msat, 2000000000);
+    assert_eq!(transactions[1].confirmations, 34);
+    assert_eq!(transactions[1].block_height, Some(761201));
+    assert_eq!(transactions[1].timestamp, Some(1663161812));
+
+    let request = form(&stub.request("/onchaintransactions").body);
+    assert_eq!(request["skip"], "0");
+}
+
+#[tokio::test]
+async fn create_invoice() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/createinvoice",
+            200,
+            fixture("eclair/createinvoice.json"),
+        )
+        .start()
+        .await;
+
+    let params = CreateInvoiceParams {
+        amount: None,
+        amount_msat: Some(250000000),
+        description: Some(String::from("1 cup coffee")),
+        description_hash: None,
+        label: None,
+        expire_in: Some(60),
+        fallback_address: None,
+        payment_preimage: None,
+        cltv_expiry: None,
+    };
+    let result = eclair(&stub).create_invoice(params).await.unwrap();
+
+    assert_eq!(result.payment_hash, PAYMENT_HASH);
+
+    let request = form(&stub.request("/createinvoice").body);
+    assert_eq!(request["amountMsat"], "250000000");
+    assert_eq!(request["description"], "1 cup coffee");
+    assert_eq!(request["expireIn"], "60");
+}
+
+#[tokio::test]
+async fn hold_invoices_not_implemented() {
+    let stub = RestStub::new().start().await;
+    let node = eclair(&stub);
+
+    let params = CreateHoldInvoiceParams {
+        payment_hash: PAYMENT_HASH.to_string(),
+        amount: Some(250000),
+        amount_msat: None,
+        description: None,
+        description_hash: None,
+        expire_in: None,
+        fallback_address: None,
+        cltv_expiry: None,
+    };
+    let error = node.create_hold_invoice(params).await.unwrap_err();
+    assert!(matches!(error, Error::NotImplemented));
+
+    let error = node
+        .settle_hold_invoice(PAYMENT_PREIMAGE.to_string())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::NotImplemented));
+
+    let error = node
+        .cancel_hold_invoice(PAYMENT_HASH.to_string())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::NotImplemented));
+}
+
+#[tokio::test]
+async fn cancel_invoice_not_implemented() {
+    let stub = RestStub::new().start().await;
+    let node = eclair(&stub);
+
+    let error = node
+        .cancel_invoice(PAYMENT_HASH.to_string())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::NotImplemented));
+
+    let error = node.delete_expired_invoices(None).await.unwrap_err();
+    assert!(matches!(error, Error::NotImplemented));
+    assert!(stub.requests().is_empty());
+}
+
+#[tokio::test]
+async fn get_invoice_settled() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/getinvoice",
+            200,
+            fixture("eclair/getinvoice.json"),
+        )
+        .route(
+            "POST",
+            "/getreceivedinfo",
+            200,
+            fixture("eclair/getreceivedinfo.json"),
+        )
+        .start()
+        .await;
+
+    let invoice = eclair(&stub)
+        .get_invoice(PAYMENT_HASH.to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(invoice.status, InvoiceStatus::Settled);
+    assert_eq!(invoice.pre_image.as_deref(), Some(PAYMENT_PREIMAGE));
+    assert_eq!(invoice.settle_date, Some(1496314700));
+
+    let request = form(&stub.request("/getreceivedinfo").body);
+    assert_eq!(request["paymentHash"], PAYMENT_HASH);
+}
+
+#[tokio::test]
+async fn get_invoice_pending() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/getinvoice",
+            200,
+            fixture("eclair/getinvoice.json"),
+        )
+        .route(
+            "POST",
+            "/getreceivedinfo",
+            404,
+            String::from("{\"error\": \"Not found\"}"),
+        )
+        .start()
+        .await;
+
+    let invoice = eclair(&stub)
+        .get_invoice(PAYMENT_HASH.to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(invoice.status, InvoiceStatus::Pending);
+    assert_eq!(invoice.pre_image, None);
+    assert_eq!(invoice.settle_date, None);
+}
+
+#[tokio::test]
+async fn list_invoices() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/listinvoices",
+            200,
+            fixture("eclair/listinvoices.json"),
+        )
+        .route(
+            "POST",
+            "/listpendinginvoices",
+            200,
+            fixture("eclair/listpendinginvoices.json"),
+        )
+        .route("POST", "/audit", 200, fixture("eclair/audit_received.json"))
+        .route(
+            "POST",
+            "/getreceivedinfo",
+            200,
+            fixture("eclair/getreceivedinfo.json"),
+        )
+        .start()
+        .await;
+    let node = eclair(&stub);
+
+    let params = ListInvoicesParams {
+        status: None,
+        pending_only: None,
+        creation_date_start: None,
+        creation_date_end: None,
+        cursor: None,
+        max_invoices: None,
+    };
+    let result = node.list_invoices(params).await.unwrap();
+
+    assert_eq!(result.invoices.len(), 2);
+    assert_eq!(result.invoices[0].status, InvoiceStatus::Settled);
+    assert_eq!(
+        result.invoices[0].pre_image.as_deref(),
+        Some(PAYMENT_PREIMAGE)
+    );
+    assert_eq!(result.invoices[1].status, InvoiceStatus::Pending);
+    assert_eq!(result.next_cursor, None);
+
+    // Only the settled invoice is looked up, for its preimage.
+    let lookups = stub
+        .requests()
+        .into_iter()
+        .filter(|request| request.path == "/getreceivedinfo")
+        .count();
+    assert_eq!(lookups, 1);
+
+    // Filtering on status doesn't look up the invoices left out, and a page
+    // size of 0 gets the default one.
+    let params = ListInvoicesParams {
+        status: Some(InvoiceStatus::Pending),
+        pending_only: None,
+        creation_date_start: None,
+        creation_date_end: None,
+        cursor: None,
+        max_invoices: Some(0),
+    };
+    let result = node.list_invoices(params).await.unwrap();
+
+    assert_eq!(result.invoices.len(), 1);
+    assert_eq!(result.invoices[0].status, InvoiceStatus::Pending);
+    assert_eq!(result.next_cursor, None);
+    let lookups = stub
+        .requests()
+        .into_iter()
+        .filter(|request| request.path == "/getreceivedinfo")
+        .count();
+    assert_eq!(lookups, 1);
+}
+
+#[tokio::test]
+async fn request_timeout() {
+    let stub = RestStub::new()
+        .route("POST", "/getinfo", 200, fixture("eclair/getinfo.json"))
+        .delay(Duration::from_secs(2))
+        .route(
+            "POST",
+            "/payinvoice",
+            200,
+            fixture("eclair/payinvoice.json"),
+        )
+        .delay(Duration::from_secs(2))
+        .start()
+        .await;
+    let config = NodeConfig {
+        username: Some(String::from("eclair")),
+        password: Some(String::from("hunter2")),
+        request_timeout: Some(1),
+        ..node_config(&stub.url)
+    };
+    let eclair = EclairRest::new(config.try_into().unwrap()).unwrap();
+
+    assert!(eclair.get_info().await.is_err());
+    // Payments last longer than a request, they aren't timed out.
+    eclair.pay_invoice(pay_params()).await.unwrap();
+}
+
+#[tokio::test]
+async fn pay_invoice() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/payinvoice",
+            200,
+            fixture("eclair/payinvoice.json"),
+        )
+        .start()
+        .await;
+
+    let result = eclair(&stub).pay_invoice(pay_params()).await.unwrap();
+
+    assert_eq!(result.payment_hash, PAYMENT_HASH);
+    assert_eq!(result.payment_preimage, PAYMENT_PREIMAGE);
+    assert_eq!(result.fees_msat, Some(1250));
+
+    let request = form(&stub.request("/payinvoice").body);
+    assert_eq!(request["blocking"], "true");
+}
+
+#[tokio::test]
+async fn pay_invoice_failed() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/payinvoice",
+            200,
+            fixture("eclair/payinvoice_failed.json"),
+        )
+        .start()
+        .await;
+
+    let error = eclair(&stub).pay_invoice(pay_params()).await.unwrap_err();
+
+    assert!(matches!(error, Error::ApiError(message) if message == "route not found"));
+}
+
+fn keysend_params() -> KeysendParams {
+    KeysendParams {
+        destination: String::from(
+            "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad",
+        ),
+        amount: Some(1000),
+        amount_msat: None,
+        custom_records: None,
+        max_fee_sat: Some(2),
+        max_fee_msat: None,
+        max_fee_percent: None,
+    }
+}
+
+#[tokio::test]
+async fn keysend() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/sendtonode",
+            200,
+            String::from("\"b7c5fd4d-7e5b-4a4c-8c2e-3c8f4e0d8a11\""),
+        )
+        .route(
+            "POST",
+            "/getsentinfo",
+            200,
+            fixture("eclair/getsentinfo.json"),
+        )
+        .start()
+        .await;
+
+    let result = eclair(&stub).keysend(keysend_params()).await.unwrap();
+
+    assert_eq!(result.payment_hash, PAYMENT_HASH);
+    assert_eq!(result.payment_preimage, PAYMENT_PREIMAGE);
+    assert_eq!(result.fees_msat, Some(1001));
+
+    let request = form(&stub.request("/sendtonode").body);
+    assert_eq!(request["amountMsat"], "1000000");
+    assert_eq!(request["maxFeeFlatSat"], "2");
+    assert_eq!(request["maxFeePct"], "0.0");
+    let request = form(&stub.request("/getsentinfo").body);
+    assert_eq!(request["id"], "b7c5fd4d-7e5b-4a4c-8c2e-3c8f4e0d8a11");
+}
+
+#[tokio::test]
+async fn keysend_in_flight() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/sendtonode",
+            200,
+            String::from("\"b7c5fd4d-7e5b-4a4c-8c2e-3c8f4e0d8a11\""),
+        )
+        .route(
+            "POST",
+            "/getsentinfo",
+            200,
+            fixture("eclair/getsentinfo_pending.json"),
+        )
+        .route(
+            "POST",
+            "/getsentinfo",
+            200,
+            fixture("eclair/getsentinfo_pending.json"),
+        )
+        .route(
+            "POST",
+            "/getsentinfo",
+            200,
+            fixture("eclair/getsentinfo.json"),
+        )
+        .start()
+        .await;
+
+    // The payment is waited for while in flight, as sending it again could
+    // pay twice.
+    let result = eclair(&stub).keysend(keysend_params()).await.unwrap();
+
+    assert_eq!(result.payment_preimage, PAYMENT_PREIMAGE);
+    let polls = stub
+        .requests()
+        .into_iter()
+        .filter(|request| request.path == "/getsentinfo")
+        .count();
+    assert_eq!(polls, 3);
+}
+
+#[tokio::test]
+async fn keysend_failed() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/sendtonode",
+            200,
+            String::from("\"b7c5fd4d-7e5b-4a4c-8c2e-3c8f4e0d8a11\""),
+        )
+        .route(
+            "POST",
+            "/getsentinfo",
+            200,
+            fixture("eclair/getsentinfo_failed.json"),
+        )
+        .start()
+        .await;
+
+    let error = eclair(&stub).keysend(keysend_params()).await.unwrap_err();
+
+    assert!(matches!(error, Error::ApiError(message) if message == "route not found"));
+}
+
+#[tokio::test]
+async fn keysend_custom_records_not_implemented() {
+    let stub = RestStub::new().start().await;
+
+    let params = KeysendParams {
+        custom_records: Some(vec![CustomRecord {
+            record_type: 7629169,
+            value: String::from("cafe"),
+        }]),
+        ..keysend_params()
+    };
+    let error = eclair(&stub).keysend(params).await.unwrap_err();
+
+    assert!(matches!(error, Error::NotImplemented));
+    assert!(stub.requests().is_empty());
+}
+
+#[tokio::test]
+async fn decode_invoice() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/parseinvoice",
+            200,
+            fixture("eclair/parseinvoice.json"),
+        )
+        .start()
+        .await;
+
+    let bolt11 = fixture("bolt11.txt").trim().to_string();
+    let invoice = eclair(&stub).decode_invoice(bolt11).await.unwrap();
+
+    assert!(matches!(invoice.network, Network::Mainnet));
+    assert_eq!(invoice.payment_hash, PAYMENT_HASH);
+    assert_eq!(invoice.amount_msat, Some(250000000));
+    assert_eq!(invoice.min_final_cltv_expiry, 18);
+    assert_eq!(
+        invoice.route_hints[0].hops[0].short_channel_id,
+        "66051x263430x1800"
+    );
+    let unknown = invoice
+        .features
+        .iter()
+        .find(|feature| feature.bit == 101)
+        .unwrap();
+    assert!(!unknown.is_known);
+    let trampoline = invoice
+        .features
+        .iter()
+        .find(|feature| feature.bit == 149)
+        .unwrap();
+    assert!(trampoline.is_known);
+    assert!(!trampoline.is_required);
+    assert_eq!(
+        trampoline.name.as_deref(),
+        Some("trampoline_payment_prototype")
+    );
+}
+
+#[tokio::test]
+async fn bad_request_is_api_error() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/getinvoice",
+            400,
+            fixture("eclair/error_bad_request.json"),
+        )
+        .start()
+        .await;
+
+    let error = eclair(&stub)
+        .get_invoice(String::from("00"))
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::ApiError(message) if message == "invalid payment hash: 00"));
+}
+
+#[tokio::test]
+async fn unauthorized() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/getinfo",
+            401,
+            String::from("The supplied authentication is invalid"),
+        )
+        .start()
+        .await;
+
+    let error = eclair(&stub).get_info().await.unwrap_err();
+
+    assert!(matches!(error, Error::Unauthorized));
+}
+
+#[tokio::test]
+async fn other_status_is_api_error() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/getinfo",
+            500,
+            String::from("There was an internal server error."),
+        )
+        .start()
+        .await;
+
+    let error = eclair(&stub).get_info().await.unwrap_err();
+
+    assert!(matches!(error, Error::ApiError(message) if message.contains("500")));
+}
+
+#[tokio::test]
+async fn get_payment() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/getsentinfo",
+            200,
+            fixture("eclair/getsentinfo_parts.json"),
+        )
+        .start()
+        .await;
+
+    let payment = eclair(&stub)
+        .get_payment(PAYMENT_HASH.to_string())
+        .await
+        .unwrap();
+
+    // The failed first attempt is left out.
+    assert_eq!(payment.status, PaymentStatus::Succeeded);
+    assert_eq!(payment.payment_preimage.as_deref(), Some(PAYMENT_PREIMAGE));
+    assert_eq!(payment.amount_msat, 250000000);
+    assert_eq!(payment.fee_msat, Some(1250));
+    assert_eq!(payment.created_at, 1496314680);
+    assert_eq!(payment.parts.len(), 2);
+    assert_eq!(
+        payment.bolt11.as_deref(),
+        Some(fixture("bolt11.txt").trim())
+    );
+
+    let request = form(&stub.request("/getsentinfo").body);
+    assert_eq!(request["paymentHash"], PAYMENT_HASH);
+    assert!(!request.contains_key("id"));
+}
+
+#[tokio::test]
+async fn get_payment_failed() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/getsentinfo",
+            200,
+            fixture("eclair/getsentinfo_failed.json"),
+        )
+        .start()
+        .await;
+
+    let payment = eclair(&stub)
+        .get_payment(PAYMENT_HASH.to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(payment.status, PaymentStatus::Failed);
+    assert_eq!(payment.failure_reason.as_deref(), Some("route not found"));
+    assert_eq!(payment.fee_msat, None);
+}
+
+#[tokio::test]
+async fn list_forwards() {
+    let stub = RestStub::new()
+        .route("POST", "/audit", 200, fixture("eclair/audit_relayed.json"))
+        .route(
+            "POST",
+            "/channels",
+            200,
+            fixture("eclair/channels_full.json"),
+        )
+        .start()
+        .await;
+
+    let params = ListForwardsParams {
+        date_start: Some(1664575200),
+        date_end: Some(1664661600),
+        cursor: None,
+        max_forwards: None,
+    };
+    let result = eclair(&stub).list_forwards(params).await.unwrap();
+
+    // Trampoline relays are left out.
+    assert_eq!(result.forwards.len(), 1);
+    assert_eq!(result.next_cursor, None);
+    let forward = &result.forwards[0];
+    assert_eq!(forward.status, ForwardStatus::Settled);
+    assert_eq!(forward.amount_in_msat, 100001100);
+    assert_eq!(forward.amount_out_msat, Some(100000000));
+    assert_eq!(forward.fee_msat, 1100);
+    assert_eq!(forward.resolved_at, Some(1664575261));
+    // The short channel ids are filled from the open channels only.
+    assert_eq!(forward.incoming_channel_id.as_deref(), Some(CHANNEL_ID));
+    assert_eq!(
+        forward.incoming_short_channel_id.as_deref(),
+        Some("761300x1x0")
+    );
+    assert_eq!(forward.outgoing_short_channel_id, None);
+
+    let request = form(&stub.request("/audit").body);
+    assert_eq!(request["from"], "1664575200");
+    assert_eq!(request["to"], "1664661600");
+}
+
+#[tokio::test]
+async fn list_payments() {
+    let stub = RestStub::new()
+        .route("POST", "/audit", 200, fixture("eclair/audit.json"))
+        .start()
+        .await;
+
+    let params = ListPaymentsParams {
+        status: None,
+        creation_date_start: Some(1496314000),
+        creation_date_end: None,
+        cursor: None,
+        max_payments: None,
+    };
+    let result = eclair(&stub).list_payments(params).await.unwrap();
+
+    assert_eq!(result.payments.len(), 1);
+    assert_eq!(result.next_cursor, None);
+    let payment = &result.payments[0];
+    assert_eq!(payment.status, PaymentStatus::Succeeded);
+    assert_eq!(payment.amount_msat, 250000000);
+    assert_eq!(payment.fee_msat, Some(1250));
+    assert_eq!(payment.created_at, 1496314700);
+
+    let request = form(&stub.request("/audit").body);
+    assert_eq!(request["from"], "1496314000");
+    assert!(!request.contains_key("to"));
+}
+
+#[tokio::test]
+async fn list_payments_failed() {
+    let stub = RestStub::new().start().await;
+
+    let params = ListPaymentsParams {
+        status: Some(PaymentStatus::Failed),
+        creation_date_start: None,
+        creation_date_end: None,
+        cursor: None,
+        max_payments: None,
+    };
+    let result = eclair(&stub).list_payments(params).await;
+
+    // Eclair only lists the payments that succeeded.
+    assert!(matches!(result, Err(Error::NotImplemented)));
+}
+
+#[tokio::test]
+async fn send_payment_async() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/payinvoice",
+            200,
+            String::from("\"b7c5fd4d-7e5b-4a4c-8c2e-3c8f4e0d8a11\""),
+        )
+        .start()
+        .await;
+
+    let payment_hash = eclair(&stub)
+        .send_payment_async(pay_params())
+        .await
+        .unwrap();
+
+    assert_eq!(payment_hash, PAYMENT_HASH);
+
+    // The payment isn't waited for.
+    let requests = stub.requests();
+    assert_eq!(requests.len(), 1);
+    let request = form(&requests[0].body);
+    assert_eq!(request["blocking"], "false");
+}
+
+#[tokio::test]
+async fn subscribe_invoices() {
+    let received = format!(
+        r#"{{"type":"payment-received","paymentHash":"{}","parts":[]}}"#,
+        PAYMENT_HASH
+    );
+    let stub = RestStub::new()
+        .websocket(
+            "/ws",
+            vec![
+                String::from(r#"{"type":"payment-relayed","paymentHash":"00"}"#),
+                String::from("not an event"),
+                received,
+            ],
+        )
+        .route(
+            "POST",
+            "/getinvoice",
+            200,
+            fixture("eclair/getinvoice.json"),
+        )
+        .route(
+            "POST",
+            "/getreceivedinfo",
+            200,
+            fixture("eclair/getreceivedinfo.json"),
+        )
+        .start()
+        .await;
+
+    let events: Vec<_> = eclair(&stub)
+        .subscribe_invoices(SubscribeInvoicesParams { cursor: None })
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    // Other events are skipped, and unreadable ones reported without ending
+    // the subscription, which ends when the socket is closed.
+    assert_eq!(events.len(), 2);
+    assert!(events[0].is_err());
+    let event = events[1].as_ref().unwrap();
+    assert!(matches!(event.event_type, InvoiceEventType::Settled));
+    assert_eq!(event.invoice.payment_hash, PAYMENT_HASH);
+    assert_eq!(event.invoice.status, InvoiceStatus::Settled);
+
+    let request = stub.request("/ws");
+    assert!(request.headers["authorization"]
+        .to_str()
+        .unwrap()
+        .starts_with("Basic "));
+}
+
+#[tokio::test]
+async fn subscribe_invoices_lookup_error() {
+    let received = format!(
+        r#"{{"type":"payment-received","paymentHash":"{}","parts":[]}}"#,
+        PAYMENT_HASH
+    );
+    let stub = RestStub::new()
+        .websocket("/ws", vec![received.clone(), received])
+        .route(
+            "POST",
+            "/getinvoice",
+            500,
+            fixture("eclair/error_bad_request.json"),
+        )
+        .start()
+        .await;
+
+    let events: Vec<_> = eclair(&stub)
+        .subscribe_invoices(SubscribeInvoicesParams { cursor: None })
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    // Lookup errors don't end the subscription.
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|event| event.is_err()));
+}
+
+#[tokio::test]
+async fn track_payment() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/getsentinfo",
+            200,
+            fixture("eclair/getsentinfo_parts.json"),
+        )
+        .start()
+        .await;
+
+    let payments: Vec<_> = eclair(&stub)
+        .track_payment(PAYMENT_HASH.to_string())
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    // The payment is already final, so the tracking ends right away.
+    assert_eq!(payments.len(), 1);
+    let payment = payments[0].as_ref().unwrap();
+    assert_eq!(payment.status, PaymentStatus::Succeeded);
+    assert_eq!(payment.payment_preimage.as_deref(), Some(PAYMENT_PREIMAGE));
+}
+
+#[tokio::test]
+async fn sign_message() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/signmessage",
+            200,
+            format!(
+                "{{\"nodeId\": \"{}\", \"message\": \"SSBvd24gdGhpcyBub2Rl\", \"signature\": \"{}\"}}",
+                SIGNER_PUBKEY, SIGNATURE_HEX
+            ),
+        )
+        .start()
+        .await;
+
+    let signature = eclair(&stub)
+        .sign_message(String::from("I own this node"))
+        .await
+        .unwrap();
+
+    // Eclair signs in hex, converted to zbase32 like the other backends.
+    assert_eq!(signature, SIGNATURE);
+    let request = form(&stub.request("/signmessage").body);
+    assert_eq!(request["msg"], "SSBvd24gdGhpcyBub2Rl");
+}
+
+#[tokio::test]
+async fn verify_message() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/verifymessage",
+            200,
+            format!("{{\"valid\": true, \"publicKey\": \"{}\"}}", SIGNER_PUBKEY),
+        )
+        .start()
+        .await;
+
+    let params = |pubkey: &str| VerifyMessageParams {
+        message: String::from("I own this node"),
+        signature: SIGNATURE.to_string(),
+        pubkey: Some(pubkey.to_string()),
+    };
+
+    let result = eclair(&stub)
+        .verify_message(params(SIGNER_PUBKEY))
+        .await
+        .unwrap();
+    assert!(result.valid);
+    assert_eq!(result.pubkey, SIGNER_PUBKEY);
+
+    // The case of the given key doesn't matter.
+    let result = eclair(&stub)
+        .verify_message(params(&SIGNER_PUBKEY.to_uppercase()))
+        .await
+        .unwrap();
+    assert!(result.valid);
+
+    // A signature recovering another key isn't valid.
+    let other_pubkey = "029e03a901b85534ff1e92c43c74431f7ce72046060fcf7a95c37e148f78c77255";
+    let result = eclair(&stub)
+        .verify_message(params(other_pubkey))
+        .await
+        .unwrap();
+    assert!(!result.valid);
+
+    let request = form(&stub.request("/verifymessage").body);
+    assert_eq!(request["msg"], "SSBvd24gdGhpcyBub2Rl");
+    assert_eq!(request["sig"], SIGNATURE_HEX);
+}
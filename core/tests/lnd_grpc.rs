@@ -0,0 +1,824 @@
+mod common;
+
+use futures::StreamExt;
+use sha2::{Digest, Sha256};
+use tonic::Code;
+
+use common::{fixture, LndStub, RunningLndStub, MACAROON};
+use una_core::backends::lnd::grpc::node::LndGrpc;
+use una_core::backends::lnd::grpc::types::{invoicesrpc, lnrpc, routerrpc};
+use una_core::error::Error;
+use una_core::node::NodeMethods;
+use una_core::types::{
+    ChannelStatus, CloseChannelParams, ConnectPeerParams, CreateHoldInvoiceParams,
+    CreateInvoiceParams, CustomRecord, InvoiceCursor, InvoiceEventType, InvoiceStatus,
+    KeysendParams, ListPaymentsParams, Network, PayInvoiceParams, PaymentStatus,
+    SubscribeInvoicesParams, VerifyMessageParams,
+};
+
+const CHANNEL_ID: &str = "04c93763383228475771918a70676fb27a60f9749695855d5712427498f94822";
+const CHANNEL_POINT: &str = "2348f998744212575d85959674f9607ab26f67708a917157472832386337c904:1";
+const NODE_ID: &str = "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad";
+const PEER_ID: &str = "029e03a901b85534ff1e92c43c74431f7ce72046060fcf7a95c37e148f78c77255";
+const PAYMENT_HASH: &str = "0001020304050607080900010203040506070809000102030405060708090102";
+const PAYMENT_PREIMAGE: &str = "5e2b9c0bb4d0f0a6c5e3b1f7c2d8a9e0f1a2b3c4d5e6f708192a3b4c5d6e7f80";
+
+fn lnd(stub: &RunningLndStub) -> LndGrpc {
+    LndGrpc::new(stub.config().try_into().unwrap()).unwrap()
+}
+
+fn pay_params() -> PayInvoiceParams {
+    PayInvoiceParams {
+        payment_request: fixture("bolt11.txt").trim().to_string(),
+        amount: None,
+        amount_msat: None,
+        max_fee_sat: None,
+        max_fee_msat: Some(5000),
+        max_fee_percent: None,
+    }
+}
+
+fn payment(status: lnrpc::payment::PaymentStatus) -> lnrpc::Payment {
+    lnrpc::Payment {
+        payment_hash: PAYMENT_HASH.to_string(),
+        payment_preimage: PAYMENT_PREIMAGE.to_string(),
+        value_msat: 250000000,
+        fee_msat: 1250,
+        status: status as i32,
+        creation_time_ns: 1667000000000000000,
+        ..Default::default()
+    }
+}
+
+fn invoice(
+    state: lnrpc::invoice::InvoiceState,
+    add_index: u64,
+    settle_index: u64,
+) -> lnrpc::Invoice {
+    lnrpc::Invoice {
+        memo: String::from("coffee"),
+        r_preimage: hex::decode(PAYMENT_PREIMAGE).unwrap(),
+        r_hash: hex::decode(PAYMENT_HASH).unwrap(),
+        value: 250000,
+        value_msat: 250000000,
+        creation_date: 1667000000,
+        settle_date: 1667000100,
+        expiry: 3600,
+        add_index,
+        settle_index,
+        state: state as i32,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn get_info() {
+    let stub = LndStub::new()
+        .reply(
+            "GetInfo",
+            lnrpc::GetInfoResponse {
+                identity_pubkey: NODE_ID.to_string(),
+                version: String::from("0.15.4-beta"),
+                num_active_channels: 3,
+                num_inactive_channels: 2,
+                num_pending_channels: 1,
+                chains: vec![lnrpc::Chain {
+                    chain: String::from("bitcoin"),
+                    network: String::from("mainnet"),
+                }],
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    let info = lnd(&stub).get_info().await.unwrap();
+
+    assert_eq!(info.node_pubkey, NODE_ID);
+    assert_eq!(info.version, "0.15.4-beta");
+    assert!(matches!(info.network, Network::Mainnet));
+    assert_eq!(info.channels.active, 3);
+    assert_eq!(info.channels.pending, 1);
+
+    let metadata = stub.metadata("GetInfo");
+    assert_eq!(metadata.get("macaroon").unwrap(), MACAROON);
+}
+
+#[tokio::test]
+async fn request_timeout() {
+    let stub = LndStub::new()
+        .reply("GetInfo", lnrpc::GetInfoResponse::default())
+        .reply(
+            "SendPaymentV2",
+            payment(lnrpc::payment::PaymentStatus::Succeeded),
+        )
+        .start()
+        .await;
+    let config = una_core::types::NodeConfig {
+        request_timeout: Some(30),
+        ..stub.config()
+    };
+    let lnd = LndGrpc::new(config.try_into().unwrap()).unwrap();
+
+    lnd.get_info().await.unwrap();
+    lnd.pay_invoice(pay_params()).await.unwrap();
+
+    assert!(stub.metadata("GetInfo").get("grpc-timeout").is_some());
+    // Payments last longer than a request, they aren't timed out.
+    assert!(stub.metadata("SendPaymentV2").get("grpc-timeout").is_none());
+}
+
+#[tokio::test]
+async fn get_balance() {
+    let stub = LndStub::new()
+        .reply(
+            "WalletBalance",
+            lnrpc::WalletBalanceResponse {
+                confirmed_balance: 150000,
+                unconfirmed_balance: 20000,
+                locked_balance: 10000,
+                ..Default::default()
+            },
+        )
+        .reply(
+            "ChannelBalance",
+            lnrpc::ChannelBalanceResponse {
+                local_balance: Some(lnrpc::Amount {
+                    sat: 700000,
+                    msat: 700000000,
+                }),
+                remote_balance: Some(lnrpc::Amount {
+                    sat: 250000,
+                    msat: 250000000,
+                }),
+                pending_open_local_balance: Some(lnrpc::Amount {
+                    sat: 50000,
+                    msat: 50000000,
+                }),
+                ..Default::default()
+            },
+        )
+        .reply(
+            "ListChannels",
+            lnrpc::ListChannelsResponse {
+                channels: vec![lnrpc::Channel {
+                    channel_point: CHANNEL_POINT.to_string(),
+                    local_balance: 700000,
+                    remote_balance: 250000,
+                    local_constraints: Some(lnrpc::ChannelConstraints {
+                        chan_reserve_sat: 10000,
+                        ..Default::default()
+                    }),
+                    remote_constraints: Some(lnrpc::ChannelConstraints {
+                        chan_reserve_sat: 5000,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+            },
+        )
+        .reply(
+            "PendingChannels",
+            lnrpc::PendingChannelsResponse {
+                total_limbo_balance: 80000,
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    let balance = lnd(&stub).get_balance().await.unwrap();
+
+    assert_eq!(balance.onchain_confirmed_msat, 150000000);
+    assert_eq!(balance.onchain_unconfirmed_msat, 20000000);
+    assert_eq!(balance.onchain_locked_msat, 10000000);
+    // The channel reserves are left out.
+    assert_eq!(balance.lightning_local_msat, 690000000);
+    assert_eq!(balance.lightning_remote_msat, 245000000);
+    assert_eq!(balance.pending_open_msat, 50000000);
+    assert_eq!(balance.pending_close_msat, 80000000);
+}
+
+#[tokio::test]
+async fn list_channels() {
+    use lnrpc::pending_channels_response::{PendingChannel, PendingOpenChannel};
+
+    let stub = LndStub::new()
+        .reply(
+            "ListChannels",
+            lnrpc::ListChannelsResponse {
+                channels: vec![lnrpc::Channel {
+                    active: true,
+                    remote_pubkey: PEER_ID.to_string(),
+                    channel_point: CHANNEL_POINT.to_string(),
+                    chan_id: 837058202225934337,
+                    capacity: 1000000,
+                    local_balance: 700000,
+                    remote_balance: 250000,
+                    local_constraints: Some(lnrpc::ChannelConstraints {
+                        chan_reserve_sat: 10000,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+            },
+        )
+        .reply(
+            "PendingChannels",
+            lnrpc::PendingChannelsResponse {
+                pending_open_channels: vec![PendingOpenChannel {
+                    channel: Some(PendingChannel {
+                        remote_node_pub: PEER_ID.to_string(),
+                        channel_point: CHANNEL_POINT.replace(":1", ":0"),
+                        capacity: 500000,
+                        local_balance: 490000,
+                        initiator: lnrpc::Initiator::Local as i32,
+                        private: true,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    let channels = lnd(&stub).list_channels().await.unwrap();
+
+    assert_eq!(channels.len(), 2);
+    let opening = &channels[0];
+    assert_eq!(opening.status, ChannelStatus::Opening);
+    assert_eq!(opening.short_channel_id, None);
+    assert!(opening.initiator);
+    assert!(opening.private);
+
+    let open = &channels[1];
+    assert_eq!(open.status, ChannelStatus::Open);
+    assert_eq!(open.channel_id, CHANNEL_ID);
+    assert_eq!(open.short_channel_id.as_deref(), Some("761300x1x1"));
+    assert_eq!(open.capacity_msat, 1000000000);
+    assert_eq!(open.local_balance_msat, 700000000);
+    assert_eq!(open.local_reserve_msat, 10000000);
+    assert!(open.active);
+}
+
+#[tokio::test]
+async fn close_channel() {
+    let txid = hex::decode(CHANNEL_POINT.split(':').next().unwrap()).unwrap();
+    let stub = LndStub::new()
+        .stream(
+            "CloseChannel",
+            vec![lnrpc::CloseStatusUpdate {
+                update: Some(lnrpc::close_status_update::Update::ClosePending(
+                    lnrpc::PendingUpdate {
+                        txid: txid.iter().rev().cloned().collect(),
+                        output_index: 0,
+                    },
+                )),
+            }],
+        )
+        .start()
+        .await;
+
+    let params = CloseChannelParams {
+        channel_id: None,
+        channel_point: Some(CHANNEL_POINT.to_string()),
+        force: None,
+        fee_rate: Some(5),
+        delivery_address: None,
+    };
+    let result = lnd(&stub).close_channel(params).await.unwrap();
+
+    assert_eq!(
+        result.closing_txids,
+        vec![CHANNEL_POINT.split(':').next().unwrap().to_string()]
+    );
+    let request: lnrpc::CloseChannelRequest = stub.request("CloseChannel");
+    assert_eq!(request.channel_point.unwrap().output_index, 1);
+    assert_eq!(request.sat_per_vbyte, 5);
+    assert!(!request.force);
+}
+
+#[tokio::test]
+async fn connect_peer_already_connected() {
+    let stub = LndStub::new()
+        .fail(
+            "ConnectPeer",
+            Code::Unknown,
+            "already connected to peer: 029e03a9",
+        )
+        .start()
+        .await;
+
+    let params = ConnectPeerParams {
+        pubkey: PEER_ID.to_string(),
+        host: Some(String::from("127.0.0.1:9735")),
+    };
+    lnd(&stub).connect_peer(params).await.unwrap();
+
+    let request: lnrpc::ConnectPeerRequest = stub.request("ConnectPeer");
+    assert_eq!(request.addr.unwrap().host, "127.0.0.1:9735");
+}
+
+#[tokio::test]
+async fn connect_peer_graph_address() {
+    let stub = LndStub::new()
+        .reply(
+            "GetNodeInfo",
+            lnrpc::NodeInfo {
+                node: Some(lnrpc::LightningNode {
+                    pub_key: PEER_ID.to_string(),
+                    addresses: vec![lnrpc::NodeAddress {
+                        network: String::from("tcp"),
+                        addr: String::from("172.18.0.3:9735"),
+                    }],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .reply("ConnectPeer", lnrpc::ConnectPeerResponse::default())
+        .start()
+        .await;
+
+    let params = ConnectPeerParams {
+        pubkey: PEER_ID.to_string(),
+        host: None,
+    };
+    lnd(&stub).connect_peer(params).await.unwrap();
+
+    let request: lnrpc::NodeInfoRequest = stub.request("GetNodeInfo");
+    assert_eq!(request.pub_key, PEER_ID);
+    assert!(!request.include_channels);
+    let request: lnrpc::ConnectPeerRequest = stub.request("ConnectPeer");
+    assert_eq!(request.addr.unwrap().host, "172.18.0.3:9735");
+}
+
+#[tokio::test]
+async fn create_invoice() {
+    let stub = LndStub::new()
+        .reply(
+            "AddInvoice",
+            lnrpc::AddInvoiceResponse {
+                r_hash: hex::decode(PAYMENT_HASH).unwrap(),
+                payment_request: String::from("lnbc2500u1p"),
+                add_index: 12,
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    let params = CreateInvoiceParams {
+        amount: None,
+        amount_msat: Some(250000000),
+        description: Some(String::from("coffee")),
+        description_hash: None,
+        label: None,
+        expire_in: Some(600),
+        fallback_address: None,
+        payment_preimage: None,
+        cltv_expiry: None,
+    };
+    let result = lnd(&stub).create_invoice(params).await.unwrap();
+
+    assert_eq!(result.payment_hash, PAYMENT_HASH);
+    assert_eq!(result.payment_request, "lnbc2500u1p");
+    let request: lnrpc::Invoice = stub.request("AddInvoice");
+    assert_eq!(request.value_msat, 250000000);
+    assert_eq!(request.memo, "coffee");
+    assert_eq!(request.expiry, 600);
+}
+
+#[tokio::test]
+async fn create_hold_invoice() {
+    let stub = LndStub::new()
+        .reply(
+            "AddHoldInvoice",
+            invoicesrpc::AddHoldInvoiceResp {
+                payment_request: String::from("lnbc2500u1p"),
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    let params = CreateHoldInvoiceParams {
+        payment_hash: PAYMENT_HASH.to_string(),
+        amount: Some(250000),
+        amount_msat: None,
+        description: Some(String::from("coffee")),
+        description_hash: None,
+        expire_in: None,
+        fallback_address: None,
+        cltv_expiry: Some(40),
+    };
+    let result = lnd(&stub).create_hold_invoice(params).await.unwrap();
+
+    assert_eq!(result.payment_hash, PAYMENT_HASH);
+    let request: invoicesrpc::AddHoldInvoiceRequest = stub.request("AddHoldInvoice");
+    assert_eq!(hex::encode(request.hash), PAYMENT_HASH);
+    assert_eq!(request.value_msat, 250000000);
+    assert_eq!(request.cltv_expiry, 40);
+}
+
+#[tokio::test]
+async fn create_hold_invoice_amount_overflow() {
+    let stub = LndStub::new().start().await;
+
+    let params = CreateHoldInvoiceParams {
+        payment_hash: PAYMENT_HASH.to_string(),
+        amount: None,
+        amount_msat: Some(u64::MAX),
+        description: None,
+        description_hash: None,
+        expire_in: None,
+        fallback_address: None,
+        cltv_expiry: None,
+    };
+    let error = lnd(&stub).create_hold_invoice(params).await.unwrap_err();
+
+    assert!(matches!(error, Error::ConversionError(_)));
+}
+
+#[tokio::test]
+async fn settle_and_cancel_hold_invoice() {
+    let stub = LndStub::new()
+        .reply("SettleInvoice", invoicesrpc::SettleInvoiceResp {})
+        .reply("CancelInvoice", invoicesrpc::CancelInvoiceResp {})
+        .start()
+        .await;
+    let lnd = lnd(&stub);
+
+    lnd.settle_hold_invoice(PAYMENT_PREIMAGE.to_string())
+        .await
+        .unwrap();
+    lnd.cancel_hold_invoice(PAYMENT_HASH.to_string())
+        .await
+        .unwrap();
+
+    let request: invoicesrpc::SettleInvoiceMsg = stub.request("SettleInvoice");
+    assert_eq!(hex::encode(request.preimage), PAYMENT_PREIMAGE);
+    let request: invoicesrpc::CancelInvoiceMsg = stub.request("CancelInvoice");
+    assert_eq!(hex::encode(request.payment_hash), PAYMENT_HASH);
+}
+
+#[tokio::test]
+async fn get_invoice() {
+    let stub = LndStub::new()
+        .reply(
+            "LookupInvoice",
+            invoice(lnrpc::invoice::InvoiceState::Settled, 12, 5),
+        )
+        .start()
+        .await;
+
+    let invoice = lnd(&stub)
+        .get_invoice(PAYMENT_HASH.to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(invoice.status, InvoiceStatus::Settled);
+    assert!(invoice.settled);
+    assert_eq!(invoice.amount_msat, 250000000);
+    assert_eq!(invoice.pre_image.as_deref(), Some(PAYMENT_PREIMAGE));
+    assert_eq!(invoice.settle_date, Some(1667000100));
+    let request: lnrpc::PaymentHash = stub.request("LookupInvoice");
+    assert_eq!(hex::encode(request.r_hash), PAYMENT_HASH);
+}
+
+#[tokio::test]
+async fn pay_invoice() {
+    let stub = LndStub::new()
+        .reply(
+            "SendPaymentV2",
+            payment(lnrpc::payment::PaymentStatus::Succeeded),
+        )
+        .start()
+        .await;
+
+    let result = lnd(&stub).pay_invoice(pay_params()).await.unwrap();
+
+    assert_eq!(result.payment_hash, PAYMENT_HASH);
+    assert_eq!(result.payment_preimage, PAYMENT_PREIMAGE);
+    assert_eq!(result.fees_msat, Some(1250));
+    let request: routerrpc::SendPaymentRequest = stub.request("SendPaymentV2");
+    assert_eq!(request.fee_limit_msat, 5000);
+    assert!(request.no_inflight_updates);
+}
+
+#[tokio::test]
+async fn pay_invoice_failed() {
+    let mut failed = payment(lnrpc::payment::PaymentStatus::Failed);
+    failed.failure_reason = lnrpc::PaymentFailureReason::FailureReasonNoRoute as i32;
+    let stub = LndStub::new().reply("SendPaymentV2", failed).start().await;
+
+    let result = lnd(&stub).pay_invoice(pay_params()).await;
+
+    assert!(matches!(result, Err(Error::ApiError(message)) if message == "no route found"));
+}
+
+#[tokio::test]
+async fn keysend() {
+    let stub = LndStub::new()
+        .reply(
+            "SendPaymentV2",
+            payment(lnrpc::payment::PaymentStatus::Succeeded),
+        )
+        .start()
+        .await;
+
+    let params = KeysendParams {
+        destination: PEER_ID.to_string(),
+        amount: Some(1000),
+        amount_msat: None,
+        max_fee_sat: None,
+        max_fee_msat: None,
+        max_fee_percent: None,
+        custom_records: Some(vec![CustomRecord {
+            record_type: 34349334,
+            value: String::from("68656c6c6f"),
+        }]),
+    };
+    lnd(&stub).keysend(params).await.unwrap();
+
+    let request: routerrpc::SendPaymentRequest = stub.request("SendPaymentV2");
+    assert_eq!(hex::encode(request.dest), PEER_ID);
+    assert_eq!(request.amt_msat, 1000000);
+    assert_eq!(request.dest_custom_records[&34349334], b"hello");
+    let preimage = &request.dest_custom_records[&5482373484];
+    assert_eq!(Sha256::digest(preimage).to_vec(), request.payment_hash);
+}
+
+#[tokio::test]
+async fn send_payment_async() {
+    let stub = LndStub::new()
+        .stream(
+            "SendPaymentV2",
+            vec![
+                payment(lnrpc::payment::PaymentStatus::InFlight),
+                payment(lnrpc::payment::PaymentStatus::Succeeded),
+            ],
+        )
+        .start()
+        .await;
+
+    let payment_hash = lnd(&stub).send_payment_async(pay_params()).await.unwrap();
+
+    assert_eq!(payment_hash, PAYMENT_HASH);
+    let request: routerrpc::SendPaymentRequest = stub.request("SendPaymentV2");
+    assert!(!request.no_inflight_updates);
+}
+
+#[tokio::test]
+async fn track_payment() {
+    let stub = LndStub::new()
+        .stream(
+            "TrackPaymentV2",
+            vec![
+                payment(lnrpc::payment::PaymentStatus::InFlight),
+                payment(lnrpc::payment::PaymentStatus::Succeeded),
+            ],
+        )
+        .start()
+        .await;
+
+    let payments: Vec<_> = lnd(&stub)
+        .track_payment(PAYMENT_HASH.to_string())
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    assert_eq!(payments.len(), 2);
+    let pending = payments[0].as_ref().unwrap();
+    assert_eq!(pending.status, PaymentStatus::Pending);
+    assert_eq!(pending.payment_preimage, None);
+    let succeeded = payments[1].as_ref().unwrap();
+    assert_eq!(succeeded.status, PaymentStatus::Succeeded);
+    assert_eq!(
+        succeeded.payment_preimage.as_deref(),
+        Some(PAYMENT_PREIMAGE)
+    );
+    assert_eq!(succeeded.created_at, 1667000000);
+}
+
+#[tokio::test]
+async fn list_payments() {
+    let mut failed = payment(lnrpc::payment::PaymentStatus::Failed);
+    failed.payment_hash = "ff".repeat(32);
+    let stub = LndStub::new()
+        .reply(
+            "ListPayments",
+            lnrpc::ListPaymentsResponse {
+                payments: vec![payment(lnrpc::payment::PaymentStatus::Succeeded), failed],
+                last_index_offset: 8,
+                ..Default::default()
+            },
+        )
+        .start()
+        .await;
+
+    let params = ListPaymentsParams {
+        cursor: Some(6),
+        max_payments: Some(2),
+        status: Some(PaymentStatus::Succeeded),
+        creation_date_start: None,
+        creation_date_end: None,
+    };
+    let result = lnd(&stub).list_payments(params).await.unwrap();
+
+    assert_eq!(result.payments.len(), 1);
+    assert_eq!(result.payments[0].payment_hash, PAYMENT_HASH);
+    assert_eq!(result.next_cursor, Some(8));
+    let request: lnrpc::ListPaymentsRequest = stub.request("ListPayments");
+    assert_eq!(request.index_offset, 6);
+    assert_eq!(request.max_payments, 2);
+}
+
+#[tokio::test]
+async fn subscribe_invoices() {
+    let stub = LndStub::new()
+        .stream(
+            "SubscribeInvoices",
+            vec![
+                invoice(lnrpc::invoice::InvoiceState::Open, 13, 0),
+                invoice(lnrpc::invoice::InvoiceState::Settled, 13, 5),
+            ],
+        )
+        .start()
+        .await;
+
+    let params = SubscribeInvoicesParams {
+        cursor: Some(InvoiceCursor {
+            add_index: Some(11),
+            settle_index: Some(4),
+        }),
+    };
+    let events: Vec<_> = lnd(&stub)
+        .subscribe_invoices(params)
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 2);
+    let created = events[0].as_ref().unwrap();
+    assert_eq!(created.invoice.status, InvoiceStatus::Pending);
+    assert_eq!(created.cursor.add_index, Some(13));
+    assert_eq!(created.cursor.settle_index, Some(4));
+    let settled = events[1].as_ref().unwrap();
+    assert_eq!(settled.invoice.status, InvoiceStatus::Settled);
+    assert_eq!(settled.cursor.settle_index, Some(5));
+
+    let request: lnrpc::InvoiceSubscription = stub.request("SubscribeInvoices");
+    assert_eq!(request.add_index, 11);
+    assert_eq!(request.settle_index, 4);
+}
+
+#[tokio::test]
+async fn subscribe_invoices_without_settle_index() {
+    let stub = LndStub::new()
+        .reply(
+            "ListInvoices",
+            lnrpc::ListInvoiceResponse {
+                invoices: vec![
+                    invoice(lnrpc::invoice::InvoiceState::Settled, 10, 3),
+                    invoice(lnrpc::invoice::InvoiceState::Settled, 12, 2),
+                    invoice(lnrpc::invoice::InvoiceState::Open, 13, 0),
+                ],
+                ..Default::default()
+            },
+        )
+        .stream(
+            "SubscribeInvoices",
+            vec![invoice(lnrpc::invoice::InvoiceState::Settled, 13, 4)],
+        )
+        .start()
+        .await;
+
+    // Only created events were seen, the settlements since are sent again.
+    let params = SubscribeInvoicesParams {
+        cursor: Some(InvoiceCursor {
+            add_index: Some(12),
+            settle_index: None,
+        }),
+    };
+    let events: Vec<_> = lnd(&stub)
+        .subscribe_invoices(params)
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    let settle_indexes: Vec<_> = events
+        .iter()
+        .map(|event| event.as_ref().unwrap().cursor.settle_index)
+        .collect();
+    assert_eq!(settle_indexes, vec![Some(2), Some(3), Some(4)]);
+    let replayed = events[0].as_ref().unwrap();
+    assert!(matches!(replayed.event_type, InvoiceEventType::Settled));
+    assert_eq!(replayed.invoice.status, InvoiceStatus::Settled);
+
+    let request: lnrpc::InvoiceSubscription = stub.request("SubscribeInvoices");
+    assert_eq!(request.add_index, 12);
+    assert_eq!(request.settle_index, 0);
+}
+
+#[tokio::test]
+async fn subscribe_invoices_new() {
+    let stub = LndStub::new()
+        .reply(
+            "ListInvoices",
+            lnrpc::ListInvoiceResponse {
+                invoices: vec![invoice(lnrpc::invoice::InvoiceState::Settled, 12, 2)],
+                ..Default::default()
+            },
+        )
+        .stream(
+            "SubscribeInvoices",
+            vec![invoice(lnrpc::invoice::InvoiceState::Open, 13, 0)],
+        )
+        .start()
+        .await;
+
+    let events: Vec<_> = lnd(&stub)
+        .subscribe_invoices(SubscribeInvoicesParams::default())
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    // The cursor starts from the last settlement, so that none is missed once
+    // resumed.
+    assert_eq!(events.len(), 1);
+    let created = events[0].as_ref().unwrap();
+    assert_eq!(created.cursor.add_index, Some(13));
+    assert_eq!(created.cursor.settle_index, Some(2));
+}
+
+#[tokio::test]
+async fn sign_and_verify_message() {
+    let stub = LndStub::new()
+        .reply(
+            "SignMessage",
+            lnrpc::SignMessageResponse {
+                signature: String::from("d9fjxye13ep551z4aea58gtd5tg4xbzx"),
+            },
+        )
+        .reply(
+            "VerifyMessage",
+            lnrpc::VerifyMessageResponse {
+                valid: false,
+                pubkey: NODE_ID.to_string(),
+            },
+        )
+        .start()
+        .await;
+    let lnd = lnd(&stub);
+
+    let signature = lnd.sign_message(String::from("hello")).await.unwrap();
+    assert_eq!(signature, "d9fjxye13ep551z4aea58gtd5tg4xbzx");
+
+    // The signer isn't in the graph of the node, but is the one expected,
+    // whatever the case of the given key.
+    let params = VerifyMessageParams {
+        message: String::from("hello"),
+        signature,
+        pubkey: Some(NODE_ID.to_uppercase()),
+    };
+    let result = lnd.verify_message(params).await.unwrap();
+
+    assert!(result.valid);
+    assert_eq!(result.pubkey, NODE_ID);
+    let request: lnrpc::SignMessageRequest = stub.request("SignMessage");
+    assert_eq!(request.msg, b"hello");
+}
+
+#[tokio::test]
+async fn permission_denied_is_unauthorized() {
+    let stub = LndStub::new()
+        .fail("GetInfo", Code::PermissionDenied, "verification failed")
+        .start()
+        .await;
+
+    let result = lnd(&stub).get_info().await;
+
+    assert!(matches!(result, Err(Error::Unauthorized)));
+}
+
+#[tokio::test]
+async fn unknown_certificate_is_refused() {
+    let stub = LndStub::new()
+        .reply("GetInfo", lnrpc::GetInfoResponse::default())
+        .start()
+        .await;
+    let config = una_core::types::NodeConfig {
+        tls_certificate: Some(common::fixture_hex("tls/client.pem")),
+        ..stub.config()
+    };
+    let lnd = LndGrpc::new(config.try_into().unwrap()).unwrap();
+
+    assert!(lnd.get_info().await.is_err());
+}
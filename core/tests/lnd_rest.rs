@@ -0,0 +1,1266 @@
+mod common;
+
+use std::time::Duration;
+
+use futures::StreamExt;
+use sha2::{Digest, Sha256};
+
+use common::{fixture, fixture_hex, node_config, RestStub, RunningRestStub};
+use una_core::backends::lnd::rest::node::LndRest;
+use una_core::error::Error;
+use una_core::node::NodeMethods;
+use una_core::types::{
+    AddressType, ChannelStatus, CloseChannelParams, ConnectPeerParams, CreateHoldInvoiceParams,
+    CreateInvoiceParams, CustomRecord, ForwardStatus, InvoiceCursor, InvoiceEventType,
+    InvoiceStatus, KeysendParams, ListForwardsParams, ListInvoicesParams, ListPaymentsParams,
+    Network, OpenChannelParams, PayInvoiceParams, PaymentStatus, SendOnchainParams,
+    SubscribeInvoicesParams, UpdatePolicyParams, VerifyMessageParams,
+};
+
+const MACAROON: &str = "0201036c6e6402f801";
+const CHANNEL_ID: &str = "04c93763383228475771918a70676fb27a60f9749695855d5712427498f94822";
+const PAYMENT_HASH: &str = "0001020304050607080900010203040506070809000102030405060708090102";
+const PAYMENT_PREIMAGE: &str = "5e2b9c0bb4d0f0a6c5e3b1f7c2d8a9e0f1a2b3c4d5e6f708192a3b4c5d6e7f80";
+const SIGNATURE: &str = "d9fjxye13ep551z4aea58gtd5tg4xbzx9yk8auu1zgy8xbpx73rmsxtd7ymyyqk3je3a1u5fcuo5nprmzi7ybngwftfcsh9qi5k3ayr7";
+const SIGNER_PUBKEY: &str = "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad";
+
+fn lnd(stub: &RunningRestStub) -> LndRest {
+    let config = una_core::types::NodeConfig {
+        macaroon: Some(MACAROON.to_string()),
+        tls_certificate: Some(fixture_hex("tls/ca.pem")),
+        ..node_config(&stub.url)
+    };
+
+    LndRest::new(config.try_into().unwrap()).unwrap()
+}
+
+fn pay_params() -> PayInvoiceParams {
+    PayInvoiceParams {
+        payment_request: fixture("bolt11.txt").trim().to_string(),
+        amount: None,
+        amount_msat: None,
+        max_fee_sat: None,
+        max_fee_msat: Some(5000),
+        max_fee_percent: None,
+    }
+}
+
+#[tokio::test]
+async fn get_info() {
+    let stub = RestStub::new()
+        .route("GET", "/v1/getinfo", 200, fixture("lnd/getinfo.json"))
+        .start()
+        .await;
+
+    let info = lnd(&stub).get_info().await.unwrap();
+
+    assert_eq!(
+        info.node_pubkey,
+        "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad"
+    );
+    assert!(matches!(info.network, Network::Mainnet));
+    assert_eq!(info.channels.active, 3);
+    assert_eq!(info.channels.inactive, 2);
+    assert_eq!(info.channels.pending, 1);
+
+    let request = stub.request("/v1/getinfo");
+    assert_eq!(request.headers["grpc-metadata-macaroon"], MACAROON);
+}
+
+#[tokio::test]
+async fn get_balance() {
+    let stub = RestStub::new()
+        .route(
+            "GET",
+            "/v1/balance/blockchain",
+            200,
+            fixture("lnd/walletbalance.json"),
+        )
+        .route(
+            "GET",
+            "/v1/balance/channels",
+            200,
+            fixture("lnd/channelbalance.json"),
+        )
+        .route("GET", "/v1/channels", 200, fixture("lnd/listchannels.json"))
+        .route(
+            "GET",
+            "/v1/channels/pending",
+            200,
+            fixture("lnd/pendingchannels.json"),
+        )
+        .start()
+        .await;
+
+    let balance = lnd(&stub).get_balance().await.unwrap();
+
+    assert_eq!(balance.onchain_confirmed_msat, 1200000000);
+    assert_eq!(balance.onchain_unconfirmed_msat, 300000000);
+    assert_eq!(balance.onchain_locked_msat, 50000000);
+    // The channel reserves are left out, and only open channels counted.
+    assert_eq!(balance.lightning_local_msat, 690000000);
+    assert_eq!(balance.lightning_remote_msat, 240000000);
+    assert_eq!(balance.pending_open_msat, 200000000);
+    assert_eq!(balance.pending_close_msat, 80000000);
+}
+
+#[tokio::test]
+async fn list_channels() {
+    let stub = RestStub::new()
+        .route("GET", "/v1/channels", 200, fixture("lnd/listchannels.json"))
+        .route(
+            "GET",
+            "/v1/channels/pending",
+            200,
+            fixture("lnd/pendingchannels.json"),
+        )
+        .start()
+        .await;
+
+    let channels = lnd(&stub).list_channels().await.unwrap();
+
+    assert_eq!(channels.len(), 3);
+    let opening = &channels[0];
+    assert_eq!(opening.status, ChannelStatus::Opening);
+    assert_eq!(opening.short_channel_id, None);
+    assert!(opening.initiator);
+    assert!(opening.private);
+    assert!(!opening.active);
+
+    let open = &channels[1];
+    assert_eq!(open.status, ChannelStatus::Open);
+    assert_eq!(
+        open.channel_id,
+        "04c93763383228475771918a70676fb27a60f9749695855d5712427498f94822"
+    );
+    assert_eq!(open.short_channel_id.as_deref(), Some("761300x1x1"));
+    assert_eq!(open.capacity_msat, 1000000000);
+    assert_eq!(open.local_balance_msat, 700000000);
+    assert_eq!(open.remote_balance_msat, 250000000);
+    assert_eq!(open.local_reserve_msat, 10000000);
+    assert!(open.active);
+
+    let closing = &channels[2];
+    assert_eq!(closing.status, ChannelStatus::Closing);
+    assert!(!closing.initiator);
+}
+
+#[tokio::test]
+async fn open_channel() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/v1/peers",
+            500,
+            fixture("lnd/error_already_connected.json"),
+        )
+        .route("POST", "/v1/channels", 200, fixture("lnd/openchannel.json"))
+        .start()
+        .await;
+
+    let params = OpenChannelParams {
+        peer_pubkey: String::from(
+            "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad",
+        ),
+        peer_host: Some(String::from("127.0.0.1:9735")),
+        amount: 1000000,
+        push_amount: Some(10000),
+        fee_rate: Some(5),
+        private: Some(true),
+        min_confs: Some(0),
+    };
+    let result = lnd(&stub).open_channel(params).await.unwrap();
+
+    // The funding transaction id comes in internal byte order.
+    assert_eq!(
+        result.funding_txid,
+        "0d1a30974ed79c68a6d05bc2c0d29d9d62bdee3a56b1eb6d58ccd0ddbed5ff98"
+    );
+    assert_eq!(result.output_index, 1);
+    assert_eq!(
+        result.channel_id,
+        "98ffd5beddd0cc586debb1563aeebd629d9dd2c0c25bd0a6689cd74e97301a0c"
+    );
+
+    let request: serde_json::Value = serde_json::from_str(&stub.request("/v1/peers").body).unwrap();
+    assert_eq!(request["addr"]["host"], "127.0.0.1:9735");
+
+    let request: serde_json::Value =
+        serde_json::from_str(&stub.request("/v1/channels").body).unwrap();
+    assert_eq!(
+        request["node_pubkey"],
+        "A+cVauM7CiCNB0QZkWMXfpCegBduVdl6LyIe3g+TTdmt"
+    );
+    assert_eq!(request["local_funding_amount"], "1000000");
+    assert_eq!(request["push_sat"], "10000");
+    assert_eq!(request["sat_per_vbyte"], "5");
+    assert_eq!(request["private"], true);
+    assert_eq!(request["spend_unconfirmed"], true);
+}
+
+#[tokio::test]
+async fn close_channel_by_channel_id() {
+    let stub = RestStub::new()
+        .route("GET", "/v1/channels", 200, fixture("lnd/listchannels.json"))
+        .route(
+            "GET",
+            "/v1/channels/pending",
+            200,
+            fixture("lnd/pendingchannels.json"),
+        )
+        .route(
+            "DELETE",
+            "/v1/channels/2348f998744212575d85959674f9607ab26f67708a917157472832386337c904/1",
+            200,
+            fixture("lnd/closechannel.jsonl"),
+        )
+        .start()
+        .await;
+
+    let params = CloseChannelParams {
+        channel_point: None,
+        channel_id: Some(String::from(
+            "04c93763383228475771918a70676fb27a60f9749695855d5712427498f94822",
+        )),
+        force: Some(true),
+        fee_rate: None,
+        delivery_address: None,
+    };
+    let result = lnd(&stub).close_channel(params).await.unwrap();
+
+    assert_eq!(
+        result.closing_txids,
+        vec!["a85aeb8057eea358e16d7afe1d31776ae015722faf2bb44b0a771f9fdd924839"]
+    );
+
+    // LND closes channels by channel point, looked up from the channel id.
+    let request = stub
+        .requests()
+        .into_iter()
+        .find(|request| request.method == "DELETE")
+        .unwrap();
+    assert_eq!(request.query.as_deref(), Some("force=true"));
+}
+
+#[tokio::test]
+async fn list_peers() {
+    let stub = RestStub::new()
+        .route("GET", "/v1/peers", 200, fixture("lnd/listpeers.json"))
+        .start()
+        .await;
+
+    let peers = lnd(&stub).list_peers().await.unwrap();
+
+    assert_eq!(peers.len(), 2);
+    assert_eq!(
+        peers[0].pubkey,
+        "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad"
+    );
+    assert_eq!(peers[0].addresses, vec!["172.18.0.3:9735"]);
+    assert!(peers[0].connected);
+    assert_eq!(peers[0].inbound, Some(false));
+    let bits: Vec<u32> = peers[0]
+        .features
+        .iter()
+        .map(|feature| feature.bit)
+        .collect();
+    assert_eq!(bits, vec![0, 17]);
+    assert!(peers[0].features[0].is_required);
+    assert_eq!(peers[1].inbound, Some(true));
+    assert!(peers[1].features.is_empty());
+}
+
+fn channels_stub() -> RestStub {
+    RestStub::new()
+        .route("GET", "/v1/channels", 200, fixture("lnd/listchannels.json"))
+        .route(
+            "GET",
+            "/v1/channels/pending",
+            200,
+            fixture("lnd/pendingchannels.json"),
+        )
+        .route(
+            "GET",
+            "/v1/graph/edge/837058202225934337",
+            200,
+            fixture("lnd/getchaninfo.json"),
+        )
+}
+
+#[tokio::test]
+async fn get_channel_policy() {
+    let stub = channels_stub().start().await;
+
+    let policy = lnd(&stub)
+        .get_channel_policy(CHANNEL_ID.to_string())
+        .await
+        .unwrap();
+
+    // The policy of the peer is the other one of the edge.
+    assert_eq!(policy.channel_id, CHANNEL_ID);
+    assert_eq!(policy.base_fee_msat, 500);
+    assert_eq!(policy.fee_rate_ppm, 250);
+    assert_eq!(policy.time_lock_delta, Some(40));
+    assert_eq!(policy.min_htlc_msat, 1000);
+    assert_eq!(policy.max_htlc_msat, Some(990000000));
+}
+
+#[tokio::test]
+async fn update_channel_policy() {
+    let stub = channels_stub()
+        .route(
+            "POST",
+            "/v1/chanpolicy",
+            200,
+            fixture("lnd/updatechanpolicy.json"),
+        )
+        .start()
+        .await;
+
+    let params = UpdatePolicyParams {
+        channel_id: Some(CHANNEL_ID.to_string()),
+        fee_rate_ppm: Some(100),
+        ..Default::default()
+    };
+    lnd(&stub).update_channel_policy(params).await.unwrap();
+
+    // The fields not given are kept from the current policy.
+    let request: serde_json::Value =
+        serde_json::from_str(&stub.request("/v1/chanpolicy").body).unwrap();
+    assert_eq!(
+        request["chan_point"]["funding_txid_str"],
+        "2348f998744212575d85959674f9607ab26f67708a917157472832386337c904"
+    );
+    assert_eq!(request["chan_point"]["output_index"], 1);
+    assert!(request.get("global").is_none());
+    assert_eq!(request["base_fee_msat"], "500");
+    assert_eq!(request["fee_rate_ppm"], 100);
+    assert_eq!(request["time_lock_delta"], 40);
+    assert_eq!(request["min_htlc_msat"], "1000");
+    assert_eq!(request["min_htlc_msat_specified"], true);
+}
+
+#[tokio::test]
+async fn update_channel_policy_every_channel() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/v1/chanpolicy",
+            200,
+            fixture("lnd/updatechanpolicy.json"),
+        )
+        .start()
+        .await;
+
+    let params = UpdatePolicyParams {
+        fee_rate_ppm: Some(100),
+        ..Default::default()
+    };
+    let error = lnd(&stub).update_channel_policy(params).await.unwrap_err();
+    assert!(matches!(error, Error::ApiError(_)));
+
+    let params = UpdatePolicyParams {
+        base_fee_msat: Some(1000),
+        fee_rate_ppm: Some(100),
+        time_lock_delta: Some(80),
+        ..Default::default()
+    };
+    lnd(&stub).update_channel_policy(params).await.unwrap();
+
+    let request: serde_json::Value =
+        serde_json::from_str(&stub.request("/v1/chanpolicy").body).unwrap();
+    assert_eq!(request["global"], true);
+    assert!(request.get("chan_point").is_none());
+    assert_eq!(request["min_htlc_msat_specified"], false);
+}
+
+#[tokio::test]
+async fn connect_peer_graph_address() {
+    let path = format!("/v1/graph/node/{}", SIGNER_PUBKEY);
+    let stub = RestStub::new()
+        .route("GET", &path, 200, fixture("lnd/getnodeinfo.json"))
+        .route("POST", "/v1/peers", 200, String::from("{}"))
+        .start()
+        .await;
+
+    let params = ConnectPeerParams {
+        pubkey: SIGNER_PUBKEY.to_string(),
+        host: None,
+    };
+    lnd(&stub).connect_peer(params).await.unwrap();
+
+    // LND needs an address, taken from the announcement of the peer.
+    let request: serde_json::Value = serde_json::from_str(&stub.request("/v1/peers").body).unwrap();
+    assert_eq!(request["addr"]["pubkey"], SIGNER_PUBKEY);
+    assert_eq!(request["addr"]["host"], "172.18.0.3:9735");
+}
+
+#[tokio::test]
+async fn connect_peer_without_address() {
+    let path = format!("/v1/graph/node/{}", SIGNER_PUBKEY);
+    let stub = RestStub::new()
+        .route(
+            "GET",
+            &path,
+            200,
+            format!(
+                "{{\"node\": {{\"pub_key\": \"{}\", \"addresses\": []}}}}",
+                SIGNER_PUBKEY
+            ),
+        )
+        .start()
+        .await;
+
+    let params = ConnectPeerParams {
+        pubkey: SIGNER_PUBKEY.to_string(),
+        host: None,
+    };
+    let err = lnd(&stub).connect_peer(params).await.unwrap_err();
+
+    assert!(matches!(err, Error::ApiError(message) if message == "no address known for the peer"));
+    assert!(stub
+        .requests()
+        .iter()
+        .all(|request| request.path != "/v1/peers"));
+}
+
+#[tokio::test]
+async fn disconnect_peer() {
+    let pubkey = "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad";
+    let path = format!("/v1/peers/{}", pubkey);
+    let stub = RestStub::new()
+        .route("DELETE", &path, 200, String::from("{}"))
+        .start()
+        .await;
+
+    lnd(&stub)
+        .disconnect_peer(pubkey.to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(stub.request(&path).method, "DELETE");
+}
+
+#[tokio::test]
+async fn new_address() {
+    let stub = RestStub::new()
+        .route("GET", "/v1/newaddress", 200, fixture("lnd/newaddress.json"))
+        .start()
+        .await;
+
+    let address = lnd(&stub).new_address(AddressType::P2tr).await.unwrap();
+
+    assert_eq!(
+        address,
+        "bcrt1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqc8gma6"
+    );
+    assert_eq!(
+        stub.request("/v1/newaddress").query.as_deref(),
+        Some("type=TAPROOT_PUBKEY")
+    );
+}
+
+#[tokio::test]
+async fn send_onchain_all() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/v1/transactions",
+            200,
+            fixture("lnd/sendcoins.json"),
+        )
+        .start()
+        .await;
+
+    let params = SendOnchainParams {
+        address: String::from("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kyd6q0j"),
+        amount: None,
+        send_all: Some(true),
+        fee_rate: None,
+        target_conf: Some(3),
+        utxos: None,
+    };
+    let txid = lnd(&stub).send_onchain(params).await.unwrap();
+
+    assert_eq!(
+        txid,
+        "6d4cfdbd0e5d9e0c7d9cd6e1a37c2f4a1b76b0e1df6be2d9ab3a05d0f17a2c93"
+    );
+
+    let request: serde_json::Value =
+        serde_json::from_str(&stub.request("/v1/transactions").body).unwrap();
+    assert_eq!(
+        request["addr"],
+        "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kyd6q0j"
+    );
+    assert_eq!(request["send_all"], true);
+    assert_eq!(request["amount"], serde_json::Value::Null);
+    assert_eq!(request["target_conf"], 3);
+}
+
+#[tokio::test]
+async fn send_onchain_requires_amount() {
+    let stub = RestStub::new().start().await;
+
+    let params = SendOnchainParams {
+        address: String::from("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kyd6q0j"),
+        amount: None,
+        send_all: None,
+        fee_rate: Some(2),
+        target_conf: None,
+        utxos: None,
+    };
+    let err = lnd(&stub).send_onchain(params).await.unwrap_err();
+
+    assert!(matches!(err, Error::ApiError(message) if message == "amount or send_all is required"));
+    assert!(stub.requests().is_empty());
+}
+
+#[tokio::test]
+async fn list_onchain_transactions() {
+    let stub = RestStub::new()
+        .route(
+            "GET",
+            "/v1/transactions",
+            200,
+            fixture("lnd/gettransactions.json"),
+        )
+        .start()
+        .await;
+
+    let transactions = lnd(&stub).list_onchain_transactions().await.unwrap();
+
+    assert_eq!(transactions.len(), 2);
+    assert_eq!(transactions[0].amount_received_msat, 2000000000);
+    assert_eq!(transactions[0].amount_sent_msat, 0);
+    assert_eq!(transactions[0].confirmations, 12);
+    assert_eq!(transactions[0].block_height, Some(140));
+    assert_eq!(transactions[0].timestamp, Some(1663251812));
+    assert_eq!(transactions[0].label, None);
+    assert_eq!(transactions[0].short_channel_id, None);
+
+    // The channel of a transaction is read from the label LND gives it.
+    assert_eq!(transactions[1].amount_received_msat, 0);
+    assert_eq!(transactions[1].amount_sent_msat, 1000720000);
+    assert_eq!(transactions[1].fee_msat, Some(720000));
+    assert_eq!(
+        transactions[1].label.as_deref(),
+        Some("0:openchannel:shortchanid-167125767487488")
+    );
+    assert_eq!(transactions[1].short_channel_id.as_deref(), Some("152x1x0"));
+}
+
+#[tokio::test]
+async fn create_invoice() {
+    let stub = RestStub::new()
+        .route("POST", "/v1/invoices", 200, fixture("lnd/addinvoice.json"))
+        .start()
+        .await;
+
+    let params = CreateInvoiceParams {
+        amount: Some(250000),
+        amount_msat: None,
+        description: Some(String::from("1 cup coffee")),
+        description_hash: None,
+        label: None,
+        expire_in: Some(60),
+        fallback_address: None,
+        payment_preimage: None,
+        cltv_expiry: None,
+    };
+    let result = lnd(&stub).create_invoice(params).await.unwrap();
+
+    assert_eq!(result.payment_hash, PAYMENT_HASH);
+    assert!(result.payment_request.starts_with("lnbc2500u1"));
+
+    let request: serde_json::Value =
+        serde_json::from_str(&stub.request("/v1/invoices").body).unwrap();
+    assert_eq!(request["value_msat"], 250000000);
+    assert_eq!(request["memo"], "1 cup coffee");
+}
+
+#[tokio::test]
+async fn create_hold_invoice() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/v2/invoices/hodl",
+            200,
+            fixture("lnd/addholdinvoice.json"),
+        )
+        .start()
+        .await;
+
+    let params = CreateHoldInvoiceParams {
+        payment_hash: PAYMENT_HASH.to_string(),
+        amount: Some(250000),
+        amount_msat: None,
+        description: Some(String::from("1 cup coffee")),
+        description_hash: None,
+        expire_in: None,
+        fallback_address: None,
+        cltv_expiry: None,
+    };
+    let result = lnd(&stub).create_hold_invoice(params).await.unwrap();
+
+    assert_eq!(result.payment_hash, PAYMENT_HASH);
+    assert!(result.payment_request.starts_with("lnbc2500u1"));
+
+    let request: serde_json::Value =
+        serde_json::from_str(&stub.request("/v2/invoices/hodl").body).unwrap();
+    assert_eq!(
+        request["hash"],
+        "AAECAwQFBgcICQABAgMEBQYHCAkAAQIDBAUGBwgJAQI="
+    );
+    assert_eq!(request["value_msat"], 250000000);
+    assert_eq!(request["expiry"], 3600);
+}
+
+#[tokio::test]
+async fn settle_and_cancel_hold_invoice() {
+    let stub = RestStub::new()
+        .route("POST", "/v2/invoices/settle", 200, String::from("{}"))
+        .route("POST", "/v2/invoices/cancel", 200, String::from("{}"))
+        .start()
+        .await;
+    let node = lnd(&stub);
+
+    node.settle_hold_invoice(PAYMENT_PREIMAGE.to_string())
+        .await
+        .unwrap();
+    let request: serde_json::Value =
+        serde_json::from_str(&stub.request("/v2/invoices/settle").body).unwrap();
+    assert_eq!(
+        request["preimage"],
+        "XiucC7TQ8KbF47H3wtip4PGis8TV5vcIGSo7TF1uf4A="
+    );
+
+    node.cancel_hold_invoice(PAYMENT_HASH.to_string())
+        .await
+        .unwrap();
+    let request: serde_json::Value =
+        serde_json::from_str(&stub.request("/v2/invoices/cancel").body).unwrap();
+    assert_eq!(
+        request["payment_hash"],
+        "AAECAwQFBgcICQABAgMEBQYHCAkAAQIDBAUGBwgJAQI="
+    );
+}
+
+#[tokio::test]
+async fn cancel_invoice() {
+    let stub = RestStub::new()
+        .route("POST", "/v2/invoices/cancel", 200, String::from("{}"))
+        .start()
+        .await;
+    let node = lnd(&stub);
+
+    node.cancel_invoice(PAYMENT_HASH.to_string()).await.unwrap();
+
+    let request: serde_json::Value =
+        serde_json::from_str(&stub.request("/v2/invoices/cancel").body).unwrap();
+    assert_eq!(
+        request["payment_hash"],
+        "AAECAwQFBgcICQABAgMEBQYHCAkAAQIDBAUGBwgJAQI="
+    );
+
+    let error = node.delete_expired_invoices(None).await.unwrap_err();
+    assert!(matches!(error, Error::NotImplemented));
+}
+
+#[tokio::test]
+async fn get_invoice() {
+    let path = format!("/v1/invoice/{}", PAYMENT_HASH);
+    let stub = RestStub::new()
+        .route("GET", &path, 200, fixture("lnd/lookupinvoice.json"))
+        .start()
+        .await;
+
+    let invoice = lnd(&stub)
+        .get_invoice(PAYMENT_HASH.to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(invoice.payment_hash, PAYMENT_HASH);
+    assert_eq!(invoice.pre_image.as_deref(), Some(PAYMENT_PREIMAGE));
+    assert_eq!(invoice.amount_msat, 250000000);
+    assert_eq!(invoice.settle_date, Some(1496314700));
+    assert_eq!(invoice.status, InvoiceStatus::Settled);
+}
+
+#[tokio::test]
+async fn list_invoices() {
+    let stub = RestStub::new()
+        .route("GET", "/v1/invoices", 200, fixture("lnd/listinvoices.json"))
+        .start()
+        .await;
+
+    let params = ListInvoicesParams {
+        status: None,
+        pending_only: None,
+        creation_date_start: None,
+        creation_date_end: None,
+        cursor: Some(11),
+        max_invoices: Some(2),
+    };
+    let result = lnd(&stub).list_invoices(params).await.unwrap();
+
+    assert_eq!(result.invoices.len(), 2);
+    assert_eq!(result.invoices[0].status, InvoiceStatus::Settled);
+    assert_eq!(result.invoices[1].status, InvoiceStatus::Pending);
+    assert_eq!(result.invoices[1].pre_image, None);
+    assert_eq!(result.next_cursor, Some(13));
+
+    let query = stub.request("/v1/invoices").query.unwrap();
+    assert!(query.contains("index_offset=11"));
+    assert!(query.contains("num_max_invoices=2"));
+}
+
+#[tokio::test]
+async fn request_timeout() {
+    let stub = RestStub::new()
+        .route("GET", "/v1/getinfo", 200, fixture("lnd/getinfo.json"))
+        .delay(Duration::from_secs(2))
+        .route(
+            "POST",
+            "/v1/channels/transactions",
+            200,
+            fixture("lnd/sendpaymentsync.json"),
+        )
+        .delay(Duration::from_secs(2))
+        .start()
+        .await;
+    let config = una_core::types::NodeConfig {
+        macaroon: Some(MACAROON.to_string()),
+        tls_certificate: Some(fixture_hex("tls/ca.pem")),
+        request_timeout: Some(1),
+        ..node_config(&stub.url)
+    };
+    let lnd = LndRest::new(config.try_into().unwrap()).unwrap();
+
+    assert!(lnd.get_info().await.is_err());
+    // Payments last longer than a request, they aren't timed out.
+    lnd.pay_invoice(pay_params()).await.unwrap();
+}
+
+#[tokio::test]
+async fn pay_invoice() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/v1/channels/transactions",
+            200,
+            fixture("lnd/sendpaymentsync.json"),
+        )
+        .start()
+        .await;
+
+    let result = lnd(&stub).pay_invoice(pay_params()).await.unwrap();
+
+    assert_eq!(result.payment_hash, PAYMENT_HASH);
+    assert_eq!(result.payment_preimage, PAYMENT_PREIMAGE);
+    assert_eq!(result.fees_msat, Some(1250));
+}
+
+#[tokio::test]
+async fn pay_invoice_payment_error() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/v1/channels/transactions",
+            200,
+            fixture("lnd/sendpaymentsync_error.json"),
+        )
+        .start()
+        .await;
+
+    let error = lnd(&stub).pay_invoice(pay_params()).await.unwrap_err();
+
+    assert!(
+        matches!(error, Error::ApiError(message) if message == "unable to find a path to destination")
+    );
+}
+
+#[tokio::test]
+async fn keysend() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/v1/channels/transactions",
+            200,
+            fixture("lnd/sendpaymentsync.json"),
+        )
+        .start()
+        .await;
+
+    let params = KeysendParams {
+        destination: String::from(
+            "03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad",
+        ),
+        amount: Some(250000),
+        amount_msat: None,
+        custom_records: Some(vec![CustomRecord {
+            record_type: 7629169,
+            value: String::from("cafe"),
+        }]),
+        max_fee_sat: None,
+        max_fee_msat: Some(5000),
+        max_fee_percent: None,
+    };
+    let result = lnd(&stub).keysend(params).await.unwrap();
+
+    assert_eq!(result.payment_preimage, PAYMENT_PREIMAGE);
+    assert_eq!(result.fees_msat, Some(1250));
+
+    let request: serde_json::Value =
+        serde_json::from_str(&stub.request("/v1/channels/transactions").body).unwrap();
+    assert_eq!(
+        request["dest"],
+        "A+cVauM7CiCNB0QZkWMXfpCegBduVdl6LyIe3g+TTdmt"
+    );
+    assert_eq!(request["amt_msat"], "250000000");
+    assert_eq!(request["dest_custom_records"]["7629169"], "yv4=");
+    // The preimage sent to the destination must match the payment hash.
+    let preimage = request["dest_custom_records"]["5482373484"]
+        .as_str()
+        .unwrap();
+    let payment_hash = Sha256::digest(base64::decode(preimage).unwrap());
+    assert_eq!(request["payment_hash"], base64::encode(payment_hash));
+}
+
+#[tokio::test]
+async fn decode_invoice() {
+    let bolt11 = fixture("bolt11.txt").trim().to_string();
+    let path = format!("/v1/payreq/{}", bolt11);
+    let stub = RestStub::new()
+        .route("GET", &path, 200, fixture("lnd/decodepayreq.json"))
+        .start()
+        .await;
+
+    let invoice = lnd(&stub).decode_invoice(bolt11).await.unwrap();
+
+    assert!(matches!(invoice.network, Network::Mainnet));
+    assert_eq!(invoice.amount_msat, Some(250000000));
+    assert_eq!(invoice.description.as_deref(), Some("1 cup coffee"));
+    assert_eq!(invoice.min_final_cltv_expiry, 18);
+    assert_eq!(
+        invoice.route_hints[0].hops[0].short_channel_id,
+        "66051x263430x1800"
+    );
+    let bits: Vec<u32> = invoice.features.iter().map(|feature| feature.bit).collect();
+    assert_eq!(bits, vec![9, 14, 17]);
+}
+
+#[tokio::test]
+async fn subscribe_invoices() {
+    let stub = RestStub::new()
+        .route(
+            "GET",
+            "/v1/invoices/subscribe",
+            200,
+            fixture("lnd/subscribeinvoices.jsonl"),
+        )
+        .start()
+        .await;
+
+    let params = SubscribeInvoicesParams {
+        cursor: Some(InvoiceCursor {
+            add_index: Some(11),
+            settle_index: Some(4),
+        }),
+    };
+    let events: Vec<_> = lnd(&stub)
+        .subscribe_invoices(params)
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 2);
+    let created = events[0].as_ref().unwrap();
+    assert_eq!(created.invoice.status, InvoiceStatus::Pending);
+    assert_eq!(created.cursor.add_index, Some(13));
+    assert_eq!(created.cursor.settle_index, Some(4));
+    let settled = events[1].as_ref().unwrap();
+    assert_eq!(settled.invoice.status, InvoiceStatus::Settled);
+    assert_eq!(settled.cursor.add_index, Some(13));
+    assert_eq!(settled.cursor.settle_index, Some(5));
+
+    let query = stub.request("/v1/invoices/subscribe").query.unwrap();
+    assert!(query.contains("add_index=11"));
+    assert!(query.contains("settle_index=4"));
+}
+
+#[tokio::test]
+async fn subscribe_invoices_without_settle_index() {
+    let stub = RestStub::new()
+        .route("GET", "/v1/invoices", 200, fixture("lnd/listinvoices.json"))
+        .route(
+            "GET",
+            "/v1/invoices/subscribe",
+            200,
+            fixture("lnd/subscribeinvoices.jsonl"),
+        )
+        .start()
+        .await;
+
+    // Only created events were seen, the settlements since are sent again.
+    let params = SubscribeInvoicesParams {
+        cursor: Some(InvoiceCursor {
+            add_index: Some(11),
+            settle_index: None,
+        }),
+    };
+    let events: Vec<_> = lnd(&stub)
+        .subscribe_invoices(params)
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 3);
+    let replayed = events[0].as_ref().unwrap();
+    assert!(matches!(replayed.event_type, InvoiceEventType::Settled));
+    assert_eq!(replayed.invoice.status, InvoiceStatus::Settled);
+    assert_eq!(replayed.cursor.add_index, Some(11));
+    assert_eq!(replayed.cursor.settle_index, Some(5));
+
+    let query = stub.request("/v1/invoices/subscribe").query.unwrap();
+    assert!(query.contains("add_index=11"));
+    assert!(query.contains("settle_index=0"));
+}
+
+#[tokio::test]
+async fn subscribe_invoices_stream_error() {
+    let stub = RestStub::new()
+        .route(
+            "GET",
+            "/v1/invoices/subscribe",
+            200,
+            fixture("lnd/subscribeinvoices_error.jsonl"),
+        )
+        .route("GET", "/v1/invoices", 200, fixture("lnd/listinvoices.json"))
+        .start()
+        .await;
+
+    let events: Vec<_> = lnd(&stub)
+        .subscribe_invoices(SubscribeInvoicesParams::default())
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 2);
+    // A new subscription starts from the last settlement.
+    assert_eq!(events[0].as_ref().unwrap().cursor.settle_index, Some(5));
+    assert!(
+        matches!(&events[1], Err(Error::ApiError(message)) if message == "invoice subscription cancelled")
+    );
+}
+
+#[tokio::test]
+async fn permission_denied_is_unauthorized() {
+    let stub = RestStub::new()
+        .route(
+            "GET",
+            "/v1/getinfo",
+            500,
+            fixture("lnd/error_permission_denied.json"),
+        )
+        .start()
+        .await;
+
+    let error = lnd(&stub).get_info().await.unwrap_err();
+
+    assert!(matches!(error, Error::Unauthorized));
+}
+
+#[tokio::test]
+async fn not_found_is_api_error() {
+    let path = format!("/v1/invoice/{}", PAYMENT_HASH);
+    let stub = RestStub::new()
+        .route(
+            "GET",
+            &path,
+            404,
+            fixture("lnd/error_invoice_not_found.json"),
+        )
+        .start()
+        .await;
+
+    let error = lnd(&stub)
+        .get_invoice(PAYMENT_HASH.to_string())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::ApiError(message) if message == "unable to locate invoice"));
+}
+
+#[tokio::test]
+async fn internal_error_is_api_error() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/v1/channels/transactions",
+            500,
+            fixture("lnd/error_invoice_expired.json"),
+        )
+        .start()
+        .await;
+
+    let error = lnd(&stub).pay_invoice(pay_params()).await.unwrap_err();
+
+    assert!(matches!(error, Error::ApiError(message) if message.starts_with("invoice expired")));
+}
+
+#[tokio::test]
+async fn other_status_is_api_error() {
+    let stub = RestStub::new()
+        .route("GET", "/v1/getinfo", 502, String::from("Bad Gateway"))
+        .start()
+        .await;
+
+    let error = lnd(&stub).get_info().await.unwrap_err();
+
+    assert!(matches!(error, Error::ApiError(message) if message.contains("502")));
+}
+
+#[tokio::test]
+async fn get_payment() {
+    let stub = RestStub::new()
+        .route(
+            "GET",
+            "/v2/router/track/AAECAwQFBgcICQABAgMEBQYHCAkAAQIDBAUGBwgJAQI=",
+            200,
+            fixture("lnd/trackpayment.jsonl"),
+        )
+        .start()
+        .await;
+
+    let payment = lnd(&stub)
+        .get_payment(PAYMENT_HASH.to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(payment.payment_hash, PAYMENT_HASH);
+    assert_eq!(payment.status, PaymentStatus::Pending);
+    assert_eq!(payment.payment_preimage, None);
+    assert_eq!(payment.amount_msat, 250000000);
+    assert_eq!(payment.created_at, 1496314699);
+    assert_eq!(payment.parts.len(), 1);
+    assert_eq!(payment.parts[0].status, PaymentStatus::Pending);
+}
+
+#[tokio::test]
+async fn list_forwards() {
+    let stub = channels_stub()
+        .route(
+            "POST",
+            "/v1/switch",
+            200,
+            fixture("lnd/forwardinghistory.json"),
+        )
+        .start()
+        .await;
+
+    let params = ListForwardsParams {
+        date_start: Some(1664575200),
+        date_end: None,
+        cursor: Some(5),
+        max_forwards: Some(2),
+    };
+    let result = lnd(&stub).list_forwards(params).await.unwrap();
+
+    assert_eq!(result.forwards.len(), 2);
+    assert_eq!(result.next_cursor, Some(7));
+
+    let forward = &result.forwards[0];
+    assert_eq!(forward.status, ForwardStatus::Settled);
+    assert_eq!(forward.amount_in_msat, 100001100);
+    assert_eq!(forward.amount_out_msat, Some(100000000));
+    assert_eq!(forward.fee_msat, 1100);
+    assert_eq!(forward.received_at, None);
+    assert_eq!(forward.resolved_at, Some(1664575261));
+    // The channel ids are filled from the open channels only.
+    assert_eq!(forward.incoming_channel_id.as_deref(), Some(CHANNEL_ID));
+    assert_eq!(
+        forward.incoming_short_channel_id.as_deref(),
+        Some("761300x1x1")
+    );
+    assert_eq!(forward.outgoing_channel_id, None);
+    assert_eq!(
+        forward.outgoing_short_channel_id.as_deref(),
+        Some("761234x1021x0")
+    );
+
+    let request: serde_json::Value =
+        serde_json::from_str(&stub.request("/v1/switch").body).unwrap();
+    assert_eq!(request["start_time"], 1664575200);
+    assert_eq!(request["end_time"], 0);
+    assert_eq!(request["index_offset"], 5);
+    assert_eq!(request["num_max_events"], 2);
+}
+
+#[tokio::test]
+async fn list_payments() {
+    let stub = RestStub::new()
+        .route("GET", "/v1/payments", 200, fixture("lnd/listpayments.json"))
+        .start()
+        .await;
+
+    let params = ListPaymentsParams {
+        status: None,
+        creation_date_start: None,
+        creation_date_end: None,
+        cursor: Some(11),
+        max_payments: Some(2),
+    };
+    let result = lnd(&stub).list_payments(params).await.unwrap();
+
+    assert_eq!(result.payments.len(), 2);
+    assert_eq!(result.next_cursor, Some(13));
+
+    let succeeded = &result.payments[0];
+    assert_eq!(succeeded.status, PaymentStatus::Succeeded);
+    assert_eq!(
+        succeeded.payment_preimage.as_deref(),
+        Some(PAYMENT_PREIMAGE)
+    );
+    assert_eq!(succeeded.fee_msat, Some(1250));
+    assert_eq!(succeeded.parts.len(), 2);
+    assert_eq!(succeeded.parts[0].amount_msat, 150000000);
+    assert_eq!(succeeded.parts[0].fee_msat, 750);
+
+    let failed = &result.payments[1];
+    assert_eq!(failed.status, PaymentStatus::Failed);
+    assert_eq!(failed.payment_preimage, None);
+    assert_eq!(failed.failure_reason.as_deref(), Some("no route found"));
+    // Without a route, the destination is read from the invoice.
+    assert_eq!(
+        failed.destination.as_deref(),
+        Some("03e7156ae33b0a208d0744199163177e909e80176e55d97a2f221ede0f934dd9ad")
+    );
+
+    let query = stub.request("/v1/payments").query.unwrap();
+    assert!(query.contains("include_incomplete=true"));
+    assert!(query.contains("index_offset=11"));
+    assert!(query.contains("max_payments=2"));
+}
+
+#[tokio::test]
+async fn send_payment_async() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/v2/router/send",
+            200,
+            fixture("lnd/trackpayment.jsonl"),
+        )
+        .start()
+        .await;
+
+    let payment_hash = lnd(&stub).send_payment_async(pay_params()).await.unwrap();
+
+    assert_eq!(payment_hash, PAYMENT_HASH);
+
+    let request: serde_json::Value =
+        serde_json::from_str(&stub.request("/v2/router/send").body).unwrap();
+    assert_eq!(request["payment_request"], fixture("bolt11.txt").trim());
+    assert_eq!(request["fee_limit_msat"], "5000");
+    assert_eq!(request["no_inflight_updates"], false);
+}
+
+#[tokio::test]
+async fn track_payment() {
+    let stub = RestStub::new()
+        .route(
+            "GET",
+            "/v2/router/track/AAECAwQFBgcICQABAgMEBQYHCAkAAQIDBAUGBwgJAQI=",
+            200,
+            fixture("lnd/trackpayment_succeeded.jsonl"),
+        )
+        .start()
+        .await;
+
+    let payments: Vec<_> = lnd(&stub)
+        .track_payment(PAYMENT_HASH.to_string())
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    assert_eq!(payments.len(), 2);
+    let pending = payments[0].as_ref().unwrap();
+    assert_eq!(pending.status, PaymentStatus::Pending);
+    let succeeded = payments[1].as_ref().unwrap();
+    assert_eq!(succeeded.status, PaymentStatus::Succeeded);
+    assert_eq!(
+        succeeded.payment_preimage.as_deref(),
+        Some(PAYMENT_PREIMAGE)
+    );
+    assert_eq!(succeeded.fee_msat, Some(1250));
+}
+
+#[tokio::test]
+async fn sign_message() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/v1/signmessage",
+            200,
+            format!("{{\"signature\": \"{}\"}}", SIGNATURE),
+        )
+        .start()
+        .await;
+
+    let signature = lnd(&stub)
+        .sign_message(String::from("I own this node"))
+        .await
+        .unwrap();
+
+    assert_eq!(signature, SIGNATURE);
+    let request: serde_json::Value =
+        serde_json::from_str(&stub.request("/v1/signmessage").body).unwrap();
+    assert_eq!(request["msg"], "SSBvd24gdGhpcyBub2Rl");
+}
+
+#[tokio::test]
+async fn verify_message() {
+    let stub = RestStub::new()
+        .route(
+            "POST",
+            "/v1/verifymessage",
+            200,
+            format!("{{\"valid\": false, \"pubkey\": \"{}\"}}", SIGNER_PUBKEY),
+        )
+        .start()
+        .await;
+
+    let params = |pubkey: Option<&str>| VerifyMessageParams {
+        message: String::from("I own this node"),
+        signature: SIGNATURE.to_string(),
+        pubkey: pubkey.map(String::from),
+    };
+
+    // LND doesn't know the signer, which doesn't matter once it is given.
+    let result = lnd(&stub).verify_message(params(None)).await.unwrap();
+    assert!(!result.valid);
+    assert_eq!(result.pubkey, SIGNER_PUBKEY);
+
+    let result = lnd(&stub)
+        .verify_message(params(Some(SIGNER_PUBKEY)))
+        .await
+        .unwrap();
+    assert!(result.valid);
+
+    // The case of the given key doesn't matter.
+    let result = lnd(&stub)
+        .verify_message(params(Some(&SIGNER_PUBKEY.to_uppercase())))
+        .await
+        .unwrap();
+    assert!(result.valid);
+
+    let request: serde_json::Value =
+        serde_json::from_str(&stub.request("/v1/verifymessage").body).unwrap();
+    assert_eq!(request["msg"], "SSBvd24gdGhpcyBub2Rl");
+    assert_eq!(request["signature"], SIGNATURE);
+}
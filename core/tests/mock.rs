@@ -0,0 +1,332 @@
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use una_core::backends::mock::config::MockConfig;
+use una_core::backends::mock::node::Mock;
+use una_core::error::Error;
+use una_core::node::{Node, NodeMethods};
+use una_core::types::{
+    Balance, CreateHoldInvoiceParams, CreateInvoiceParams, InvoiceStatus, PayInvoiceParams,
+    PaymentStatus, SendOnchainParams,
+};
+
+const PREIMAGE: &str = "5e2b9c0bb4d0f0a6c5e3b1f7c2d8a9e0f1a2b3c4d5e6f708192a3b4c5d6e7f80";
+
+fn mock(config: MockConfig) -> (Mock, Node) {
+    let mock = Mock::new(config).unwrap();
+    let node = Node::from(mock.clone());
+
+    (mock, node)
+}
+
+fn invoice_params(amount_msat: u64) -> CreateInvoiceParams {
+    CreateInvoiceParams {
+        amount: None,
+        amount_msat: Some(amount_msat),
+        description: Some(String::from("coffee")),
+        description_hash: None,
+        label: None,
+        expire_in: None,
+        fallback_address: None,
+        payment_preimage: None,
+        cltv_expiry: None,
+    }
+}
+
+fn hold_invoice_params() -> CreateHoldInvoiceParams {
+    CreateHoldInvoiceParams {
+        payment_hash: hex::encode(Sha256::digest(hex::decode(PREIMAGE).unwrap())),
+        amount: None,
+        amount_msat: Some(250000),
+        description: Some(String::from("coffee")),
+        description_hash: None,
+        expire_in: None,
+        fallback_address: None,
+        cltv_expiry: None,
+    }
+}
+
+fn pay_params(payment_request: String) -> PayInvoiceParams {
+    PayInvoiceParams {
+        payment_request,
+        amount: None,
+        amount_msat: None,
+        max_fee_sat: None,
+        max_fee_msat: None,
+        max_fee_percent: None,
+    }
+}
+
+// Invoices of another node, which the mock pays without settling them.
+async fn external_invoice() -> String {
+    let (_, other) = mock(MockConfig {
+        seed: 1,
+        ..Default::default()
+    });
+
+    other
+        .create_invoice(invoice_params(250000))
+        .await
+        .unwrap()
+        .payment_request
+}
+
+async fn wait_for_status(node: &Node, payment_hash: &str, status: InvoiceStatus) {
+    for _ in 0..100 {
+        let invoice = node.get_invoice(payment_hash.to_string()).await.unwrap();
+        if invoice.status == status {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("invoice never got {:?}", status);
+}
+
+#[tokio::test]
+async fn settle_invoice() {
+    let (mock, node) = mock(MockConfig::default());
+
+    let created = node.create_invoice(invoice_params(250000)).await.unwrap();
+    let invoice = node
+        .get_invoice(created.payment_hash.clone())
+        .await
+        .unwrap();
+    assert_eq!(invoice.status, InvoiceStatus::Pending);
+    assert_eq!(invoice.amount_msat, 250000);
+
+    let settled = mock.settle_invoice(&created.payment_hash).unwrap();
+
+    assert_eq!(settled.status, InvoiceStatus::Settled);
+    assert!(settled.settled);
+    let preimage = hex::decode(settled.pre_image.unwrap()).unwrap();
+    assert_eq!(hex::encode(Sha256::digest(preimage)), created.payment_hash);
+
+    let invoice = node.get_invoice(created.payment_hash).await.unwrap();
+    assert_eq!(invoice.status, InvoiceStatus::Settled);
+}
+
+#[tokio::test]
+async fn pay_own_invoice() {
+    let (_, node) = mock(MockConfig::default());
+
+    let created = node.create_invoice(invoice_params(250000)).await.unwrap();
+    let result = node
+        .pay_invoice(pay_params(created.payment_request))
+        .await
+        .unwrap();
+
+    assert_eq!(result.payment_hash, created.payment_hash);
+    assert_eq!(result.fees_msat, Some(0));
+    let invoice = node.get_invoice(created.payment_hash).await.unwrap();
+    assert_eq!(invoice.status, InvoiceStatus::Settled);
+    assert_eq!(invoice.pre_image, Some(result.payment_preimage));
+}
+
+#[tokio::test]
+async fn pay_invoice_fee() {
+    let (_, node) = mock(MockConfig {
+        payment_fee_msat: 1250,
+        ..Default::default()
+    });
+
+    let params = PayInvoiceParams {
+        max_fee_msat: Some(1000),
+        ..pay_params(external_invoice().await)
+    };
+    let result = node.pay_invoice(params).await;
+    assert!(matches!(result, Err(Error::ApiError(_))));
+
+    let result = node
+        .pay_invoice(pay_params(external_invoice().await))
+        .await
+        .unwrap();
+    assert_eq!(result.fees_msat, Some(1250));
+}
+
+#[tokio::test]
+async fn pay_invoice_failure() {
+    let (mock, node) = mock(MockConfig {
+        payment_failure: Some(String::from("no route")),
+        ..Default::default()
+    });
+    let invoice = external_invoice().await;
+
+    let result = node.pay_invoice(pay_params(invoice.clone())).await;
+    assert!(matches!(result, Err(Error::ApiError(message)) if message == "no route"));
+
+    let payment_hash = node
+        .decode_invoice(invoice.clone())
+        .await
+        .unwrap()
+        .payment_hash;
+    let payment = node.get_payment(payment_hash).await.unwrap();
+    assert_eq!(payment.status, PaymentStatus::Failed);
+    assert_eq!(payment.failure_reason, Some(String::from("no route")));
+
+    // Failed payments can be retried.
+    mock.set_payment_failure(None);
+    node.pay_invoice(pay_params(invoice)).await.unwrap();
+}
+
+#[tokio::test]
+async fn pay_invoice_latency() {
+    let (_, node) = mock(MockConfig {
+        payment_latency: Duration::from_millis(200),
+        ..Default::default()
+    });
+
+    let payment_hash = node
+        .send_payment_async(pay_params(external_invoice().await))
+        .await
+        .unwrap();
+
+    let payment = node.get_payment(payment_hash.clone()).await.unwrap();
+    assert_eq!(payment.status, PaymentStatus::Pending);
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    let payment = node.get_payment(payment_hash).await.unwrap();
+    assert_eq!(payment.status, PaymentStatus::Succeeded);
+}
+
+#[tokio::test]
+async fn settle_hold_invoice() {
+    let (_, node) = mock(MockConfig::default());
+
+    let created = node
+        .create_hold_invoice(hold_invoice_params())
+        .await
+        .unwrap();
+    node.send_payment_async(pay_params(created.payment_request))
+        .await
+        .unwrap();
+    wait_for_status(&node, &created.payment_hash, InvoiceStatus::Accepted).await;
+
+    let payment = node
+        .get_payment(created.payment_hash.clone())
+        .await
+        .unwrap();
+    assert_eq!(payment.status, PaymentStatus::Pending);
+
+    node.settle_hold_invoice(PREIMAGE.to_string())
+        .await
+        .unwrap();
+
+    let invoice = node
+        .get_invoice(created.payment_hash.clone())
+        .await
+        .unwrap();
+    assert_eq!(invoice.status, InvoiceStatus::Settled);
+    assert_eq!(invoice.pre_image, Some(PREIMAGE.to_string()));
+    let payment = node.get_payment(created.payment_hash).await.unwrap();
+    assert_eq!(payment.status, PaymentStatus::Succeeded);
+    assert_eq!(payment.payment_preimage, Some(PREIMAGE.to_string()));
+}
+
+#[tokio::test]
+async fn settle_hold_invoice_not_accepted() {
+    let (_, node) = mock(MockConfig::default());
+
+    node.create_hold_invoice(hold_invoice_params())
+        .await
+        .unwrap();
+    let result = node.settle_hold_invoice(PREIMAGE.to_string()).await;
+
+    assert!(matches!(result, Err(Error::ApiError(_))));
+}
+
+#[tokio::test]
+async fn cancel_hold_invoice() {
+    let (_, node) = mock(MockConfig::default());
+
+    let created = node
+        .create_hold_invoice(hold_invoice_params())
+        .await
+        .unwrap();
+    node.send_payment_async(pay_params(created.payment_request))
+        .await
+        .unwrap();
+    wait_for_status(&node, &created.payment_hash, InvoiceStatus::Accepted).await;
+
+    node.cancel_hold_invoice(created.payment_hash.clone())
+        .await
+        .unwrap();
+
+    let invoice = node
+        .get_invoice(created.payment_hash.clone())
+        .await
+        .unwrap();
+    assert_eq!(invoice.status, InvoiceStatus::Cancelled);
+    let payment = node.get_payment(created.payment_hash).await.unwrap();
+    assert_eq!(payment.status, PaymentStatus::Failed);
+    assert!(payment.payment_preimage.is_none());
+}
+
+#[tokio::test]
+async fn cancel_invoice() {
+    let (_, node) = mock(MockConfig::default());
+
+    let created = node.create_invoice(invoice_params(250000)).await.unwrap();
+    node.cancel_invoice(created.payment_hash.clone())
+        .await
+        .unwrap();
+
+    let invoice = node.get_invoice(created.payment_hash).await.unwrap();
+    assert_eq!(invoice.status, InvoiceStatus::Cancelled);
+    let result = node.pay_invoice(pay_params(created.payment_request)).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn get_balance() {
+    let (mock, node) = mock(MockConfig {
+        balance: Balance {
+            onchain_confirmed_msat: 1000000,
+            lightning_local_msat: 500000,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+
+    let balance = node.get_balance().await.unwrap();
+    assert_eq!(balance.onchain_confirmed_msat, 1000000);
+    assert_eq!(balance.lightning_local_msat, 500000);
+
+    node.send_onchain(SendOnchainParams {
+        address: String::from("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080"),
+        amount: Some(400),
+        send_all: None,
+        fee_rate: None,
+        target_conf: None,
+        utxos: None,
+    })
+    .await
+    .unwrap();
+    let balance = node.get_balance().await.unwrap();
+    assert_eq!(balance.onchain_confirmed_msat, 600000);
+
+    mock.set_balance(Balance {
+        lightning_remote_msat: 700000,
+        ..Default::default()
+    });
+    let balance = node.get_balance().await.unwrap();
+    assert_eq!(balance.onchain_confirmed_msat, 0);
+    assert_eq!(balance.lightning_remote_msat, 700000);
+}
+
+#[test]
+fn new_from_node_config() {
+    let config = una_core::types::NodeConfig {
+        url: None,
+        macaroon: None,
+        tls_certificate: None,
+        tls_client_key: None,
+        tls_client_certificate: None,
+        username: None,
+        password: None,
+        connect_timeout: None,
+        request_timeout: None,
+    };
+    let result = Node::new(una_core::types::Backend::Mock, config);
+
+    // The config would be ignored, the mock is built with `Node::from`.
+    assert!(matches!(result, Err(Error::ConfigError(_))));
+}
@@ -5,12 +5,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     std::env::set_var("OUT_DIR", Path::new(&cargo_workspace_dir).join("proto/out"));
 
-    tonic_build::configure()
-        .build_server(false)
-        .compile(
-            &[Path::new(&cargo_workspace_dir).join("proto/cln-v0.11.2/node.proto")],
-            &[Path::new(&cargo_workspace_dir).join("proto/cln-v0.11.2")],
-        )?;
+    tonic_build::configure().build_server(false).compile(
+        &[Path::new(&cargo_workspace_dir).join("proto/cln-grpc-v0.1.3/node.proto")],
+        &[Path::new(&cargo_workspace_dir).join("proto/cln-grpc-v0.1.3")],
+    )?;
+
+    tonic_build::configure().build_server(false).compile(
+        &[
+            Path::new(&cargo_workspace_dir).join("proto/lnd-v0.15.4/lightning.proto"),
+            Path::new(&cargo_workspace_dir).join("proto/lnd-v0.15.4/router.proto"),
+            Path::new(&cargo_workspace_dir).join("proto/lnd-v0.15.4/invoices.proto"),
+        ],
+        &[Path::new(&cargo_workspace_dir).join("proto/lnd-v0.15.4")],
+    )?;
 
     Ok(())
 }
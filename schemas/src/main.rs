@@ -2,8 +2,15 @@ use schemars::{schema::RootSchema, schema_for};
 use std::env;
 
 use una_core::types::{
-    Backend, ChannelStats, CreateInvoiceParams, CreateInvoiceResult, Network, NodeConfig, NodeInfo,
-    PayInvoiceParams, PayInvoiceResult,
+    AddressType, Backend, Balance, Channel, ChannelPolicy, ChannelStats, ChannelStatus,
+    CloseChannelParams, CloseChannelResult, ConnectPeerParams, CreateHoldInvoiceParams,
+    CreateInvoiceParams, CreateInvoiceResult, CustomRecord, DecodedInvoice, Feature, Forward,
+    ForwardStatus, Invoice, InvoiceCursor, InvoiceEvent, InvoiceEventType, InvoiceStatus,
+    KeysendParams, ListForwardsParams, ListForwardsResult, ListInvoicesParams, ListInvoicesResult,
+    ListPaymentsParams, ListPaymentsResult, Network, NodeConfig, NodeInfo, OnchainTransaction,
+    OpenChannelParams, OpenChannelResult, PayInvoiceParams, PayInvoiceResult, Payment, PaymentPart,
+    PaymentStatus, Peer, RouteHint, RouteHintHop, SendOnchainParams, SubscribeInvoicesParams,
+    UpdatePolicyParams, VerifyMessageParams, VerifyMessageResult,
 };
 
 fn write_schema(dir: &std::path::Path, name: &str, schema: &RootSchema) -> std::io::Result<()> {
@@ -42,11 +49,131 @@ fn main() {
     let schema = schema_for!(CreateInvoiceResult);
     write_schema(&dir, "create_invoice_result", &schema).unwrap();
 
+    let schema = schema_for!(Invoice);
+    write_schema(&dir, "invoice", &schema).unwrap();
+
+    let schema = schema_for!(InvoiceStatus);
+    write_schema(&dir, "invoice_status", &schema).unwrap();
+
     let schema = schema_for!(PayInvoiceParams);
     write_schema(&dir, "pay_invoice_params", &schema).unwrap();
 
     let schema = schema_for!(PayInvoiceResult);
     write_schema(&dir, "pay_invoice_result", &schema).unwrap();
 
+    let schema = schema_for!(ListInvoicesParams);
+    write_schema(&dir, "list_invoices_params", &schema).unwrap();
+
+    let schema = schema_for!(ListInvoicesResult);
+    write_schema(&dir, "list_invoices_result", &schema).unwrap();
+
+    let schema = schema_for!(InvoiceCursor);
+    write_schema(&dir, "invoice_cursor", &schema).unwrap();
+
+    let schema = schema_for!(InvoiceEvent);
+    write_schema(&dir, "invoice_event", &schema).unwrap();
+
+    let schema = schema_for!(InvoiceEventType);
+    write_schema(&dir, "invoice_event_type", &schema).unwrap();
+
+    let schema = schema_for!(SubscribeInvoicesParams);
+    write_schema(&dir, "subscribe_invoices_params", &schema).unwrap();
+
+    let schema = schema_for!(DecodedInvoice);
+    write_schema(&dir, "decoded_invoice", &schema).unwrap();
+
+    let schema = schema_for!(Feature);
+    write_schema(&dir, "feature", &schema).unwrap();
+
+    let schema = schema_for!(RouteHint);
+    write_schema(&dir, "route_hint", &schema).unwrap();
+
+    let schema = schema_for!(RouteHintHop);
+    write_schema(&dir, "route_hint_hop", &schema).unwrap();
+
+    let schema = schema_for!(KeysendParams);
+    write_schema(&dir, "keysend_params", &schema).unwrap();
+
+    let schema = schema_for!(CustomRecord);
+    write_schema(&dir, "custom_record", &schema).unwrap();
+
+    let schema = schema_for!(Balance);
+    write_schema(&dir, "balance", &schema).unwrap();
+
+    let schema = schema_for!(Channel);
+    write_schema(&dir, "channel", &schema).unwrap();
+
+    let schema = schema_for!(ChannelStatus);
+    write_schema(&dir, "channel_status", &schema).unwrap();
+
+    let schema = schema_for!(OpenChannelParams);
+    write_schema(&dir, "open_channel_params", &schema).unwrap();
+
+    let schema = schema_for!(OpenChannelResult);
+    write_schema(&dir, "open_channel_result", &schema).unwrap();
+
+    let schema = schema_for!(CloseChannelParams);
+    write_schema(&dir, "close_channel_params", &schema).unwrap();
+
+    let schema = schema_for!(CloseChannelResult);
+    write_schema(&dir, "close_channel_result", &schema).unwrap();
+
+    let schema = schema_for!(ConnectPeerParams);
+    write_schema(&dir, "connect_peer_params", &schema).unwrap();
+
+    let schema = schema_for!(Peer);
+    write_schema(&dir, "peer", &schema).unwrap();
+
+    let schema = schema_for!(AddressType);
+    write_schema(&dir, "address_type", &schema).unwrap();
+
+    let schema = schema_for!(SendOnchainParams);
+    write_schema(&dir, "send_onchain_params", &schema).unwrap();
+
+    let schema = schema_for!(OnchainTransaction);
+    write_schema(&dir, "onchain_transaction", &schema).unwrap();
+
+    let schema = schema_for!(PaymentStatus);
+    write_schema(&dir, "payment_status", &schema).unwrap();
+
+    let schema = schema_for!(PaymentPart);
+    write_schema(&dir, "payment_part", &schema).unwrap();
+
+    let schema = schema_for!(Payment);
+    write_schema(&dir, "payment", &schema).unwrap();
+
+    let schema = schema_for!(ListPaymentsParams);
+    write_schema(&dir, "list_payments_params", &schema).unwrap();
+
+    let schema = schema_for!(ListPaymentsResult);
+    write_schema(&dir, "list_payments_result", &schema).unwrap();
+
+    let schema = schema_for!(ChannelPolicy);
+    write_schema(&dir, "channel_policy", &schema).unwrap();
+
+    let schema = schema_for!(UpdatePolicyParams);
+    write_schema(&dir, "update_policy_params", &schema).unwrap();
+
+    let schema = schema_for!(ForwardStatus);
+    write_schema(&dir, "forward_status", &schema).unwrap();
+
+    let schema = schema_for!(Forward);
+    write_schema(&dir, "forward", &schema).unwrap();
+
+    let schema = schema_for!(ListForwardsParams);
+    write_schema(&dir, "list_forwards_params", &schema).unwrap();
+
+    let schema = schema_for!(ListForwardsResult);
+    write_schema(&dir, "list_forwards_result", &schema).unwrap();
+
+    let schema = schema_for!(VerifyMessageParams);
+    write_schema(&dir, "verify_message_params", &schema).unwrap();
+
+    let schema = schema_for!(VerifyMessageResult);
+    write_schema(&dir, "verify_message_result", &schema).unwrap();
+
+    let schema = schema_for!(CreateHoldInvoiceParams);
+    write_schema(&dir, "create_hold_invoice_params", &schema).unwrap();
+
     println!("Wrote schemas to {}", dir.to_string_lossy());
 }
@@ -14,32 +14,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Arg::new("backend")
                 .short('b')
                 .long("backend")
-                .value_parser(["LndRest", "ClnGrpc", "EclairRest"])
+                .value_parser(["LndRest", "LndGrpc", "ClnGrpc", "EclairRest"])
                 .help("Specifies the node backend")
                 .takes_value(true),
         )
         .arg(
             Arg::new("url")
                 .long("url")
-                .help("[LndRest,ClnGrpc,EclairRest] Sets the node URL")
+                .help("[LndRest,LndGrpc,ClnGrpc,EclairRest] Sets the node URL")
                 .takes_value(true)
                 .requires_if("LndRest", "backend")
+                .requires_if("LndGrpc", "backend")
                 .requires_if("ClnGrpc", "backend")
                 .requires_if("EclairRest", "backend"),
         )
         .arg(
             Arg::new("macaroon")
                 .long("macaroon")
-                .help("[LndRest] Sets the node macaroon")
+                .help("[LndRest,LndGrpc] Sets the node macaroon")
                 .takes_value(true)
-                .requires_if("LndRest", "backend"),
+                .requires_if("LndRest", "backend")
+                .requires_if("LndGrpc", "backend"),
         )
         .arg(
             Arg::new("tls_certificate")
                 .long("tls_certificate")
-                .help("[LndRest,ClnGrpc] Sets the node self-signed TLS certificate")
+                .help("[LndRest,LndGrpc,ClnGrpc] Sets the node self-signed TLS certificate")
                 .takes_value(true)
                 .requires_if("LndRest", "backend")
+                .requires_if("LndGrpc", "backend")
                 .requires_if("ClnGrpc", "backend"),
         )
         .arg(
@@ -70,6 +73,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .takes_value(true)
                 .requires_if("EclairRest", "backend"),
         )
+        .arg(
+            Arg::new("connect_timeout")
+                .long("connect_timeout")
+                .help("Sets the connection timeout, in seconds")
+                .takes_value(true)
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("request_timeout")
+                .long("request_timeout")
+                .help(
+                    "Sets the request timeout, in seconds, except for payments and channel closes",
+                )
+                .takes_value(true)
+                .value_parser(clap::value_parser!(u64)),
+        )
         .subcommand(Command::new("info").about("see information about your node"))
         .subcommand(
             Command::new("createinvoice")
@@ -87,8 +106,46 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .help("description"),
                 ),
         )
+        .subcommand(
+            Command::new("getinvoice")
+                .about("get an invoice by its payment hash")
+                .arg(
+                    Arg::new("payment_hash")
+                        .required(true)
+                        .index(1)
+                        .help("payment hash"),
+                ),
+        )
+        .subcommand(
+            Command::new("decode")
+                .about("decode an invoice locally, without connecting to the node")
+                .arg(
+                    Arg::new("bolt11")
+                        .required(true)
+                        .index(1)
+                        .help("bolt11 invoice"),
+                ),
+        )
         .get_matches();
 
+    if let Some(("decode", command_args)) = matches.subcommand() {
+        let bolt11 = command_args
+            .value_of("bolt11")
+            .expect("bolt11 is a required field");
+
+        let invoice = match una_core::bolt11::decode_invoice(bolt11) {
+            Ok(invoice) => invoice,
+            Err(error) => {
+                eprintln!("couldn't decode the invoice: {}", error);
+                std::process::exit(1);
+            }
+        };
+
+        println!("{:}", serde_json::to_string_pretty(&invoice).unwrap());
+
+        return Ok(());
+    }
+
     let backend: Backend = matches
         .value_of("backend")
         .expect("backend is required")
@@ -104,6 +161,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         tls_client_key: matches.value_of("tls_client_key").map(|s| s.to_string()),
         username: matches.value_of("username").map(|s| s.to_string()),
         password: matches.value_of("password").map(|s| s.to_string()),
+        connect_timeout: matches.get_one::<u64>("connect_timeout").copied(),
+        request_timeout: matches.get_one::<u64>("request_timeout").copied(),
     };
 
     let node = una_core::node::Node::new(backend, config).unwrap();
@@ -142,6 +201,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             println!("{:}", serde_json::to_string_pretty(&invoice).unwrap());
         }
+        "getinvoice" => {
+            let payment_hash = command_args
+                .value_of("payment_hash")
+                .expect("payment_hash is a required field");
+
+            let invoice = node.get_invoice(payment_hash.to_string()).await.unwrap();
+
+            println!("{:}", serde_json::to_string_pretty(&invoice).unwrap());
+        }
         _ => {
             println!("invalid command. use una-cli --help to see usage instructions.")
         }